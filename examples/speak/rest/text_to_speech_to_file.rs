@@ -1,4 +1,4 @@
-use std::{env, path::Path, time::Instant};
+use std::{path::Path, time::Instant};
 
 use deepgram::{
     speak::options::{Container, Encoding, Model, Options},
@@ -7,10 +7,7 @@ use deepgram::{
 
 #[tokio::main]
 async fn main() -> Result<(), DeepgramError> {
-    let deepgram_api_key =
-        env::var("DEEPGRAM_API_KEY").expect("DEEPGRAM_API_KEY environmental variable");
-
-    let dg_client = Deepgram::new(&deepgram_api_key)?;
+    let dg_client = Deepgram::from_env()?;
 
     let options = Options::builder()
         .model(Model::AuraAsteriaEn)
@@ -3,7 +3,7 @@ use std::env;
 use deepgram::{
     common::{
         audio_source::AudioSource,
-        options::{Language, Options},
+        options::{Callback, Language, Options},
     },
     Deepgram, DeepgramError,
 };
@@ -12,10 +12,7 @@ static AUDIO_URL: &str = "https://static.deepgram.com/examples/Bueller-Life-move
 
 #[tokio::main]
 async fn main() -> Result<(), DeepgramError> {
-    let deepgram_api_key =
-        env::var("DEEPGRAM_API_KEY").expect("DEEPGRAM_API_KEY environmental variable");
-
-    let dg_client = Deepgram::new(&deepgram_api_key)?;
+    let dg_client = Deepgram::from_env()?;
 
     let source = AudioSource::from_url(AUDIO_URL);
 
@@ -27,9 +24,11 @@ async fn main() -> Result<(), DeepgramError> {
     let callback_url =
         env::var("DEEPGRAM_CALLBACK_URL").expect("DEEPGRAM_CALLBACK_URL environmental variable");
 
+    let callback = Callback::new(&callback_url)?;
+
     let response = dg_client
         .transcription()
-        .prerecorded_callback(source, &options, &callback_url)
+        .prerecorded_callback(source, &options, &callback)
         .await?;
 
     println!("{}", response.request_id);
@@ -1,5 +1,3 @@
-use std::env;
-
 use deepgram::{
     common::{
         audio_source::AudioSource,
@@ -7,20 +5,14 @@ use deepgram::{
     },
     Deepgram, DeepgramError,
 };
-use tokio::fs::File;
 
 static PATH_TO_FILE: &str = "examples/audio/bueller.wav";
 
 #[tokio::main]
 async fn main() -> Result<(), DeepgramError> {
-    let deepgram_api_key =
-        env::var("DEEPGRAM_API_KEY").expect("DEEPGRAM_API_KEY environmental variable");
-
-    let dg_client = Deepgram::new(&deepgram_api_key)?;
-
-    let file = File::open(PATH_TO_FILE).await.unwrap();
+    let dg_client = Deepgram::from_env()?;
 
-    let source = AudioSource::from_buffer_with_mime_type(file, "audio/wav");
+    let source = AudioSource::from_path(PATH_TO_FILE).await?;
 
     let options = Options::builder()
         .punctuate(true)
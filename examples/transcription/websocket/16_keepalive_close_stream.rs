@@ -10,7 +10,6 @@
 ///
 /// Usage:
 ///   DEEPGRAM_API_KEY=your-key cargo run --example 16_keepalive_close_stream
-use std::env;
 use std::time::Duration;
 
 use deepgram::{
@@ -20,10 +19,7 @@ use deepgram::{
 
 #[tokio::main]
 async fn main() -> Result<(), DeepgramError> {
-    let deepgram_api_key =
-        env::var("DEEPGRAM_API_KEY").expect("DEEPGRAM_API_KEY environment variable");
-
-    let dg_client = Deepgram::new(&deepgram_api_key)?;
+    let dg_client = Deepgram::from_env()?;
 
     let options = Options::builder()
         .query_params([("mip_opt_out".to_string(), "true".to_string())])
@@ -35,6 +31,7 @@ async fn main() -> Result<(), DeepgramError> {
         .transcription()
         .stream_request_with_options(options)
         .encoding(Encoding::Linear16)
+        .sample_rate(16000)
         .endpointing(Endpointing::Disabled)
         .keep_alive()
         .handle()
@@ -4,7 +4,7 @@ use std::time::Duration;
 use futures::stream::StreamExt;
 
 use deepgram::{
-    common::options::{Encoding, Endpointing, Language, Options},
+    common::options::{Callback, Encoding, Endpointing, Language, Options},
     Deepgram, DeepgramError,
 };
 
@@ -14,20 +14,16 @@ static FRAME_DELAY: Duration = Duration::from_millis(16);
 
 #[tokio::main]
 async fn main() -> Result<(), DeepgramError> {
-    let deepgram_api_key =
-        env::var("DEEPGRAM_API_KEY").expect("DEEPGRAM_API_KEY environmental variable");
-
-    let dg_client = Deepgram::new(&deepgram_api_key)?;
+    let dg_client = Deepgram::from_env()?;
 
     let options = Options::builder()
         .smart_format(true)
         .language(Language::en_US)
         .build();
 
-    let callback_url = env::var("DEEPGRAM_CALLBACK_URL")
-        .expect("DEEPGRAM_CALLBACK_URL environmental variable")
-        .parse()
-        .expect("DEEPGRAM_CALLBACK_URL not a valid URL");
+    let callback_url =
+        env::var("DEEPGRAM_CALLBACK_URL").expect("DEEPGRAM_CALLBACK_URL environmental variable");
+    let callback = Callback::new(&callback_url)?;
 
     let mut results = dg_client
         .transcription()
@@ -41,7 +37,7 @@ async fn main() -> Result<(), DeepgramError> {
         .utterance_end_ms(1000)
         .vad_events(true)
         .no_delay(true)
-        .callback(callback_url)
+        .callback(callback)
         .file(PATH_TO_FILE, AUDIO_CHUNK_SIZE, FRAME_DELAY)
         .await?;
 
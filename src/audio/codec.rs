@@ -0,0 +1,160 @@
+//! A [`tokio_util::codec`] codec for framing raw audio.
+//!
+//! [`AudioFrameCodec`] turns any [`AsyncRead`](tokio::io::AsyncRead) carrying
+//! raw PCM (a TCP socket, a pipe from another process, a file) into a stream
+//! of evenly sized chunks with [`FramedRead`](tokio_util::codec::FramedRead),
+//! which can be passed straight to a websocket builder's `stream` method. It
+//! also implements [`Encoder`], so audio can be written back out to an
+//! [`AsyncWrite`](tokio::io::AsyncWrite) with
+//! [`FramedWrite`](tokio_util::codec::FramedWrite).
+//!
+//! Only available with the `audio-utils` feature.
+
+use std::time::Duration;
+
+use bytes::{Bytes, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::DeepgramError;
+
+/// Frames raw audio into chunks of a fixed number of bytes.
+///
+/// Deepgram recommends sending between 20 and 100 milliseconds of audio per
+/// message; [`AudioFrameCodec::for_duration`] computes the matching chunk size
+/// for PCM audio. When the underlying reader reaches end of file, whatever is
+/// left over is yielded as one shorter chunk.
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # use deepgram::{audio::codec::AudioFrameCodec, Deepgram, DeepgramError};
+/// # use tokio_util::codec::FramedRead;
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), DeepgramError> {
+/// let dg = Deepgram::new(std::env::var("DEEPGRAM_API_KEY").unwrap_or_default())?;
+///
+/// // Bridge 16kHz mono linear16 audio arriving on a socket to Deepgram.
+/// let socket = tokio::net::TcpStream::connect("127.0.0.1:9000").await?;
+/// let codec = AudioFrameCodec::for_duration(16_000, 1, 2, Duration::from_millis(50));
+/// let audio = FramedRead::new(socket, codec);
+///
+/// let results = dg
+///     .transcription()
+///     .stream_request()
+///     .sample_rate(16_000)
+///     .channels(1)
+///     .stream(audio)
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AudioFrameCodec {
+    frame_size: usize,
+}
+
+impl AudioFrameCodec {
+    /// Construct a codec that yields chunks of `frame_size` bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `frame_size` is zero.
+    pub fn new(frame_size: usize) -> Self {
+        assert!(frame_size > 0, "frame size must be non-zero");
+        Self { frame_size }
+    }
+
+    /// Construct a codec that yields chunks holding `duration` of PCM audio.
+    ///
+    /// The chunk size is rounded down to a whole number of frames (one sample
+    /// for every channel), but is always at least one frame.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channels` or `bytes_per_sample` is zero.
+    pub fn for_duration(
+        sample_rate: u32,
+        channels: u16,
+        bytes_per_sample: usize,
+        duration: Duration,
+    ) -> Self {
+        let frames = ((f64::from(sample_rate) * duration.as_secs_f64()) as usize).max(1);
+        Self::new(frames * usize::from(channels) * bytes_per_sample)
+    }
+
+    /// The number of bytes in each chunk.
+    pub fn frame_size(&self) -> usize {
+        self.frame_size
+    }
+}
+
+impl Decoder for AudioFrameCodec {
+    type Item = Bytes;
+    type Error = DeepgramError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Bytes>, DeepgramError> {
+        if src.len() < self.frame_size {
+            src.reserve(self.frame_size - src.len());
+            return Ok(None);
+        }
+
+        Ok(Some(src.split_to(self.frame_size).freeze()))
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Bytes>, DeepgramError> {
+        match self.decode(src)? {
+            Some(frame) => Ok(Some(frame)),
+            None if src.is_empty() => Ok(None),
+            None => Ok(Some(src.split().freeze())),
+        }
+    }
+}
+
+impl Encoder<Bytes> for AudioFrameCodec {
+    type Error = DeepgramError;
+
+    fn encode(&mut self, item: Bytes, dst: &mut BytesMut) -> Result<(), DeepgramError> {
+        dst.extend_from_slice(&item);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{SinkExt, StreamExt};
+    use tokio_util::codec::{FramedRead, FramedWrite};
+
+    use super::*;
+
+    #[test]
+    fn frame_size_for_duration() {
+        let codec = AudioFrameCodec::for_duration(16_000, 2, 2, Duration::from_millis(20));
+        assert_eq!(codec.frame_size(), 320 * 2 * 2);
+
+        let codec = AudioFrameCodec::for_duration(8_000, 1, 1, Duration::ZERO);
+        assert_eq!(codec.frame_size(), 1);
+    }
+
+    #[tokio::test]
+    async fn frames_reader_with_short_tail() {
+        let data: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        let frames: Vec<Bytes> = FramedRead::new(&data[..], AudioFrameCodec::new(400))
+            .map(|frame| frame.unwrap())
+            .collect()
+            .await;
+
+        let lengths: Vec<usize> = frames.iter().map(Bytes::len).collect();
+        assert_eq!(lengths, vec![400, 400, 200]);
+        assert_eq!(frames.concat(), data);
+    }
+
+    #[tokio::test]
+    async fn writes_frames() {
+        let mut out = Vec::new();
+        let mut sink = FramedWrite::new(&mut out, AudioFrameCodec::new(2));
+        sink.send(Bytes::from_static(b"abc")).await.unwrap();
+        sink.send(Bytes::from_static(b"de")).await.unwrap();
+        drop(sink);
+
+        assert_eq!(out, b"abcde");
+    }
+}
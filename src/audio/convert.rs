@@ -0,0 +1,217 @@
+//! Conversions between the raw encodings Deepgram accepts.
+//!
+//! All linear16 audio handled here is signed 16-bit little-endian PCM, which is
+//! what Deepgram expects for the `linear16` encoding.
+//!
+//! Only available with the `audio-utils` feature.
+
+use bytes::{BufMut, Bytes, BytesMut};
+
+use crate::mulaw::{decode_mulaw, MULAW_BIAS};
+
+const MULAW_CLIP: i32 = 32635;
+
+/// Decode G.711 mu-law bytes into linear16 PCM.
+///
+/// Every input byte becomes one 16-bit sample, so the output is twice as long.
+///
+/// ```
+/// use deepgram::audio::convert::mulaw_to_linear16;
+///
+/// // 0xFF is mu-law silence.
+/// assert_eq!(&mulaw_to_linear16(&[0xFF])[..], &[0, 0]);
+/// ```
+pub fn mulaw_to_linear16(mulaw: &[u8]) -> Bytes {
+    let mut out = BytesMut::with_capacity(mulaw.len() * 2);
+    for &byte in mulaw {
+        out.put_i16_le(decode_mulaw(byte));
+    }
+    out.freeze()
+}
+
+/// Encode linear16 PCM into G.711 mu-law bytes.
+///
+/// Every 16-bit sample becomes one byte. A trailing odd byte is ignored, so
+/// callers splitting a stream into chunks should keep chunks sample-aligned.
+///
+/// ```
+/// use deepgram::audio::convert::{linear16_to_mulaw, mulaw_to_linear16};
+///
+/// let mulaw = linear16_to_mulaw(&[0, 0, 0x00, 0x10]);
+/// assert_eq!(mulaw.len(), 2);
+/// assert_eq!(&mulaw_to_linear16(&mulaw)[..2], &[0, 0]);
+/// ```
+pub fn linear16_to_mulaw(linear16: &[u8]) -> Bytes {
+    linear16
+        .chunks_exact(2)
+        .map(|sample| encode_mulaw(i16::from_le_bytes([sample[0], sample[1]])))
+        .collect::<Vec<u8>>()
+        .into()
+}
+
+fn encode_mulaw(sample: i16) -> u8 {
+    let sample = i32::from(sample);
+    let sign: u8 = if sample < 0 { 0x80 } else { 0 };
+    let magnitude = sample.abs().min(MULAW_CLIP) + MULAW_BIAS;
+
+    // `magnitude >> 7` is in 1..=255, so the exponent is in 0..=7.
+    let exponent = 7 - ((magnitude >> 7) as u8).leading_zeros() as u8;
+    let mantissa = ((magnitude >> (exponent + 3)) & 0x0F) as u8;
+
+    !(sign | (exponent << 4) | mantissa)
+}
+
+/// Converts interleaved linear16 PCM from one sample rate to another.
+///
+/// This is a simple linear-interpolation resampler intended for adapting
+/// microphone or WebRTC audio (typically 44.1kHz or 48kHz) to the rates used
+/// for speech recognition (typically 16kHz or 8kHz). It does not apply an
+/// anti-aliasing filter, which is acceptable for speech but not for music.
+///
+/// The resampler keeps state between calls to [`Resampler::process`], so a
+/// stream of arbitrarily sized chunks produces the same output as a single
+/// large buffer, even when a chunk ends in the middle of a sample.
+///
+/// ```
+/// use deepgram::audio::convert::Resampler;
+///
+/// let mut resampler = Resampler::new(48_000, 16_000, 1);
+/// let one_second = vec![0u8; 48_000 * 2];
+/// let resampled = resampler.process(&one_second);
+/// assert_eq!(resampled.len(), 16_000 * 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Resampler {
+    step: f64,
+    channels: usize,
+    position: f64,
+    previous_frame: Option<Vec<i16>>,
+    partial: Vec<u8>,
+}
+
+impl Resampler {
+    /// Construct a resampler for interleaved audio with `channels` channels.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either sample rate or the channel count is zero.
+    pub fn new(from_sample_rate: u32, to_sample_rate: u32, channels: u16) -> Self {
+        assert!(
+            from_sample_rate > 0 && to_sample_rate > 0,
+            "sample rates must be non-zero"
+        );
+        assert!(channels > 0, "channel count must be non-zero");
+
+        Self {
+            step: f64::from(from_sample_rate) / f64::from(to_sample_rate),
+            channels: usize::from(channels),
+            position: 0.0,
+            previous_frame: None,
+            partial: Vec::new(),
+        }
+    }
+
+    /// Resample the next chunk of linear16 audio.
+    pub fn process(&mut self, linear16: &[u8]) -> Bytes {
+        let frame_bytes = self.channels * 2;
+
+        let mut input = std::mem::take(&mut self.partial);
+        input.extend_from_slice(linear16);
+        let whole = input.len() - input.len() % frame_bytes;
+        self.partial = input.split_off(whole);
+
+        // Frame 0 is the last frame of the previous chunk, if there was one.
+        let mut frames: Vec<Vec<i16>> = self.previous_frame.take().into_iter().collect();
+        frames.extend(input.chunks_exact(frame_bytes).map(|frame| {
+            frame
+                .chunks_exact(2)
+                .map(|sample| i16::from_le_bytes([sample[0], sample[1]]))
+                .collect()
+        }));
+
+        let Some(last) = frames.last().cloned() else {
+            return Bytes::new();
+        };
+
+        let mut out = BytesMut::new();
+        while self.position + 1.0 < frames.len() as f64 {
+            let index = self.position as usize;
+            let fraction = self.position - index as f64;
+            for (&a, &b) in frames[index].iter().zip(&frames[index + 1]) {
+                let (a, b) = (f64::from(a), f64::from(b));
+                out.put_i16_le((a + (b - a) * fraction).round() as i16);
+            }
+            self.position += self.step;
+        }
+
+        self.position -= (frames.len() - 1) as f64;
+        self.previous_frame = Some(last);
+
+        out.freeze()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples(linear16: &[u8]) -> Vec<i16> {
+        linear16
+            .chunks_exact(2)
+            .map(|sample| i16::from_le_bytes([sample[0], sample[1]]))
+            .collect()
+    }
+
+    fn bytes(samples: &[i16]) -> Vec<u8> {
+        samples
+            .iter()
+            .flat_map(|sample| sample.to_le_bytes())
+            .collect()
+    }
+
+    #[test]
+    fn mulaw_roundtrip_is_close() {
+        let input = [0i16, 1000, -1000, 12345, -32768, 32767];
+        let decoded = samples(&mulaw_to_linear16(&linear16_to_mulaw(&bytes(&input))));
+
+        for (original, decoded) in input.iter().zip(decoded) {
+            let tolerance = (i32::from(*original).abs() / 16).max(8);
+            assert!(
+                (i32::from(*original) - i32::from(decoded)).abs() <= tolerance,
+                "{original} decoded as {decoded}"
+            );
+        }
+    }
+
+    #[test]
+    fn mulaw_known_values() {
+        assert_eq!(encode_mulaw(0), 0xFF);
+        assert_eq!(encode_mulaw(-1), 0x7F);
+        assert_eq!(decode_mulaw(0x00), -32124);
+        assert_eq!(decode_mulaw(0x80), 32124);
+    }
+
+    #[test]
+    fn resample_chunked_matches_whole() {
+        let input: Vec<i16> = (0..4_800).map(|i| (i % 200) as i16 * 10).collect();
+        let input = bytes(&input);
+
+        let whole = Resampler::new(48_000, 16_000, 2).process(&input);
+
+        let mut resampler = Resampler::new(48_000, 16_000, 2);
+        let mut chunked = Vec::new();
+        for chunk in input.chunks(333) {
+            chunked.extend_from_slice(&resampler.process(chunk));
+        }
+
+        assert_eq!(whole.len(), input.len() / 3);
+        assert_eq!(&whole[..], &chunked[..]);
+    }
+
+    #[test]
+    fn upsample_interpolates() {
+        let mut resampler = Resampler::new(8_000, 16_000, 1);
+        let out = samples(&resampler.process(&bytes(&[0, 100, 200])));
+        assert_eq!(out, vec![0, 50, 100, 150]);
+    }
+}
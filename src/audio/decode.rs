@@ -0,0 +1,355 @@
+//! Decode compressed recordings to linear16 for streaming.
+//!
+//! The live and Flux endpoints need raw audio with a declared encoding and
+//! sample rate, so compressed recordings would otherwise have to be converted
+//! with an external tool before they can be replayed. [`DecodedFile`] uses
+//! [`symphonia`] to decode MP3, Ogg Vorbis, FLAC, M4A/AAC, and WAV files on the
+//! fly, configures a [`WebsocketBuilder`] or [`FluxBuilder`] to match, and
+//! produces a stream of frame-aligned linear16 chunks.
+//!
+//! Only available with the `decode` feature.
+
+use std::{path::Path, time::Duration};
+
+use bytes::{BufMut, Bytes, BytesMut};
+use futures::Stream;
+use symphonia::core::{
+    audio::SampleBuffer,
+    codecs::{Decoder, DecoderOptions, CODEC_TYPE_NULL},
+    errors::Error as SymphoniaError,
+    formats::{FormatOptions, FormatReader},
+    io::MediaSourceStream,
+    meta::MetadataOptions,
+    probe::Hint,
+};
+use tokio_stream::wrappers::ReceiverStream;
+
+use crate::{
+    common::options::Encoding,
+    listen::{flux::FluxBuilder, websocket::WebsocketBuilder},
+    DeepgramError, Result,
+};
+
+/// A compressed audio file opened for decoding and streaming.
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # use deepgram::{audio::decode::DecodedFile, Deepgram, DeepgramError};
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), DeepgramError> {
+/// let dg = Deepgram::new(std::env::var("DEEPGRAM_API_KEY").unwrap_or_default())?;
+/// let transcription = dg.transcription();
+///
+/// let audio = DecodedFile::open("recording.mp3")?;
+/// let builder = audio.configure(transcription.stream_request());
+/// let results = builder
+///     .stream(audio.into_stream(Duration::from_millis(100)))
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct DecodedFile {
+    format: Box<dyn FormatReader>,
+    decoder: Box<dyn Decoder>,
+    track_id: u32,
+    sample_rate: u32,
+    channels: u16,
+    mono: bool,
+    pending: Vec<i16>,
+}
+
+impl std::fmt::Debug for DecodedFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DecodedFile")
+            .field("sample_rate", &self.sample_rate)
+            .field("channels", &self.channels)
+            .field("mono", &self.mono)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DecodedFile {
+    /// Open an audio file and decode enough of it to learn its sample rate and channel count.
+    ///
+    /// The container format is detected from the file's contents, using its
+    /// extension as a hint. This performs blocking I/O.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DeepgramError::IoError`] if the file cannot be read, or
+    /// [`DeepgramError::UnsupportedAudio`] if it contains no audio that can be decoded.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = std::fs::File::open(path)?;
+        let source = MediaSourceStream::new(Box::new(file), Default::default());
+
+        let mut hint = Hint::new();
+        if let Some(extension) = path.extension().and_then(|extension| extension.to_str()) {
+            hint.with_extension(extension);
+        }
+
+        let probed = symphonia::default::get_probe()
+            .format(
+                &hint,
+                source,
+                &FormatOptions::default(),
+                &MetadataOptions::default(),
+            )
+            .map_err(from_symphonia_error)?;
+
+        let track = probed
+            .format
+            .tracks()
+            .iter()
+            .find(|track| track.codec_params.codec != CODEC_TYPE_NULL)
+            .ok_or_else(|| DeepgramError::UnsupportedAudio("no audio track found".to_string()))?;
+        let track_id = track.id;
+        let decoder = symphonia::default::get_codecs()
+            .make(&track.codec_params, &DecoderOptions::default())
+            .map_err(from_symphonia_error)?;
+
+        let mut file = Self {
+            format: probed.format,
+            decoder,
+            track_id,
+            sample_rate: 0,
+            channels: 0,
+            mono: false,
+            pending: Vec::new(),
+        };
+
+        // Container metadata is not always reliable (or present) for sample
+        // rate and channel layout, so take them from the first decoded packet.
+        if !file.decode_packet()? {
+            return Err(DeepgramError::UnsupportedAudio(
+                "file contains no audio".to_string(),
+            ));
+        }
+
+        Ok(file)
+    }
+
+    /// Mix all channels down to one.
+    ///
+    /// Flux only accepts single-channel audio, so use this before
+    /// [`DecodedFile::configure_flux`] when the recording may be stereo.
+    pub fn mono(mut self) -> Self {
+        if !self.mono && self.channels > 1 {
+            self.pending = downmix(&self.pending, self.channels);
+        }
+        self.mono = true;
+        self
+    }
+
+    /// Samples per second, per channel.
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Number of interleaved channels in the stream produced by [`DecodedFile::into_stream`].
+    pub fn channels(&self) -> u16 {
+        if self.mono {
+            1
+        } else {
+            self.channels
+        }
+    }
+
+    /// Set the encoding, sample rate, and channel count of `builder` to match the decoded audio.
+    pub fn configure<'a>(&self, builder: WebsocketBuilder<'a>) -> WebsocketBuilder<'a> {
+        builder
+            .encoding(Encoding::Linear16)
+            .sample_rate(self.sample_rate())
+            .channels(self.channels())
+    }
+
+    /// Set the encoding and sample rate of `builder` to match the decoded audio.
+    pub fn configure_flux<'a>(&self, builder: FluxBuilder<'a>) -> FluxBuilder<'a> {
+        builder
+            .encoding(Encoding::Linear16)
+            .sample_rate(self.sample_rate())
+    }
+
+    /// Stream the decoded audio as linear16 in chunks of `frame_duration` of audio.
+    ///
+    /// Chunks always contain a whole number of frames (one sample per channel),
+    /// and are produced as fast as they are consumed. Decoding happens on a
+    /// blocking thread, so this must be called from within a Tokio runtime.
+    pub fn into_stream(
+        mut self,
+        frame_duration: Duration,
+    ) -> impl Stream<Item = Result<Bytes>> + Send + Unpin + 'static {
+        let frames_per_chunk =
+            ((f64::from(self.sample_rate) * frame_duration.as_secs_f64()) as usize).max(1);
+        let samples_per_chunk = frames_per_chunk * usize::from(self.channels());
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+
+        tokio::task::spawn_blocking(move || loop {
+            let mut done = false;
+            if self.pending.len() < samples_per_chunk {
+                match self.decode_packet() {
+                    Ok(more) => done = !more,
+                    Err(err) => {
+                        let _ = tx.blocking_send(Err(err));
+                        break;
+                    }
+                }
+            }
+
+            if self.pending.len() >= samples_per_chunk || (done && !self.pending.is_empty()) {
+                let take = samples_per_chunk.min(self.pending.len());
+                let mut chunk = BytesMut::with_capacity(take * 2);
+                for sample in self.pending.drain(..take) {
+                    chunk.put_i16_le(sample);
+                }
+                if tx.blocking_send(Ok(chunk.freeze())).is_err() {
+                    break;
+                }
+            } else if done {
+                break;
+            }
+        });
+
+        ReceiverStream::new(rx)
+    }
+
+    /// Decode the next packet into `pending`. Returns `false` at the end of the file.
+    fn decode_packet(&mut self) -> Result<bool> {
+        loop {
+            let packet = match self.format.next_packet() {
+                Ok(packet) => packet,
+                Err(SymphoniaError::IoError(err))
+                    if err.kind() == std::io::ErrorKind::UnexpectedEof =>
+                {
+                    return Ok(false)
+                }
+                Err(err) => return Err(from_symphonia_error(err)),
+            };
+            if packet.track_id() != self.track_id {
+                continue;
+            }
+
+            let decoded = match self.decoder.decode(&packet) {
+                Ok(decoded) => decoded,
+                // A corrupt packet is skipped rather than ending the stream.
+                Err(SymphoniaError::DecodeError(_)) => continue,
+                Err(err) => return Err(from_symphonia_error(err)),
+            };
+            if decoded.frames() == 0 {
+                continue;
+            }
+
+            let spec = *decoded.spec();
+            self.sample_rate = spec.rate;
+            self.channels = spec.channels.count() as u16;
+
+            let mut samples = SampleBuffer::<i16>::new(decoded.capacity() as u64, spec);
+            samples.copy_interleaved_ref(decoded);
+            if self.mono && self.channels > 1 {
+                self.pending
+                    .extend(downmix(samples.samples(), self.channels));
+            } else {
+                self.pending.extend_from_slice(samples.samples());
+            }
+
+            return Ok(true);
+        }
+    }
+}
+
+fn downmix(interleaved: &[i16], channels: u16) -> Vec<i16> {
+    interleaved
+        .chunks_exact(usize::from(channels))
+        .map(|frame| {
+            let sum: i32 = frame.iter().map(|&sample| i32::from(sample)).sum();
+            (sum / i32::from(channels)) as i16
+        })
+        .collect()
+}
+
+fn from_symphonia_error(err: SymphoniaError) -> DeepgramError {
+    match err {
+        SymphoniaError::IoError(err) => DeepgramError::IoError(err),
+        err => DeepgramError::UnsupportedAudio(err.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+
+    use super::*;
+
+    // symphonia can decode WAV, so hound-free test data is written by hand.
+    fn write_wav(path: &Path, sample_rate: u32, channels: u16, samples: &[i16]) {
+        let data_len = (samples.len() * 2) as u32;
+        let mut wav = Vec::new();
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes());
+        wav.extend_from_slice(&channels.to_le_bytes());
+        wav.extend_from_slice(&sample_rate.to_le_bytes());
+        wav.extend_from_slice(&(sample_rate * u32::from(channels) * 2).to_le_bytes());
+        wav.extend_from_slice(&(channels * 2).to_le_bytes());
+        wav.extend_from_slice(&16u16.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&data_len.to_le_bytes());
+        for sample in samples {
+            wav.extend_from_slice(&sample.to_le_bytes());
+        }
+        std::fs::write(path, wav).unwrap();
+    }
+
+    #[tokio::test]
+    async fn decodes_and_downmixes() {
+        let path = std::env::temp_dir().join(format!(
+            "deepgram-decode-test-stereo-{}.wav",
+            std::process::id()
+        ));
+        let samples: Vec<i16> = (0..2000)
+            .map(|i| if i % 2 == 0 { 100 } else { 300 })
+            .collect();
+        write_wav(&path, 8000, 2, &samples);
+
+        let audio = DecodedFile::open(&path).unwrap();
+        assert_eq!(audio.sample_rate(), 8000);
+        assert_eq!(audio.channels(), 2);
+
+        let audio = audio.mono();
+        assert_eq!(audio.channels(), 1);
+
+        let chunks: Vec<Bytes> = audio
+            .into_stream(Duration::from_millis(40))
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await;
+
+        // 1000 mono frames in chunks of 320 frames.
+        let lengths: Vec<usize> = chunks.iter().map(Bytes::len).collect();
+        assert_eq!(lengths, vec![640, 640, 640, 80]);
+        assert!(chunks
+            .concat()
+            .chunks_exact(2)
+            .all(|sample| i16::from_le_bytes([sample[0], sample[1]]) == 200));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn rejects_unknown_format() {
+        let path = std::env::temp_dir().join(format!(
+            "deepgram-decode-test-garbage-{}.mp3",
+            std::process::id()
+        ));
+        std::fs::write(&path, [0u8; 64]).unwrap();
+
+        assert!(matches!(
+            DecodedFile::open(&path),
+            Err(DeepgramError::UnsupportedAudio(_))
+        ));
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
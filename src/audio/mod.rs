@@ -0,0 +1,45 @@
+//! Utilities for capturing and preparing audio before sending it to Deepgram.
+
+#[cfg(feature = "audio-utils")]
+pub mod codec;
+
+#[cfg(feature = "audio-utils")]
+pub mod convert;
+
+#[cfg(feature = "decode")]
+pub mod decode;
+
+#[cfg(feature = "hound")]
+pub mod wav;
+
+#[cfg(feature = "microphone")]
+pub use crate::listen::sources::MicrophoneStream;
+
+/// Start capturing audio from the default input device.
+///
+/// Shorthand for [`Microphone::default_input`] followed by
+/// [`Microphone::into_stream`]. The returned stream reports the device's
+/// sample rate and channel count, and can configure a
+/// [`WebsocketBuilder`](crate::listen::websocket::WebsocketBuilder) to match.
+///
+/// ```no_run
+/// # use deepgram::{audio::microphone_stream, Deepgram, DeepgramError};
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), DeepgramError> {
+/// let dg = Deepgram::new(std::env::var("DEEPGRAM_API_KEY").unwrap_or_default())?;
+///
+/// let audio = microphone_stream()?;
+/// let results = audio
+///     .configure(dg.transcription().stream_request())
+///     .stream(audio)
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Microphone::default_input`]: crate::listen::sources::Microphone::default_input
+/// [`Microphone::into_stream`]: crate::listen::sources::Microphone::into_stream
+#[cfg(feature = "microphone")]
+pub fn microphone_stream() -> crate::Result<MicrophoneStream> {
+    crate::listen::sources::Microphone::default_input()?.into_stream()
+}
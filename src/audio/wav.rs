@@ -0,0 +1,268 @@
+//! Stream WAV files with the encoding parameters taken from their header.
+//!
+//! A WAV file's header describes exactly how its samples are encoded, but the
+//! live transcription API has to be told the encoding, sample rate, and channel
+//! count up front. Declaring them by hand is error prone, and a mismatch
+//! usually produces an empty transcript rather than an error. [`WavFile`] reads
+//! the header with [`hound`] and does both halves of the job: it configures a
+//! [`WebsocketBuilder`] and produces a stream of frame-aligned chunks of sample
+//! data, without the header.
+//!
+//! Only available with the `hound` feature.
+
+use std::{io::BufReader, path::Path, time::Duration};
+
+use bytes::{BufMut, Bytes, BytesMut};
+use futures::Stream;
+use hound::{SampleFormat, WavReader};
+use tokio_stream::wrappers::ReceiverStream;
+
+use crate::{
+    common::options::Encoding, listen::websocket::WebsocketBuilder, DeepgramError, Result,
+};
+
+/// The parameters Deepgram needs to decode the audio produced by [`WavFile::into_stream`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct WavSpec {
+    /// [`Encoding::Linear16`] for integer WAV files, [`Encoding::Linear32`] for floating point ones.
+    pub encoding: Encoding,
+
+    /// Samples per second, per channel.
+    pub sample_rate: u32,
+
+    /// Number of interleaved channels.
+    pub channels: u16,
+}
+
+/// A WAV file opened for streaming.
+///
+/// Integer samples of any bit depth are converted to 16-bit linear PCM, and
+/// 32-bit floating point samples are sent as-is.
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # use deepgram::{audio::wav::WavFile, Deepgram, DeepgramError};
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), DeepgramError> {
+/// let dg = Deepgram::new(std::env::var("DEEPGRAM_API_KEY").unwrap_or_default())?;
+/// let transcription = dg.transcription();
+///
+/// let wav = WavFile::open("examples/audio/bueller.wav")?;
+/// println!("{:?}", wav.spec());
+///
+/// let builder = wav.configure(transcription.stream_request());
+/// let results = builder
+///     .stream(wav.into_stream(Duration::from_millis(100)))
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct WavFile {
+    reader: WavReader<BufReader<std::fs::File>>,
+    spec: WavSpec,
+}
+
+impl std::fmt::Debug for WavFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WavFile")
+            .field("spec", &self.spec)
+            .finish_non_exhaustive()
+    }
+}
+
+impl WavFile {
+    /// Open a WAV file and read its header.
+    ///
+    /// This performs blocking I/O.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DeepgramError::IoError`] if the file cannot be read, or
+    /// [`DeepgramError::UnsupportedAudio`] if it is not a WAV file with
+    /// integer or 32-bit floating point samples.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let reader = WavReader::open(path).map_err(from_hound_error)?;
+        let header = reader.spec();
+
+        let encoding = match (header.sample_format, header.bits_per_sample) {
+            (SampleFormat::Int, 1..=32) => Encoding::Linear16,
+            (SampleFormat::Float, 32) => Encoding::Linear32,
+            (format, bits) => {
+                return Err(DeepgramError::UnsupportedAudio(format!(
+                    "{bits}-bit {format:?} WAV samples are not supported"
+                )))
+            }
+        };
+
+        Ok(Self {
+            spec: WavSpec {
+                encoding,
+                sample_rate: header.sample_rate,
+                channels: header.channels,
+            },
+            reader,
+        })
+    }
+
+    /// The parameters Deepgram needs to decode this file's audio.
+    pub fn spec(&self) -> &WavSpec {
+        &self.spec
+    }
+
+    /// The duration of the audio in the file.
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f64(
+            f64::from(self.reader.duration()) / f64::from(self.spec.sample_rate),
+        )
+    }
+
+    /// Set the encoding, sample rate, and channel count of `builder` to match this file.
+    pub fn configure<'a>(&self, builder: WebsocketBuilder<'a>) -> WebsocketBuilder<'a> {
+        builder
+            .encoding(self.spec.encoding.clone())
+            .sample_rate(self.spec.sample_rate)
+            .channels(self.spec.channels)
+    }
+
+    /// Stream the file's sample data in chunks of `frame_duration` of audio.
+    ///
+    /// Chunks always contain a whole number of frames (one sample per channel),
+    /// and are produced as fast as they are consumed. Reading happens on a
+    /// blocking thread, so this must be called from within a Tokio runtime.
+    pub fn into_stream(
+        self,
+        frame_duration: Duration,
+    ) -> impl Stream<Item = Result<Bytes>> + Send + Unpin + 'static {
+        let frames_per_chunk =
+            ((f64::from(self.spec.sample_rate) * frame_duration.as_secs_f64()) as usize).max(1);
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+
+        tokio::task::spawn_blocking(move || {
+            let Self { mut reader, spec } = self;
+            let samples_per_chunk = frames_per_chunk * usize::from(spec.channels);
+
+            loop {
+                match read_chunk(&mut reader, samples_per_chunk) {
+                    Ok(chunk) if chunk.is_empty() => break,
+                    Ok(chunk) => {
+                        if tx.blocking_send(Ok(chunk)).is_err() {
+                            break;
+                        }
+                    }
+                    Err(err) => {
+                        let _ = tx.blocking_send(Err(err));
+                        break;
+                    }
+                }
+            }
+        });
+
+        ReceiverStream::new(rx)
+    }
+}
+
+fn read_chunk(reader: &mut WavReader<BufReader<std::fs::File>>, samples: usize) -> Result<Bytes> {
+    let header = reader.spec();
+    let mut chunk = BytesMut::with_capacity(samples * 4);
+
+    match header.sample_format {
+        SampleFormat::Float => {
+            for sample in reader.samples::<f32>().take(samples) {
+                chunk.put_f32_le(sample.map_err(from_hound_error)?);
+            }
+        }
+        SampleFormat::Int => {
+            let bits = u32::from(header.bits_per_sample);
+            for sample in reader.samples::<i32>().take(samples) {
+                let sample = sample.map_err(from_hound_error)?;
+                let sample = if bits > 16 {
+                    sample >> (bits - 16)
+                } else {
+                    sample << (16 - bits)
+                };
+                chunk.put_i16_le(sample as i16);
+            }
+        }
+    }
+
+    Ok(chunk.freeze())
+}
+
+fn from_hound_error(err: hound::Error) -> DeepgramError {
+    match err {
+        hound::Error::IoError(err) => DeepgramError::IoError(err),
+        err => DeepgramError::UnsupportedAudio(err.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+
+    use super::*;
+
+    fn write_wav(path: &Path, spec: hound::WavSpec, samples: impl IntoIterator<Item = i32>) {
+        let mut writer = hound::WavWriter::create(path, spec).unwrap();
+        for sample in samples {
+            writer.write_sample(sample).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    #[tokio::test]
+    async fn streams_frame_aligned_linear16() {
+        let path =
+            std::env::temp_dir().join(format!("deepgram-wav-test-8bit-{}.wav", std::process::id()));
+        write_wav(
+            &path,
+            hound::WavSpec {
+                channels: 2,
+                sample_rate: 1000,
+                bits_per_sample: 8,
+                sample_format: SampleFormat::Int,
+            },
+            (0..250).map(|i| i % 100 - 50),
+        );
+
+        let wav = WavFile::open(&path).unwrap();
+        assert_eq!(
+            wav.spec(),
+            &WavSpec {
+                encoding: Encoding::Linear16,
+                sample_rate: 1000,
+                channels: 2,
+            }
+        );
+        assert_eq!(wav.duration(), Duration::from_millis(125));
+
+        let chunks: Vec<Bytes> = wav
+            .into_stream(Duration::from_millis(20))
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await;
+
+        // 20 frames of 2 channels of 2 bytes per chunk, with a short final chunk.
+        assert_eq!(chunks.len(), 7);
+        assert!(chunks[..6].iter().all(|chunk| chunk.len() == 80));
+        assert_eq!(chunks[6].len(), 20);
+        assert_eq!(&chunks[0][..2], &((-50i16) << 8).to_le_bytes());
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn rejects_non_wav() {
+        let path = std::env::temp_dir().join(format!(
+            "deepgram-wav-test-not-a-wav-{}.wav",
+            std::process::id()
+        ));
+        std::fs::write(&path, b"definitely not a wav file").unwrap();
+
+        assert!(matches!(
+            WavFile::open(&path),
+            Err(DeepgramError::UnsupportedAudio(_))
+        ));
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
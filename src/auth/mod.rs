@@ -105,7 +105,7 @@ impl Auth<'_> {
     /// # }
     /// ```
     pub async fn grant(&self, options: Option<&Options>) -> crate::Result<GrantResponse> {
-        let url = "https://api.deepgram.com/v1/auth/grant";
+        let url = self.0.manage_url("v1/auth/grant");
 
         let request = if let Some(opts) = options {
             self.0
@@ -117,6 +117,6 @@ impl Auth<'_> {
             self.0.client.post(url).json(&serde_json::json!({}))
         };
 
-        send_and_translate_response(request).await
+        send_and_translate_response(self.0, request).await
     }
 }
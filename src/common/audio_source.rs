@@ -4,22 +4,66 @@
 //!
 //! [api]: https://developers.deepgram.com/api-reference/#transcription-prerecorded
 
-use reqwest::{header::CONTENT_TYPE, RequestBuilder};
+use std::{
+    error::Error,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use bytes::Bytes;
+use futures::{Stream, StreamExt};
+use http_body_util::BodyDataStream;
+use reqwest::{
+    header::{CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE},
+    RequestBuilder,
+};
 use serde::Serialize;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use url::Url;
+
+use crate::{DeepgramError, RedactedUrl, Result};
 
-/// Used as a parameter for [`Transcription::prerecorded`](crate::Transcription::prerecorded) and similar functions.
+#[cfg_attr(
+    feature = "listen-rest",
+    doc = "Used as a parameter for [`Transcription::prerecorded`](crate::Transcription::prerecorded) and similar functions."
+)]
+#[cfg_attr(
+    not(feature = "listen-rest"),
+    doc = "Used as a parameter for `Transcription::prerecorded` and similar functions."
+)]
 #[derive(Debug)]
 pub struct AudioSource(InternalAudioSource);
 
-#[derive(Debug)]
 enum InternalAudioSource {
     Url(String),
     Buffer {
         buffer: reqwest::Body,
         mime_type: Option<String>,
+        content_length: Option<u64>,
+        gzip: bool,
     },
 }
 
+impl fmt::Debug for InternalAudioSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InternalAudioSource::Url(url) => fmt_url(url, f),
+            InternalAudioSource::Buffer {
+                buffer,
+                mime_type,
+                content_length,
+                gzip,
+            } => f
+                .debug_struct("Buffer")
+                .field("buffer", buffer)
+                .field("mime_type", mime_type)
+                .field("content_length", content_length)
+                .field("gzip", gzip)
+                .finish(),
+        }
+    }
+}
+
 impl AudioSource {
     /// Constructs an [`AudioSource`] that will instruct Deepgram to download the audio from the specified URL.
     pub fn from_url(url: impl Into<String>) -> Self {
@@ -39,6 +83,8 @@ impl AudioSource {
         Self(InternalAudioSource::Buffer {
             buffer: buffer.into(),
             mime_type: None,
+            content_length: None,
+            gzip: false,
         })
     }
 
@@ -52,9 +98,212 @@ impl AudioSource {
         Self(InternalAudioSource::Buffer {
             buffer: buffer.into(),
             mime_type: Some(mime_type.into()),
+            content_length: None,
+            gzip: false,
+        })
+    }
+
+    /// Constructs an [`AudioSource`] that will upload the audio produced by `stream`
+    /// to Deepgram as part of the request, such as a download from object storage.
+    ///
+    /// The audio is sent as it is produced, so it never needs to be held in
+    /// memory all at once. If the total size is known, pass it as
+    /// `content_length` to send it in the `Content-Length` header. Otherwise
+    /// the upload uses chunked transfer encoding.
+    ///
+    /// Use [`AudioSource::from_stream_with_mime_type`] if you want to specify a [MIME type][mime].
+    ///
+    /// [mime]: https://developer.mozilla.org/en-US/docs/Web/HTTP/Basics_of_HTTP/MIME_types#audio_and_video_types
+    pub fn from_stream<S, E>(stream: S, content_length: Option<u64>) -> Self
+    where
+        S: Stream<Item = Result<Bytes, E>> + Send + 'static,
+        E: Into<Box<dyn Error + Send + Sync>> + 'static,
+    {
+        Self(InternalAudioSource::Buffer {
+            buffer: reqwest::Body::wrap_stream(stream),
+            mime_type: None,
+            content_length,
+            gzip: false,
+        })
+    }
+
+    /// Same as [`AudioSource::from_stream`], but allows you to specify a [MIME type][mime].
+    ///
+    /// [mime]: https://developer.mozilla.org/en-US/docs/Web/HTTP/Basics_of_HTTP/MIME_types#audio_and_video_types
+    pub fn from_stream_with_mime_type<S, E>(
+        stream: S,
+        content_length: Option<u64>,
+        mime_type: impl Into<String>,
+    ) -> Self
+    where
+        S: Stream<Item = Result<Bytes, E>> + Send + 'static,
+        E: Into<Box<dyn Error + Send + Sync>> + 'static,
+    {
+        Self(InternalAudioSource::Buffer {
+            buffer: reqwest::Body::wrap_stream(stream),
+            mime_type: Some(mime_type.into()),
+            content_length,
+            gzip: false,
         })
     }
 
+    /// Constructs an [`AudioSource`] that will upload the audio file at `path`
+    /// to Deepgram as part of the request.
+    ///
+    /// The MIME type is detected from the start of the file, falling back to
+    /// its extension, and the file size is sent as the content length. The
+    /// file is streamed rather than read into memory.
+    ///
+    /// ```no_run
+    /// # use deepgram::{common::audio_source::AudioSource, DeepgramError};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), DeepgramError> {
+    /// let source = AudioSource::from_path("examples/audio/bueller.wav").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DeepgramError::IoError`] if the file cannot be read, and
+    /// [`DeepgramError::UnsupportedAudio`] if it is not in a recognized audio
+    /// or video container.
+    pub async fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let mut file = tokio::fs::File::open(path).await?;
+        let content_length = file.metadata().await?.len();
+
+        let mut header = Vec::with_capacity(MAGIC_LEN);
+        (&mut file)
+            .take(MAGIC_LEN as u64)
+            .read_to_end(&mut header)
+            .await?;
+        file.rewind().await?;
+
+        let mime_type = mime_type_from_magic(&header)
+            .or_else(|| mime_type_from_extension(path))
+            .ok_or_else(|| {
+                DeepgramError::UnsupportedAudio(format!(
+                    "could not detect the audio format of {}; supported formats are {SUPPORTED_FORMATS}",
+                    path.display()
+                ))
+            })?;
+
+        Ok(Self(InternalAudioSource::Buffer {
+            buffer: file.into(),
+            mime_type: Some(mime_type.to_string()),
+            content_length: Some(content_length),
+            gzip: false,
+        }))
+    }
+
+    /// Calls `callback` as the audio is uploaded, with the number of bytes
+    /// sent so far and the total size, if known.
+    ///
+    /// This lets long uploads report their progress. It has no effect on a
+    /// source made with [`AudioSource::from_url`], since Deepgram downloads
+    /// that audio itself.
+    ///
+    /// ```no_run
+    /// # use deepgram::{common::audio_source::AudioSource, DeepgramError};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), DeepgramError> {
+    /// let source = AudioSource::from_path("examples/audio/bueller.wav")
+    ///     .await?
+    ///     .on_upload_progress(|sent, total| {
+    ///         if let Some(total) = total {
+    ///             println!("uploaded {}%", sent * 100 / total.max(1));
+    ///         }
+    ///     });
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn on_upload_progress(
+        self,
+        mut callback: impl FnMut(u64, Option<u64>) + Send + 'static,
+    ) -> Self {
+        match self.0 {
+            InternalAudioSource::Url(_) => self,
+            InternalAudioSource::Buffer {
+                buffer,
+                mime_type,
+                content_length,
+                gzip,
+            } => {
+                let total =
+                    content_length.or_else(|| buffer.as_bytes().map(|bytes| bytes.len() as u64));
+                let mut sent = 0;
+                let stream = BodyDataStream::new(buffer).inspect(move |chunk| {
+                    if let Ok(chunk) = chunk {
+                        sent += chunk.len() as u64;
+                        callback(sent, total);
+                    }
+                });
+
+                Self(InternalAudioSource::Buffer {
+                    buffer: reqwest::Body::wrap_stream(stream),
+                    mime_type,
+                    content_length: total,
+                    gzip,
+                })
+            }
+        }
+    }
+
+    /// Compresses the uploaded audio with gzip and sends it with a
+    /// `Content-Encoding: gzip` header.
+    ///
+    /// Uncompressed formats such as WAV often shrink considerably, which cuts
+    /// upload time on slow links. Already compressed formats such as MP3 gain
+    /// little. In-memory buffers are compressed up front; streams and files
+    /// are compressed as they are uploaded, so their length is no longer
+    /// known in advance. This has no effect on a source made with
+    /// [`AudioSource::from_url`].
+    ///
+    /// ```no_run
+    /// # use deepgram::{common::audio_source::AudioSource, DeepgramError};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), DeepgramError> {
+    /// let source = AudioSource::from_path("examples/audio/bueller.wav")
+    ///     .await?
+    ///     .gzip();
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "gzip")]
+    pub fn gzip(self) -> Self {
+        match self.0 {
+            InternalAudioSource::Buffer {
+                buffer,
+                mime_type,
+                gzip: false,
+                ..
+            } => {
+                let (buffer, content_length) = match buffer.as_bytes() {
+                    Some(bytes) => {
+                        let compressed = gzip::compress(bytes);
+                        let content_length = compressed.len() as u64;
+                        (compressed.into(), Some(content_length))
+                    }
+                    None => (
+                        reqwest::Body::wrap_stream(gzip::compress_stream(BodyDataStream::new(
+                            buffer,
+                        ))),
+                        None,
+                    ),
+                };
+
+                Self(InternalAudioSource::Buffer {
+                    buffer,
+                    mime_type,
+                    content_length,
+                    gzip: true,
+                })
+            }
+            _ => self,
+        }
+    }
+
     #[allow(missing_docs)]
     pub fn fill_body(self, request_builder: RequestBuilder) -> RequestBuilder {
         match self.0 {
@@ -66,15 +315,438 @@ impl AudioSource {
 
                 request_builder.json(&UrlSource { url })
             }
-            InternalAudioSource::Buffer { buffer, mime_type } => {
-                let request_builder = request_builder.body(buffer);
+            InternalAudioSource::Buffer {
+                buffer,
+                mime_type,
+                content_length,
+                gzip,
+            } => {
+                let mut request_builder = request_builder.body(buffer);
 
                 if let Some(mime_type) = mime_type {
-                    request_builder.header(CONTENT_TYPE, mime_type)
-                } else {
-                    request_builder
+                    request_builder = request_builder.header(CONTENT_TYPE, mime_type);
+                }
+                if let Some(content_length) = content_length {
+                    request_builder = request_builder.header(CONTENT_LENGTH, content_length);
+                }
+                if gzip {
+                    request_builder = request_builder.header(CONTENT_ENCODING, "gzip");
+                }
+                request_builder
+            }
+        }
+    }
+}
+
+#[cfg(feature = "gzip")]
+mod gzip {
+    use std::io::{self, Write};
+
+    use bytes::Bytes;
+    use flate2::{write::GzEncoder, Compression};
+    use futures::{stream, Stream, StreamExt};
+
+    pub(super) fn compress(bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        // Writing to a `Vec` cannot fail.
+        encoder.write_all(bytes).expect("write to Vec");
+        encoder.finish().expect("write to Vec")
+    }
+
+    /// Compresses `chunks` as they are produced, yielding compressed output
+    /// whenever the encoder has some.
+    pub(super) fn compress_stream<S>(
+        chunks: S,
+    ) -> impl Stream<Item = io::Result<Bytes>> + Send + 'static
+    where
+        S: Stream<Item = Result<Bytes, reqwest::Error>> + Send + 'static,
+    {
+        let encoder = GzEncoder::new(Vec::new(), Compression::default());
+        stream::unfold(
+            (Box::pin(chunks), Some(encoder)),
+            |(mut chunks, encoder)| async move {
+                let mut encoder = encoder?;
+                loop {
+                    match chunks.next().await {
+                        Some(Ok(chunk)) => {
+                            encoder.write_all(&chunk).expect("write to Vec");
+                            if !encoder.get_ref().is_empty() {
+                                let output = std::mem::take(encoder.get_mut());
+                                return Some((Ok(output.into()), (chunks, Some(encoder))));
+                            }
+                        }
+                        Some(Err(err)) => {
+                            return Some((Err(io::Error::other(err)), (chunks, None)))
+                        }
+                        None => {
+                            let output = encoder.finish().map(Bytes::from);
+                            return Some((output, (chunks, None)));
+                        }
+                    }
                 }
+            },
+        )
+    }
+}
+
+/// Formats a source URL, which may be presigned with credentials in its query string.
+fn fmt_url(url: &str, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match Url::parse(url) {
+        Ok(url) => f.debug_tuple("Url").field(&RedactedUrl(&url)).finish(),
+        Err(_) => f.debug_tuple("Url").field(&url).finish(),
+    }
+}
+
+/// Audio that can be uploaded more than once, so a failed request can be retried.
+///
+/// An [`AudioSource`] made from a stream or file can only be read once. A
+/// `ReplayableAudioSource` instead holds the audio in memory or re-opens the
+/// file for each attempt. Use it with
+#[cfg_attr(
+    feature = "listen-rest",
+    doc = "[`Transcription::prerecorded_replayable`](crate::Transcription::prerecorded_replayable),"
+)]
+#[cfg_attr(
+    not(feature = "listen-rest"),
+    doc = "`Transcription::prerecorded_replayable`,"
+)]
+/// which resends the audio when the request is retried, or call
+/// [`ReplayableAudioSource::audio_source`] for each attempt of your own retry loop.
+#[derive(Debug, Clone)]
+pub struct ReplayableAudioSource(InternalReplayableAudioSource);
+
+#[derive(Clone)]
+enum InternalReplayableAudioSource {
+    Url(String),
+    Bytes {
+        bytes: Bytes,
+        mime_type: Option<String>,
+    },
+    Path(PathBuf),
+}
+
+impl fmt::Debug for InternalReplayableAudioSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InternalReplayableAudioSource::Url(url) => fmt_url(url, f),
+            InternalReplayableAudioSource::Bytes { bytes, mime_type } => f
+                .debug_struct("Bytes")
+                .field("len", &bytes.len())
+                .field("mime_type", mime_type)
+                .finish(),
+            InternalReplayableAudioSource::Path(path) => f.debug_tuple("Path").field(path).finish(),
+        }
+    }
+}
+
+impl ReplayableAudioSource {
+    /// Constructs a [`ReplayableAudioSource`] that will instruct Deepgram to
+    /// download the audio from the specified URL, as in [`AudioSource::from_url`].
+    pub fn from_url(url: impl Into<String>) -> Self {
+        Self(InternalReplayableAudioSource::Url(url.into()))
+    }
+
+    /// Constructs a [`ReplayableAudioSource`] that uploads `bytes`.
+    ///
+    /// Cloning [`Bytes`] is cheap, so the audio is not copied for each attempt.
+    pub fn from_bytes(bytes: impl Into<Bytes>) -> Self {
+        Self(InternalReplayableAudioSource::Bytes {
+            bytes: bytes.into(),
+            mime_type: None,
+        })
+    }
+
+    /// Same as [`ReplayableAudioSource::from_bytes`], but allows you to specify a [MIME type][mime].
+    ///
+    /// [mime]: https://developer.mozilla.org/en-US/docs/Web/HTTP/Basics_of_HTTP/MIME_types#audio_and_video_types
+    pub fn from_bytes_with_mime_type(
+        bytes: impl Into<Bytes>,
+        mime_type: impl Into<String>,
+    ) -> Self {
+        Self(InternalReplayableAudioSource::Bytes {
+            bytes: bytes.into(),
+            mime_type: Some(mime_type.into()),
+        })
+    }
+
+    /// Constructs a [`ReplayableAudioSource`] that opens the file at `path`
+    /// for each attempt, as in [`AudioSource::from_path`].
+    ///
+    /// The file is not opened until the first attempt, so errors reading it
+    /// are reported then.
+    pub fn from_path(path: impl Into<PathBuf>) -> Self {
+        Self(InternalReplayableAudioSource::Path(path.into()))
+    }
+
+    /// A fresh [`AudioSource`] for one attempt at uploading the audio.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`AudioSource::from_path`] for a source made
+    /// with [`ReplayableAudioSource::from_path`].
+    pub async fn audio_source(&self) -> Result<AudioSource> {
+        match &self.0 {
+            InternalReplayableAudioSource::Url(url) => Ok(AudioSource::from_url(url.clone())),
+            InternalReplayableAudioSource::Bytes { bytes, mime_type } => {
+                let content_length = bytes.len() as u64;
+                Ok(AudioSource(InternalAudioSource::Buffer {
+                    buffer: bytes.clone().into(),
+                    mime_type: mime_type.clone(),
+                    content_length: Some(content_length),
+                    gzip: false,
+                }))
             }
+            InternalReplayableAudioSource::Path(path) => AudioSource::from_path(path).await,
+        }
+    }
+}
+
+/// How many bytes at the start of a file [`mime_type_from_magic`] looks at.
+const MAGIC_LEN: usize = 12;
+
+const SUPPORTED_FORMATS: &str = "WAV, MP3, FLAC, Ogg, WebM, MP4/M4A, AAC, AIFF, and AMR";
+
+/// The MIME type of a file starting with `header`, identified by its signature.
+fn mime_type_from_magic(header: &[u8]) -> Option<&'static str> {
+    match header {
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'A', b'V', b'E', ..] => Some("audio/wav"),
+        [b'F', b'O', b'R', b'M', _, _, _, _, b'A', b'I', b'F', ..] => Some("audio/aiff"),
+        [b'f', b'L', b'a', b'C', ..] => Some("audio/flac"),
+        [b'O', b'g', b'g', b'S', ..] => Some("audio/ogg"),
+        [0x1A, 0x45, 0xDF, 0xA3, ..] => Some("audio/webm"),
+        [_, _, _, _, b'f', b't', b'y', b'p', ..] => Some("audio/mp4"),
+        [b'#', b'!', b'A', b'M', b'R', ..] => Some("audio/amr"),
+        [b'I', b'D', b'3', ..] => Some("audio/mpeg"),
+        // ADTS frames have layer bits of zero, unlike MPEG audio frames.
+        [0xFF, second, ..] if second & 0xF6 == 0xF0 => Some("audio/aac"),
+        [0xFF, second, ..] if second & 0xE0 == 0xE0 && second & 0x06 != 0 => Some("audio/mpeg"),
+        _ => None,
+    }
+}
+
+/// The MIME type of the file at `path`, based on its extension.
+fn mime_type_from_extension(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    Some(match extension.as_str() {
+        "wav" | "wave" => "audio/wav",
+        "mp3" => "audio/mpeg",
+        "flac" => "audio/flac",
+        "ogg" | "oga" | "opus" => "audio/ogg",
+        "webm" => "audio/webm",
+        "m4a" | "mp4" => "audio/mp4",
+        "aac" => "audio/aac",
+        "aif" | "aiff" => "audio/aiff",
+        "amr" => "audio/amr",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use reqwest::header::{CONTENT_LENGTH, CONTENT_TYPE};
+
+    use super::{
+        mime_type_from_extension, mime_type_from_magic, AudioSource, ReplayableAudioSource,
+    };
+    use crate::DeepgramError;
+
+    #[test]
+    fn stream_source_sets_content_length() {
+        let chunks = futures::stream::iter([Ok::<_, std::io::Error>(Bytes::from_static(b"RIFF"))]);
+        let source = AudioSource::from_stream_with_mime_type(chunks, Some(4), "audio/wav");
+
+        let request = source
+            .fill_body(reqwest::Client::new().post("https://example.com"))
+            .build()
+            .unwrap();
+        assert_eq!(request.headers()[CONTENT_LENGTH], "4");
+        assert_eq!(request.headers()[CONTENT_TYPE], "audio/wav");
+    }
+
+    #[test]
+    fn stream_source_without_length_is_chunked() {
+        let chunks = futures::stream::iter([Ok::<_, std::io::Error>(Bytes::from_static(b"RIFF"))]);
+        let source = AudioSource::from_stream(chunks, None);
+
+        let request = source
+            .fill_body(reqwest::Client::new().post("https://example.com"))
+            .build()
+            .unwrap();
+        assert!(!request.headers().contains_key(CONTENT_LENGTH));
+        assert!(request.body().unwrap().as_bytes().is_none());
+    }
+
+    #[tokio::test]
+    async fn reports_upload_progress() {
+        use std::sync::{Arc, Mutex};
+
+        use futures::StreamExt;
+        use http_body_util::BodyDataStream;
+
+        let progress = Arc::new(Mutex::new(Vec::new()));
+        let chunks = futures::stream::iter([
+            Ok::<_, std::io::Error>(Bytes::from_static(b"RIFF")),
+            Ok(Bytes::from_static(b"WAVE")),
+        ]);
+        let source = AudioSource::from_stream(chunks, Some(8)).on_upload_progress({
+            let progress = progress.clone();
+            move |sent, total| progress.lock().unwrap().push((sent, total))
+        });
+
+        let mut request = source
+            .fill_body(reqwest::Client::new().post("https://example.com"))
+            .build()
+            .unwrap();
+        assert_eq!(request.headers()[CONTENT_LENGTH], "8");
+        let body = request.body_mut().take().unwrap();
+        BodyDataStream::new(body).for_each(|_| async {}).await;
+
+        assert_eq!(*progress.lock().unwrap(), [(4, Some(8)), (8, Some(8))]);
+    }
+
+    #[test]
+    fn buffer_progress_knows_the_total() {
+        let source = AudioSource::from_buffer(b"RIFF".to_vec()).on_upload_progress(|_, _| {});
+
+        let request = source
+            .fill_body(reqwest::Client::new().post("https://example.com"))
+            .build()
+            .unwrap();
+        assert_eq!(request.headers()[CONTENT_LENGTH], "4");
+    }
+
+    #[cfg(feature = "gzip")]
+    #[tokio::test]
+    async fn gzip_compresses_buffers_and_streams() {
+        use std::io::Read;
+
+        use flate2::read::GzDecoder;
+        use futures::StreamExt;
+        use http_body_util::BodyDataStream;
+        use reqwest::header::CONTENT_ENCODING;
+
+        async fn decompressed(source: AudioSource) -> (reqwest::Request, Vec<u8>) {
+            let mut request = source
+                .fill_body(reqwest::Client::new().post("https://example.com"))
+                .build()
+                .unwrap();
+            let body = request.body_mut().take().unwrap();
+            let compressed: Vec<u8> = BodyDataStream::new(body)
+                .map(|chunk| chunk.unwrap().to_vec())
+                .concat()
+                .await;
+            let mut audio = Vec::new();
+            GzDecoder::new(&compressed[..])
+                .read_to_end(&mut audio)
+                .unwrap();
+            (request, audio)
+        }
+
+        let audio = b"RIFF\0\0\0\0WAVE".repeat(100);
+
+        let source = AudioSource::from_buffer_with_mime_type(audio.clone(), "audio/wav").gzip();
+        let (request, body) = decompressed(source).await;
+        assert_eq!(request.headers()[CONTENT_ENCODING], "gzip");
+        assert_eq!(request.headers()[CONTENT_TYPE], "audio/wav");
+        assert!(
+            request.headers()[CONTENT_LENGTH]
+                .to_str()
+                .unwrap()
+                .parse::<usize>()
+                .unwrap()
+                < audio.len()
+        );
+        assert_eq!(body, audio);
+
+        let chunks = futures::stream::iter(
+            audio
+                .chunks(64)
+                .map(|chunk| Ok::<_, std::io::Error>(Bytes::copy_from_slice(chunk)))
+                .collect::<Vec<_>>(),
+        );
+        let source = AudioSource::from_stream(chunks, Some(audio.len() as u64)).gzip();
+        let (request, body) = decompressed(source).await;
+        assert_eq!(request.headers()[CONTENT_ENCODING], "gzip");
+        assert!(!request.headers().contains_key(CONTENT_LENGTH));
+        assert_eq!(body, audio);
+    }
+
+    #[test]
+    fn detects_mime_type_from_magic_bytes() {
+        assert_eq!(
+            mime_type_from_magic(b"RIFF\x24\x08\x00\x00WAVEfmt "),
+            Some("audio/wav")
+        );
+        assert_eq!(mime_type_from_magic(b"ID3\x04\x00"), Some("audio/mpeg"));
+        assert_eq!(
+            mime_type_from_magic(&[0xFF, 0xFB, 0x90]),
+            Some("audio/mpeg")
+        );
+        assert_eq!(mime_type_from_magic(&[0xFF, 0xF1, 0x50]), Some("audio/aac"));
+        assert_eq!(
+            mime_type_from_magic(b"\x00\x00\x00\x20ftypM4A "),
+            Some("audio/mp4")
+        );
+        assert_eq!(mime_type_from_magic(b"fLaC"), Some("audio/flac"));
+        assert_eq!(mime_type_from_magic(b"%PDF-1.7"), None);
+
+        assert_eq!(
+            mime_type_from_extension("talk.OPUS".as_ref()),
+            Some("audio/ogg")
+        );
+        assert_eq!(mime_type_from_extension("notes.txt".as_ref()), None);
+    }
+
+    #[tokio::test]
+    async fn from_path_sets_mime_type_and_length() {
+        let source = AudioSource::from_path("examples/audio/bueller.wav")
+            .await
+            .unwrap();
+        let request = source
+            .fill_body(reqwest::Client::new().post("https://example.com"))
+            .build()
+            .unwrap();
+
+        let length = std::fs::metadata("examples/audio/bueller.wav")
+            .unwrap()
+            .len();
+        assert_eq!(request.headers()[CONTENT_TYPE], "audio/wav");
+        assert_eq!(
+            request.headers()[CONTENT_LENGTH],
+            length.to_string().as_str()
+        );
+    }
+
+    #[tokio::test]
+    async fn from_path_rejects_unknown_formats() {
+        let err = AudioSource::from_path("Cargo.toml").await.unwrap_err();
+        assert!(matches!(err, DeepgramError::UnsupportedAudio(_)));
+    }
+
+    #[tokio::test]
+    async fn replayable_source_can_be_sent_repeatedly() {
+        let source = ReplayableAudioSource::from_path("examples/audio/bueller.wav");
+        for _ in 0..2 {
+            let request = source
+                .audio_source()
+                .await
+                .unwrap()
+                .fill_body(reqwest::Client::new().post("https://example.com"))
+                .build()
+                .unwrap();
+            assert_eq!(request.headers()[CONTENT_TYPE], "audio/wav");
         }
+
+        let source = ReplayableAudioSource::from_bytes_with_mime_type(&b"RIFF"[..], "audio/wav");
+        let request = source
+            .audio_source()
+            .await
+            .unwrap()
+            .fill_body(reqwest::Client::new().post("https://example.com"))
+            .build()
+            .unwrap();
+        assert_eq!(request.body().unwrap().as_bytes(), Some(&b"RIFF"[..]));
+        assert_eq!(request.headers()[CONTENT_LENGTH], "4");
     }
 }
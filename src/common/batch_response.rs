@@ -4,10 +4,21 @@
 //!
 //! [api]: https://developers.deepgram.com/api-reference/#transcription-prerecorded-responses
 
+use std::sync::Arc;
+
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-/// Returned by [`Transcription::prerecorded`](crate::Transcription::prerecorded).
+use crate::common::{intern, options::Language};
+
+#[cfg_attr(
+    feature = "listen-rest",
+    doc = "Returned by [`Transcription::prerecorded`](crate::Transcription::prerecorded)."
+)]
+#[cfg_attr(
+    not(feature = "listen-rest"),
+    doc = "Returned by `Transcription::prerecorded`."
+)]
 ///
 /// See the [Deepgram API Reference][api] for more info.
 ///
@@ -19,10 +30,18 @@ pub struct Response {
     pub metadata: ListenMetadata,
 
     #[allow(missing_docs)]
+    #[serde(deserialize_with = "intern::scoped")]
     pub results: ListenResults,
 }
 
-/// Returned by [`Transcription::prerecorded_callback`](crate::Transcription::prerecorded_callback).
+#[cfg_attr(
+    feature = "listen-rest",
+    doc = "Returned by [`Transcription::prerecorded_callback`](crate::Transcription::prerecorded_callback)."
+)]
+#[cfg_attr(
+    not(feature = "listen-rest"),
+    doc = "Returned by `Transcription::prerecorded_callback`."
+)]
 ///
 /// See the [Deepgram Callback feature docs][docs] for more info.
 ///
@@ -34,6 +53,57 @@ pub struct CallbackResponse {
     pub request_id: Uuid,
 }
 
+#[cfg_attr(
+    feature = "listen-rest",
+    doc = "Returned by [`Transcription::prerecorded_raw`](crate::Transcription::prerecorded_raw)."
+)]
+#[cfg_attr(
+    not(feature = "listen-rest"),
+    doc = "Returned by `Transcription::prerecorded_raw`."
+)]
+///
+/// Holds the response body as untyped JSON, for fields this crate does not
+/// model yet.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[non_exhaustive]
+pub struct RawResponse {
+    /// The HTTP status of the response, serialized as its numeric code.
+    #[serde(with = "status_code")]
+    pub status: reqwest::StatusCode,
+
+    /// The request ID, taken from the `dg-request-id` header or, failing
+    /// that, from the response metadata.
+    pub request_id: Option<Uuid>,
+
+    /// The response body.
+    pub body: serde_json::Value,
+}
+
+impl RawResponse {
+    /// Parse the body as a typed [`Response`].
+    pub fn to_response(&self) -> serde_json::Result<Response> {
+        serde_json::from_value(self.body.clone())
+    }
+}
+
+mod status_code {
+    use reqwest::StatusCode;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(
+        status: &StatusCode,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u16(status.as_u16())
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<StatusCode, D::Error> {
+        StatusCode::from_u16(u16::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+}
+
 /// Metadata about the transcription.
 ///
 /// See the [Deepgram API Reference][api] for more info.
@@ -89,7 +159,9 @@ pub struct ListenResults {
     #[allow(missing_docs)]
     pub topics: Option<Topics>,
 
-    #[allow(missing_docs)]
+    /// [`None`] unless the [Summarization feature][docs] is set.
+    ///
+    /// [docs]: https://developers.deepgram.com/docs/summarization
     pub summary: Option<Summary>,
 }
 
@@ -117,7 +189,27 @@ pub struct ChannelResult {
     ///
     /// [bcp47]: https://tools.ietf.org/html/bcp47
     /// [docs]: https://developers.deepgram.com/docs/language-detection/
-    pub detected_language: Option<String>,
+    pub detected_language: Option<Language>,
+
+    /// Confidence in [`detected_language`](Self::detected_language), from 0 to 1.
+    ///
+    /// [`None`] unless the [Language Detection feature][docs] is set.
+    ///
+    /// [docs]: https://developers.deepgram.com/docs/language-detection/
+    pub language_confidence: Option<f64>,
+}
+
+impl ChannelResult {
+    /// The search results for `query`, one of the terms passed to
+    /// [`OptionsBuilder::search`](crate::common::options::OptionsBuilder::search).
+    ///
+    /// Returns [`None`] if the Search feature was not set or `query` was not searched for.
+    pub fn search_results(&self, query: &str) -> Option<&SearchResults> {
+        self.search
+            .as_deref()?
+            .iter()
+            .find(|results| results.query == query)
+    }
 }
 
 /// Transcription results for a single utterance.
@@ -137,7 +229,11 @@ pub struct Utterance {
     #[allow(missing_docs)]
     pub confidence: f64,
 
-    #[allow(missing_docs)]
+    /// The index of the audio channel the utterance was spoken on.
+    ///
+    /// Always `0` unless the [Multichannel feature][docs] is set.
+    ///
+    /// [docs]: https://developers.deepgram.com/documentation/features/multichannel/
     pub channel: usize,
 
     #[allow(missing_docs)]
@@ -155,6 +251,18 @@ pub struct Utterance {
     pub id: Uuid,
 }
 
+impl Utterance {
+    /// The transcript prefixed with its speaker, such as `"Speaker 0: Hello."`.
+    ///
+    /// Returns the bare transcript if the Diarization feature was not set.
+    pub fn text_with_speaker(&self) -> String {
+        match self.speaker {
+            Some(speaker) => format!("Speaker {speaker}: {}", self.transcript),
+            None => self.transcript.clone(),
+        }
+    }
+}
+
 /// Search results.
 ///
 /// See the [Deepgram API Reference][api]
@@ -180,15 +288,65 @@ pub struct Sentence {
     end: f64,
 }
 
+impl Sentence {
+    /// The text of the sentence.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The start time of the sentence in seconds.
+    pub fn start(&self) -> f64 {
+        self.start
+    }
+
+    /// The end time of the sentence in seconds.
+    pub fn end(&self) -> f64 {
+        self.end
+    }
+}
+
 /// Paragraph
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct Paragraph {
     sentences: Vec<Sentence>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    speaker: Option<usize>,
     num_words: usize,
     start: f64,
     end: f64,
 }
 
+impl Paragraph {
+    /// The sentences in the paragraph, in order.
+    pub fn sentences(&self) -> impl Iterator<Item = &Sentence> {
+        self.sentences.iter()
+    }
+
+    /// The speaker of the paragraph.
+    ///
+    /// [`None`] unless the [Diarization feature][docs] is set.
+    ///
+    /// [docs]: https://developers.deepgram.com/docs/diarization
+    pub fn speaker(&self) -> Option<usize> {
+        self.speaker
+    }
+
+    /// The number of words in the paragraph.
+    pub fn num_words(&self) -> usize {
+        self.num_words
+    }
+
+    /// The start time of the paragraph in seconds.
+    pub fn start(&self) -> f64 {
+        self.start
+    }
+
+    /// The end time of the paragraph in seconds.
+    pub fn end(&self) -> f64 {
+        self.end
+    }
+}
+
 /// Paragraph results.
 ///
 /// See the [Deepgram API Reference][api]
@@ -202,6 +360,31 @@ pub struct Paragraphs {
     paragraphs: Vec<Paragraph>,
 }
 
+impl Paragraphs {
+    /// The transcript, with paragraphs separated by blank lines.
+    pub fn transcript(&self) -> &str {
+        &self.transcript
+    }
+
+    /// The paragraphs, in order.
+    pub fn paragraphs(&self) -> impl Iterator<Item = &Paragraph> {
+        self.paragraphs.iter()
+    }
+
+    /// Rewrite the transcript and the text of every sentence with `f`.
+    pub(crate) fn map_text(&mut self, mut f: impl FnMut(&str) -> String) {
+        self.transcript = f(&self.transcript);
+
+        for sentence in self
+            .paragraphs
+            .iter_mut()
+            .flat_map(|paragraph| &mut paragraph.sentences)
+        {
+            sentence.text = f(&sentence.text);
+        }
+    }
+}
+
 /// Entity Detection results.
 ///
 /// See the [Deepgram API Reference][api]
@@ -314,9 +497,62 @@ pub struct Topics {
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct Summary {
     result: String,
+    #[serde(default)]
     short: String,
 }
 
+impl Summary {
+    /// Whether the summary was generated, as reported in its `result` field.
+    ///
+    /// Deepgram reports `"success"` when it could summarize the audio.
+    pub fn is_success(&self) -> bool {
+        self.result == "success"
+    }
+
+    /// The `result` field, such as `"success"`.
+    pub fn result(&self) -> &str {
+        &self.result
+    }
+
+    /// The summary of the whole transcript.
+    pub fn short(&self) -> &str {
+        &self.short
+    }
+}
+
+/// A summary of part of a transcript, from the legacy (v1) Summarization feature.
+///
+/// Deepgram returns these per alternative, in [`ResultAlternative::summaries`].
+/// The current feature instead returns a single [`Summary`] for the whole
+/// response.
+///
+/// See the [Deepgram Summarization feature docs][docs] for more info.
+///
+/// [docs]: https://developers.deepgram.com/docs/summarization
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct LegacySummary {
+    summary: String,
+    start_word: usize,
+    end_word: usize,
+}
+
+impl LegacySummary {
+    /// The summary text.
+    pub fn summary(&self) -> &str {
+        &self.summary
+    }
+
+    /// The index of the first summarized word in [`ResultAlternative::words`].
+    pub fn start_word(&self) -> usize {
+        self.start_word
+    }
+
+    /// The index of the last summarized word in [`ResultAlternative::words`].
+    pub fn end_word(&self) -> usize {
+        self.end_word
+    }
+}
+
 /// Transcript alternatives.
 ///
 /// See the [Deepgram API Reference][api] for more info.
@@ -343,10 +579,18 @@ pub struct ResultAlternative {
     #[allow(missing_docs)]
     #[serde(default)]
     pub languages: Vec<String>,
+
+    /// [`None`] unless the legacy (v1) [Summarization feature][docs] is set.
+    ///
+    /// [docs]: https://developers.deepgram.com/docs/summarization
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summaries: Option<Vec<LegacySummary>>,
 }
 
 /// A single transcribed word.
 ///
+/// Within a [`Response`], words with identical text share one allocation.
+///
 /// See the [Deepgram API Reference][api] for more info.
 ///
 /// [api]: https://developers.deepgram.com/api-reference/#transcription-prerecorded
@@ -354,7 +598,8 @@ pub struct ResultAlternative {
 #[non_exhaustive]
 pub struct Word {
     #[allow(missing_docs)]
-    pub word: String,
+    #[serde(deserialize_with = "intern::string")]
+    pub word: Arc<str>,
 
     #[allow(missing_docs)]
     pub start: f64,
@@ -373,7 +618,29 @@ pub struct Word {
     /// [`None`] unless the [Punctuation feature][docs] is set.
     ///
     /// [docs]: https://developers.deepgram.com/documentation/features/punctuate/
-    pub punctuated_word: Option<String>,
+    #[serde(default, deserialize_with = "intern::option_string")]
+    pub punctuated_word: Option<Arc<str>>,
+}
+
+impl Word {
+    /// The punctuated form of the word when it is set, and otherwise the word.
+    pub(crate) fn text(&self) -> &str {
+        self.punctuated_word.as_deref().unwrap_or(&self.word)
+    }
+}
+
+/// The [text](Word::text) of `words`, separated by spaces.
+pub(crate) fn join_words(words: &[Word]) -> String {
+    words.iter().map(Word::text).collect::<Vec<_>>().join(" ")
+}
+
+/// The average confidence of `words`, or [`None`] if there are none.
+pub(crate) fn average_confidence<'a>(words: impl IntoIterator<Item = &'a Word>) -> Option<f64> {
+    let (count, sum) = words.into_iter().fold((0, 0.0), |(count, sum), word| {
+        (count + 1, sum + word.confidence)
+    });
+
+    (count > 0).then(|| sum / f64::from(count))
 }
 
 /// Search result.
@@ -398,3 +665,106 @@ pub struct Hit {
     #[allow(missing_docs)]
     pub snippet: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{RawResponse, Response};
+    use crate::common::fixtures::{ResponseBuilder, WordBuilder};
+    use crate::common::options::Language;
+
+    #[test]
+    fn parses_summaries() {
+        let mut body = serde_json::to_value(
+            ResponseBuilder::new()
+                .channel([WordBuilder::new("hello", 0.0, 0.5).build()])
+                .build(),
+        )
+        .unwrap();
+        body["results"]["summary"] = json!({"result": "success", "short": "A greeting."});
+        body["results"]["channels"][0]["alternatives"][0]["summaries"] =
+            json!([{"summary": "Hello.", "start_word": 0, "end_word": 0}]);
+
+        let response: Response = serde_json::from_value(body).unwrap();
+        let summary = response.results.summary.unwrap();
+        assert!(summary.is_success());
+        assert_eq!(summary.short(), "A greeting.");
+
+        let legacy = &response.results.channels[0].alternatives[0]
+            .summaries
+            .as_ref()
+            .unwrap()[0];
+        assert_eq!(legacy.summary(), "Hello.");
+        assert_eq!((legacy.start_word(), legacy.end_word()), (0, 0));
+    }
+
+    #[test]
+    fn raw_response_roundtrip() {
+        let raw = RawResponse {
+            status: reqwest::StatusCode::OK,
+            request_id: None,
+            body: json!({"metadata": {}}),
+        };
+
+        let value = serde_json::to_value(&raw).unwrap();
+        assert_eq!(value["status"], 200);
+        assert_eq!(serde_json::from_value::<RawResponse>(value).unwrap(), raw);
+    }
+
+    #[test]
+    fn finds_search_results_by_query() {
+        let mut body = serde_json::to_value(ResponseBuilder::new().channel([]).build()).unwrap();
+        body["results"]["channels"][0]["search"] = json!([
+            {"query": "hello", "hits": [{"confidence": 0.9, "start": 0.0, "end": 0.5, "snippet": "hello"}]},
+            {"query": "world", "hits": []},
+        ]);
+
+        let response: Response = serde_json::from_value(body).unwrap();
+        let channel = &response.results.channels[0];
+        let hello = channel.search_results("hello").unwrap();
+        assert_eq!(hello.hits.len(), 1);
+        assert_eq!(hello.hits[0].snippet, "hello");
+        assert!(channel.search_results("world").unwrap().hits.is_empty());
+        assert!(channel.search_results("missing").is_none());
+    }
+
+    #[test]
+    fn formats_utterance_with_speaker() {
+        let response = ResponseBuilder::new()
+            .utterance(0, [WordBuilder::new("hello", 0.0, 0.5).speaker(2).build()])
+            .utterance(1, [WordBuilder::new("world", 0.5, 1.0).build()])
+            .build();
+
+        let utterances = response.results.utterances.unwrap();
+        assert_eq!(utterances[0].text_with_speaker(), "Speaker 2: hello");
+        assert_eq!(utterances[1].channel, 1);
+        assert_eq!(utterances[1].text_with_speaker(), "world");
+    }
+
+    #[test]
+    fn parses_detected_language() {
+        let mut body = serde_json::to_value(ResponseBuilder::new().channel([]).build()).unwrap();
+        body["results"]["channels"][0]["detected_language"] = json!("en-US");
+        body["results"]["channels"][0]["language_confidence"] = json!(0.97);
+
+        let response: Response = serde_json::from_value(body).unwrap();
+        let channel = &response.results.channels[0];
+        assert_eq!(channel.detected_language, Some(Language::en_US));
+        assert_eq!(channel.language_confidence, Some(0.97));
+
+        let body = serde_json::to_value(&response).unwrap();
+        assert_eq!(body["results"]["channels"][0]["detected_language"], "en-US");
+    }
+
+    #[test]
+    fn parses_failed_summary() {
+        let mut body = serde_json::to_value(ResponseBuilder::new().build()).unwrap();
+        body["results"]["summary"] = json!({"result": "failure"});
+
+        let response: Response = serde_json::from_value(body).unwrap();
+        let summary = response.results.summary.unwrap();
+        assert!(!summary.is_success());
+        assert_eq!(summary.short(), "");
+    }
+}
@@ -0,0 +1,569 @@
+//! Export pre-recorded transcripts as captions, and caption live streams.
+//!
+//! See [`Response::to_srt`], [`Response::to_webvtt`], and [`LiveCaptioner`]
+//! for more info.
+
+use std::{collections::VecDeque, fmt::Write, mem, time::Duration};
+
+use crate::common::{
+    batch_response::{Response, Sentence, Utterance, Word},
+    stream_response::{self, StreamResponse},
+};
+
+/// The longest caption line [`Response::to_webvtt`] produces from words alone.
+const WEBVTT_LINE_LENGTH: usize = 42;
+
+/// The longest caption [`Response::to_webvtt`] produces from words alone.
+const WEBVTT_MAX_DURATION: Duration = Duration::from_secs(7);
+
+/// A single caption, spanning `start..end` seconds.
+#[derive(Debug, PartialEq, Clone)]
+struct Cue {
+    start: f64,
+    end: f64,
+    speaker: Option<usize>,
+    text: String,
+}
+
+impl Cue {
+    fn new(word: &Word) -> Self {
+        Self {
+            start: word.start,
+            end: word.end,
+            speaker: word.speaker,
+            text: String::from(word.text()),
+        }
+    }
+
+    fn from_utterance(utterance: &Utterance) -> Self {
+        Self {
+            start: utterance.start,
+            end: utterance.end,
+            speaker: utterance.speaker,
+            text: utterance.transcript.clone(),
+        }
+    }
+
+    fn from_sentence(sentence: &Sentence, speaker: Option<usize>) -> Self {
+        Self {
+            start: sentence.start(),
+            end: sentence.end(),
+            speaker,
+            text: String::from(sentence.text()),
+        }
+    }
+
+    fn fits(&self, word: &Word, line_length: usize, max_duration: f64) -> bool {
+        word.speaker == self.speaker
+            && self.text.chars().count() + 1 + word.text().chars().count() <= line_length
+            && word.end - self.start <= max_duration
+    }
+
+    fn push(&mut self, word: &Word) {
+        self.text.push(' ');
+        self.text.push_str(word.text());
+        self.end = word.end;
+    }
+
+    fn labeled_text(&self) -> String {
+        match self.speaker {
+            Some(speaker) => format!("Speaker {speaker}: {}", self.text),
+            None => self.text.clone(),
+        }
+    }
+}
+
+impl Response {
+    /// Convert the transcript into a [SubRip (SRT)][srt] caption file.
+    ///
+    /// Words are grouped into captions of at most `line_length` characters
+    /// and `max_duration` long, preferring the punctuated form of each word
+    /// when the Punctuation feature is set. A caption never spans two
+    /// utterances or two diarized speakers, and is prefixed with its speaker,
+    /// such as `"Speaker 0: "`, when the Diarization feature is set.
+    ///
+    /// Uses the utterances when the Utterances feature is set, and otherwise
+    /// the first alternative of the first channel.
+    ///
+    /// [srt]: https://en.wikipedia.org/wiki/SubRip
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use deepgram::common::batch_response::Response;
+    /// # fn example(response: &Response) -> std::io::Result<()> {
+    /// std::fs::write("captions.srt", response.to_srt(42, Duration::from_secs(5)))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_srt(&self, line_length: usize, max_duration: Duration) -> String {
+        let mut srt = String::new();
+
+        for (index, cue) in self.cues(line_length, max_duration).iter().enumerate() {
+            if index > 0 {
+                srt.push('\n');
+            }
+
+            let _ = writeln!(
+                srt,
+                "{}\n{} --> {}\n{}",
+                index + 1,
+                srt_timestamp(cue.start),
+                srt_timestamp(cue.end),
+                cue.labeled_text(),
+            );
+        }
+
+        srt
+    }
+
+    /// Convert the transcript into a [WebVTT][webvtt] caption file.
+    ///
+    /// Each utterance becomes a caption when the Utterances feature is set.
+    /// Otherwise each sentence becomes a caption when the Paragraphs feature
+    /// is set, and failing both, words are grouped into captions as in
+    /// [`to_srt`](Self::to_srt). Captions are wrapped in a voice tag, such as
+    /// `<v Speaker 0>`, when the Diarization feature is set.
+    ///
+    /// [webvtt]: https://www.w3.org/TR/webvtt1/
+    pub fn to_webvtt(&self) -> String {
+        let alternative = self
+            .results
+            .channels
+            .first()
+            .and_then(|channel| channel.alternatives.first());
+
+        let cues: Vec<Cue> = match (&self.results.utterances, alternative) {
+            (Some(utterances), _) => utterances.iter().map(Cue::from_utterance).collect(),
+            (None, Some(alternative)) if alternative.paragraphs.is_some() => alternative
+                .paragraphs
+                .iter()
+                .flat_map(|paragraphs| paragraphs.paragraphs())
+                .flat_map(|paragraph| {
+                    paragraph
+                        .sentences()
+                        .map(|sentence| Cue::from_sentence(sentence, paragraph.speaker()))
+                })
+                .collect(),
+            _ => self.cues(WEBVTT_LINE_LENGTH, WEBVTT_MAX_DURATION),
+        };
+
+        let mut webvtt = String::from("WEBVTT\n");
+
+        for cue in cues {
+            let _ = write!(
+                webvtt,
+                "\n{} --> {}\n",
+                webvtt_timestamp(cue.start),
+                webvtt_timestamp(cue.end),
+            );
+
+            if let Some(speaker) = cue.speaker {
+                let _ = write!(webvtt, "<v Speaker {speaker}>");
+            }
+            webvtt.push_str(&webvtt_escape(&cue.text));
+            webvtt.push('\n');
+        }
+
+        webvtt
+    }
+
+    fn cues(&self, line_length: usize, max_duration: Duration) -> Vec<Cue> {
+        let max_duration = max_duration.as_secs_f64();
+
+        let runs: Vec<&[Word]> = match &self.results.utterances {
+            Some(utterances) => utterances
+                .iter()
+                .map(|utterance| utterance.words.as_slice())
+                .collect(),
+            None => self
+                .results
+                .channels
+                .first()
+                .and_then(|channel| channel.alternatives.first())
+                .map(|alternative| vec![alternative.words.as_slice()])
+                .unwrap_or_default(),
+        };
+
+        let mut cues = Vec::new();
+        for words in runs {
+            let mut cue: Option<Cue> = None;
+
+            for word in words {
+                match &mut cue {
+                    Some(current) if current.fits(word, line_length, max_duration) => {
+                        current.push(word);
+                    }
+                    _ => cues.extend(cue.replace(Cue::new(word))),
+                }
+            }
+
+            cues.extend(cue);
+        }
+
+        cues
+    }
+}
+
+/// Rolling caption lines for a live stream.
+///
+/// Feed it each [`StreamResponse`] with [`push`](Self::push). Final results
+/// are wrapped into lines of at most `line_length` characters, which don't
+/// change once they're full. Interim results are shown after them until the
+/// next result for the same audio replaces them, so only the words at the
+/// end of the captions change as Deepgram refines its transcript. Only the
+/// last `max_lines` lines are kept.
+///
+/// Uses the first alternative of the first channel, preferring the
+/// punctuated form of each word when the Punctuation feature is set.
+///
+/// ```no_run
+/// # use deepgram::{common::captions::LiveCaptioner, Deepgram, DeepgramError};
+/// # use futures::StreamExt;
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), DeepgramError> {
+/// # let dg = Deepgram::new(std::env::var("DEEPGRAM_API_KEY").unwrap_or_default())?;
+/// # let audio = futures::stream::empty::<Result<bytes::Bytes, std::io::Error>>();
+/// let mut results = dg
+///     .transcription()
+///     .stream_request()
+///     .interim_results(true)
+///     .stream(audio)
+///     .await?;
+///
+/// let mut captioner = LiveCaptioner::new(32, 2);
+/// while let Some(response) = results.next().await {
+///     if let Some(lines) = captioner.push(&response?) {
+///         println!("{}", lines.join("\n"));
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct LiveCaptioner {
+    line_length: usize,
+    max_lines: usize,
+    /// Full lines of final words, oldest first.
+    lines: VecDeque<String>,
+    /// The final words of the line being filled.
+    current: String,
+    /// The words of the latest interim result.
+    interim: Vec<String>,
+}
+
+impl LiveCaptioner {
+    /// Caption with lines of at most `line_length` characters, keeping the
+    /// last `max_lines` of them.
+    ///
+    /// Words longer than `line_length` get a line of their own.
+    pub fn new(line_length: usize, max_lines: usize) -> Self {
+        Self {
+            line_length: line_length.max(1),
+            max_lines: max_lines.max(1),
+            lines: VecDeque::new(),
+            current: String::new(),
+            interim: Vec::new(),
+        }
+    }
+
+    /// Update the captions with `response`.
+    ///
+    /// Returns the caption lines if `response` changed them. Responses other
+    /// than transcripts of the first channel never do, except for
+    /// [`StreamResponse::Reconnected`], which drops the interim words that
+    /// will no longer be finalized.
+    pub fn push(&mut self, response: &StreamResponse) -> Option<Vec<String>> {
+        let before = self.lines();
+
+        match response {
+            StreamResponse::TranscriptResponse {
+                is_final,
+                channel,
+                channel_index,
+                ..
+            } if channel_index.first().is_none_or(|&index| index == 0) => {
+                let words = channel
+                    .alternatives
+                    .first()
+                    .map(|alternative| alternative.words.as_slice())
+                    .unwrap_or_default()
+                    .iter()
+                    .map(stream_response::Word::text);
+
+                if *is_final {
+                    self.interim.clear();
+                    for word in words {
+                        if let Some(line) = wrap(&mut self.current, word, self.line_length) {
+                            self.lines.push_back(line);
+                        }
+                    }
+                    while self.lines.len() > self.max_lines {
+                        self.lines.pop_front();
+                    }
+                } else {
+                    self.interim = words.map(String::from).collect();
+                }
+            }
+            StreamResponse::Reconnected { .. } => self.interim.clear(),
+            _ => {}
+        }
+
+        let after = self.lines();
+        (after != before).then_some(after)
+    }
+
+    /// The current caption lines, oldest first.
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines: Vec<String> = self.lines.iter().cloned().collect();
+        let mut current = self.current.clone();
+        for word in &self.interim {
+            lines.extend(wrap(&mut current, word, self.line_length));
+        }
+        if !current.is_empty() {
+            lines.push(current);
+        }
+
+        let scrolled = lines.len().saturating_sub(self.max_lines);
+        lines.split_off(scrolled)
+    }
+}
+
+/// Add `word` to the end of `line`, returning the full line it replaces if
+/// `word` doesn't fit.
+fn wrap(line: &mut String, word: &str, line_length: usize) -> Option<String> {
+    if line.is_empty() {
+        line.push_str(word);
+        None
+    } else if line.chars().count() + 1 + word.chars().count() <= line_length {
+        line.push(' ');
+        line.push_str(word);
+        None
+    } else {
+        Some(mem::replace(line, String::from(word)))
+    }
+}
+
+/// Format `seconds` as `HH:MM:SS,mmm`.
+fn srt_timestamp(seconds: f64) -> String {
+    timestamp(seconds, ',')
+}
+
+/// Format `seconds` as `HH:MM:SS.mmm`.
+fn webvtt_timestamp(seconds: f64) -> String {
+    timestamp(seconds, '.')
+}
+
+fn timestamp(seconds: f64, separator: char) -> String {
+    let millis = (seconds.max(0.0) * 1000.0).round() as u64;
+
+    format!(
+        "{:02}:{:02}:{:02}{separator}{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        millis % 1000,
+    )
+}
+
+/// Escape the characters WebVTT cue text reserves for markup.
+fn webvtt_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use serde_json::json;
+
+    use super::{srt_timestamp, webvtt_timestamp, LiveCaptioner};
+    use crate::common::{
+        batch_response::Response,
+        fixtures::{stream_word, ResponseBuilder, TranscriptResponseBuilder, WordBuilder},
+        stream_response::StreamResponse,
+    };
+
+    fn result(words: &[&str], start: f64, is_final: bool) -> StreamResponse {
+        TranscriptResponseBuilder::new(
+            words
+                .iter()
+                .enumerate()
+                .map(|(i, word)| stream_word(*word, start + i as f64, start + i as f64 + 1.0)),
+        )
+        .start(start)
+        .is_final(is_final)
+        .build()
+    }
+
+    #[test]
+    fn formats_timestamps() {
+        assert_eq!(srt_timestamp(0.0), "00:00:00,000");
+        assert_eq!(srt_timestamp(3725.5), "01:02:05,500");
+        assert_eq!(webvtt_timestamp(3725.5), "01:02:05.500");
+    }
+
+    #[test]
+    fn splits_words_into_cues() {
+        let response = ResponseBuilder::new()
+            .channel([
+                WordBuilder::new("hello", 0.0, 0.5)
+                    .punctuated_word("Hello,")
+                    .build(),
+                WordBuilder::new("world", 0.5, 1.0)
+                    .punctuated_word("world.")
+                    .build(),
+                WordBuilder::new("goodbye", 1.5, 2.0).build(),
+            ])
+            .build();
+
+        assert_eq!(
+            response.to_srt(13, Duration::from_secs(5)),
+            "1\n00:00:00,000 --> 00:00:01,000\nHello, world.\n\n\
+             2\n00:00:01,500 --> 00:00:02,000\ngoodbye\n",
+        );
+    }
+
+    #[test]
+    fn splits_cues_by_duration_and_speaker() {
+        let response = ResponseBuilder::new()
+            .channel([
+                WordBuilder::new("a", 0.0, 1.0).speaker(0).build(),
+                WordBuilder::new("b", 1.0, 2.0).speaker(0).build(),
+                WordBuilder::new("c", 2.0, 3.0).speaker(0).build(),
+                WordBuilder::new("d", 3.0, 4.0).speaker(1).build(),
+            ])
+            .build();
+
+        assert_eq!(
+            response.to_srt(42, Duration::from_secs(2)),
+            "1\n00:00:00,000 --> 00:00:02,000\nSpeaker 0: a b\n\n\
+             2\n00:00:02,000 --> 00:00:03,000\nSpeaker 0: c\n\n\
+             3\n00:00:03,000 --> 00:00:04,000\nSpeaker 1: d\n",
+        );
+    }
+
+    #[test]
+    fn prefers_utterances() {
+        let response = ResponseBuilder::new()
+            .channel([
+                WordBuilder::new("hello", 0.0, 0.5).build(),
+                WordBuilder::new("world", 0.5, 1.0).build(),
+            ])
+            .utterance(0, [WordBuilder::new("hello", 0.0, 0.5).build()])
+            .utterance(0, [WordBuilder::new("world", 0.5, 1.0).build()])
+            .build();
+
+        assert_eq!(
+            response.to_srt(42, Duration::from_secs(5)),
+            "1\n00:00:00,000 --> 00:00:00,500\nhello\n\n\
+             2\n00:00:00,500 --> 00:00:01,000\nworld\n",
+        );
+    }
+
+    #[test]
+    fn webvtt_from_utterances() {
+        let response = ResponseBuilder::new()
+            .utterance(0, [WordBuilder::new("hi", 0.0, 0.5).speaker(0).build()])
+            .utterance(
+                0,
+                [WordBuilder::new("<laughs>", 0.5, 1.0)
+                    .speaker(1)
+                    .punctuated_word("<laughs> & hi")
+                    .build()],
+            )
+            .build();
+
+        assert_eq!(
+            response.to_webvtt(),
+            "WEBVTT\n\
+             \n00:00:00.000 --> 00:00:00.500\n<v Speaker 0>hi\n\
+             \n00:00:00.500 --> 00:00:01.000\n<v Speaker 1>&lt;laughs&gt; &amp; hi\n",
+        );
+    }
+
+    #[test]
+    fn webvtt_from_paragraphs() {
+        let mut body = serde_json::to_value(
+            ResponseBuilder::new()
+                .channel([WordBuilder::new("hello", 0.0, 0.5).build()])
+                .build(),
+        )
+        .unwrap();
+        body["results"]["channels"][0]["alternatives"][0]["paragraphs"] = json!({
+            "transcript": "Hello. Goodbye.",
+            "paragraphs": [{
+                "sentences": [
+                    {"text": "Hello.", "start": 0.0, "end": 0.5},
+                    {"text": "Goodbye.", "start": 1.0, "end": 1.5},
+                ],
+                "speaker": 2,
+                "num_words": 2,
+                "start": 0.0,
+                "end": 1.5,
+            }],
+        });
+        let response: Response = serde_json::from_value(body).unwrap();
+
+        assert_eq!(
+            response.to_webvtt(),
+            "WEBVTT\n\
+             \n00:00:00.000 --> 00:00:00.500\n<v Speaker 2>Hello.\n\
+             \n00:00:01.000 --> 00:00:01.500\n<v Speaker 2>Goodbye.\n",
+        );
+    }
+
+    #[test]
+    fn webvtt_from_words() {
+        let response = ResponseBuilder::new()
+            .channel([WordBuilder::new("hello", 0.0, 0.5).build()])
+            .build();
+
+        assert_eq!(
+            response.to_webvtt(),
+            "WEBVTT\n\n00:00:00.000 --> 00:00:00.500\nhello\n",
+        );
+    }
+
+    #[test]
+    fn replaces_interim_words() {
+        let mut captioner = LiveCaptioner::new(12, 2);
+
+        assert_eq!(
+            captioner.push(&result(&["the", "quick"], 0.0, false)),
+            Some(vec![String::from("the quick")])
+        );
+        assert_eq!(
+            captioner.push(&result(&["the", "quick", "brown", "fox"], 0.0, false)),
+            Some(vec![String::from("the quick"), String::from("brown fox")])
+        );
+        // The final result settles on different words.
+        assert_eq!(
+            captioner.push(&result(&["a", "quick", "brown", "fox"], 0.0, true)),
+            Some(vec![String::from("a quick"), String::from("brown fox")])
+        );
+        assert_eq!(
+            captioner.push(&StreamResponse::Unknown(json!({"type": "Warning"}))),
+            None
+        );
+    }
+
+    #[test]
+    fn rolls_full_lines() {
+        let mut captioner = LiveCaptioner::new(12, 2);
+        captioner.push(&result(&["one", "two", "three"], 0.0, true));
+        captioner.push(&result(&["four", "five"], 3.0, true));
+        assert_eq!(captioner.lines(), ["three four", "five"]);
+
+        // Full lines keep their words while interim words are added after them.
+        captioner.push(&result(&["six", "seven"], 5.0, false));
+        assert_eq!(captioner.lines(), ["five six", "seven"]);
+
+        captioner.push(&StreamResponse::Reconnected {
+            request_id: uuid::Uuid::nil(),
+            attempts: 1,
+        });
+        assert_eq!(captioner.lines(), ["three four", "five"]);
+    }
+}
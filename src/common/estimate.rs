@@ -0,0 +1,111 @@
+//! Estimate how much audio Deepgram will bill for.
+//!
+//! Deepgram bills pre-recorded and streaming transcription by audio duration,
+//! counting each channel separately when the Multichannel feature is set.
+//! [`audio_duration`] forecasts the duration of raw audio before it is sent,
+//! and [`ListenMetadata::billed_duration`] reconciles it afterwards.
+//!
+//! # Examples
+//!
+//! ```
+//! # use std::time::Duration;
+//! # use deepgram::common::{estimate::{audio_duration, estimate_cost}, options::Encoding};
+//! // One minute of 16 kHz stereo linear16 audio
+//! let duration = audio_duration(&Encoding::Linear16, 16_000, 2, 3_840_000).unwrap();
+//! assert_eq!(duration, Duration::from_secs(60));
+//!
+//! // Transcribed with multichannel, at $0.0043 per minute
+//! let cost = estimate_cost(duration * 2, 0.0043);
+//! assert!((cost - 0.0086).abs() < 1e-9);
+//! ```
+
+use std::time::Duration;
+
+use crate::common::{batch_response::ListenMetadata, options::Encoding};
+
+impl Encoding {
+    /// The size of one sample of one channel in bytes, or [`None`] if the
+    /// encoding is compressed and has no fixed sample size.
+    pub fn bytes_per_sample(&self) -> Option<u32> {
+        match self {
+            Encoding::Linear32 => Some(4),
+            Encoding::Linear16 => Some(2),
+            Encoding::Mulaw => Some(1),
+            _ => None,
+        }
+    }
+}
+
+impl ListenMetadata {
+    /// The duration Deepgram bills for the request.
+    ///
+    /// This is the audio duration multiplied by the number of channels
+    /// transcribed, which is more than one only when the Multichannel feature
+    /// is set.
+    pub fn billed_duration(&self) -> Duration {
+        Duration::from_secs_f64(self.duration.max(0.0) * self.channels.max(1) as f64)
+    }
+}
+
+/// The duration of `bytes` of raw audio, or [`None`] if `encoding` has no
+/// fixed sample size or `sample_rate` or `channels` is zero.
+///
+/// See [`Encoding::bytes_per_sample`].
+pub fn audio_duration(
+    encoding: &Encoding,
+    sample_rate: u32,
+    channels: u16,
+    bytes: u64,
+) -> Option<Duration> {
+    let bytes_per_second =
+        u64::from(encoding.bytes_per_sample()?) * u64::from(sample_rate) * u64::from(channels);
+
+    if bytes_per_second == 0 {
+        return None;
+    }
+
+    Some(Duration::from_secs_f64(
+        bytes as f64 / bytes_per_second as f64,
+    ))
+}
+
+/// The cost of transcribing `billed` audio at `price_per_minute`.
+///
+/// `billed` should count each channel separately when the Multichannel
+/// feature is set, as [`ListenMetadata::billed_duration`] does.
+pub fn estimate_cost(billed: Duration, price_per_minute: f64) -> f64 {
+    billed.as_secs_f64() / 60.0 * price_per_minute
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::audio_duration;
+    use crate::common::{fixtures::ResponseBuilder, options::Encoding};
+
+    #[test]
+    fn estimates_raw_audio_duration() {
+        assert_eq!(
+            audio_duration(&Encoding::Mulaw, 8_000, 1, 4_000),
+            Some(Duration::from_millis(500))
+        );
+        assert_eq!(
+            audio_duration(&Encoding::Linear32, 48_000, 2, 384_000),
+            Some(Duration::from_secs(1))
+        );
+        assert_eq!(audio_duration(&Encoding::Opus, 48_000, 1, 1_000), None);
+        assert_eq!(audio_duration(&Encoding::Linear16, 0, 1, 1_000), None);
+    }
+
+    #[test]
+    fn bills_each_channel() {
+        let response = ResponseBuilder::new()
+            .channel([])
+            .channel([])
+            .duration(30.0)
+            .build();
+
+        assert_eq!(response.metadata.billed_duration(), Duration::from_secs(60));
+    }
+}
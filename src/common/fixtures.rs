@@ -0,0 +1,499 @@
+//! Builders for fabricating response values in tests.
+//!
+//! Most response types are `#[non_exhaustive]` and are only ever produced by
+//! deserializing Deepgram's JSON, which makes them impossible to construct in
+//! downstream unit tests. The builders in this module fill in realistic
+//! defaults for everything you don't care about.
+//!
+//! Only available with the `test-util` feature.
+//!
+//! ```
+//! use deepgram::common::fixtures::{ResponseBuilder, WordBuilder};
+//!
+//! let response = ResponseBuilder::new()
+//!     .channel([
+//!         WordBuilder::new("hello", 0.0, 0.4).build(),
+//!         WordBuilder::new("world", 0.5, 0.9).build(),
+//!     ])
+//!     .build();
+//!
+//! assert_eq!(
+//!     response.results.channels[0].alternatives[0].transcript,
+//!     "hello world"
+//! );
+//! ```
+
+use std::sync::Arc;
+
+use uuid::Uuid;
+
+use crate::common::{
+    batch_response::{
+        average_confidence, join_words, ChannelResult, ListenMetadata, ListenResults, Response,
+        ResultAlternative, Utterance, Word,
+    },
+    flux_response::{FluxResponse, FluxWord, TurnEvent},
+    stream_response::{self, StreamResponse},
+};
+
+/// Builds a [`batch_response::Word`](Word).
+#[derive(Debug, Clone)]
+pub struct WordBuilder(Word);
+
+impl WordBuilder {
+    /// Start building a word spanning `start..end` seconds with a confidence of `1.0`.
+    pub fn new(word: impl Into<Arc<str>>, start: f64, end: f64) -> Self {
+        Self(Word {
+            word: word.into(),
+            start,
+            end,
+            confidence: 1.0,
+            speaker: None,
+            punctuated_word: None,
+        })
+    }
+
+    /// Set the confidence of the word.
+    pub fn confidence(mut self, confidence: f64) -> Self {
+        self.0.confidence = confidence;
+        self
+    }
+
+    /// Set the diarized speaker of the word.
+    pub fn speaker(mut self, speaker: usize) -> Self {
+        self.0.speaker = Some(speaker);
+        self
+    }
+
+    /// Set the punctuated form of the word.
+    pub fn punctuated_word(mut self, punctuated_word: impl Into<Arc<str>>) -> Self {
+        self.0.punctuated_word = Some(punctuated_word.into());
+        self
+    }
+
+    /// Finish building the [`Word`].
+    pub fn build(self) -> Word {
+        self.0
+    }
+}
+
+/// Builds a pre-recorded [`Response`].
+#[derive(Debug, Clone)]
+pub struct ResponseBuilder {
+    metadata: ListenMetadata,
+    duration: Option<f64>,
+    channels: Vec<ChannelResult>,
+    utterances: Option<Vec<Utterance>>,
+}
+
+impl ResponseBuilder {
+    /// Start building a response with no channels.
+    pub fn new() -> Self {
+        Self {
+            metadata: ListenMetadata {
+                request_id: Uuid::nil(),
+                transaction_key: String::from("deprecated"),
+                sha256: String::new(),
+                created: String::from("2024-01-01T00:00:00.000Z"),
+                duration: 0.0,
+                channels: 0,
+                language: None,
+            },
+            duration: None,
+            channels: Vec::new(),
+            utterances: None,
+        }
+    }
+
+    /// Set the request ID reported in the metadata.
+    pub fn request_id(mut self, request_id: Uuid) -> Self {
+        self.metadata.request_id = request_id;
+        self
+    }
+
+    /// Set the audio duration reported in the metadata.
+    ///
+    /// Defaults to the latest end time of the words added to any channel.
+    pub fn duration(mut self, duration: f64) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    /// Add a channel with a single alternative made of `words`.
+    ///
+    /// The transcript is the words joined with spaces, preferring the
+    /// punctuated form of each word when it is set.
+    pub fn channel(mut self, words: impl IntoIterator<Item = Word>) -> Self {
+        let words: Vec<Word> = words.into_iter().collect();
+
+        self.channels.push(ChannelResult {
+            search: None,
+            alternatives: vec![ResultAlternative {
+                transcript: join_words(&words),
+                confidence: average_confidence(&words).unwrap_or(0.0),
+                words,
+                paragraphs: None,
+                entities: None,
+                languages: Vec::new(),
+                summaries: None,
+            }],
+            detected_language: None,
+            language_confidence: None,
+        });
+        self.metadata.channels = self.channels.len();
+        self
+    }
+
+    /// Add an utterance on `channel` made of `words`.
+    ///
+    /// The utterance takes its speaker from its first word.
+    pub fn utterance(mut self, channel: usize, words: impl IntoIterator<Item = Word>) -> Self {
+        let words: Vec<Word> = words.into_iter().collect();
+
+        let utterance = Utterance {
+            start: words.first().map_or(0.0, |word| word.start),
+            end: words.last().map_or(0.0, |word| word.end),
+            confidence: average_confidence(&words).unwrap_or(0.0),
+            channel,
+            transcript: join_words(&words),
+            speaker: words.first().and_then(|word| word.speaker),
+            words,
+            id: Uuid::nil(),
+        };
+
+        self.utterances.get_or_insert_with(Vec::new).push(utterance);
+        self
+    }
+
+    /// Finish building the [`Response`].
+    pub fn build(mut self) -> Response {
+        self.metadata.duration = self.duration.unwrap_or_else(|| {
+            self.channels
+                .iter()
+                .flat_map(|channel| channel.alternatives[0].words.last())
+                .fold(0.0, |duration, word| duration.max(word.end))
+        });
+
+        Response {
+            metadata: self.metadata,
+            results: ListenResults {
+                channels: self.channels,
+                utterances: self.utterances,
+                intents: None,
+                sentiments: None,
+                topics: None,
+                summary: None,
+            },
+        }
+    }
+}
+
+impl Default for ResponseBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Builds a [`StreamResponse::TranscriptResponse`].
+#[derive(Debug)]
+pub struct TranscriptResponseBuilder {
+    start: f64,
+    duration: Option<f64>,
+    is_final: bool,
+    speech_final: bool,
+    from_finalize: bool,
+    channel_index: Vec<i32>,
+    words: Vec<stream_response::Word>,
+}
+
+impl TranscriptResponseBuilder {
+    /// Start building an interim result made of `words`.
+    pub fn new(words: impl IntoIterator<Item = stream_response::Word>) -> Self {
+        Self {
+            start: 0.0,
+            duration: None,
+            is_final: false,
+            speech_final: false,
+            from_finalize: false,
+            channel_index: vec![0, 1],
+            words: words.into_iter().collect(),
+        }
+    }
+
+    /// Set the start of the audio window covered by this result.
+    pub fn start(mut self, start: f64) -> Self {
+        self.start = start;
+        self
+    }
+
+    /// Set the duration of the audio window covered by this result.
+    ///
+    /// Defaults to the span from `start` to the end of the last word.
+    pub fn duration(mut self, duration: f64) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    /// Mark the result as final.
+    pub fn is_final(mut self, is_final: bool) -> Self {
+        self.is_final = is_final;
+        self
+    }
+
+    /// Mark the result as the end of speech. Implies [`TranscriptResponseBuilder::is_final`].
+    pub fn speech_final(mut self, speech_final: bool) -> Self {
+        self.speech_final = speech_final;
+        self.is_final |= speech_final;
+        self
+    }
+
+    /// Mark the result as produced by a Finalize message.
+    pub fn from_finalize(mut self, from_finalize: bool) -> Self {
+        self.from_finalize = from_finalize;
+        self
+    }
+
+    /// Set the `[channel, total_channels]` pair the result belongs to.
+    pub fn channel_index(mut self, channel: i32, total_channels: i32) -> Self {
+        self.channel_index = vec![channel, total_channels];
+        self
+    }
+
+    /// Finish building the [`StreamResponse`].
+    pub fn build(self) -> StreamResponse {
+        let duration = self.duration.unwrap_or_else(|| {
+            self.words
+                .last()
+                .map_or(0.0, |word| (word.end - self.start).max(0.0))
+        });
+        let transcript = self
+            .words
+            .iter()
+            .map(|word| word.punctuated_word.as_deref().unwrap_or(&word.word))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let confidence = if self.words.is_empty() {
+            0.0
+        } else {
+            self.words.iter().map(|word| word.confidence).sum::<f64>() / self.words.len() as f64
+        };
+
+        StreamResponse::TranscriptResponse {
+            type_field: String::from("Results"),
+            start: self.start,
+            duration,
+            is_final: self.is_final,
+            speech_final: self.speech_final,
+            from_finalize: self.from_finalize,
+            channel: stream_response::Channel {
+                alternatives: vec![stream_response::Alternatives {
+                    transcript,
+                    words: self.words,
+                    confidence,
+                    languages: Vec::new(),
+                }],
+            },
+            metadata: stream_response::Metadata {
+                request_id: Uuid::nil().to_string(),
+                model_info: stream_response::ModelInfo {
+                    name: String::from("general-nova-3"),
+                    version: String::from("2024-01-01.0"),
+                    arch: String::from("nova-3"),
+                },
+                model_uuid: Uuid::nil().to_string(),
+            },
+            channel_index: self.channel_index,
+        }
+    }
+}
+
+/// Construct a [`stream_response::Word`] spanning `start..end` seconds with a confidence of `1.0`.
+pub fn stream_word(word: impl Into<String>, start: f64, end: f64) -> stream_response::Word {
+    stream_response::Word {
+        word: word.into(),
+        start,
+        end,
+        confidence: 1.0,
+        speaker: None,
+        punctuated_word: None,
+        language: None,
+    }
+}
+
+/// Builds a [`FluxResponse::TurnInfo`].
+#[derive(Debug, Clone)]
+pub struct TurnInfoBuilder {
+    sequence_id: u32,
+    event: TurnEvent,
+    turn_index: u32,
+    audio_window_start: f64,
+    audio_window_end: f64,
+    words: Vec<FluxWord>,
+    end_of_turn_confidence: f64,
+}
+
+impl TurnInfoBuilder {
+    /// Start building a turn update for `event`.
+    pub fn new(event: TurnEvent) -> Self {
+        Self {
+            sequence_id: 0,
+            event,
+            turn_index: 0,
+            audio_window_start: 0.0,
+            audio_window_end: 0.0,
+            words: Vec::new(),
+            end_of_turn_confidence: 0.0,
+        }
+    }
+
+    /// Set the sequence ID of the message.
+    pub fn sequence_id(mut self, sequence_id: u32) -> Self {
+        self.sequence_id = sequence_id;
+        self
+    }
+
+    /// Set the index of the turn.
+    pub fn turn_index(mut self, turn_index: u32) -> Self {
+        self.turn_index = turn_index;
+        self
+    }
+
+    /// Set the audio window covered by the turn, in seconds.
+    pub fn audio_window(mut self, start: f64, end: f64) -> Self {
+        self.audio_window_start = start;
+        self.audio_window_end = end;
+        self
+    }
+
+    /// Append a word to the turn transcript.
+    pub fn word(mut self, word: impl Into<String>, confidence: f64) -> Self {
+        self.words.push(FluxWord {
+            word: word.into(),
+            confidence,
+        });
+        self
+    }
+
+    /// Set the end-of-turn confidence.
+    pub fn end_of_turn_confidence(mut self, end_of_turn_confidence: f64) -> Self {
+        self.end_of_turn_confidence = end_of_turn_confidence;
+        self
+    }
+
+    /// Finish building the [`FluxResponse`].
+    pub fn build(self) -> FluxResponse {
+        FluxResponse::TurnInfo {
+            request_id: Uuid::nil(),
+            sequence_id: self.sequence_id,
+            event: self.event,
+            turn_index: self.turn_index,
+            audio_window_start: self.audio_window_start,
+            audio_window_end: self.audio_window_end,
+            transcript: self
+                .words
+                .iter()
+                .map(|word| word.word.as_str())
+                .collect::<Vec<_>>()
+                .join(" "),
+            words: self.words,
+            end_of_turn_confidence: self.end_of_turn_confidence,
+        }
+    }
+}
+
+/// Construct a [`FluxResponse::Connected`] message.
+pub fn flux_connected(request_id: Uuid) -> FluxResponse {
+    FluxResponse::Connected {
+        request_id,
+        sequence_id: 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn explicit_duration_is_kept() {
+        let response = ResponseBuilder::new()
+            .duration(2.0)
+            .channel([WordBuilder::new("hello", 0.0, 0.4).build()])
+            .build();
+        assert_eq!(response.metadata.duration, 2.0);
+    }
+
+    #[test]
+    fn response_roundtrips_through_json() {
+        let response = ResponseBuilder::new()
+            .channel([
+                WordBuilder::new("hello", 0.0, 0.4)
+                    .punctuated_word("Hello")
+                    .speaker(0)
+                    .build(),
+                WordBuilder::new("world", 0.5, 0.9)
+                    .punctuated_word("world.")
+                    .speaker(0)
+                    .confidence(0.5)
+                    .build(),
+            ])
+            .utterance(0, [WordBuilder::new("hello", 0.0, 0.4).speaker(1).build()])
+            .build();
+
+        assert_eq!(response.metadata.channels, 1);
+        assert_eq!(response.metadata.duration, 0.9);
+        assert_eq!(
+            response.results.channels[0].alternatives[0].transcript,
+            "Hello world."
+        );
+        assert_eq!(
+            response.results.utterances.as_ref().unwrap()[0].speaker,
+            Some(1)
+        );
+
+        let json = serde_json::to_string(&response).unwrap();
+        assert_eq!(serde_json::from_str::<Response>(&json).unwrap(), response);
+    }
+
+    #[test]
+    fn transcript_response_deserializes() {
+        let response = TranscriptResponseBuilder::new([stream_word("hi", 1.0, 1.5)])
+            .start(1.0)
+            .speech_final(true)
+            .build();
+
+        let json = serde_json::to_string(&response).unwrap();
+        match serde_json::from_str::<StreamResponse>(&json).unwrap() {
+            StreamResponse::TranscriptResponse {
+                is_final,
+                speech_final,
+                duration,
+                ..
+            } => {
+                assert!(is_final);
+                assert!(speech_final);
+                assert_eq!(duration, 0.5);
+            }
+            other => panic!("expected TranscriptResponse, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn turn_info_deserializes() {
+        let response = TurnInfoBuilder::new(TurnEvent::EndOfTurn)
+            .word("good", 0.9)
+            .word("morning", 0.8)
+            .end_of_turn_confidence(0.95)
+            .build();
+
+        let json = serde_json::to_string(&response).unwrap();
+        match serde_json::from_str::<FluxResponse>(&json).unwrap() {
+            FluxResponse::TurnInfo {
+                event, transcript, ..
+            } => {
+                assert_eq!(event, TurnEvent::EndOfTurn);
+                assert_eq!(transcript, "good morning");
+            }
+            other => panic!("expected TurnInfo, got {other:?}"),
+        }
+    }
+}
@@ -9,7 +9,7 @@ use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use uuid::Uuid;
 
 /// Flux WebSocket message types
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Clone)]
 #[non_exhaustive]
 pub enum FluxResponse {
     /// Initial connection confirmation
@@ -69,6 +69,87 @@ pub enum FluxResponse {
     /// a message type that this version of the SDK does not recognize.
     /// The raw JSON value is preserved for inspection and logging.
     Unknown(serde_json::Value),
+
+    /// The connection dropped and was re-established by
+    #[cfg_attr(
+        feature = "listen-ws",
+        doc = "[`FluxBuilder::reconnect`](crate::listen::flux::FluxBuilder::reconnect)."
+    )]
+    #[cfg_attr(not(feature = "listen-ws"), doc = "`FluxBuilder::reconnect`.")]
+    ///
+    /// Produced by this crate rather than sent by Deepgram.
+    Reconnected {
+        /// The request ID of the new connection.
+        request_id: Uuid,
+
+        /// How many attempts it took to reconnect.
+        attempts: u32,
+
+        /// The turn that had started but not ended when the connection
+        /// dropped, as of the last message received for it.
+        ///
+        /// Deepgram won't finish it, and turns on the new connection are
+        /// numbered from zero again.
+        interrupted_turn: Option<Turn>,
+    },
+}
+
+impl FluxResponse {
+    /// The turn lifecycle event this message reports, if it is a
+    /// [`FluxResponse::TurnInfo`] with a known [`TurnEvent`].
+    ///
+    /// ```
+    /// # use deepgram::common::flux_response::{FluxResponse, TurnLifecycle};
+    /// # fn example(response: FluxResponse) {
+    /// match response.into_turn_lifecycle() {
+    ///     Some(TurnLifecycle::EagerEndOfTurn(turn)) => println!("draft reply to {}", turn.transcript),
+    ///     Some(TurnLifecycle::TurnResumed(_)) => println!("discard the draft"),
+    ///     Some(TurnLifecycle::EndOfTurn(turn)) => println!("reply to {}", turn.transcript),
+    ///     _ => {}
+    /// }
+    /// # }
+    /// ```
+    pub fn into_turn_lifecycle(self) -> Option<TurnLifecycle> {
+        let FluxResponse::TurnInfo {
+            event,
+            turn_index,
+            audio_window_start,
+            audio_window_end,
+            transcript,
+            words,
+            end_of_turn_confidence,
+            ..
+        } = self
+        else {
+            return None;
+        };
+
+        let turn = Turn {
+            turn_index,
+            audio_window_start,
+            audio_window_end,
+            transcript,
+            words,
+            end_of_turn_confidence,
+        };
+        match event {
+            TurnEvent::StartOfTurn => Some(TurnLifecycle::StartOfTurn(turn)),
+            TurnEvent::Update => Some(TurnLifecycle::Update(turn)),
+            TurnEvent::EagerEndOfTurn => Some(TurnLifecycle::EagerEndOfTurn(turn)),
+            TurnEvent::TurnResumed => Some(TurnLifecycle::TurnResumed(turn)),
+            TurnEvent::EndOfTurn => Some(TurnLifecycle::EndOfTurn(turn)),
+            TurnEvent::Unknown => None,
+        }
+    }
+
+    /// Like [`FluxResponse::into_turn_lifecycle`], but copies the turn out of
+    /// a borrowed message.
+    pub fn turn_lifecycle(&self) -> Option<TurnLifecycle> {
+        match self {
+            FluxResponse::TurnInfo { .. } => self.clone().into_turn_lifecycle(),
+            _ => None,
+        }
+    }
 }
 
 /// Private helper enum for deserializing/serializing known FluxResponse variants
@@ -97,6 +178,12 @@ enum TaggedFluxResponse {
         code: String,
         description: String,
     },
+    #[serde(skip_deserializing)]
+    Reconnected {
+        request_id: Uuid,
+        attempts: u32,
+        interrupted_turn: Option<Turn>,
+    },
 }
 
 impl From<TaggedFluxResponse> for FluxResponse {
@@ -139,6 +226,15 @@ impl From<TaggedFluxResponse> for FluxResponse {
                 code,
                 description,
             },
+            TaggedFluxResponse::Reconnected {
+                request_id,
+                attempts,
+                interrupted_turn,
+            } => FluxResponse::Reconnected {
+                request_id,
+                attempts,
+                interrupted_turn,
+            },
         }
     }
 }
@@ -216,6 +312,18 @@ impl Serialize for FluxResponse {
                 tagged.serialize(serializer)
             }
             FluxResponse::Unknown(value) => value.serialize(serializer),
+            FluxResponse::Reconnected {
+                request_id,
+                attempts,
+                interrupted_turn,
+            } => {
+                let tagged = TaggedFluxResponse::Reconnected {
+                    request_id: *request_id,
+                    attempts: *attempts,
+                    interrupted_turn: interrupted_turn.clone(),
+                };
+                tagged.serialize(serializer)
+            }
         }
     }
 }
@@ -244,8 +352,94 @@ pub enum TurnEvent {
     Unknown,
 }
 
+/// A turn lifecycle event, as returned by
+/// [`FluxResponse::into_turn_lifecycle`].
+///
+/// A turn starts with [`StartOfTurn`](Self::StartOfTurn), is refined by
+/// [`Update`](Self::Update)s, and ends with [`EndOfTurn`](Self::EndOfTurn).
+/// With an eager end-of-turn threshold set, an
+/// [`EagerEndOfTurn`](Self::EagerEndOfTurn) may come first, followed by
+/// either the [`EndOfTurn`](Self::EndOfTurn) or, if the speaker carries on,
+/// [`TurnResumed`](Self::TurnResumed).
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum TurnLifecycle {
+    /// The speaker started a new turn.
+    StartOfTurn(Turn),
+
+    /// The transcript of the current turn changed.
+    Update(Turn),
+
+    /// The turn is likely to have ended, so a reply can be prepared.
+    EagerEndOfTurn(Turn),
+
+    /// The speaker carried on after an [`EagerEndOfTurn`](Self::EagerEndOfTurn).
+    TurnResumed(Turn),
+
+    /// The turn ended.
+    EndOfTurn(Turn),
+}
+
+impl TurnLifecycle {
+    /// The turn the event is about.
+    pub fn turn(&self) -> &Turn {
+        match self {
+            TurnLifecycle::StartOfTurn(turn)
+            | TurnLifecycle::Update(turn)
+            | TurnLifecycle::EagerEndOfTurn(turn)
+            | TurnLifecycle::TurnResumed(turn)
+            | TurnLifecycle::EndOfTurn(turn) => turn,
+        }
+    }
+
+    /// The turn the event is about, without the event.
+    pub fn into_turn(self) -> Turn {
+        match self {
+            TurnLifecycle::StartOfTurn(turn)
+            | TurnLifecycle::Update(turn)
+            | TurnLifecycle::EagerEndOfTurn(turn)
+            | TurnLifecycle::TurnResumed(turn)
+            | TurnLifecycle::EndOfTurn(turn) => turn,
+        }
+    }
+
+    /// The event as reported in [`FluxResponse::TurnInfo`].
+    pub fn event(&self) -> TurnEvent {
+        match self {
+            TurnLifecycle::StartOfTurn(_) => TurnEvent::StartOfTurn,
+            TurnLifecycle::Update(_) => TurnEvent::Update,
+            TurnLifecycle::EagerEndOfTurn(_) => TurnEvent::EagerEndOfTurn,
+            TurnLifecycle::TurnResumed(_) => TurnEvent::TurnResumed,
+            TurnLifecycle::EndOfTurn(_) => TurnEvent::EndOfTurn,
+        }
+    }
+}
+
+/// The state of a turn reported by a [`TurnLifecycle`] event.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct Turn {
+    /// The index of the turn in the conversation, starting from zero.
+    pub turn_index: u32,
+
+    /// When the audio of the turn starts, in seconds.
+    pub audio_window_start: f64,
+
+    /// When the audio of the turn received so far ends, in seconds.
+    pub audio_window_end: f64,
+
+    /// The transcript of the turn so far.
+    pub transcript: String,
+
+    /// The words of the transcript.
+    pub words: Vec<FluxWord>,
+
+    /// Confidence that the turn has ended.
+    pub end_of_turn_confidence: f64,
+}
+
 /// A word in a Flux turn with confidence
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[non_exhaustive]
 pub struct FluxWord {
     #[allow(missing_docs)]
@@ -305,6 +499,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn reads_turn_lifecycle() {
+        let json = r#"{"type": "TurnInfo", "request_id": "550e8400-e29b-41d4-a716-446655440000", "sequence_id": 3, "event": "EagerEndOfTurn", "turn_index": 2, "audio_window_start": 4.0, "audio_window_end": 5.5, "transcript": "hello", "words": [{"word": "hello", "confidence": 0.9}], "end_of_turn_confidence": 0.6}"#;
+        let response: FluxResponse = serde_json::from_str(json).unwrap();
+
+        let event = response.turn_lifecycle().unwrap();
+        assert_eq!(event.event(), TurnEvent::EagerEndOfTurn);
+        let TurnLifecycle::EagerEndOfTurn(turn) = response.into_turn_lifecycle().unwrap() else {
+            panic!("expected EagerEndOfTurn");
+        };
+        assert_eq!(&turn, event.turn());
+        assert_eq!(turn.turn_index, 2);
+        assert_eq!((turn.audio_window_start, turn.audio_window_end), (4.0, 5.5));
+        assert_eq!(turn.transcript, "hello");
+
+        let connected = r#"{"type": "Connected", "request_id": "550e8400-e29b-41d4-a716-446655440000", "sequence_id": 0}"#;
+        let response: FluxResponse = serde_json::from_str(connected).unwrap();
+        assert_eq!(response.turn_lifecycle(), None);
+    }
+
     #[test]
     fn serialize_roundtrip_connected() {
         let json = r#"{"type":"Connected","request_id":"550e8400-e29b-41d4-a716-446655440000","sequence_id":0}"#;
@@ -313,6 +527,15 @@ mod tests {
         assert_eq!(serialized, json);
     }
 
+    #[test]
+    fn serialize_roundtrip_turn_info() {
+        let json = r#"{"type":"TurnInfo","request_id":"550e8400-e29b-41d4-a716-446655440000","sequence_id":1,"event":"EndOfTurn","turn_index":0,"audio_window_start":0.0,"audio_window_end":1.0,"transcript":"hello","words":[{"word":"hello","confidence":0.9}],"end_of_turn_confidence":0.8}"#;
+        let response: FluxResponse = serde_json::from_str(json).unwrap();
+        let serialized = serde_json::to_string(&response).unwrap();
+        let roundtrip: FluxResponse = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(roundtrip, response);
+    }
+
     #[test]
     fn serialize_unknown_preserves_original() {
         let json = r#"{"type":"NewFeature","some_field":42}"#;
@@ -0,0 +1,152 @@
+//! String interning for word-level response data.
+//!
+//! A long transcript repeats a small vocabulary many thousands of times. Words
+//! are stored as [`Arc<str>`], and while a response is being deserialized every
+//! occurrence of the same text shares a single allocation. This keeps peak
+//! memory and allocation count proportional to the number of distinct words
+//! rather than the length of the recording.
+
+use std::{cell::RefCell, collections::HashSet, fmt, sync::Arc};
+
+use serde::{
+    de::{Error, Visitor},
+    Deserialize, Deserializer,
+};
+
+thread_local! {
+    static INTERNER: RefCell<Option<HashSet<Arc<str>>>> = const { RefCell::new(None) };
+}
+
+/// Deserialize `T`, sharing the allocations of identical interned strings within it.
+pub(crate) fn scoped<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    let _scope = Scope::enter();
+    T::deserialize(deserializer)
+}
+
+/// Deserialize a string, reusing an identical one from the current scope if there is one.
+pub(crate) fn string<'de, D>(deserializer: D) -> Result<Arc<str>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_str(InternVisitor)
+}
+
+/// Like [`string`], for optional fields. Use together with `#[serde(default)]`.
+pub(crate) fn option_string<'de, D>(deserializer: D) -> Result<Option<Arc<str>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    struct Interned(#[serde(deserialize_with = "string")] Arc<str>);
+
+    Ok(Option::<Interned>::deserialize(deserializer)?.map(|Interned(s)| s))
+}
+
+fn intern(s: &str) -> Arc<str> {
+    INTERNER.with_borrow_mut(|interner| match interner {
+        Some(strings) => match strings.get(s) {
+            Some(existing) => Arc::clone(existing),
+            None => {
+                let new: Arc<str> = Arc::from(s);
+                strings.insert(Arc::clone(&new));
+                new
+            }
+        },
+        None => Arc::from(s),
+    })
+}
+
+/// Interning is active on this thread while a `Scope` is alive.
+///
+/// Nested scopes share the outermost scope's strings.
+struct Scope {
+    outermost: bool,
+}
+
+impl Scope {
+    fn enter() -> Self {
+        INTERNER.with_borrow_mut(|interner| {
+            let outermost = interner.is_none();
+            if outermost {
+                *interner = Some(HashSet::new());
+            }
+            Scope { outermost }
+        })
+    }
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        if self.outermost {
+            INTERNER.with_borrow_mut(|interner| *interner = None);
+        }
+    }
+}
+
+struct InternVisitor;
+
+impl Visitor<'_> for InternVisitor {
+    type Value = Arc<str>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a string")
+    }
+
+    fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(intern(v))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    struct Words {
+        #[serde(deserialize_with = "super::scoped")]
+        words: Vec<Word>,
+    }
+
+    #[derive(Deserialize)]
+    struct Word {
+        #[serde(deserialize_with = "super::string")]
+        word: Arc<str>,
+        #[serde(default, deserialize_with = "super::option_string")]
+        punctuated: Option<Arc<str>>,
+    }
+
+    #[test]
+    fn identical_words_share_an_allocation() {
+        let words: Words = serde_json::from_str(
+            r#"{"words": [
+                {"word": "the", "punctuated": "The"},
+                {"word": "cat"},
+                {"word": "the", "punctuated": null},
+                {"word": "the"}
+            ]}"#,
+        )
+        .unwrap();
+        let words = words.words;
+
+        assert_eq!(&*words[0].word, "the");
+        assert!(Arc::ptr_eq(&words[0].word, &words[2].word));
+        assert!(Arc::ptr_eq(&words[0].word, &words[3].word));
+        assert!(!Arc::ptr_eq(&words[0].word, &words[1].word));
+        assert_eq!(words[0].punctuated.as_deref(), Some("The"));
+        assert_eq!(words[1].punctuated, None);
+        assert_eq!(words[2].punctuated, None);
+    }
+
+    #[test]
+    fn no_sharing_outside_a_scope() {
+        let first: Word = serde_json::from_str(r#"{"word": "the"}"#).unwrap();
+        let second: Word = serde_json::from_str(r#"{"word": "the"}"#).unwrap();
+        assert!(!Arc::ptr_eq(&first.word, &second.word));
+    }
+}
@@ -0,0 +1,194 @@
+//! Reconcile the interim and final results of a live stream.
+//!
+//! See [`TranscriptAggregator`] for more info.
+
+use crate::common::stream_response::{StreamResponse, Word};
+
+/// The best transcript so far of a live stream with interim results.
+///
+/// Deepgram sends interim results for the audio after the last final result,
+/// each replacing the one before, until a final result settles the words for
+/// part of that audio. Feed each [`StreamResponse`] to
+/// [`push`](Self::push), and [`words`](Self::words) holds the final words
+/// followed by the latest interim ones.
+///
+/// The first [`confirmed`](Self::confirmed) words come from final results.
+/// They keep their indices and never change, so a display only needs to
+/// redraw from the index [`push`](Self::push) returns.
+///
+/// Uses the first alternative of the first channel.
+///
+/// ```
+/// # use deepgram::common::{live_transcript::TranscriptAggregator, stream_response::StreamResponse};
+/// # fn example(responses: Vec<StreamResponse>) {
+/// let mut aggregator = TranscriptAggregator::new();
+/// for response in &responses {
+///     if let Some(changed) = aggregator.push(response) {
+///         println!("redraw from word {changed}: {}", aggregator.transcript());
+///     }
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TranscriptAggregator {
+    words: Vec<Word>,
+    confirmed: usize,
+}
+
+impl TranscriptAggregator {
+    /// Start with an empty transcript.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Update the transcript with `response`.
+    ///
+    /// Returns the index of the first word that may have changed, or was
+    /// confirmed, if `response` is a result for the first channel.
+    /// [`StreamResponse::Reconnected`] drops the interim words, since the
+    /// audio they are for will not be finalized.
+    pub fn push(&mut self, response: &StreamResponse) -> Option<usize> {
+        let changed = self.confirmed;
+
+        match response {
+            StreamResponse::TranscriptResponse {
+                start,
+                duration,
+                is_final,
+                channel,
+                channel_index,
+                ..
+            } if channel_index.first().is_none_or(|&index| index == 0) => {
+                let words = channel
+                    .alternatives
+                    .first()
+                    .map(|alternative| alternative.words.as_slice())
+                    .unwrap_or_default();
+
+                if *is_final {
+                    // Interim words for audio past the end of the final result
+                    // still stand until the next interim result.
+                    let end = start + duration;
+                    let later: Vec<Word> = self
+                        .words
+                        .drain(self.confirmed..)
+                        .filter(|word| word.start >= end)
+                        .collect();
+                    self.words.extend_from_slice(words);
+                    self.confirmed = self.words.len();
+                    self.words.extend(later);
+                } else {
+                    self.words.truncate(self.confirmed);
+                    self.words.extend_from_slice(words);
+                }
+
+                Some(changed)
+            }
+            StreamResponse::Reconnected { .. } if self.words.len() > self.confirmed => {
+                self.words.truncate(self.confirmed);
+                Some(changed)
+            }
+            _ => None,
+        }
+    }
+
+    /// The confirmed words, followed by the interim ones.
+    pub fn words(&self) -> &[Word] {
+        &self.words
+    }
+
+    /// How many of [`words`](Self::words) are confirmed by final results.
+    pub fn confirmed(&self) -> usize {
+        self.confirmed
+    }
+
+    /// Whether the word at `index` is confirmed by a final result.
+    pub fn is_confirmed(&self, index: usize) -> bool {
+        index < self.confirmed
+    }
+
+    /// The words joined with spaces, preferring the punctuated form of each
+    /// word.
+    pub fn transcript(&self) -> String {
+        join(&self.words)
+    }
+
+    /// The confirmed words joined with spaces, preferring the punctuated form
+    /// of each word.
+    pub fn confirmed_transcript(&self) -> String {
+        join(&self.words[..self.confirmed])
+    }
+}
+
+fn join(words: &[Word]) -> String {
+    words.iter().map(Word::text).collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::TranscriptAggregator;
+    use crate::common::{
+        fixtures::{stream_word, TranscriptResponseBuilder},
+        stream_response::StreamResponse,
+    };
+
+    fn result(words: &[(&str, f64)], start: f64, end: f64, is_final: bool) -> StreamResponse {
+        TranscriptResponseBuilder::new(
+            words
+                .iter()
+                .map(|&(word, word_start)| stream_word(word, word_start, word_start + 0.4)),
+        )
+        .start(start)
+        .duration(end - start)
+        .is_final(is_final)
+        .build()
+    }
+
+    #[test]
+    fn confirms_final_words() {
+        let mut aggregator = TranscriptAggregator::new();
+
+        assert_eq!(
+            aggregator.push(&result(&[("the", 0.0), ("quick", 0.5)], 0.0, 1.0, false)),
+            Some(0)
+        );
+        assert_eq!(aggregator.transcript(), "the quick");
+        assert_eq!(aggregator.confirmed(), 0);
+
+        let interim = [("a", 0.0), ("quick", 0.5), ("brown", 1.0), ("fox", 1.5)];
+        aggregator.push(&result(&interim, 0.0, 2.0, false));
+        assert_eq!(aggregator.transcript(), "a quick brown fox");
+
+        // The final result only covers the first second.
+        assert_eq!(
+            aggregator.push(&result(&[("a", 0.0), ("quick", 0.5)], 0.0, 1.0, true)),
+            Some(0)
+        );
+        assert_eq!(aggregator.transcript(), "a quick brown fox");
+        assert_eq!(aggregator.confirmed_transcript(), "a quick");
+        assert!(aggregator.is_confirmed(1) && !aggregator.is_confirmed(2));
+
+        assert_eq!(
+            aggregator.push(&result(&[("brown", 1.0), ("box", 1.5)], 1.0, 2.0, false)),
+            Some(2)
+        );
+        assert_eq!(aggregator.transcript(), "a quick brown box");
+    }
+
+    #[test]
+    fn drops_interim_words_on_reconnect() {
+        let mut aggregator = TranscriptAggregator::new();
+        aggregator.push(&result(&[("hello", 0.0)], 0.0, 0.5, true));
+        aggregator.push(&result(&[("wor", 0.5)], 0.5, 1.0, false));
+
+        let reconnected = StreamResponse::Reconnected {
+            request_id: Uuid::nil(),
+            attempts: 1,
+        };
+        assert_eq!(aggregator.push(&reconnected), Some(1));
+        assert_eq!(aggregator.transcript(), "hello");
+        assert_eq!(aggregator.push(&reconnected), None);
+    }
+}
@@ -2,7 +2,14 @@
 
 pub mod audio_source;
 pub mod batch_response;
-#[cfg(feature = "listen")]
+pub mod captions;
+pub mod estimate;
+#[cfg(any(test, feature = "test-util"))]
+pub mod fixtures;
 pub mod flux_response;
+mod intern;
+pub mod live_transcript;
 pub mod options;
+pub mod redaction;
 pub mod stream_response;
+pub mod transcript;
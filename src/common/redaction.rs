@@ -0,0 +1,398 @@
+//! Redact pre-recorded transcripts on the client.
+//!
+//! Deepgram can redact sensitive information itself with
+//! [`OptionsBuilder::redact`](crate::common::options::OptionsBuilder::redact).
+//! [`Redactor`] applies further redactions to a [`Response`] after it has
+//! been received, for rules that must be enforced locally.
+
+use std::{ops::Range, sync::Arc};
+
+#[cfg(feature = "regex")]
+use regex::Regex;
+
+use crate::common::{
+    batch_response::{join_words, Response, Word},
+    options::Replace,
+    transcript::normalize,
+};
+
+/// Applies find-and-replace and regex redactions to a [`Response`].
+///
+/// ```
+/// # use deepgram::common::{batch_response::Response, options::Replace, redaction::Redactor};
+/// # fn example(mut response: Response) {
+/// let redactor = Redactor::new().replace(Replace {
+///     find: String::from("Acme Corp"),
+///     replace: Some(String::from("[COMPANY]")),
+/// });
+///
+/// redactor.redact(&mut response);
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Redactor {
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug, Clone)]
+enum Rule {
+    Replace {
+        find: Vec<String>,
+        replace: Option<String>,
+    },
+
+    #[cfg(feature = "regex")]
+    Regex { pattern: Regex, replacement: String },
+}
+
+/// A whitespace-separated token of text, or a word, being redacted.
+trait Token: Sized {
+    fn key(&self) -> &str;
+
+    fn display(&self) -> &str;
+
+    /// Merge `tokens` into one token for `replace`, displayed as `display`.
+    fn merge(tokens: &[Self], replace: &str, display: String) -> Self;
+
+    #[cfg(feature = "regex")]
+    fn map(&mut self, f: impl FnMut(&str) -> String);
+}
+
+impl Redactor {
+    /// A redactor that leaves responses unchanged.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace every occurrence of [`find`](Replace::find) with
+    /// [`replace`](Replace::replace), or remove it if that is [`None`].
+    ///
+    /// Matches whole words, ignoring case and the punctuation around each
+    /// word. A replaced match keeps the punctuation before and after it,
+    /// while a removed match is dropped along with its punctuation.
+    pub fn replace(mut self, replace: Replace) -> Self {
+        let find: Vec<String> = replace
+            .find
+            .split_whitespace()
+            .map(normalize)
+            .filter(|token| !token.is_empty())
+            .collect();
+
+        if !find.is_empty() {
+            self.rules.push(Rule::Replace {
+                find,
+                replace: replace.replace,
+            });
+        }
+
+        self
+    }
+
+    /// Replace every match of `pattern` with `replacement`, which may refer
+    /// to capture groups as in [`Regex::replace_all`].
+    ///
+    /// `pattern` is matched against each word separately, so it should not
+    /// span whitespace.
+    #[cfg(feature = "regex")]
+    pub fn regex(mut self, pattern: Regex, replacement: impl Into<String>) -> Self {
+        self.rules.push(Rule::Regex {
+            pattern,
+            replacement: replacement.into(),
+        });
+
+        self
+    }
+
+    /// Redact the transcripts, words, utterances, and paragraphs of `response`.
+    ///
+    /// Transcripts of alternatives and utterances with any redacted words are
+    /// rebuilt from their words. Other results, such as summaries and search
+    /// hits, are left as they are.
+    pub fn redact(&self, response: &mut Response) {
+        for channel in &mut response.results.channels {
+            for alternative in &mut channel.alternatives {
+                if self.redact_tokens(&mut alternative.words) {
+                    alternative.transcript = join_words(&alternative.words);
+                }
+
+                if let Some(paragraphs) = &mut alternative.paragraphs {
+                    paragraphs.map_text(|text| self.redact_text(text));
+                }
+            }
+        }
+
+        for utterance in response.results.utterances.iter_mut().flatten() {
+            if self.redact_tokens(&mut utterance.words) {
+                utterance.transcript = join_words(&utterance.words);
+            }
+        }
+    }
+
+    /// Redact `text`, keeping its whitespace unless a match spans it.
+    pub fn redact_text(&self, text: &str) -> String {
+        let mut tokens: Vec<TextToken> = Vec::new();
+        let mut rest = text;
+
+        while let Some(start) = rest.find(|c: char| !c.is_whitespace()) {
+            let end = rest[start..]
+                .find(char::is_whitespace)
+                .map_or(rest.len(), |end| start + end);
+
+            tokens.push(TextToken {
+                leading: String::from(&rest[..start]),
+                text: String::from(&rest[start..end]),
+            });
+            rest = &rest[end..];
+        }
+
+        self.redact_tokens(&mut tokens);
+
+        let mut redacted: String = tokens
+            .into_iter()
+            .flat_map(|token| [token.leading, token.text])
+            .collect();
+        redacted.push_str(rest);
+        redacted
+    }
+
+    /// Apply every rule to `tokens`, returning whether any of them changed.
+    fn redact_tokens<T: Token>(&self, tokens: &mut Vec<T>) -> bool {
+        let mut changed = false;
+
+        for rule in &self.rules {
+            match rule {
+                Rule::Replace { find, replace } => {
+                    for range in matches(tokens, find).into_iter().rev() {
+                        let matched = &tokens[range.clone()];
+                        let (prefix, _) = split_punctuation(matched[0].display());
+                        let (_, suffix) = split_punctuation(matched[matched.len() - 1].display());
+
+                        let merged = replace.as_ref().map(|replace| {
+                            T::merge(matched, replace, format!("{prefix}{replace}{suffix}"))
+                        });
+                        tokens.splice(range, merged);
+                        changed = true;
+                    }
+                }
+
+                #[cfg(feature = "regex")]
+                Rule::Regex {
+                    pattern,
+                    replacement,
+                } => {
+                    for token in tokens.iter_mut() {
+                        if pattern.is_match(token.key()) || pattern.is_match(token.display()) {
+                            token.map(|text| {
+                                pattern.replace_all(text, replacement.as_str()).into_owned()
+                            });
+                            changed = true;
+                        }
+                    }
+                }
+            }
+        }
+
+        changed
+    }
+}
+
+/// The non-overlapping ranges of `tokens` matching `find`, in order.
+fn matches<T: Token>(tokens: &[T], find: &[String]) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut start = 0;
+
+    while start + find.len() <= tokens.len() {
+        let end = start + find.len();
+
+        if tokens[start..end]
+            .iter()
+            .zip(find)
+            .all(|(token, find)| normalize(token.key()) == *find)
+        {
+            ranges.push(start..end);
+            start = end;
+        } else {
+            start += 1;
+        }
+    }
+
+    ranges
+}
+
+/// Split `text` into its leading and trailing punctuation.
+fn split_punctuation(text: &str) -> (&str, &str) {
+    let is_punctuation = |c: char| !c.is_alphanumeric();
+    let start = text.len() - text.trim_start_matches(is_punctuation).len();
+    let end = text.trim_end_matches(is_punctuation).len().max(start);
+
+    (&text[..start], &text[end..])
+}
+
+impl Token for Word {
+    fn key(&self) -> &str {
+        &self.word
+    }
+
+    fn display(&self) -> &str {
+        self.text()
+    }
+
+    fn merge(words: &[Self], replace: &str, display: String) -> Self {
+        let first = &words[0];
+        let last = &words[words.len() - 1];
+
+        Word {
+            word: Arc::from(replace),
+            start: first.start,
+            end: last.end,
+            confidence: words
+                .iter()
+                .map(|word| word.confidence)
+                .fold(f64::INFINITY, f64::min),
+            speaker: first.speaker,
+            punctuated_word: words
+                .iter()
+                .any(|word| word.punctuated_word.is_some())
+                .then(|| Arc::from(display)),
+        }
+    }
+
+    #[cfg(feature = "regex")]
+    fn map(&mut self, mut f: impl FnMut(&str) -> String) {
+        self.word = Arc::from(f(&self.word));
+        if let Some(punctuated_word) = &mut self.punctuated_word {
+            *punctuated_word = Arc::from(f(punctuated_word));
+        }
+    }
+}
+
+struct TextToken {
+    leading: String,
+    text: String,
+}
+
+impl Token for TextToken {
+    fn key(&self) -> &str {
+        &self.text
+    }
+
+    fn display(&self) -> &str {
+        &self.text
+    }
+
+    fn merge(tokens: &[Self], _replace: &str, display: String) -> Self {
+        TextToken {
+            leading: tokens[0].leading.clone(),
+            text: display,
+        }
+    }
+
+    #[cfg(feature = "regex")]
+    fn map(&mut self, mut f: impl FnMut(&str) -> String) {
+        self.text = f(&self.text);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Redactor;
+    use crate::common::{
+        fixtures::{ResponseBuilder, WordBuilder},
+        options::Replace,
+    };
+
+    fn replace(find: &str, replace: Option<&str>) -> Replace {
+        Replace {
+            find: String::from(find),
+            replace: replace.map(String::from),
+        }
+    }
+
+    #[test]
+    fn replaces_words() {
+        let mut response = ResponseBuilder::new()
+            .channel([
+                WordBuilder::new("call", 0.0, 0.5)
+                    .punctuated_word("Call")
+                    .build(),
+                WordBuilder::new("acme", 0.5, 1.0).confidence(0.8).build(),
+                WordBuilder::new("corp", 1.0, 1.5)
+                    .confidence(0.6)
+                    .punctuated_word("Corp.")
+                    .build(),
+                WordBuilder::new("um", 1.5, 2.0).build(),
+            ])
+            .utterance(0, [WordBuilder::new("acme", 0.5, 1.0).build()])
+            .build();
+
+        Redactor::new()
+            .replace(replace("ACME corp", Some("[COMPANY]")))
+            .replace(replace("um", None))
+            .redact(&mut response);
+
+        let alternative = &response.results.channels[0].alternatives[0];
+        assert_eq!(alternative.transcript, "Call [COMPANY].");
+        assert_eq!(alternative.words.len(), 2);
+        assert_eq!(
+            (alternative.words[1].start, alternative.words[1].end),
+            (0.5, 1.5)
+        );
+        assert_eq!(alternative.words[1].confidence, 0.6);
+        assert_eq!(response.results.utterances.unwrap()[0].transcript, "acme");
+    }
+
+    #[test]
+    fn redacts_text() {
+        let redactor = Redactor::new().replace(replace("acme corp", Some("[COMPANY]")));
+
+        assert_eq!(
+            redactor.redact_text("\nI work at (Acme\n Corp), right?\n"),
+            "\nI work at ([COMPANY]), right?\n",
+        );
+        assert_eq!(redactor.redact_text("Acme Co"), "Acme Co");
+
+        let redactor = Redactor::new().replace(replace("acme corp", None));
+        assert_eq!(
+            redactor.redact_text("I work at (Acme Corp), right?"),
+            "I work at right?",
+        );
+    }
+
+    #[test]
+    fn leaves_unmatched_transcripts() {
+        let mut response = ResponseBuilder::new()
+            .channel([WordBuilder::new("hello", 0.0, 0.5).build()])
+            .build();
+        response.results.channels[0].alternatives[0].transcript = String::from("Hello!");
+
+        Redactor::new()
+            .replace(replace("goodbye", None))
+            .redact(&mut response);
+
+        assert_eq!(
+            response.results.channels[0].alternatives[0].transcript,
+            "Hello!"
+        );
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn redacts_regex() {
+        let mut response = ResponseBuilder::new()
+            .channel([
+                WordBuilder::new("pin", 0.0, 0.5).build(),
+                WordBuilder::new("1234", 0.5, 1.0)
+                    .punctuated_word("1234.")
+                    .build(),
+            ])
+            .build();
+
+        let redactor = Redactor::new().regex(regex::Regex::new(r"\d").unwrap(), "*");
+        redactor.redact(&mut response);
+
+        let alternative = &response.results.channels[0].alternatives[0];
+        assert_eq!(alternative.transcript, "pin ****.");
+        assert_eq!(&*alternative.words[1].word, "****");
+        assert_eq!(redactor.redact_text("PIN 42"), "PIN **");
+    }
+}
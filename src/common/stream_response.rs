@@ -1,13 +1,16 @@
 //! Stream Response module
 
-use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use serde::{de, Deserialize, Deserializer, Serialize};
+use uuid::Uuid;
 
 /// A single transcribed word.
 ///
 /// See the [Deepgram API Reference][api] for more info.
 ///
 /// [api]: https://developers.deepgram.com/api-reference/#transcription-prerecorded
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Word {
     #[allow(missing_docs)]
     pub word: String,
@@ -31,12 +34,19 @@ pub struct Word {
     pub language: Option<String>,
 }
 
+impl Word {
+    /// The punctuated form of the word when it is set, and otherwise the word.
+    pub(crate) fn text(&self) -> &str {
+        self.punctuated_word.as_deref().unwrap_or(&self.word)
+    }
+}
+
 /// Transcript alternatives.
 ///
 /// See the [Deepgram API Reference][api] for more info.
 ///
 /// [api]: https://developers.deepgram.com/api-reference/#transcription-prerecorded
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Alternatives {
     #[allow(missing_docs)]
     pub transcript: String,
@@ -59,14 +69,42 @@ pub struct Alternatives {
 ///
 /// [api]: https://developers.deepgram.com/api-reference/#transcription-prerecorded
 /// [docs]: https://developers.deepgram.com/documentation/features/multichannel/
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Channel {
     #[allow(missing_docs)]
     pub alternatives: Vec<Alternatives>,
 }
 
+/// Which of the audio channels of a multichannel stream a response is for,
+/// as returned by [`StreamResponse::channel_index`].
+///
+/// See the [Deepgram Multichannel feature docs][docs] for more info.
+///
+/// [docs]: https://developers.deepgram.com/docs/multichannel
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ChannelIndex {
+    /// The channel, counting from zero.
+    pub index: usize,
+
+    /// How many channels the stream has.
+    pub count: usize,
+}
+
+impl ChannelIndex {
+    /// Read Deepgram's `[index, count]` pair.
+    fn from_pair<T: Copy + TryInto<usize>>(pair: &[T]) -> Option<Self> {
+        match pair {
+            [index, count] => Some(Self {
+                index: (*index).try_into().ok()?,
+                count: (*count).try_into().ok()?,
+            }),
+            _ => None,
+        }
+    }
+}
+
 /// Modle info
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct ModelInfo {
     #[allow(missing_docs)]
     pub name: String,
@@ -83,7 +121,7 @@ pub struct ModelInfo {
 /// See the [Deepgram API Reference][api] for more info.
 ///
 /// [api]: https://developers.deepgram.com/api-reference/#transcription-prerecorded
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Metadata {
     #[allow(missing_docs)]
     pub request_id: String,
@@ -96,7 +134,7 @@ pub struct Metadata {
 }
 
 /// Possible websocket message types
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
 #[non_exhaustive]
 pub enum StreamResponse {
@@ -143,6 +181,18 @@ pub enum StreamResponse {
 
         #[allow(missing_docs)]
         channels: u32,
+
+        /// The SHA-256 hash of the audio received.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        sha256: Option<String>,
+
+        /// The UUIDs of the models used for the request.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        models: Vec<String>,
+
+        /// Info about each model in `models`, keyed by UUID.
+        #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+        model_info: HashMap<String, ModelInfo>,
     },
     #[allow(missing_docs)]
     SpeechStartedResponse {
@@ -168,4 +218,178 @@ pub enum StreamResponse {
         #[allow(missing_docs)]
         last_word_end: f64,
     },
+    /// Deepgram could not process the stream.
+    ///
+    /// Deepgram closes the connection after sending this.
+    ErrorResponse {
+        #[allow(missing_docs)]
+        #[serde(rename = "type")]
+        type_field: String,
+
+        #[allow(missing_docs)]
+        description: String,
+
+        #[allow(missing_docs)]
+        message: String,
+
+        #[allow(missing_docs)]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        variant: Option<String>,
+    },
+    /// An event of a type this version of the crate does not know about,
+    /// kept as the raw JSON so newer server events do not break the stream.
+    #[serde(deserialize_with = "deserialize_unknown")]
+    Unknown(serde_json::Value),
+    /// The connection dropped and was re-established by
+    #[cfg_attr(
+        feature = "listen-ws",
+        doc = "[`WebsocketBuilder::reconnect`](crate::listen::websocket::WebsocketBuilder::reconnect)."
+    )]
+    #[cfg_attr(not(feature = "listen-ws"), doc = "`WebsocketBuilder::reconnect`.")]
+    ///
+    /// Produced by this crate rather than sent by Deepgram.
+    #[serde(skip_deserializing)]
+    Reconnected {
+        /// The request ID of the new connection.
+        request_id: Uuid,
+
+        /// How many attempts it took to reconnect.
+        attempts: u32,
+    },
+}
+
+/// The event types with their own [`StreamResponse`] variant.
+const KNOWN_TYPES: [&str; 5] = [
+    "Results",
+    "Metadata",
+    "SpeechStarted",
+    "UtteranceEnd",
+    "Error",
+];
+
+/// Deserialize [`StreamResponse::Unknown`], failing for known event types so
+/// that malformed events are reported rather than passed through.
+fn deserialize_unknown<'de, D>(deserializer: D) -> Result<serde_json::Value, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = serde_json::Value::deserialize(deserializer)?;
+
+    match value.get("type").and_then(serde_json::Value::as_str) {
+        Some(event_type) if !KNOWN_TYPES.contains(&event_type) => Ok(value),
+        _ => Err(de::Error::custom("malformed or untyped streaming event")),
+    }
+}
+
+impl StreamResponse {
+    /// The audio channel the response is for.
+    ///
+    /// Returns [`None`] for responses that aren't about a single channel,
+    /// such as [`StreamResponse::TerminalResponse`].
+    pub fn channel_index(&self) -> Option<ChannelIndex> {
+        match self {
+            StreamResponse::TranscriptResponse { channel_index, .. } => {
+                ChannelIndex::from_pair(channel_index)
+            }
+            StreamResponse::SpeechStartedResponse { channel, .. }
+            | StreamResponse::UtteranceEndResponse { channel, .. } => {
+                ChannelIndex::from_pair(channel)
+            }
+            _ => None,
+        }
+    }
+
+    /// The type of the event, such as `"Results"` or `"UtteranceEnd"`.
+    pub fn event_type(&self) -> &str {
+        match self {
+            StreamResponse::TranscriptResponse { type_field, .. }
+            | StreamResponse::SpeechStartedResponse { type_field, .. }
+            | StreamResponse::UtteranceEndResponse { type_field, .. }
+            | StreamResponse::ErrorResponse { type_field, .. } => type_field,
+            StreamResponse::TerminalResponse { .. } => "Metadata",
+            StreamResponse::Unknown(value) => value
+                .get("type")
+                .and_then(serde_json::Value::as_str)
+                .unwrap_or("Unknown"),
+            StreamResponse::Reconnected { .. } => "Reconnected",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChannelIndex, StreamResponse};
+
+    #[test]
+    fn deserialize_metadata() {
+        let json = r#"{
+            "type": "Metadata",
+            "transaction_key": "deprecated",
+            "request_id": "a6d1f0c4-6e4a-4e8b-9c8b-3f1f3f1f3f1f",
+            "sha256": "abc",
+            "created": "2024-01-01T00:00:00.000Z",
+            "duration": 2.5,
+            "channels": 1,
+            "models": ["1abfe86b-e047-4eed-858a-35e5625b41ee"],
+            "model_info": {
+                "1abfe86b-e047-4eed-858a-35e5625b41ee": {
+                    "name": "2-general-nova",
+                    "version": "2024-01-06.5664",
+                    "arch": "nova-2"
+                }
+            }
+        }"#;
+
+        match serde_json::from_str(json).unwrap() {
+            StreamResponse::TerminalResponse {
+                models, model_info, ..
+            } => assert_eq!(model_info[&models[0]].arch, "nova-2"),
+            response => panic!("expected metadata, got {response:?}"),
+        }
+    }
+
+    #[test]
+    fn reads_channel_index() {
+        let json = r#"{"type": "UtteranceEnd", "channel": [1, 2], "last_word_end": 2.5}"#;
+        let response: StreamResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            response.channel_index(),
+            Some(ChannelIndex { index: 1, count: 2 })
+        );
+
+        let json = r#"{"type": "SpeechStarted", "channel": [], "timestamp": 0.5}"#;
+        let response: StreamResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(response.channel_index(), None);
+    }
+
+    #[test]
+    fn deserialize_error() {
+        let json = r#"{"type": "Error", "description": "Bad audio.", "message": "DATA-0000", "variant": "data"}"#;
+        let response: StreamResponse = serde_json::from_str(json).unwrap();
+
+        assert!(matches!(
+            &response,
+            StreamResponse::ErrorResponse { message, .. } if message == "DATA-0000"
+        ));
+        assert_eq!(response.event_type(), "Error");
+    }
+
+    #[test]
+    fn deserialize_unknown_type() {
+        let json = r#"{"type": "NewFeature", "data": [1, 2, 3]}"#;
+        let response: StreamResponse = serde_json::from_str(json).unwrap();
+
+        assert!(matches!(response, StreamResponse::Unknown(_)));
+        assert_eq!(response.event_type(), "NewFeature");
+        assert_eq!(
+            serde_json::to_string(&response).unwrap(),
+            r#"{"data":[1,2,3],"type":"NewFeature"}"#
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_known_type() {
+        let json = r#"{"type": "UtteranceEnd", "channel": [0, 1]}"#;
+        assert!(serde_json::from_str::<StreamResponse>(json).is_err());
+    }
 }
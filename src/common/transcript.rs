@@ -0,0 +1,660 @@
+//! Render, traverse, and search pre-recorded transcripts.
+//!
+//! See [`Response::channels`], [`Response::speaker_transcript`],
+//! [`Response::speaker_segments`], [`Response::paragraphs`],
+//! [`Response::find_phrase`], and [`ResultAlternative::words_below`] for more info.
+
+use std::{fmt::Write, time::Duration};
+
+use crate::common::batch_response::{
+    average_confidence, ChannelResult, Paragraph, Response, ResultAlternative, Sentence, Utterance,
+    Word,
+};
+
+/// An occurrence of a phrase in a transcript, found by [`Response::find_phrase`].
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct PhraseMatch<'a> {
+    words: &'a [Word],
+}
+
+impl<'a> PhraseMatch<'a> {
+    /// The matched words, in order.
+    pub fn words(&self) -> &'a [Word] {
+        self.words
+    }
+
+    /// The start time of the first matched word in seconds.
+    pub fn start(&self) -> f64 {
+        self.words[0].start
+    }
+
+    /// The end time of the last matched word in seconds.
+    pub fn end(&self) -> f64 {
+        self.words[self.words.len() - 1].end
+    }
+}
+
+/// A single audio channel of a [`Response`], returned by [`Response::channel`]
+/// and [`Response::channels`].
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ChannelView<'a> {
+    index: usize,
+    channel: &'a ChannelResult,
+}
+
+impl<'a> ChannelView<'a> {
+    /// The index of the channel, starting from `0`.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The transcript of the first alternative, or `""` if there is none.
+    pub fn transcript(&self) -> &'a str {
+        self.alternative()
+            .map_or("", |alternative| alternative.transcript.as_str())
+    }
+
+    /// The words of the first alternative.
+    pub fn words(&self) -> &'a [Word] {
+        self.alternative()
+            .map_or(&[][..], |alternative| alternative.words.as_slice())
+    }
+
+    /// The full results for the channel, including every alternative.
+    pub fn result(&self) -> &'a ChannelResult {
+        self.channel
+    }
+
+    fn alternative(&self) -> Option<&'a ResultAlternative> {
+        self.channel.alternatives.first()
+    }
+}
+
+/// How [`speaker_segments`] splits words into segments.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SegmentOptions {
+    max_gap: Option<Duration>,
+    max_length: Option<Duration>,
+}
+
+impl SegmentOptions {
+    /// Split segments only when the speaker changes.
+    pub fn new() -> Self {
+        Self {
+            max_gap: None,
+            max_length: None,
+        }
+    }
+
+    /// Start a new segment when the silence between two words of the same
+    /// speaker is longer than `max_gap`.
+    pub fn max_gap(mut self, max_gap: Duration) -> Self {
+        self.max_gap = Some(max_gap);
+
+        self
+    }
+
+    /// Start a new segment rather than let one grow longer than `max_length`.
+    ///
+    /// A single word longer than `max_length` still gets a segment of its own.
+    pub fn max_length(mut self, max_length: Duration) -> Self {
+        self.max_length = Some(max_length);
+
+        self
+    }
+}
+
+impl Default for SegmentOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A run of consecutive words by one speaker, returned by [`speaker_segments`].
+#[derive(Debug, PartialEq, Clone)]
+#[non_exhaustive]
+pub struct SpeakerSegment {
+    /// [`None`] unless the [Diarization feature][docs] is set.
+    ///
+    /// [docs]: https://developers.deepgram.com/docs/diarization
+    pub speaker: Option<usize>,
+
+    /// The start time of the first word in seconds.
+    pub start: f64,
+
+    /// The end time of the last word in seconds.
+    pub end: f64,
+
+    /// The words joined with spaces, preferring the punctuated form of each word.
+    pub text: String,
+}
+
+/// Merge consecutive words by the same speaker into [`SpeakerSegment`]s.
+///
+/// ```
+/// # use std::time::Duration;
+/// # use deepgram::common::{batch_response::Word, transcript::{speaker_segments, SegmentOptions}};
+/// # fn example(words: &[Word]) {
+/// let options = SegmentOptions::new()
+///     .max_gap(Duration::from_secs(2))
+///     .max_length(Duration::from_secs(30));
+///
+/// for segment in speaker_segments(words, &options) {
+///     println!("{:?} [{:.2}-{:.2}]: {}", segment.speaker, segment.start, segment.end, segment.text);
+/// }
+/// # }
+/// ```
+pub fn speaker_segments(words: &[Word], options: &SegmentOptions) -> Vec<SpeakerSegment> {
+    let max_gap = options
+        .max_gap
+        .map_or(f64::INFINITY, |gap| gap.as_secs_f64());
+    let max_length = options
+        .max_length
+        .map_or(f64::INFINITY, |length| length.as_secs_f64());
+
+    let mut segments: Vec<SpeakerSegment> = Vec::new();
+
+    for word in words {
+        match segments.last_mut() {
+            Some(segment)
+                if segment.speaker == word.speaker
+                    && word.start - segment.end <= max_gap
+                    && word.end - segment.start <= max_length =>
+            {
+                segment.text.push(' ');
+                segment.text.push_str(word.text());
+                segment.end = word.end;
+            }
+            _ => segments.push(SpeakerSegment {
+                speaker: word.speaker,
+                start: word.start,
+                end: word.end,
+                text: String::from(word.text()),
+            }),
+        }
+    }
+
+    segments
+}
+
+impl Response {
+    /// The audio channel at `index`, or [`None`] if there is no such channel.
+    ///
+    /// There is one channel per audio channel when the [Multichannel feature][docs]
+    /// is set, and otherwise a single channel.
+    ///
+    /// [docs]: https://developers.deepgram.com/documentation/features/multichannel/
+    pub fn channel(&self, index: usize) -> Option<ChannelView<'_>> {
+        self.results
+            .channels
+            .get(index)
+            .map(|channel| ChannelView { index, channel })
+    }
+
+    /// Every audio channel, in order.
+    ///
+    /// ```
+    /// # use deepgram::common::batch_response::Response;
+    /// # fn example(response: &Response) {
+    /// for channel in response.channels() {
+    ///     println!("Channel {}: {}", channel.index(), channel.transcript());
+    /// }
+    /// # }
+    /// ```
+    pub fn channels(&self) -> impl Iterator<Item = ChannelView<'_>> {
+        self.results
+            .channels
+            .iter()
+            .enumerate()
+            .map(|(index, channel)| ChannelView { index, channel })
+    }
+
+    /// Merge the words of the transcript into [`SpeakerSegment`]s.
+    ///
+    /// Uses the first alternative of the first channel.
+    /// See [`speaker_segments`] for more info.
+    pub fn speaker_segments(&self, options: &SegmentOptions) -> Vec<SpeakerSegment> {
+        speaker_segments(
+            self.first_alternative()
+                .map_or(&[][..], |alternative| alternative.words.as_slice()),
+            options,
+        )
+    }
+
+    /// The paragraphs of the transcript, in order.
+    ///
+    /// Empty unless the [Paragraphs feature][docs] is set.
+    /// Uses the first alternative of the first channel.
+    ///
+    /// [docs]: https://developers.deepgram.com/docs/paragraphs
+    ///
+    /// ```
+    /// # use deepgram::common::batch_response::Response;
+    /// # fn example(response: &Response) {
+    /// for paragraph in response.paragraphs() {
+    ///     for sentence in paragraph.sentences() {
+    ///         println!("[{:.2}-{:.2}] {}", sentence.start(), sentence.end(), sentence.text());
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn paragraphs(&self) -> impl Iterator<Item = &Paragraph> {
+        self.first_alternative()
+            .and_then(|alternative| alternative.paragraphs.as_ref())
+            .into_iter()
+            .flat_map(|paragraphs| paragraphs.paragraphs())
+    }
+
+    /// The sentences of every [paragraph](Self::paragraphs), in order.
+    pub fn sentences(&self) -> impl Iterator<Item = &Sentence> {
+        self.paragraphs().flat_map(Paragraph::sentences)
+    }
+
+    /// Render the transcript with a line per speaker turn, such as
+    /// `"Speaker 0: Hello.\nSpeaker 1: Hi."`.
+    ///
+    /// Consecutive words by the same speaker are merged into one turn,
+    /// preferring the punctuated form of each word when the Punctuation
+    /// feature is set. Words without a speaker, because the Diarization
+    /// feature is not set, are rendered without a label.
+    ///
+    /// Uses the first alternative of the first channel.
+    /// See [`speaker_transcript_with`](Self::speaker_transcript_with) to name the speakers.
+    pub fn speaker_transcript(&self) -> String {
+        self.speaker_transcript_with(|speaker| format!("Speaker {speaker}"))
+    }
+
+    /// Like [`speaker_transcript`](Self::speaker_transcript), but labels each
+    /// turn with the name `speaker_name` returns for its speaker.
+    ///
+    /// ```
+    /// # use deepgram::common::batch_response::Response;
+    /// # fn example(response: &Response) {
+    /// let names = ["Agent", "Customer"];
+    ///
+    /// let transcript = response.speaker_transcript_with(|speaker| {
+    ///     names
+    ///         .get(speaker)
+    ///         .map_or_else(|| format!("Speaker {speaker}"), |name| name.to_string())
+    /// });
+    /// # }
+    /// ```
+    pub fn speaker_transcript_with<F, S>(&self, mut speaker_name: F) -> String
+    where
+        F: FnMut(usize) -> S,
+        S: AsRef<str>,
+    {
+        let words = self
+            .first_alternative()
+            .map_or(&[][..], |alternative| alternative.words.as_slice());
+
+        let mut transcript = String::new();
+
+        for turn in words.chunk_by(|a, b| a.speaker == b.speaker) {
+            if !transcript.is_empty() {
+                transcript.push('\n');
+            }
+
+            if let Some(speaker) = turn[0].speaker {
+                let _ = write!(transcript, "{}: ", speaker_name(speaker).as_ref());
+            }
+
+            for (index, word) in turn.iter().enumerate() {
+                if index > 0 {
+                    transcript.push(' ');
+                }
+                transcript.push_str(word.text());
+            }
+        }
+
+        transcript
+    }
+
+    /// Find every occurrence of `phrase` in the words of the transcript.
+    ///
+    /// Matching ignores case and any punctuation around each word, so
+    /// `"New York"` matches the words `new york,`. Occurrences may overlap.
+    /// Returns nothing if `phrase` has no words.
+    ///
+    /// Uses the first alternative of the first channel.
+    ///
+    /// ```
+    /// # use deepgram::common::batch_response::Response;
+    /// # fn example(response: &Response) {
+    /// for found in response.find_phrase("thank you") {
+    ///     println!("{:.2}-{:.2}", found.start(), found.end());
+    /// }
+    /// # }
+    /// ```
+    pub fn find_phrase(&self, phrase: &str) -> Vec<PhraseMatch<'_>> {
+        let needle: Vec<String> = phrase
+            .split_whitespace()
+            .map(normalize)
+            .filter(|token| !token.is_empty())
+            .collect();
+
+        if needle.is_empty() {
+            return Vec::new();
+        }
+
+        let words = self
+            .first_alternative()
+            .map_or(&[][..], |alternative| alternative.words.as_slice());
+
+        words
+            .windows(needle.len())
+            .filter(|window| {
+                window
+                    .iter()
+                    .zip(&needle)
+                    .all(|(word, token)| normalize(&word.word) == *token)
+            })
+            .map(|words| PhraseMatch { words })
+            .collect()
+    }
+
+    fn first_alternative(&self) -> Option<&ResultAlternative> {
+        self.results
+            .channels
+            .first()
+            .and_then(|channel| channel.alternatives.first())
+    }
+}
+
+impl ResultAlternative {
+    /// The words with a confidence below `threshold`, in order.
+    ///
+    /// ```
+    /// # use deepgram::common::batch_response::ResultAlternative;
+    /// # fn example(alternative: &ResultAlternative) {
+    /// if alternative.words_below(0.5).count() > 10 {
+    ///     // Route the audio to a human for review
+    /// }
+    /// # }
+    /// ```
+    pub fn words_below(&self, threshold: f64) -> impl Iterator<Item = &Word> {
+        words_below(&self.words, threshold)
+    }
+
+    /// The average confidence of the words spoken during `paragraph`, or
+    /// [`None`] if there are none.
+    ///
+    /// `paragraph` should come from this alternative's
+    /// [`paragraphs`](ResultAlternative::paragraphs).
+    pub fn paragraph_confidence(&self, paragraph: &Paragraph) -> Option<f64> {
+        average_confidence(
+            self.words
+                .iter()
+                .filter(|word| word.start >= paragraph.start() && word.end <= paragraph.end()),
+        )
+    }
+}
+
+impl Utterance {
+    /// The words with a confidence below `threshold`, in order.
+    pub fn words_below(&self, threshold: f64) -> impl Iterator<Item = &Word> {
+        words_below(&self.words, threshold)
+    }
+
+    /// The average confidence of the words in the utterance, or [`None`] if
+    /// there are none.
+    ///
+    /// Unlike [`confidence`](Utterance::confidence), which Deepgram reports
+    /// for the utterance as a whole, this weighs every word equally.
+    pub fn average_word_confidence(&self) -> Option<f64> {
+        average_confidence(&self.words)
+    }
+}
+
+fn words_below(words: &[Word], threshold: f64) -> impl Iterator<Item = &Word> {
+    words.iter().filter(move |word| word.confidence < threshold)
+}
+
+/// Lowercase `token` and strip the punctuation around it.
+pub(crate) fn normalize(token: &str) -> String {
+    token
+        .trim_matches(|c: char| !c.is_alphanumeric())
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use std::time::Duration;
+
+    use super::{SegmentOptions, SpeakerSegment};
+    use crate::common::{
+        batch_response::Response,
+        fixtures::{ResponseBuilder, WordBuilder},
+    };
+
+    #[test]
+    fn merges_speaker_turns() {
+        let response = ResponseBuilder::new()
+            .channel([
+                WordBuilder::new("hello", 0.0, 0.5)
+                    .speaker(0)
+                    .punctuated_word("Hello.")
+                    .build(),
+                WordBuilder::new("hi", 0.5, 1.0).speaker(1).build(),
+                WordBuilder::new("there", 1.0, 1.5).speaker(1).build(),
+                WordBuilder::new("bye", 1.5, 2.0).speaker(0).build(),
+            ])
+            .build();
+
+        assert_eq!(
+            response.speaker_transcript(),
+            "Speaker 0: Hello.\nSpeaker 1: hi there\nSpeaker 0: bye",
+        );
+        assert_eq!(
+            response.speaker_transcript_with(|speaker| ["Agent", "Customer"][speaker]),
+            "Agent: Hello.\nCustomer: hi there\nAgent: bye",
+        );
+    }
+
+    #[test]
+    fn omits_missing_speakers() {
+        let response = ResponseBuilder::new()
+            .channel([
+                WordBuilder::new("hello", 0.0, 0.5).build(),
+                WordBuilder::new("world", 0.5, 1.0).build(),
+            ])
+            .build();
+
+        assert_eq!(response.speaker_transcript(), "hello world");
+    }
+
+    #[test]
+    fn iterates_paragraphs_and_sentences() {
+        let mut body = serde_json::to_value(ResponseBuilder::new().channel([]).build()).unwrap();
+        body["results"]["channels"][0]["alternatives"][0]["paragraphs"] = json!({
+            "transcript": "\nHello. Hi.\n\nBye.",
+            "paragraphs": [
+                {
+                    "sentences": [
+                        {"text": "Hello.", "start": 0.0, "end": 0.5},
+                        {"text": "Hi.", "start": 0.5, "end": 1.0},
+                    ],
+                    "num_words": 2,
+                    "start": 0.0,
+                    "end": 1.0,
+                },
+                {
+                    "sentences": [{"text": "Bye.", "start": 2.0, "end": 2.5}],
+                    "num_words": 1,
+                    "start": 2.0,
+                    "end": 2.5,
+                },
+            ],
+        });
+        let response: Response = serde_json::from_value(body).unwrap();
+
+        let paragraphs: Vec<_> = response.paragraphs().collect();
+        assert_eq!(paragraphs.len(), 2);
+        assert_eq!((paragraphs[1].start(), paragraphs[1].end()), (2.0, 2.5));
+        assert_eq!(paragraphs[0].num_words(), 2);
+
+        let sentences: Vec<_> = response.sentences().map(|s| s.text()).collect();
+        assert_eq!(sentences, ["Hello.", "Hi.", "Bye."]);
+    }
+
+    #[test]
+    fn no_paragraphs_without_feature() {
+        let response = ResponseBuilder::new()
+            .channel([WordBuilder::new("hello", 0.0, 0.5).build()])
+            .build();
+
+        assert_eq!(response.paragraphs().count(), 0);
+    }
+
+    #[test]
+    fn finds_phrases() {
+        let response = ResponseBuilder::new()
+            .channel([
+                WordBuilder::new("new", 0.0, 0.5).build(),
+                WordBuilder::new("york,", 0.5, 1.0).build(),
+                WordBuilder::new("new", 1.0, 1.5).build(),
+                WordBuilder::new("New", 2.0, 2.5).build(),
+                WordBuilder::new("York", 2.5, 3.0).build(),
+            ])
+            .build();
+
+        let found: Vec<_> = response
+            .find_phrase("  new YORK. ")
+            .iter()
+            .map(|found| (found.start(), found.end()))
+            .collect();
+        assert_eq!(found, [(0.0, 1.0), (2.0, 3.0)]);
+
+        assert_eq!(response.find_phrase("new").len(), 3);
+        assert!(response.find_phrase("boston").is_empty());
+        assert!(response.find_phrase(" ").is_empty());
+    }
+
+    #[test]
+    fn merges_speaker_segments() {
+        let response = ResponseBuilder::new()
+            .channel([
+                WordBuilder::new("hello", 0.0, 0.5).speaker(0).build(),
+                WordBuilder::new("there", 0.5, 1.0).speaker(0).build(),
+                WordBuilder::new("again", 4.0, 4.5).speaker(0).build(),
+                WordBuilder::new("hi", 5.0, 5.5)
+                    .speaker(1)
+                    .punctuated_word("Hi.")
+                    .build(),
+            ])
+            .build();
+
+        let segment = |speaker, start, end, text: &str| SpeakerSegment {
+            speaker: Some(speaker),
+            start,
+            end,
+            text: String::from(text),
+        };
+
+        assert_eq!(
+            response.speaker_segments(&SegmentOptions::new()),
+            [
+                segment(0, 0.0, 4.5, "hello there again"),
+                segment(1, 5.0, 5.5, "Hi."),
+            ],
+        );
+        assert_eq!(
+            response.speaker_segments(&SegmentOptions::new().max_gap(Duration::from_secs(2))),
+            [
+                segment(0, 0.0, 1.0, "hello there"),
+                segment(0, 4.0, 4.5, "again"),
+                segment(1, 5.0, 5.5, "Hi."),
+            ],
+        );
+        assert_eq!(
+            response
+                .speaker_segments(&SegmentOptions::new().max_length(Duration::from_millis(500))),
+            [
+                segment(0, 0.0, 0.5, "hello"),
+                segment(0, 0.5, 1.0, "there"),
+                segment(0, 4.0, 4.5, "again"),
+                segment(1, 5.0, 5.5, "Hi."),
+            ],
+        );
+    }
+
+    #[test]
+    fn views_channels() {
+        let response = ResponseBuilder::new()
+            .channel([WordBuilder::new("hello", 0.0, 0.5).build()])
+            .channel([
+                WordBuilder::new("hi", 0.0, 0.5).build(),
+                WordBuilder::new("there", 0.5, 1.0).build(),
+            ])
+            .build();
+
+        let channels: Vec<_> = response
+            .channels()
+            .map(|channel| (channel.index(), channel.transcript(), channel.words().len()))
+            .collect();
+        assert_eq!(channels, [(0, "hello", 1), (1, "hi there", 2)]);
+
+        assert_eq!(response.channel(1).unwrap().transcript(), "hi there");
+        assert!(response.channel(2).is_none());
+    }
+
+    #[test]
+    fn flags_low_confidence_words() {
+        let response = ResponseBuilder::new()
+            .channel([
+                WordBuilder::new("hello", 0.0, 0.5).confidence(0.9).build(),
+                WordBuilder::new("wrld", 0.5, 1.0).confidence(0.3).build(),
+            ])
+            .utterance(
+                0,
+                [
+                    WordBuilder::new("hello", 0.0, 0.5).confidence(0.9).build(),
+                    WordBuilder::new("wrld", 0.5, 1.0).confidence(0.3).build(),
+                ],
+            )
+            .build();
+
+        let alternative = &response.results.channels[0].alternatives[0];
+        let low: Vec<_> = alternative
+            .words_below(0.5)
+            .map(|word| &*word.word)
+            .collect();
+        assert_eq!(low, ["wrld"]);
+        assert_eq!(alternative.words_below(0.1).count(), 0);
+
+        let utterance = &response.results.utterances.as_ref().unwrap()[0];
+        assert_eq!(utterance.words_below(0.5).count(), 1);
+        assert!((utterance.average_word_confidence().unwrap() - 0.6).abs() < 1e-9);
+    }
+
+    #[test]
+    fn averages_paragraph_confidence() {
+        let mut body = serde_json::to_value(
+            ResponseBuilder::new()
+                .channel([
+                    WordBuilder::new("hello", 0.0, 0.5).confidence(0.8).build(),
+                    WordBuilder::new("bye", 2.0, 2.5).confidence(0.4).build(),
+                ])
+                .build(),
+        )
+        .unwrap();
+        body["results"]["channels"][0]["alternatives"][0]["paragraphs"] = json!({
+            "transcript": "\nHello.\n\nBye.",
+            "paragraphs": [
+                {"sentences": [], "num_words": 1, "start": 0.0, "end": 0.5},
+                {"sentences": [], "num_words": 1, "start": 2.0, "end": 2.5},
+                {"sentences": [], "num_words": 0, "start": 3.0, "end": 3.5},
+            ],
+        });
+        let response: Response = serde_json::from_value(body).unwrap();
+
+        let alternative = &response.results.channels[0].alternatives[0];
+        let confidences: Vec<_> = response
+            .paragraphs()
+            .map(|paragraph| alternative.paragraph_confidence(paragraph))
+            .collect();
+        assert_eq!(confidences, [Some(0.8), Some(0.4), None]);
+    }
+}
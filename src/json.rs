@@ -0,0 +1,44 @@
+//! JSON deserialization of API responses.
+//!
+//! Responses are parsed with `serde_json` by default. With the `simd-json`
+//! feature they are parsed with [simd-json](https://docs.rs/simd-json)
+//! instead, which is considerably faster for large pre-recorded responses and
+//! high-rate streaming events. Either way, parse failures are reported as
+//! [`serde_json::Error`] so error handling does not depend on the backend.
+
+use serde::de::DeserializeOwned;
+
+/// Deserialize `T` from the JSON in `bytes`.
+#[cfg(not(feature = "simd-json"))]
+pub(crate) fn from_slice<T: DeserializeOwned>(bytes: &[u8]) -> serde_json::Result<T> {
+    serde_json::from_slice(bytes)
+}
+
+/// Deserialize `T` from the JSON in `bytes`.
+#[cfg(feature = "simd-json")]
+pub(crate) fn from_slice<T: DeserializeOwned>(bytes: &[u8]) -> serde_json::Result<T> {
+    use serde::de::Error;
+
+    // simd-json parses in place, so it needs its own mutable copy.
+    let mut bytes = bytes.to_vec();
+    simd_json::serde::from_slice(&mut bytes).map_err(serde_json::Error::custom)
+}
+
+#[cfg(all(test, any(feature = "listen-rest", feature = "listen-ws")))]
+mod tests {
+    use super::from_slice;
+    use crate::common::{batch_response::Response, stream_response::StreamResponse};
+
+    #[test]
+    fn parses_responses() {
+        let response: StreamResponse =
+            from_slice(br#"{"type":"UtteranceEnd","channel":[0,1],"last_word_end":2.5}"#).unwrap();
+        assert!(matches!(
+            response,
+            StreamResponse::UtteranceEndResponse { last_word_end, .. } if last_word_end == 2.5
+        ));
+
+        let err = from_slice::<Response>(br#"{"metadata": "#).unwrap_err();
+        assert!(!err.to_string().is_empty());
+    }
+}
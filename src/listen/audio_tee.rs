@@ -0,0 +1,303 @@
+//! Copies the audio sent over a live connection to a writer or file.
+//!
+//! Used by [`WebsocketBuilder::tee_audio`] and
+//! [`WebsocketBuilder::tee_audio_wav`] to record exactly the audio Deepgram
+//! was sent, so problems such as Deepgram hearing only silence can be
+//! reproduced by replaying it.
+//!
+//! [`WebsocketBuilder::tee_audio`]: crate::listen::websocket::WebsocketBuilder::tee_audio
+//! [`WebsocketBuilder::tee_audio_wav`]: crate::listen::websocket::WebsocketBuilder::tee_audio_wav
+
+use std::{
+    fmt,
+    fs::File,
+    future::Future,
+    io::{self, BufWriter, Seek, SeekFrom, Write},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use bytes::Bytes;
+use futures::{
+    channel::{mpsc, oneshot},
+    StreamExt,
+};
+
+use crate::{
+    common::options::Encoding,
+    listen::{
+        transport::{unblock, Runtime},
+        wav_header::WavFormat,
+    },
+    Result,
+};
+
+/// Where copied audio goes.
+///
+/// Clones share the same output, so audio sent after a reconnect is appended
+/// to the audio sent before it. The output is written by a task of its own,
+/// using [`Runtime::spawn_blocking`], so a slow disk or writer doesn't hold up
+/// the connection.
+#[derive(Clone)]
+pub(crate) struct AudioTee(Arc<Mutex<State>>);
+
+enum State {
+    /// Waiting for the first connection to open.
+    Pending(Target),
+    /// Being written by the task on the other end of the channel.
+    Open(mpsc::UnboundedSender<Command>),
+    /// Copying failed, or another connection is opening the output.
+    Closed,
+}
+
+enum Target {
+    Writer(Box<dyn Write + Send>),
+    /// A file that is created when the first connection is opened.
+    Wav(PathBuf),
+}
+
+enum Command {
+    Write(Bytes),
+    Flush(oneshot::Sender<()>),
+}
+
+impl fmt::Debug for AudioTee {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AudioTee").finish_non_exhaustive()
+    }
+}
+
+impl AudioTee {
+    pub(crate) fn writer(writer: impl Write + Send + 'static) -> Self {
+        AudioTee(Arc::new(Mutex::new(State::Pending(Target::Writer(
+            Box::new(writer),
+        )))))
+    }
+
+    pub(crate) fn wav(path: PathBuf) -> Self {
+        AudioTee(Arc::new(Mutex::new(State::Pending(Target::Wav(path)))))
+    }
+
+    /// Start copying, creating the file if there is one, unless an earlier
+    /// connection already did.
+    ///
+    /// Raw audio gets a WAV header describing it. Audio in a container, or
+    /// with an unknown sample rate, is written as is.
+    pub(crate) async fn open(
+        &self,
+        runtime: &Arc<dyn Runtime>,
+        encoding: Option<&Encoding>,
+        sample_rate: Option<u32>,
+        channels: Option<u16>,
+    ) -> Result<()> {
+        let target = {
+            let mut state = self.0.lock().unwrap();
+            match std::mem::replace(&mut *state, State::Closed) {
+                State::Pending(target) => target,
+                open => {
+                    *state = open;
+                    return Ok(());
+                }
+            }
+        };
+
+        let output = match target {
+            Target::Writer(writer) => Output::Writer(writer),
+            Target::Wav(path) => {
+                let format = WavFormat::new(encoding, sample_rate, channels);
+                let file = unblock(runtime.as_ref(), move || {
+                    TeeFile::create(File::create(path)?, format)
+                })
+                .await??;
+                Output::File(file)
+            }
+        };
+
+        let (tx, rx) = mpsc::unbounded();
+        runtime.spawn(Box::pin(run(Arc::clone(runtime), output, rx)));
+        *self.0.lock().unwrap() = State::Open(tx);
+        Ok(())
+    }
+
+    /// Copy `audio`.
+    ///
+    /// A failure to copy doesn't affect the stream. It is logged, and nothing
+    /// more is copied.
+    pub(crate) fn write(&self, audio: &Bytes) {
+        if let State::Open(tx) = &*self.0.lock().unwrap() {
+            let _ = tx.unbounded_send(Command::Write(audio.clone()));
+        }
+    }
+
+    /// Flush the copied audio, and bring the file's WAV header up to date,
+    /// completing once everything copied so far has been written.
+    ///
+    /// Called when a connection closes, so the file can be played while the
+    /// stream reconnects or after it ends.
+    pub(crate) fn flush(&self) -> impl Future<Output = ()> {
+        let (tx, rx) = oneshot::channel();
+        if let State::Open(commands) = &*self.0.lock().unwrap() {
+            let _ = commands.unbounded_send(Command::Flush(tx));
+        }
+
+        async move {
+            let _ = rx.await;
+        }
+    }
+}
+
+/// Write `output` as commands arrive, until every [`AudioTee`] is dropped.
+async fn run(
+    runtime: Arc<dyn Runtime>,
+    mut output: Output,
+    mut commands: mpsc::UnboundedReceiver<Command>,
+) {
+    while let Some(command) = commands.next().await {
+        // Write everything that has queued up at once.
+        let mut batch = vec![command];
+        while let Ok(command) = commands.try_recv() {
+            batch.push(command);
+        }
+
+        let result = unblock(runtime.as_ref(), move || {
+            let result = output.apply(batch);
+            (output, result)
+        })
+        .await;
+        match result {
+            Ok((written, Ok(()))) => output = written,
+            Ok((_, Err(err))) | Err(err) => {
+                tracing::warn!(error = %err, "could not copy the audio sent to Deepgram");
+                return;
+            }
+        }
+    }
+
+    // Dropping a file patches its header, which is blocking I/O too.
+    let _ = unblock(runtime.as_ref(), move || drop(output)).await;
+}
+
+enum Output {
+    Writer(Box<dyn Write + Send>),
+    File(TeeFile),
+}
+
+impl Output {
+    fn apply(&mut self, commands: Vec<Command>) -> io::Result<()> {
+        for command in commands {
+            match (command, &mut *self) {
+                (Command::Write(audio), Output::Writer(writer)) => writer.write_all(&audio)?,
+                (Command::Write(audio), Output::File(file)) => file.write(&audio)?,
+                (Command::Flush(done), output) => {
+                    match output {
+                        Output::Writer(writer) => writer.flush()?,
+                        Output::File(file) => file.finish()?,
+                    }
+                    let _ = done.send(());
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A file of copied audio, with a WAV header if the audio is raw.
+///
+/// Writes are buffered, and the header's sizes are patched in by
+/// [`TeeFile::finish`], and again when the file is dropped.
+struct TeeFile {
+    file: BufWriter<File>,
+    wav: Option<(WavFormat, u32)>,
+}
+
+impl TeeFile {
+    fn create(file: File, format: Option<WavFormat>) -> io::Result<Self> {
+        let mut file = BufWriter::new(file);
+        if let Some(format) = format {
+            file.write_all(&format.header(0))?;
+        }
+
+        Ok(TeeFile {
+            file,
+            wav: format.map(|format| (format, 0)),
+        })
+    }
+
+    fn write(&mut self, audio: &[u8]) -> io::Result<()> {
+        self.file.write_all(audio)?;
+        if let Some((_, data_len)) = &mut self.wav {
+            *data_len = data_len.saturating_add(audio.len() as u32);
+        }
+
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        // Seeking flushes the buffer first.
+        if let Some((format, data_len)) = &self.wav {
+            self.file.seek(SeekFrom::Start(0))?;
+            self.file.write_all(&format.header(*data_len))?;
+            self.file.seek(SeekFrom::End(0))?;
+        }
+
+        self.file.flush()
+    }
+}
+
+impl Drop for TeeFile {
+    fn drop(&mut self) {
+        if let Err(err) = self.finish() {
+            tracing::warn!(error = %err, "could not copy the audio sent to Deepgram");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use bytes::Bytes;
+
+    use super::AudioTee;
+    use crate::{
+        common::options::Encoding,
+        listen::{
+            transport::{Runtime, TokioRuntime},
+            wav_header::read_wav_header,
+        },
+    };
+
+    #[tokio::test]
+    async fn writes_playable_wav() {
+        let path = std::env::temp_dir().join(format!(
+            "deepgram-audio-tee-test-{}.wav",
+            std::process::id()
+        ));
+        let tee = AudioTee::wav(path.clone());
+        let runtime: Arc<dyn Runtime> = Arc::new(TokioRuntime);
+        tee.open(&runtime, Some(&Encoding::Mulaw), Some(8000), None)
+            .await
+            .unwrap();
+        tee.write(&Bytes::from_static(b"abc"));
+        // Reconnecting doesn't start the file over.
+        tee.open(&runtime, Some(&Encoding::Mulaw), Some(8000), None)
+            .await
+            .unwrap();
+        tee.write(&Bytes::from_static(b"def"));
+        tee.flush().await;
+
+        let contents = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(&contents[40..44], 6u32.to_le_bytes());
+
+        let mut stream = futures::stream::iter([Ok::<_, std::io::Error>(Bytes::from(contents))]);
+        let (header, audio) = read_wav_header(&mut stream).await.unwrap();
+        let header = header.unwrap();
+        assert_eq!(
+            (header.encoding, header.sample_rate, header.channels),
+            (Encoding::Mulaw, 8000, 1)
+        );
+        assert_eq!(audio, Bytes::from_static(b"abcdef"));
+    }
+}
@@ -0,0 +1,192 @@
+//! Receive results sent to a callback URL.
+//!
+//! With the Callback feature, Deepgram sends the results of a request to your
+//! server instead of returning them. [`CallbackPayload`] parses the body of
+//! that request, and [`verify_token`] and [`verify_basic_auth`] check that it
+//! came from Deepgram.
+//!
+//! See the [Deepgram Callback feature docs][docs] for more info.
+//!
+//! [docs]: https://developers.deepgram.com/docs/callback
+//!
+//! # Examples
+//!
+//! ```
+//! # use deepgram::{common::options::Callback, listen::callback::{verify_basic_auth, CallbackPayload}};
+//! # fn example(callback: &Callback, headers: &http::HeaderMap, body: &[u8]) {
+//! if !verify_basic_auth(headers, callback) {
+//!     // Respond with 401 Unauthorized
+//!     return;
+//! }
+//!
+//! match CallbackPayload::from_slice(body) {
+//!     Ok(CallbackPayload::Transcript(response)) => {
+//!         println!("{}", response.results.channels[0].alternatives[0].transcript);
+//!     }
+//!     Ok(CallbackPayload::Error(err)) => eprintln!("{}: {}", err.err_code, err.err_msg),
+//!     Ok(_) => {}
+//!     Err(err) => eprintln!("unexpected callback body: {err}"),
+//! }
+//! # }
+//! ```
+
+use base64::Engine;
+use http::{header::AUTHORIZATION, HeaderMap};
+use percent_encoding::percent_decode_str;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    common::{batch_response::Response, options::Callback},
+    json,
+};
+
+/// The header Deepgram sets to the identifier of the API key that made the request.
+pub const TOKEN_HEADER: &str = "dg-token";
+
+/// The body of a request Deepgram sends to a callback URL.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+#[non_exhaustive]
+pub enum CallbackPayload {
+    /// The results of a successful request.
+    Transcript(Box<Response>),
+
+    /// Deepgram could not process the request.
+    Error(CallbackError),
+}
+
+/// Sent to a callback URL when Deepgram could not process the request.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct CallbackError {
+    #[allow(missing_docs)]
+    pub err_code: String,
+
+    #[allow(missing_docs)]
+    pub err_msg: String,
+
+    #[allow(missing_docs)]
+    pub request_id: Option<Uuid>,
+}
+
+impl CallbackPayload {
+    /// Parse the body of a callback request.
+    pub fn from_slice(body: &[u8]) -> serde_json::Result<Self> {
+        json::from_slice(body)
+    }
+
+    /// The ID of the request the callback is for, if Deepgram reported it.
+    pub fn request_id(&self) -> Option<Uuid> {
+        match self {
+            CallbackPayload::Transcript(response) => Some(response.metadata.request_id),
+            CallbackPayload::Error(err) => err.request_id,
+        }
+    }
+}
+
+/// Check that the [`TOKEN_HEADER`] of a callback request is `token`, the
+/// identifier of the API key used to make the request.
+///
+/// The key identifier is not a secret, so prefer [`verify_basic_auth`] where
+/// possible.
+pub fn verify_token(headers: &HeaderMap, token: &str) -> bool {
+    headers
+        .get(TOKEN_HEADER)
+        .is_some_and(|value| constant_time_eq(value.as_bytes(), token.as_bytes()))
+}
+
+/// Check that the `Authorization` header of a callback request has the
+/// credentials set with [`Callback::basic_auth`].
+///
+/// Always fails if `callback` has no credentials.
+pub fn verify_basic_auth(headers: &HeaderMap, callback: &Callback) -> bool {
+    let url = callback.url();
+    if url.username().is_empty() {
+        return false;
+    }
+
+    let credentials = format!(
+        "{}:{}",
+        percent_decode_str(url.username()).decode_utf8_lossy(),
+        percent_decode_str(url.password().unwrap_or_default()).decode_utf8_lossy()
+    );
+    let expected = format!(
+        "Basic {}",
+        base64::engine::general_purpose::STANDARD.encode(credentials)
+    );
+
+    headers
+        .get(AUTHORIZATION)
+        .is_some_and(|value| constant_time_eq(value.as_bytes(), expected.as_bytes()))
+}
+
+/// Compare `a` and `b` in time that depends only on their lengths.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use http::{header::AUTHORIZATION, HeaderMap, HeaderValue};
+    use uuid::Uuid;
+
+    use super::{verify_basic_auth, verify_token, CallbackPayload, TOKEN_HEADER};
+    use crate::common::{fixtures::ResponseBuilder, options::Callback};
+
+    #[test]
+    fn parses_transcript() {
+        let request_id = Uuid::from_u128(1);
+        let body =
+            serde_json::to_vec(&ResponseBuilder::new().request_id(request_id).build()).unwrap();
+
+        let payload = CallbackPayload::from_slice(&body).unwrap();
+        assert!(matches!(payload, CallbackPayload::Transcript(_)));
+        assert_eq!(payload.request_id(), Some(request_id));
+    }
+
+    #[test]
+    fn parses_error() {
+        let body = br#"{"err_code": "Bad Request", "err_msg": "Bad audio.", "request_id": "00000000-0000-0000-0000-000000000001"}"#;
+
+        match CallbackPayload::from_slice(body).unwrap() {
+            CallbackPayload::Error(err) => {
+                assert_eq!(err.err_code, "Bad Request");
+                assert_eq!(err.request_id, Some(Uuid::from_u128(1)));
+            }
+            payload => panic!("expected an error, got {payload:?}"),
+        }
+    }
+
+    #[test]
+    fn verifies_token() {
+        let mut headers = HeaderMap::new();
+        assert!(!verify_token(&headers, "key-id"));
+
+        headers.insert(TOKEN_HEADER, HeaderValue::from_static("key-id"));
+        assert!(verify_token(&headers, "key-id"));
+        assert!(!verify_token(&headers, "other-key-id"));
+    }
+
+    #[test]
+    fn verifies_basic_auth() {
+        let callback = Callback::new("https://example.com/callback")
+            .unwrap()
+            .basic_auth("deepgram", "p@ss word");
+        let mut headers = HeaderMap::new();
+        assert!(!verify_basic_auth(&headers, &callback));
+
+        // base64("deepgram:p@ss word")
+        headers.insert(
+            AUTHORIZATION,
+            HeaderValue::from_static("Basic ZGVlcGdyYW06cEBzcyB3b3Jk"),
+        );
+        assert!(verify_basic_auth(&headers, &callback));
+
+        let other = callback.clone().basic_auth("deepgram", "wrong");
+        assert!(!verify_basic_auth(&headers, &other));
+
+        let unauthenticated = Callback::new("https://example.com/callback").unwrap();
+        assert!(!verify_basic_auth(&headers, &unauthenticated));
+    }
+}
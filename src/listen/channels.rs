@@ -0,0 +1,297 @@
+//! Split the results of a multichannel live stream by channel.
+//!
+//! With the [Multichannel feature][docs], Deepgram transcribes each channel
+//! of the audio separately and interleaves the results.
+//! [`TranscriptionStream::split_channels`] turns them back into a
+//! [`ChannelStream`] for each channel.
+//!
+//! [docs]: https://developers.deepgram.com/docs/multichannel
+
+use std::{
+    collections::VecDeque,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Wake, Waker},
+};
+
+use futures::{Stream, StreamExt};
+
+use crate::{
+    common::stream_response::StreamResponse,
+    listen::{websocket::TranscriptionStream, LiveError},
+};
+
+type Item = Result<StreamResponse, LiveError>;
+
+impl TranscriptionStream {
+    /// Split the stream into a stream for each of its `channels` channels.
+    ///
+    /// Responses about a single channel, such as results and
+    /// `UtteranceEnd` messages, go to that channel's stream. Other responses,
+    /// such as the closing Metadata, go to every stream. Errors go to the
+    /// stream of the first channel, and responses for channels past
+    /// `channels` are dropped.
+    ///
+    /// Responses for each channel are queued until its stream is polled, so
+    /// all of the streams should be polled.
+    ///
+    /// ```no_run
+    /// # use deepgram::{common::options::{Encoding, Options}, Deepgram, DeepgramError};
+    /// # use futures::StreamExt;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), DeepgramError> {
+    /// # let dg = Deepgram::new(std::env::var("DEEPGRAM_API_KEY").unwrap_or_default())?;
+    /// # let audio = futures::stream::empty::<Result<bytes::Bytes, std::io::Error>>();
+    /// let options = Options::builder().multichannel(true).build();
+    /// let results = dg
+    ///     .transcription()
+    ///     .stream_request_with_options(options)
+    ///     .encoding(Encoding::Linear16)
+    ///     .sample_rate(16000)
+    ///     .channels(2)
+    ///     .stream(audio)
+    ///     .await?;
+    ///
+    /// for mut channel in results.split_channels(2) {
+    ///     tokio::spawn(async move {
+    ///         while let Some(response) = channel.next().await {
+    ///             println!("channel {}: {:?}", channel.channel(), response);
+    ///         }
+    ///     });
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn split_channels(self, channels: usize) -> Vec<ChannelStream> {
+        let channels = channels.max(1);
+        let waiting = Arc::new(Waiting(Mutex::new(vec![None; channels])));
+        let demux = Arc::new(Mutex::new(Demux {
+            stream: self,
+            queues: (0..channels).map(|_| VecDeque::new()).collect(),
+            waker: Waker::from(Arc::clone(&waiting)),
+            waiting,
+            done: false,
+        }));
+
+        (0..channels)
+            .map(|channel| ChannelStream {
+                channel,
+                demux: Arc::clone(&demux),
+            })
+            .collect()
+    }
+}
+
+/// The responses for one channel of a [`TranscriptionStream`], returned by
+/// [`TranscriptionStream::split_channels`].
+#[derive(Debug)]
+pub struct ChannelStream {
+    channel: usize,
+    demux: Arc<Mutex<Demux>>,
+}
+
+impl ChannelStream {
+    /// The channel this stream is for, counting from zero.
+    pub fn channel(&self) -> usize {
+        self.channel
+    }
+}
+
+impl Stream for ChannelStream {
+    type Item = Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.demux.lock().unwrap().poll_channel(self.channel, cx)
+    }
+}
+
+/// Reads the shared stream on behalf of whichever [`ChannelStream`] is
+/// polled, queueing responses for the others.
+#[derive(Debug)]
+struct Demux {
+    stream: TranscriptionStream,
+    queues: Vec<VecDeque<Item>>,
+    /// Wakes every waiting channel when the shared stream has more, since
+    /// the channel that polled it last may never poll again.
+    waker: Waker,
+    waiting: Arc<Waiting>,
+    done: bool,
+}
+
+impl Demux {
+    fn poll_channel(&mut self, channel: usize, cx: &mut Context<'_>) -> Poll<Option<Item>> {
+        loop {
+            if let Some(item) = self.queues[channel].pop_front() {
+                return Poll::Ready(Some(item));
+            }
+            if self.done {
+                return Poll::Ready(None);
+            }
+
+            self.waiting.0.lock().unwrap()[channel] = Some(cx.waker().clone());
+            match self
+                .stream
+                .poll_next_unpin(&mut Context::from_waker(&self.waker))
+            {
+                Poll::Ready(Some(item)) => self.route(item),
+                Poll::Ready(None) => {
+                    self.done = true;
+                    self.waiting.wake_by_ref();
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+
+    fn route(&mut self, item: Item) {
+        let channel = match &item {
+            Ok(response) => match response.channel_index() {
+                Some(channel_index) => channel_index.index,
+                None => {
+                    for channel in 0..self.queues.len() {
+                        self.queues[channel].push_back(Ok(response.clone()));
+                        self.wake(channel);
+                    }
+                    return;
+                }
+            },
+            Err(_) => 0,
+        };
+
+        if let Some(queue) = self.queues.get_mut(channel) {
+            queue.push_back(item);
+            self.wake(channel);
+        }
+    }
+
+    fn wake(&mut self, channel: usize) {
+        if let Some(waker) = self.waiting.0.lock().unwrap()[channel].take() {
+            waker.wake();
+        }
+    }
+}
+
+/// The wakers of the channels waiting for a response, by channel.
+#[derive(Debug)]
+struct Waiting(Mutex<Vec<Option<Waker>>>);
+
+impl Wake for Waiting {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        let wakers: Vec<Waker> = self
+            .0
+            .lock()
+            .unwrap()
+            .iter_mut()
+            .flat_map(Option::take)
+            .collect();
+        wakers.into_iter().for_each(Waker::wake);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bytes::Bytes;
+    use futures::StreamExt;
+    use tungstenite::Message;
+
+    use crate::{
+        common::{
+            fixtures::{stream_word, TranscriptResponseBuilder},
+            stream_response::StreamResponse,
+        },
+        listen::websocket::tests::fake_connection,
+    };
+
+    #[tokio::test]
+    async fn splits_results_by_channel() {
+        let (transport, _sent_rx, incoming_tx) = fake_connection();
+
+        let dg = crate::Deepgram::new("token").unwrap();
+        let transcription = dg.transcription();
+        let results = transcription
+            .stream_request()
+            .transport(transport)
+            .stream(futures::stream::pending::<Result<Bytes, std::io::Error>>())
+            .await
+            .unwrap();
+
+        let left = TranscriptResponseBuilder::new([stream_word("left", 0.0, 0.5)])
+            .channel_index(0, 2)
+            .build();
+        let right = TranscriptResponseBuilder::new([stream_word("right", 0.0, 0.5)])
+            .channel_index(1, 2)
+            .build();
+        let send = |json: String| {
+            incoming_tx
+                .unbounded_send(Ok(Message::Text(json.into())))
+                .unwrap()
+        };
+        send(serde_json::to_string(&right).unwrap());
+        send(serde_json::to_string(&left).unwrap());
+        send(String::from(
+            r#"{"type":"Metadata","request_id":"00000000-0000-0000-0000-000000000001","created":"","duration":0.5,"channels":2}"#,
+        ));
+        drop(incoming_tx);
+
+        let mut channels = results.split_channels(2);
+        let mut second = channels.pop().unwrap();
+        let mut first = channels.pop().unwrap();
+        assert_eq!(first.channel(), 0);
+
+        assert_eq!(first.next().await.unwrap().unwrap(), left);
+        assert_eq!(second.next().await.unwrap().unwrap(), right);
+        for channel in [&mut first, &mut second] {
+            assert!(matches!(
+                channel.next().await.unwrap().unwrap(),
+                StreamResponse::TerminalResponse { .. }
+            ));
+            assert!(channel.next().await.is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn wakes_channel_after_another_is_dropped() {
+        let (transport, _sent_rx, incoming_tx) = fake_connection();
+
+        let dg = crate::Deepgram::new("token").unwrap();
+        let transcription = dg.transcription();
+        let results = transcription
+            .stream_request()
+            .transport(transport)
+            .stream(futures::stream::pending::<Result<Bytes, std::io::Error>>())
+            .await
+            .unwrap();
+
+        let mut channels = results.split_channels(2);
+        let mut second = channels.pop().unwrap();
+        let mut first = channels.pop().unwrap();
+
+        // The second channel waits, then the first polls the shared stream
+        // last and is dropped without polling again.
+        let second = tokio::spawn(async move { second.next().await });
+        tokio::task::yield_now().await;
+        assert!(futures::poll!(first.next()).is_pending());
+        drop(first);
+
+        let right = TranscriptResponseBuilder::new([stream_word("right", 0.0, 0.5)])
+            .channel_index(1, 2)
+            .build();
+        incoming_tx
+            .unbounded_send(Ok(Message::Text(
+                serde_json::to_string(&right).unwrap().into(),
+            )))
+            .unwrap();
+
+        let response = tokio::time::timeout(Duration::from_secs(5), second)
+            .await
+            .expect("the second channel was never woken")
+            .unwrap();
+        assert_eq!(response.unwrap().unwrap(), right);
+    }
+}
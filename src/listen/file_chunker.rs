@@ -0,0 +1,317 @@
+//! Splits an audio file into chunks for streaming.
+//!
+//! Shared by the live and Flux websocket builders. Compressed files in Ogg
+//! or ADTS containers are split between pages or frames, since Deepgram
+//! can't decode audio that starts partway through one.
+
+use std::{
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use bytes::{Bytes, BytesMut};
+use futures::{io::AsyncRead, Stream};
+use pin_project::pin_project;
+
+use crate::{DeepgramError, Result};
+
+/// Reads `R` straight into a single [`BytesMut`] and yields it `chunk_size`
+/// bytes at a time, with a shorter final chunk if the input doesn't divide evenly.
+///
+/// If the input is an Ogg or ADTS stream, each chunk instead holds as many
+/// whole pages or frames as fit in `chunk_size` bytes, or a single one if it
+/// is larger than that. Input that stops looking like the container it
+/// started as is split anywhere from then on.
+///
+/// Each yielded chunk is split off the front of the buffer rather than copied.
+/// Once the consumer drops a chunk, the next [`BytesMut::reserve`] reclaims its
+/// space instead of allocating, so a long file is streamed through one or two
+/// allocations rather than one per read.
+#[pin_project]
+pub(crate) struct FileChunker<R> {
+    chunk_size: usize,
+    framing: Framing,
+    buf: BytesMut,
+    eof: bool,
+    #[pin]
+    reader: R,
+}
+
+/// Where chunks may end.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Framing {
+    /// Not known until the first bytes have been read.
+    Detect,
+    /// Anywhere.
+    Bytes,
+    /// Between Ogg pages.
+    Ogg,
+    /// Between ADTS frames.
+    Adts,
+}
+
+/// The length of the page or frame at the start of a buffer.
+#[derive(Debug, PartialEq)]
+enum Frame {
+    Len(usize),
+    /// More bytes are needed to tell.
+    Incomplete,
+    /// The buffer doesn't start with a page or frame header.
+    Invalid,
+}
+
+impl<R> FileChunker<R> {
+    pub(crate) fn new(reader: R, chunk_size: usize) -> Self {
+        let chunk_size = chunk_size.max(1);
+        FileChunker {
+            chunk_size,
+            framing: Framing::Detect,
+            buf: BytesMut::with_capacity(chunk_size),
+            eof: false,
+            reader,
+        }
+    }
+}
+
+impl<R: AsyncRead> Stream for FileChunker<R> {
+    type Item = Result<Bytes>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+
+        loop {
+            if let Some(len) = split_at(this.framing, this.buf, *this.chunk_size, *this.eof) {
+                if len == 0 {
+                    return Poll::Ready(None);
+                }
+                return Poll::Ready(Some(Ok(this.buf.split_to(len).freeze())));
+            }
+
+            // Pages and frames can be longer than a chunk.
+            let additional = match this.chunk_size.checked_sub(this.buf.len()) {
+                Some(0) | None => *this.chunk_size,
+                Some(additional) => additional,
+            };
+            // Read into the zeroed end of the buffer, and drop what wasn't filled.
+            let start = this.buf.len();
+            this.buf.resize(start + additional, 0);
+            let read = this.reader.as_mut().poll_read(cx, &mut this.buf[start..]);
+            let filled = match &read {
+                Poll::Ready(Ok(len)) => *len,
+                _ => 0,
+            };
+            this.buf.truncate(start + filled);
+            match ready!(read) {
+                Ok(0) => *this.eof = true,
+                Ok(_) => {}
+                Err(e) => return Poll::Ready(Some(Err(DeepgramError::from(e)))),
+            }
+        }
+    }
+}
+
+/// The length of the next chunk of `buf`, or `None` if more input is needed
+/// to tell. Returns `Some(0)` once the input has been used up.
+fn split_at(framing: &mut Framing, buf: &[u8], chunk_size: usize, eof: bool) -> Option<usize> {
+    if *framing == Framing::Detect {
+        *framing = detect(buf, eof)?;
+    }
+
+    let frame_len = match framing {
+        Framing::Ogg => ogg_page_len,
+        Framing::Adts => adts_frame_len,
+        Framing::Detect | Framing::Bytes => {
+            return (buf.len() >= chunk_size || eof).then(|| buf.len().min(chunk_size));
+        }
+    };
+
+    let mut end = 0;
+    while end < chunk_size {
+        match frame_len(&buf[end..]) {
+            Frame::Len(len) if end > 0 && end + len > chunk_size => break,
+            Frame::Len(len) if end + len <= buf.len() => end += len,
+            Frame::Len(_) | Frame::Incomplete if !eof => return None,
+            // A truncated final page or frame is sent as it is.
+            Frame::Len(_) | Frame::Incomplete => return Some(buf.len()),
+            Frame::Invalid => {
+                *framing = Framing::Bytes;
+                if end == 0 {
+                    return split_at(framing, buf, chunk_size, eof);
+                }
+                break;
+            }
+        }
+    }
+
+    Some(end)
+}
+
+/// Tell the framing of the input from its first bytes, or `None` if more
+/// input is needed to tell.
+fn detect(buf: &[u8], eof: bool) -> Option<Framing> {
+    if buf.len() < 4 && !eof {
+        return None;
+    }
+    if buf.starts_with(b"OggS") {
+        return Some(Framing::Ogg);
+    }
+
+    // Look for two frames in a row, so that other audio that happens to
+    // start with a syncword isn't mistaken for ADTS.
+    let framing = match adts_frame_len(buf) {
+        Frame::Len(len) => match adts_frame_len(buf.get(len..).unwrap_or_default()) {
+            Frame::Len(_) => Framing::Adts,
+            Frame::Incomplete if eof => Framing::Adts,
+            Frame::Incomplete => return None,
+            Frame::Invalid => Framing::Bytes,
+        },
+        Frame::Incomplete if !eof => return None,
+        Frame::Incomplete | Frame::Invalid => Framing::Bytes,
+    };
+
+    Some(framing)
+}
+
+/// See [RFC 3533, section 6](https://www.rfc-editor.org/rfc/rfc3533#section-6).
+fn ogg_page_len(buf: &[u8]) -> Frame {
+    const HEADER_LEN: usize = 27;
+
+    if !b"OggS".starts_with(&buf[..buf.len().min(4)]) {
+        return Frame::Invalid;
+    }
+    let Some(&segments) = buf.get(HEADER_LEN - 1) else {
+        return Frame::Incomplete;
+    };
+    let segments_end = HEADER_LEN + usize::from(segments);
+    match buf.get(HEADER_LEN..segments_end) {
+        Some(lacing) => {
+            Frame::Len(segments_end + lacing.iter().map(|&b| usize::from(b)).sum::<usize>())
+        }
+        None => Frame::Incomplete,
+    }
+}
+
+/// See the ADTS header layout in ISO/IEC 13818-7, section 6.2.
+fn adts_frame_len(buf: &[u8]) -> Frame {
+    const HEADER_LEN: usize = 7;
+
+    // A 12-bit syncword, then the MPEG version and a layer that is always 0.
+    match buf {
+        [] | [0xFF] => return Frame::Incomplete,
+        [0xFF, b, ..] if b & 0xF6 == 0xF0 => {}
+        _ => return Frame::Invalid,
+    }
+    let Some(header) = buf.get(..HEADER_LEN) else {
+        return Frame::Incomplete;
+    };
+    let len = (usize::from(header[3] & 0x03) << 11)
+        | (usize::from(header[4]) << 3)
+        | (usize::from(header[5]) >> 5);
+    if len < HEADER_LEN {
+        return Frame::Invalid;
+    }
+
+    Frame::Len(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+
+    use super::{detect, FileChunker, Framing};
+
+    fn ogg_page(body_len: usize) -> Vec<u8> {
+        let mut page = b"OggS".to_vec();
+        page.resize(26, 0);
+        let mut lacing = vec![255; body_len / 255];
+        lacing.push((body_len % 255) as u8);
+        page.push(lacing.len() as u8);
+        page.extend(lacing);
+        page.resize(page.len() + body_len, 1);
+        page
+    }
+
+    fn adts_frame(len: usize) -> Vec<u8> {
+        let mut frame = vec![
+            0xFF,
+            0xF1,
+            0x50,
+            0x80 | (len >> 11) as u8,
+            (len >> 3) as u8,
+            ((len & 0x07) << 5) as u8 | 0x1F,
+            0xFC,
+        ];
+        frame.resize(len, 2);
+        frame
+    }
+
+    async fn chunk_lengths(data: &[u8], chunk_size: usize) -> Vec<usize> {
+        let chunks: Vec<_> = FileChunker::new(data, chunk_size)
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await;
+        assert_eq!(chunks.concat(), data);
+        chunks.iter().map(|chunk| chunk.len()).collect()
+    }
+
+    #[tokio::test]
+    async fn yields_fixed_size_chunks() {
+        let data: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        let chunks: Vec<_> = FileChunker::new(&data[..], 300)
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await;
+
+        let lengths: Vec<usize> = chunks.iter().map(|chunk| chunk.len()).collect();
+        assert_eq!(lengths, vec![300, 300, 300, 100]);
+        assert_eq!(chunks.concat(), data);
+    }
+
+    #[tokio::test]
+    async fn reuses_buffer_once_chunks_are_dropped() {
+        let data = vec![7u8; 4096];
+        let mut chunker = FileChunker::new(&data[..], 1024);
+
+        let first = chunker.next().await.unwrap().unwrap().as_ptr();
+        // The first chunk has been dropped, so its space is reclaimed.
+        let second = chunker.next().await.unwrap().unwrap().as_ptr();
+        assert_eq!(first, second);
+    }
+
+    #[tokio::test]
+    async fn empty_input() {
+        let mut chunker = FileChunker::new(&[][..], 16);
+        assert!(chunker.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn splits_between_ogg_pages() {
+        let pages = [ogg_page(100), ogg_page(200), ogg_page(50), ogg_page(700)];
+        let page_lengths: Vec<usize> = pages.iter().map(|page| page.len()).collect();
+        assert_eq!(page_lengths, [128, 228, 78, 730]);
+
+        let lengths = chunk_lengths(&pages.concat(), 400).await;
+        assert_eq!(lengths, [128 + 228, 78, 730]);
+    }
+
+    #[tokio::test]
+    async fn splits_between_adts_frames() {
+        let frames = [adts_frame(100), adts_frame(100), adts_frame(100)];
+        let lengths = chunk_lengths(&frames.concat(), 250).await;
+        assert_eq!(lengths, [200, 100]);
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_fixed_size_chunks() {
+        let mut data = ogg_page(100);
+        data.extend_from_slice(&[7; 500]);
+        let lengths = chunk_lengths(&data, 300).await;
+        assert_eq!(lengths, [128, 300, 200]);
+
+        // Other audio that starts with an ADTS syncword.
+        let mut data = adts_frame(100);
+        data.extend_from_slice(&[0; 100]);
+        assert_eq!(detect(&data, false), Some(Framing::Bytes));
+        assert_eq!(detect(&data[..100], false), None);
+    }
+}
@@ -9,9 +9,14 @@
 //! [api]: https://developers.deepgram.com/reference/speech-to-text/listen-flux
 
 use std::{
+    borrow::Cow,
+    convert::Infallible,
     error::Error,
+    fmt,
+    ops::RangeInclusive,
     path::Path,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
     time::Duration,
 };
@@ -20,41 +25,103 @@ use anyhow::anyhow;
 use bytes::Bytes;
 use futures::{
     channel::mpsc::{self, Receiver, Sender},
+    future::{pending, BoxFuture, FutureExt},
     select_biased,
     stream::StreamExt,
-    SinkExt, Stream,
+    Sink, SinkExt, Stream,
 };
-use http::Request;
+use http::{HeaderMap, Request};
 use pin_project::pin_project;
 use serde_urlencoded;
-use tokio::fs::File;
-use tokio_tungstenite::{tungstenite::protocol::Message, MaybeTlsStream, WebSocketStream};
 use tungstenite::{
     handshake::client,
-    protocol::frame::coding::{Data, OpCode},
+    protocol::{
+        frame::coding::{Data, OpCode},
+        Message,
+    },
     Utf8Bytes,
 };
 use url::Url;
 use uuid::Uuid;
 
-use self::file_chunker::FileChunker;
 use crate::{
+    client::add_missing_headers,
     common::{
-        flux_response::FluxResponse,
+        flux_response::{FluxResponse, Turn, TurnLifecycle},
         options::{Encoding, Options},
     },
-    Deepgram, DeepgramError, Result, Transcription,
+    listen::{
+        file_chunker::FileChunker,
+        parse_response,
+        transport::{self, BoxWebSocket, Runtime, Transport},
+        websocket::{sink_error, ReconnectPolicy, Reconnector, Redial},
+        LiveError,
+    },
+    Deepgram, DeepgramError, Metrics, RedactedUrl, Result, Transcription,
 };
 
 static FLUX_URL_PATH: &str = "v2/listen";
 
-#[derive(Clone, Debug)]
+/// The `eot_threshold` values Deepgram accepts.
+const EOT_THRESHOLD_RANGE: RangeInclusive<f64> = 0.5..=0.9;
+
+/// The `eager_eot_threshold` values Deepgram accepts.
+const EAGER_EOT_THRESHOLD_RANGE: RangeInclusive<f64> = 0.3..=0.9;
+
+/// The `eot_timeout_ms` values Deepgram accepts.
+const EOT_TIMEOUT_MS_RANGE: RangeInclusive<u32> = 500..=10_000;
+
+#[derive(Clone)]
 pub struct FluxBuilder<'a> {
-    deepgram: &'a Deepgram,
+    deepgram: Cow<'a, Deepgram>,
     options: Options,
     encoding: Option<Encoding>,
     sample_rate: Option<u32>,
+    eot_threshold: Option<f64>,
+    eager_eot_threshold: Option<f64>,
+    eot_timeout_ms: Option<u32>,
+    keyterms: Vec<String>,
     stream_url: Url,
+    resilient_parsing: Option<bool>,
+    extra_query_params: Vec<(String, String)>,
+    extra_headers: HeaderMap,
+    connect_timeout: Option<Duration>,
+    reconnect: Option<ReconnectPolicy>,
+    transport: Arc<dyn Transport>,
+    runtime: Arc<dyn Runtime>,
+}
+
+impl fmt::Debug for FluxBuilder<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FluxBuilder")
+            .field("deepgram", &self.deepgram)
+            .field("options", &self.options)
+            .field("encoding", &self.encoding)
+            .field("sample_rate", &self.sample_rate)
+            .field("eot_threshold", &self.eot_threshold)
+            .field("eager_eot_threshold", &self.eager_eot_threshold)
+            .field("eot_timeout_ms", &self.eot_timeout_ms)
+            .field("keyterms", &self.keyterms)
+            .field("stream_url", &RedactedUrl(&self.stream_url))
+            .field("resilient_parsing", &self.resilient_parsing)
+            .field(
+                "extra_query_params",
+                &self
+                    .extra_query_params
+                    .iter()
+                    .map(|(name, _)| name)
+                    .collect::<Vec<_>>(),
+            )
+            .field(
+                "extra_headers",
+                &self.extra_headers.keys().collect::<Vec<_>>(),
+            )
+            .field("connect_timeout", &self.connect_timeout)
+            .field("reconnect", &self.reconnect)
+            .field("transport", &self.transport)
+            .field("runtime", &self.runtime)
+            .finish()
+    }
 }
 
 impl Transcription<'_> {
@@ -109,17 +176,28 @@ impl Transcription<'_> {
     /// ```
     pub fn flux_request_with_options(&self, options: Options) -> FluxBuilder<'_> {
         FluxBuilder {
-            deepgram: self.0,
+            deepgram: Cow::Borrowed(self.0),
             options,
             encoding: None,
             sample_rate: None,
+            eot_threshold: None,
+            eager_eot_threshold: None,
+            eot_timeout_ms: None,
+            keyterms: Vec::new(),
             stream_url: self.flux_url(),
+            resilient_parsing: None,
+            extra_query_params: Vec::new(),
+            extra_headers: HeaderMap::new(),
+            connect_timeout: None,
+            reconnect: None,
+            transport: transport::default_transport(self.0.proxy.as_ref(), self.0.tls.as_ref()),
+            runtime: self.0.client.runtime(),
         }
     }
 
     fn flux_url(&self) -> Url {
         let mut url =
-            self.0.base_url.join(FLUX_URL_PATH).expect(
+            self.0.listen_base_url.join(FLUX_URL_PATH).expect(
                 "base_url is checked to be a valid base_url when constructing Deepgram client",
             );
 
@@ -153,17 +231,35 @@ impl FluxBuilder<'_> {
             options,
             encoding,
             sample_rate,
+            eot_threshold,
+            eager_eot_threshold,
+            eot_timeout_ms,
+            keyterms,
             stream_url,
+            resilient_parsing: _,
+            extra_query_params,
+            extra_headers: _,
+            connect_timeout: _,
+            reconnect: _,
+            transport: _,
+            runtime: _,
         } = self;
 
         let mut url = stream_url.clone();
         {
             let mut pairs = url.query_pairs_mut();
 
-            // Add standard options.
+            // Add standard options, except those set on the builder.
+            let overridden = [
+                ("eot_threshold", eot_threshold.is_some()),
+                ("eager_eot_threshold", eager_eot_threshold.is_some()),
+                ("eot_timeout_ms", eot_timeout_ms.is_some()),
+            ];
             pairs.extend_pairs(
                 serde_urlencoded::from_str::<Vec<(String, String)>>(&options.urlencoded()?)
-                    .expect("constructed query string can be deserialized"),
+                    .expect("constructed query string can be deserialized")
+                    .into_iter()
+                    .filter(|(key, _)| !overridden.contains(&(key.as_str(), true))),
             );
 
             // Add streaming-specific options
@@ -173,6 +269,20 @@ impl FluxBuilder<'_> {
             if let Some(sample_rate) = sample_rate {
                 pairs.append_pair("sample_rate", &sample_rate.to_string());
             }
+            if let Some(eot_threshold) = eot_threshold {
+                pairs.append_pair("eot_threshold", &eot_threshold.to_string());
+            }
+            if let Some(eager_eot_threshold) = eager_eot_threshold {
+                pairs.append_pair("eager_eot_threshold", &eager_eot_threshold.to_string());
+            }
+            if let Some(eot_timeout_ms) = eot_timeout_ms {
+                pairs.append_pair("eot_timeout_ms", &eot_timeout_ms.to_string());
+            }
+            for keyterm in keyterms {
+                pairs.append_pair("keyterm", keyterm);
+            }
+
+            pairs.extend_pairs(extra_query_params);
         }
 
         Ok(url)
@@ -187,59 +297,286 @@ impl FluxBuilder<'_> {
         self.sample_rate = Some(sample_rate);
         self
     }
+
+    /// Finish a turn once the end-of-turn confidence reaches `threshold`,
+    /// between 0.5 and 0.9.
+    ///
+    /// Takes precedence over [`OptionsBuilder::eot_threshold`]. A value out
+    /// of range is rejected with [`DeepgramError::InvalidOption`] when
+    /// connecting.
+    ///
+    /// [`OptionsBuilder::eot_threshold`]: crate::common::options::OptionsBuilder::eot_threshold
+    pub fn eot_threshold(mut self, threshold: f64) -> Self {
+        self.eot_threshold = Some(threshold);
+        self
+    }
+
+    /// Send `EagerEndOfTurn` and `TurnResumed` events, with an eager end of
+    /// turn once the end-of-turn confidence reaches `threshold`, between 0.3
+    /// and 0.9.
+    ///
+    /// Takes precedence over [`OptionsBuilder::eager_eot_threshold`]. A value
+    /// out of range is rejected with [`DeepgramError::InvalidOption`] when
+    /// connecting.
+    ///
+    /// [`OptionsBuilder::eager_eot_threshold`]: crate::common::options::OptionsBuilder::eager_eot_threshold
+    pub fn eager_eot_threshold(mut self, threshold: f64) -> Self {
+        self.eager_eot_threshold = Some(threshold);
+        self
+    }
+
+    /// Finish a turn once `timeout_ms` milliseconds have passed after speech,
+    /// whatever the end-of-turn confidence, between 500 and 10000.
+    ///
+    /// Takes precedence over [`OptionsBuilder::eot_timeout_ms`]. A value out
+    /// of range is rejected with [`DeepgramError::InvalidOption`] when
+    /// connecting.
+    ///
+    /// [`OptionsBuilder::eot_timeout_ms`]: crate::common::options::OptionsBuilder::eot_timeout_ms
+    pub fn eot_timeout_ms(mut self, timeout_ms: u32) -> Self {
+        self.eot_timeout_ms = Some(timeout_ms);
+        self
+    }
+
+    /// Report messages that fail to deserialize as
+    /// [`DeepgramError::UnparseableResponse`], which carries the raw payload.
+    ///
+    /// The stream keeps running after such an error. Without this, the error is
+    /// reported as [`DeepgramError::JsonError`].
+    pub fn resilient_parsing(mut self) -> Self {
+        self.resilient_parsing = Some(true);
+        self
+    }
+
+    /// Add `params` to the query string of the upgrade request, after the
+    /// options set on the builder.
+    ///
+    /// Use this for parameters required by a gateway in front of a
+    /// self-hosted instance, or for API parameters this version of the SDK
+    /// doesn't support yet. Calling this again adds to the parameters set
+    /// before.
+    pub fn extra_query_params(
+        mut self,
+        params: impl IntoIterator<Item = (String, String)>,
+    ) -> Self {
+        self.extra_query_params.extend(params);
+        self
+    }
+
+    /// Send `headers` with the upgrade request.
+    ///
+    /// These take precedence over the client's
+    /// [default headers](crate::DeepgramBuilder::default_headers), but not
+    /// over headers set by the SDK itself, such as `Authorization` and
+    /// `User-Agent`. Calling this again adds to the headers set before,
+    /// replacing any with the same name.
+    pub fn extra_headers(mut self, headers: HeaderMap) -> Self {
+        for (name, value) in &headers {
+            self.extra_headers.insert(name, value.clone());
+        }
+        self
+    }
+
+    /// Give up on opening the connection if the websocket handshake hasn't
+    /// completed within `timeout`, with [`DeepgramError::ConnectTimeout`].
+    /// By default, there is no timeout.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Re-dial the connection with the same options if it drops before the
+    /// audio source passed to [`FluxBuilder::stream`] is finished.
+    ///
+    /// Each time the connection is re-established, the stream yields a
+    /// [`FluxResponse::Reconnected`] with the new request ID and the turn that
+    /// was interrupted, if any, then continues sending audio from the source.
+    /// Audio that was in flight when the connection dropped is lost, and turn
+    /// indexes and audio windows on the new connection start from zero again.
+    /// If every attempt fails, the stream yields the last error and ends.
+    ///
+    /// A connection closed for a reason that won't go away on its own, like
+    /// a policy violation or a rejected API key, is not re-dialed. The stream
+    /// yields the [`DeepgramError::WebsocketClose`] and ends instead.
+    ///
+    /// Has no effect on [`FluxBuilder::handle`].
+    pub fn reconnect(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect = Some(policy);
+        self
+    }
+
+    /// Boost recognition of `keyterms`, such as product names or jargon.
+    ///
+    /// Calling this again adds to the keyterms set before. They are sent
+    /// after any set with [`OptionsBuilder::keyterms`].
+    ///
+    /// [`OptionsBuilder::keyterms`]: crate::common::options::OptionsBuilder::keyterms
+    pub fn keyterms<'a>(mut self, keyterms: impl IntoIterator<Item = &'a str>) -> Self {
+        self.keyterms.extend(keyterms.into_iter().map(String::from));
+        self
+    }
+
+    /// Use `transport` to open the websocket connection instead of the default,
+    /// [`TokioTransport`](transport::TokioTransport).
+    pub fn transport(mut self, transport: impl Transport) -> Self {
+        self.transport = Arc::new(transport);
+        self
+    }
+
+    /// Use `runtime` to spawn background tasks and keep time instead of the
+    /// client's [runtime](crate::DeepgramBuilder::runtime), which defaults to
+    /// [`TokioRuntime`](transport::TokioRuntime).
+    pub fn runtime(mut self, runtime: impl Runtime) -> Self {
+        self.runtime = Arc::new(runtime);
+        self
+    }
+
+    /// Check for option values that Deepgram would reject.
+    fn validate(&self) -> Result<()> {
+        if let Some(threshold) = self.eot_threshold {
+            check_range("eot_threshold", threshold, EOT_THRESHOLD_RANGE)?;
+        }
+        if let Some(threshold) = self.eager_eot_threshold {
+            check_range("eager_eot_threshold", threshold, EAGER_EOT_THRESHOLD_RANGE)?;
+        }
+        if let Some(timeout_ms) = self.eot_timeout_ms {
+            check_range("eot_timeout_ms", timeout_ms, EOT_TIMEOUT_MS_RANGE)?;
+        }
+
+        Ok(())
+    }
+
+    fn reconnector(&self) -> Option<Reconnector<FluxBuilder<'static>>> {
+        self.reconnect.clone().map(|policy| {
+            let builder = FluxBuilder {
+                deepgram: Cow::Owned(self.deepgram.clone().into_owned()),
+                ..self.clone()
+            };
+            Reconnector::new(builder, policy)
+        })
+    }
+}
+
+impl Redial for FluxBuilder<'_> {
+    type Handle = FluxHandle;
+
+    fn runtime(&self) -> &dyn Runtime {
+        self.runtime.as_ref()
+    }
+
+    fn dial(&self) -> BoxFuture<'_, Result<FluxHandle>> {
+        Box::pin(FluxHandle::new(self.clone()))
+    }
+}
+
+fn check_range<T>(name: &str, value: T, range: RangeInclusive<T>) -> Result<()>
+where
+    T: PartialOrd + fmt::Display,
+{
+    if range.contains(&value) {
+        Ok(())
+    } else {
+        Err(DeepgramError::InvalidOption(format!(
+            "{name} must be between {} and {}, got {value}",
+            range.start(),
+            range.end()
+        )))
+    }
 }
 
 impl FluxBuilder<'_> {
+    /// Stream the audio file at `filename`, sending it `frame_size` bytes at
+    /// a time, one chunk every `frame_delay`.
+    ///
+    /// Ogg and ADTS files are only split between pages or frames, so their
+    /// chunks can be somewhat shorter than `frame_size`, or longer when a
+    /// single page or frame is.
     pub async fn file(
         self,
         filename: impl AsRef<Path>,
         frame_size: usize,
         frame_delay: Duration,
-    ) -> Result<FluxStream, DeepgramError> {
-        let file = File::open(filename).await?;
+    ) -> Result<FluxStream, LiveError> {
+        let runtime = Arc::clone(&self.runtime);
+        let file = runtime.open_file(filename.as_ref().to_owned()).await?;
         let mut chunker = FileChunker::new(file, frame_size);
-        let (tx, rx) = tokio::sync::mpsc::channel(1);
-        let rx_stream = tokio_stream::wrappers::ReceiverStream::new(rx);
+        let (mut tx, rx) = mpsc::channel(1);
         let task = async move {
             while let Some(frame) = chunker.next().await {
-                tokio::time::sleep(frame_delay).await;
-                // This unwrap() is safe because application logic dictates that the Receiver won't
-                // be dropped before the Sender.
+                runtime.sleep(frame_delay).await;
+                // Stop reading the file once the stream is no longer taking audio.
                 if tx.send(frame).await.is_err() {
                     break;
                 }
             }
         };
-        tokio::spawn(task);
-        self.stream(rx_stream).await
+        self.runtime.spawn(Box::pin(task));
+        self.stream(rx).await
     }
 
-    pub async fn stream<S, E>(self, stream: S) -> Result<FluxStream>
+    /// Connect to Deepgram and send it the audio from `stream`, returning
+    /// the stream of responses.
+    ///
+    /// Errors from `stream` are passed along as responses. Once it ends, a
+    /// `CloseStream` message is sent, and the stream of responses ends after
+    /// Deepgram sends its last responses and closes the connection.
+    pub async fn stream<S, E>(self, stream: S) -> Result<FluxStream, LiveError>
     where
         S: Stream<Item = Result<Bytes, E>> + Send + Unpin + 'static,
         E: Error + Send + Sync + 'static,
     {
+        let runtime = Arc::clone(&self.runtime);
+        let reconnect = self.reconnector();
         let handle = self.handle().await?;
 
         let (tx, rx) = mpsc::channel(1);
         let request_id = handle.request_id();
-        tokio::task::spawn(async move {
+        runtime.spawn(Box::pin(async move {
             let mut handle = handle;
             let mut tx = tx;
             let mut stream = stream.fuse();
+            let mut source_done = false;
+            // The turn that has started but not yet ended.
+            let mut turn: Option<Turn> = None;
 
             loop {
                 select_biased! {
                     // Receiving messages from FluxHandle
                     response = handle.response_rx.next() => {
                         match response {
+                            Some(Err(err @ DeepgramError::WebsocketClose { .. })) if reconnect.is_some() && !source_done => {
+                                if let Some(reconnect) = &reconnect {
+                                    if reconnect.closed(err, &mut tx).await.is_break() {
+                                        break;
+                                    }
+                                }
+                            }
                             Some(response) => {
+                                if let Some(lifecycle) = response.as_ref().ok().and_then(FluxResponse::turn_lifecycle) {
+                                    turn = match lifecycle {
+                                        TurnLifecycle::EndOfTurn(_) => None,
+                                        lifecycle => Some(lifecycle.into_turn()),
+                                    };
+                                }
                                 if tx.send(response).await.is_err() {
                                     // Receiver has been dropped.
                                     break;
                                 }
                             }
                             None => {
+                                // The connection dropped before the audio source finished.
+                                if let (false, Some(reconnect)) = (source_done, &reconnect) {
+                                    let reconnected = |handle: &FluxHandle, attempts| FluxResponse::Reconnected {
+                                        request_id: handle.request_id(),
+                                        attempts,
+                                        interrupted_turn: turn.take(),
+                                    };
+                                    if let Some(new_handle) = reconnect.redial(&mut tx, reconnected).await {
+                                        handle = new_handle;
+                                        continue;
+                                    }
+                                }
+
                                 tx.close_channel();
                                 // No more responses
                                 break;
@@ -247,11 +584,11 @@ impl FluxBuilder<'_> {
                         }
                     }
                     // Receiving audio data from stream.
-                    chunk = stream.next() => {
+                    chunk = async { if source_done { pending().await } else { stream.next().await } }.fuse() => {
                         match chunk {
                             Some(Ok(audio)) => {
-                                if let Err(err) = handle.send_data(audio.to_vec()).await {
-                                    if tx.send(Err(err)).await.is_err() {
+                                if let Err(err) = handle.send_data(audio).await {
+                                    if tx.send(Err(err.into())).await.is_err() {
                                         break;
                                     }
                                 }
@@ -262,36 +599,79 @@ impl FluxBuilder<'_> {
                                 }
                             }
                             None => {
+                                // Keep receiving the responses to the last of the audio
+                                // until Deepgram closes the connection.
+                                source_done = true;
                                 if let Err(err) = handle.close_stream().await {
-                                    if tx.send(Err(err)).await.is_err() {
+                                    if tx.send(Err(err.into())).await.is_err() {
                                         break;
                                     }
                                 }
-                                break;
                             }
                         }
                     }
                 }
             }
-        });
+        }));
         Ok(FluxStream { rx, request_id })
     }
 
+    /// Connect to Deepgram, returning a sink to push audio into and the
+    /// stream of responses.
+    ///
+    /// Useful when audio arrives from callbacks or channels rather than as a
+    /// [`Stream`]. Otherwise behaves like [`FluxBuilder::stream`]: once every
+    /// clone of the sink has been closed or dropped, `CloseStream` is sent,
+    /// and the stream of responses ends when Deepgram closes the connection.
+    ///
+    /// ```no_run
+    /// # use deepgram::{common::options::Encoding, Deepgram, DeepgramError};
+    /// # use bytes::Bytes;
+    /// # use futures::{SinkExt, StreamExt};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), DeepgramError> {
+    /// let dg = Deepgram::new(std::env::var("DEEPGRAM_API_KEY").unwrap_or_default())?;
+    /// let transcription = dg.transcription();
+    /// let (audio, mut results) = transcription
+    ///     .flux_request()
+    ///     .encoding(Encoding::Linear16)
+    ///     .sample_rate(16000)
+    ///     .connect()
+    ///     .await?;
+    ///
+    /// # let source = futures::stream::empty::<Bytes>();
+    /// tokio::spawn(source.map(Ok).forward(audio));
+    /// while let Some(response) = results.next().await {
+    ///     println!("{:?}", response?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn connect(self) -> Result<(FluxAudioSink, FluxStream), LiveError> {
+        let (tx, rx) = mpsc::channel(1);
+        let results = self.stream(rx.map(Ok::<_, Infallible>)).await?;
+
+        Ok((FluxAudioSink { tx }, results))
+    }
+
     /// A low level interface to the Deepgram Flux websocket API.
-    pub async fn handle(self) -> Result<FluxHandle> {
-        FluxHandle::new(self).await
+    pub async fn handle(self) -> Result<FluxHandle, LiveError> {
+        Ok(FluxHandle::new(self).await?)
     }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
 #[serde(tag = "type")]
 enum ControlMessage {
+    Finalize,
+    KeepAlive,
     CloseStream,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum WsMessage {
-    Audio(Vec<u8>),
+    Audio(Bytes),
+    Text(Utf8Bytes),
     CloseStream,
 }
 
@@ -300,14 +680,17 @@ pub struct FluxHandle {
     message_tx: Sender<WsMessage>,
     pub(crate) response_rx: Receiver<Result<FluxResponse>>,
     request_id: Uuid,
+    metrics: Option<Arc<dyn Metrics>>,
 }
 
 impl FluxHandle {
     async fn new(builder: FluxBuilder<'_>) -> Result<FluxHandle> {
+        builder.validate()?;
         let url = builder.as_url()?;
         let host = url.host_str().ok_or(DeepgramError::InvalidUrl)?;
 
-        let request = {
+        let auth = builder.deepgram.current_auth().await?;
+        let mut request = {
             let http_builder = Request::builder()
                 .method("GET")
                 .uri(url.to_string())
@@ -316,9 +699,9 @@ impl FluxHandle {
                 .header("connection", "upgrade")
                 .header("upgrade", "websocket")
                 .header("sec-websocket-version", "13")
-                .header("user-agent", crate::USER_AGENT);
+                .header("user-agent", builder.deepgram.user_agent.clone());
 
-            let builder = if let Some(auth) = &builder.deepgram.auth {
+            let builder = if let Some(auth) = &auth {
                 http_builder.header("authorization", auth.header_value())
             } else {
                 http_builder
@@ -326,7 +709,35 @@ impl FluxHandle {
             builder.body(())?
         };
 
-        let (ws_stream, upgrade_response) = tokio_tungstenite::connect_async(request).await?;
+        add_missing_headers(request.headers_mut(), &builder.extra_headers);
+        builder.deepgram.add_default_headers(request.headers_mut());
+
+        let middleware = builder.deepgram.client.middleware();
+        for middleware in middleware {
+            middleware.on_websocket_request(&mut request);
+        }
+
+        let metrics = builder.deepgram.client.metrics().cloned();
+        let start = builder.runtime.now();
+        let connection = transport::connect(
+            builder.transport.as_ref(),
+            builder.runtime.as_ref(),
+            request,
+            builder.connect_timeout,
+        )
+        .await;
+        if let Some(metrics) = &metrics {
+            let duration = builder.runtime.now().saturating_duration_since(start);
+            metrics.record_websocket_connect(&url, connection.is_ok(), duration);
+        }
+        let transport::Connection {
+            socket: ws_stream,
+            response: upgrade_response,
+        } = connection?;
+
+        for middleware in middleware {
+            middleware.on_websocket_response(&upgrade_response);
+        }
 
         let request_id = upgrade_response
             .headers()
@@ -344,25 +755,41 @@ impl FluxHandle {
         let (message_tx, message_rx) = mpsc::channel(256);
         let (response_tx, response_rx) = mpsc::channel(256);
 
-        tokio::task::spawn(run_flux_worker(ws_stream, message_rx, response_tx));
+        builder.runtime.spawn(Box::pin(
+            run_flux_worker(
+                ws_stream,
+                message_rx,
+                response_tx,
+                builder.resilient_parsing.unwrap_or(false),
+            )
+            // The worker has already sent any unexpected close as a response.
+            .map(|_| ()),
+        ));
 
         Ok(FluxHandle {
             message_tx,
             response_rx,
             request_id,
+            metrics,
         })
     }
 
-    pub async fn send_data(&mut self, data: Vec<u8>) -> Result<()> {
+    /// Send a chunk of audio, without copying it if it is already [`Bytes`].
+    pub async fn send_data(&mut self, data: impl Into<Bytes>) -> Result<(), LiveError> {
+        let data = data.into();
+        let len = data.len();
         self.message_tx
             .send(WsMessage::Audio(data))
             .await
             .map_err(|err| DeepgramError::InternalClientError(err.into()))?;
+        if let Some(metrics) = &self.metrics {
+            metrics.record_audio_sent(len);
+        }
         Ok(())
     }
 
     /// Close the websocket stream. No more data should be sent after this is called.
-    pub async fn close_stream(&mut self) -> Result<()> {
+    pub async fn close_stream(&mut self) -> Result<(), LiveError> {
         if !self.message_tx.is_closed() {
             self.message_tx
                 .send(WsMessage::CloseStream)
@@ -373,10 +800,62 @@ impl FluxHandle {
         Ok(())
     }
 
-    #[allow(clippy::let_and_return)]
-    pub async fn receive(&mut self) -> Option<Result<FluxResponse>> {
-        let resp = self.response_rx.next().await;
-        resp
+    /// Send a Finalize message to ask Deepgram to finish the current turn
+    /// with the audio it has already received, without waiting for the
+    /// speaker to stop.
+    pub async fn finalize(&mut self) -> Result<(), LiveError> {
+        self.send_control_message(&ControlMessage::Finalize).await
+    }
+
+    /// Send a KeepAlive message so the connection isn't closed while there
+    /// is no audio to send.
+    pub async fn keep_alive(&mut self) -> Result<(), LiveError> {
+        self.send_control_message(&ControlMessage::KeepAlive).await
+    }
+
+    /// Send `message` as a control message, for messages this crate doesn't
+    /// have a method for yet.
+    ///
+    /// `message` must be a JSON object with a string `type`, or the call
+    /// fails with [`DeepgramError::InvalidOption`].
+    ///
+    /// ```no_run
+    /// # use deepgram::{listen::flux::FluxHandle, DeepgramError};
+    /// # async fn example(mut handle: FluxHandle) -> Result<(), DeepgramError> {
+    /// handle
+    ///     .send_json(serde_json::json!({ "type": "Configure", "eot_threshold": 0.8 }))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_json(&mut self, message: serde_json::Value) -> Result<(), LiveError> {
+        if !message
+            .get("type")
+            .is_some_and(serde_json::Value::is_string)
+        {
+            return Err(DeepgramError::InvalidOption(format!(
+                "control messages must be objects with a string type, got {message}"
+            ))
+            .into());
+        }
+        self.send_control_message(&message).await
+    }
+
+    async fn send_control_message(
+        &mut self,
+        message: &impl serde::Serialize,
+    ) -> Result<(), LiveError> {
+        let text = serde_json::to_string(message)?;
+        self.message_tx
+            .send(WsMessage::Text(text.into()))
+            .await
+            .map_err(|err| DeepgramError::InternalClientError(err.into()))?;
+        Ok(())
+    }
+
+    pub async fn receive(&mut self) -> Option<Result<FluxResponse, LiveError>> {
+        let resp = self.response_rx.next().await?;
+        Some(resp.map_err(Into::into))
     }
 
     pub fn request_id(&self) -> Uuid {
@@ -385,9 +864,10 @@ impl FluxHandle {
 }
 
 async fn run_flux_worker(
-    ws_stream: WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>,
+    ws_stream: BoxWebSocket,
     mut message_rx: Receiver<WsMessage>,
     mut response_tx: Sender<Result<FluxResponse>>,
+    resilient_parsing: bool,
 ) -> Result<()> {
     // We use Vec<u8> for partial frames because we don't know if a fragment of a string is valid utf-8.
     let mut partial_frame: Vec<u8> = Vec::new();
@@ -399,19 +879,10 @@ async fn run_flux_worker(
             response = ws_stream_recv.next() => {
                 match response {
                     Some(Ok(Message::Text(response))) => {
-                        match serde_json::from_str(&response) {
-                            Ok(response) => {
-                                if (response_tx.send(Ok(response)).await).is_err() {
-                                    // Responses are no longer being received; close the stream.
-                                    break;
-                                }
-                            }
-                            Err(err) => {
-                                if (response_tx.send(Err(err.into())).await).is_err() {
-                                    // Responses are no longer being received; close the stream.
-                                    break;
-                                }
-                            }
+                        let response = parse_response(response.as_bytes(), resilient_parsing);
+                        if (response_tx.send(response).await).is_err() {
+                            // Responses are no longer being received; close the stream.
+                            break;
                         }
                     }
                     Some(Ok(Message::Ping(value))) => {
@@ -422,12 +893,18 @@ async fn run_flux_worker(
                         return Ok(());
                     }
                     Some(Ok(Message::Close(Some(closeframe)))) => {
-                        return Err(DeepgramError::WebsocketClose {
+                        let close = || DeepgramError::WebsocketClose {
                             code: closeframe.code.into(),
                             reason: closeframe.reason.to_string(),
-                        });
+                        };
+                        // Anything but a normal closure tells the receiver why the responses ended.
+                        if u16::from(closeframe.code) != 1000 {
+                            let _ = response_tx.send(Err(close())).await;
+                        }
+                        return Err(close());
                     }
                     Some(Ok(Message::Frame(frame))) => {
+                        #[allow(clippy::collapsible_match)]
                         match frame.header().opcode {
                             OpCode::Data(Data::Text) => {
                                 partial_frame.extend(frame.payload());
@@ -445,7 +922,7 @@ async fn run_flux_worker(
                         }
                         if frame.header().is_final {
                             let response = std::mem::take(&mut partial_frame);
-                            let response = serde_json::from_slice(&response).map_err(|err| err.into());
+                            let response = parse_response(&response, resilient_parsing);
                             if (response_tx.send(response).await).is_err() {
                                 // Responses are no longer being received; close the stream.
                                 break
@@ -472,7 +949,14 @@ async fn run_flux_worker(
                 if is_open {
                     match message {
                         Some(WsMessage::Audio(audio)) => {
-                            if let Err(err) = ws_stream_send.send(Message::Binary(Bytes::from(audio))).await {
+                            if let Err(err) = ws_stream_send.send(Message::Binary(audio)).await {
+                                if response_tx.send(Err(err.into())).await.is_err() {
+                                    break;
+                                }
+                            }
+                        }
+                        Some(WsMessage::Text(text)) => {
+                            if let Err(err) = ws_stream_send.send(Message::Text(text)).await {
                                 if response_tx.send(Err(err.into())).await.is_err() {
                                     break;
                                 }
@@ -511,6 +995,35 @@ async fn run_flux_worker(
     Ok(())
 }
 
+/// Sends audio over a connection opened with [`FluxBuilder::connect`].
+///
+/// Any stream of audio can be sent with [`StreamExt::forward`] or
+/// [`SinkExt::send_all`].
+#[derive(Debug, Clone)]
+pub struct FluxAudioSink {
+    tx: Sender<Bytes>,
+}
+
+impl Sink<Bytes> for FluxAudioSink {
+    type Error = LiveError;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), LiveError>> {
+        self.tx.poll_ready(cx).map_err(sink_error)
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, audio: Bytes) -> Result<(), LiveError> {
+        self.tx.start_send(audio).map_err(sink_error)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), LiveError>> {
+        Pin::new(&mut self.tx).poll_flush(cx).map_err(sink_error)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), LiveError>> {
+        Pin::new(&mut self.tx).poll_close(cx).map_err(sink_error)
+    }
+}
+
 #[derive(Debug)]
 #[pin_project]
 pub struct FluxStream {
@@ -520,11 +1033,13 @@ pub struct FluxStream {
 }
 
 impl Stream for FluxStream {
-    type Item = Result<FluxResponse, DeepgramError>;
+    type Item = Result<FluxResponse, LiveError>;
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         let this = self.project();
-        this.rx.poll_next(cx)
+        this.rx
+            .poll_next(cx)
+            .map(|response| response.map(|response| response.map_err(Into::into)))
     }
 }
 
@@ -538,73 +1053,29 @@ impl FluxStream {
     }
 }
 
-mod file_chunker {
-    use bytes::{Bytes, BytesMut};
-    use futures::Stream;
-    use pin_project::pin_project;
-    use std::{
-        pin::Pin,
-        task::{Context, Poll},
-    };
-    use tokio::fs::File;
-    use tokio_util::io::ReaderStream;
-
-    use crate::{DeepgramError, Result};
-
-    #[pin_project]
-    pub(super) struct FileChunker {
-        chunk_size: usize,
-        buf: BytesMut,
-        #[pin]
-        file: ReaderStream<File>,
-    }
-
-    impl FileChunker {
-        pub(super) fn new(file: File, chunk_size: usize) -> Self {
-            FileChunker {
-                chunk_size,
-                buf: BytesMut::with_capacity(2 * chunk_size),
-                file: ReaderStream::new(file),
-            }
-        }
-    }
-
-    impl Stream for FileChunker {
-        type Item = Result<Bytes>;
-
-        fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
-            let mut this = self.project();
-
-            while this.buf.len() < *this.chunk_size {
-                match Pin::new(&mut this.file).poll_next(cx) {
-                    Poll::Pending => return Poll::Pending,
-                    Poll::Ready(next) => match next.transpose() {
-                        Err(e) => return Poll::Ready(Some(Err(DeepgramError::from(e)))),
-                        Ok(None) => {
-                            if this.buf.is_empty() {
-                                return Poll::Ready(None);
-                            } else {
-                                return Poll::Ready(Some(Ok(this
-                                    .buf
-                                    .split_to(this.buf.len())
-                                    .freeze())));
-                            }
-                        }
-                        Ok(Some(next)) => {
-                            this.buf.extend_from_slice(&next);
-                        }
-                    },
-                }
-            }
-
-            Poll::Ready(Some(Ok(this.buf.split_to(*this.chunk_size).freeze())))
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
-    use crate::common::options::Options;
+    use std::time::Duration;
+
+    use bytes::Bytes;
+    use futures::{channel::mpsc, SinkExt, StreamExt};
+    use tungstenite::Message;
+    use uuid::Uuid;
+
+    use crate::{
+        common::{
+            fixtures::TurnInfoBuilder,
+            flux_response::{FluxResponse, TurnEvent},
+            options::Options,
+        },
+        listen::{
+            websocket::{
+                tests::{fake_connection, fake_socket, FakeTransport},
+                ReconnectPolicy,
+            },
+            LiveError,
+        },
+    };
 
     #[test]
     fn test_flux_url() {
@@ -635,4 +1106,249 @@ mod tests {
         let builder = transcription.flux_request_with_options(opts.clone());
         assert_eq!(builder.urlencoded().unwrap(), opts.urlencoded().unwrap())
     }
+
+    #[test]
+    fn keyterms_follow_options() {
+        let dg = crate::Deepgram::new("token").unwrap();
+        let transcription = dg.transcription();
+        let options = Options::builder().keyterms(["Deepgram"]).build();
+        let builder = transcription
+            .flux_request_with_options(options)
+            .keyterms(["a&b", "c d"])
+            .keyterms(["é"]);
+        assert_eq!(
+            builder.urlencoded().unwrap(),
+            "keyterm=Deepgram&keyterm=a%26b&keyterm=c+d&keyterm=%C3%A9"
+        );
+    }
+
+    #[test]
+    fn extra_query_params_follow_options() {
+        let dg = crate::Deepgram::new("token").unwrap();
+        let transcription = dg.transcription();
+        let builder = transcription
+            .flux_request()
+            .sample_rate(16000)
+            .extra_query_params([("gateway".to_string(), "a&b".to_string())]);
+        assert_eq!(
+            builder.urlencoded().unwrap(),
+            "model=flux-general-en&sample_rate=16000&gateway=a%26b"
+        );
+    }
+
+    #[tokio::test]
+    async fn sets_end_of_turn_options() {
+        let dg = crate::Deepgram::new("token").unwrap();
+        let transcription = dg.transcription();
+        let options = Options::builder()
+            .eot_threshold(0.6)
+            .eot_timeout_ms(3000)
+            .build();
+        let builder = transcription
+            .flux_request_with_options(options)
+            .eot_threshold(0.8)
+            .eager_eot_threshold(0.5);
+        assert_eq!(
+            builder.urlencoded().unwrap(),
+            "eot_timeout_ms=3000&eot_threshold=0.8&eager_eot_threshold=0.5"
+        );
+
+        for builder in [
+            transcription.flux_request().eot_threshold(0.95),
+            transcription.flux_request().eager_eot_threshold(0.2),
+            transcription.flux_request().eot_timeout_ms(100),
+        ] {
+            let err = builder
+                .transport(FakeTransport::new([]))
+                .handle()
+                .await
+                .unwrap_err();
+            assert!(matches!(err, LiveError::InvalidOption(_)));
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn reconnects_with_interrupted_turn() {
+        let (first, _first_sent_rx, first_incoming_tx) = fake_socket();
+        let (second, mut second_sent_rx, _second_incoming_tx) = fake_socket();
+        let transport = FakeTransport::new([first, second]);
+
+        let dg = crate::Deepgram::new("token").unwrap();
+        let transcription = dg.transcription();
+        let (mut audio_tx, audio_rx) =
+            mpsc::channel::<std::result::Result<Bytes, std::io::Error>>(1);
+        let mut results = transcription
+            .flux_request()
+            .transport(transport)
+            .reconnect(ReconnectPolicy::new(3).backoff(Duration::from_millis(100)))
+            .stream(audio_rx)
+            .await
+            .unwrap();
+
+        let update = TurnInfoBuilder::new(TurnEvent::Update)
+            .turn_index(2)
+            .word("hello", 0.9)
+            .build();
+        first_incoming_tx
+            .unbounded_send(Ok(Message::Text(
+                serde_json::to_string(&update).unwrap().into(),
+            )))
+            .unwrap();
+        assert_eq!(results.next().await.unwrap().unwrap(), update);
+
+        // The server goes away in the middle of the turn.
+        drop(first_incoming_tx);
+        match results.next().await.unwrap().unwrap() {
+            FluxResponse::Reconnected {
+                request_id,
+                attempts,
+                interrupted_turn,
+            } => {
+                assert_eq!(request_id, Uuid::from_u128(1));
+                assert_eq!(attempts, 1);
+                assert_eq!(
+                    interrupted_turn,
+                    update.into_turn_lifecycle().map(|turn| turn.into_turn())
+                );
+            }
+            response => panic!("expected Reconnected, got {response:?}"),
+        }
+
+        audio_tx.send(Ok(Bytes::from_static(b"abc"))).await.unwrap();
+        assert_eq!(
+            second_sent_rx.next().await.unwrap(),
+            Message::Binary(Bytes::from_static(b"abc"))
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn does_not_reconnect_after_policy_violation() {
+        use tungstenite::protocol::{frame::coding::CloseCode, CloseFrame};
+
+        let (first, _first_sent_rx, first_incoming_tx) = fake_socket();
+        let (second, _second_sent_rx, _second_incoming_tx) = fake_socket();
+        let transport = FakeTransport::new([first, second]);
+
+        let dg = crate::Deepgram::new("token").unwrap();
+        let transcription = dg.transcription();
+        let (_audio_tx, audio_rx) = mpsc::channel::<std::result::Result<Bytes, std::io::Error>>(1);
+        let mut results = transcription
+            .flux_request()
+            .transport(transport)
+            .reconnect(ReconnectPolicy::new(3).backoff(Duration::from_millis(100)))
+            .stream(audio_rx)
+            .await
+            .unwrap();
+
+        first_incoming_tx
+            .unbounded_send(Ok(Message::Close(Some(CloseFrame {
+                code: CloseCode::Policy,
+                reason: "bad request".into(),
+            }))))
+            .unwrap();
+        assert!(matches!(
+            results.next().await,
+            Some(Err(LiveError::WebsocketClose { code: 1008, .. }))
+        ));
+        assert!(results.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn sends_control_messages() {
+        let (transport, mut sent_rx, _incoming_tx) = fake_connection();
+
+        let dg = crate::Deepgram::new("token").unwrap();
+        let transcription = dg.transcription();
+        let mut handle = transcription
+            .flux_request()
+            .transport(transport)
+            .handle()
+            .await
+            .unwrap();
+
+        handle.finalize().await.unwrap();
+        handle.keep_alive().await.unwrap();
+        handle
+            .send_json(serde_json::json!({ "type": "Configure", "eot_threshold": 0.8 }))
+            .await
+            .unwrap();
+        let err = handle
+            .send_json(serde_json::json!({ "eot_threshold": 0.8 }))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, LiveError::InvalidOption(_)));
+
+        for expected in [
+            r#"{"type":"Finalize"}"#,
+            r#"{"type":"KeepAlive"}"#,
+            r#"{"eot_threshold":0.8,"type":"Configure"}"#,
+        ] {
+            assert_eq!(
+                sent_rx.next().await.unwrap(),
+                Message::Text(expected.into())
+            );
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn receives_final_turn_after_sink_closes() {
+        let (transport, mut sent_rx, incoming_tx) = fake_connection();
+
+        let dg = crate::Deepgram::new("token").unwrap();
+        let transcription = dg.transcription();
+        let (mut audio, mut results) = transcription
+            .flux_request()
+            .transport(transport)
+            .connect()
+            .await
+            .unwrap();
+
+        audio.close().await.unwrap();
+        assert_eq!(
+            sent_rx.next().await.unwrap(),
+            Message::Text(r#"{"type":"CloseStream"}"#.into())
+        );
+
+        // Deepgram finishes the turn after CloseStream, then hangs up.
+        let end_of_turn = TurnInfoBuilder::new(TurnEvent::EndOfTurn)
+            .word("goodbye", 0.9)
+            .build();
+        incoming_tx
+            .unbounded_send(Ok(Message::Text(
+                serde_json::to_string(&end_of_turn).unwrap().into(),
+            )))
+            .unwrap();
+        assert_eq!(results.next().await.unwrap().unwrap(), end_of_turn);
+
+        drop(incoming_tx);
+        assert!(results.next().await.is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn connect_sends_audio_from_sink() {
+        let (transport, mut sent_rx, _incoming_tx) = fake_connection();
+
+        let dg = crate::Deepgram::new("token").unwrap();
+        let transcription = dg.transcription();
+        let (audio, _results) = transcription
+            .flux_request()
+            .transport(transport)
+            .connect()
+            .await
+            .unwrap();
+
+        let source =
+            futures::stream::iter([Bytes::from_static(b"abc"), Bytes::from_static(b"def")]);
+        source.map(Ok).forward(audio).await.unwrap();
+        for expected in [b"abc", b"def"] {
+            assert_eq!(
+                sent_rx.next().await.unwrap(),
+                Message::Binary(Bytes::from_static(expected))
+            );
+        }
+        assert_eq!(
+            sent_rx.next().await.unwrap(),
+            Message::Text(r#"{"type":"CloseStream"}"#.into())
+        );
+    }
 }
@@ -1,5 +1,575 @@
 //! Listen module
 
+#[cfg(feature = "listen-ws")]
+mod audio_tee;
+#[cfg(feature = "listen-rest")]
+pub mod callback;
+#[cfg(feature = "listen-ws")]
+pub mod channels;
+#[cfg(feature = "listen-ws")]
+mod file_chunker;
+#[cfg(feature = "listen-ws")]
 pub mod flux;
+#[cfg(feature = "listen-rest")]
+pub mod records;
+#[cfg(feature = "listen-rest")]
 pub mod rest;
+#[cfg(feature = "listen-ws")]
+pub mod sessions;
+#[cfg(feature = "decode")]
+pub mod sharded;
+#[cfg(feature = "microphone")]
+pub mod sources;
+#[cfg(feature = "listen-ws")]
+mod spool;
+#[cfg(feature = "listen-ws")]
+pub mod transport;
+#[cfg(feature = "listen-ws")]
+mod wav_header;
+#[cfg(feature = "listen-ws")]
 pub mod websocket;
+
+use std::{io, time::Duration};
+
+use reqwest::StatusCode;
+#[cfg(feature = "listen-ws")]
+use serde::de::DeserializeOwned;
+use thiserror::Error;
+
+#[cfg(feature = "listen-rest")]
+use crate::ReqwestError;
+#[cfg(feature = "listen-ws")]
+use crate::{json, CloseReason, Result, TungsteniteError};
+use crate::{
+    ApiErrorBody, DeepgramError, ErrorView, HttpError, SerdeJsonError, SerdeUrlencodedError,
+};
+
+/// Errors that may arise from the prerecorded [`listen`](crate::listen) APIs.
+///
+/// Converts into [`DeepgramError`] with `?`, keeping the same variant.
+#[cfg(feature = "listen-rest")]
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ListenError {
+    /// The Deepgram API returned an error.
+    #[error("The Deepgram API returned an error.")]
+    DeepgramApiError {
+        /// Error message from the Deepgram API.
+        body: String,
+
+        /// Underlying [`reqwest::Error`] from the HTTP request.
+        err: ReqwestError,
+    },
+
+    /// The Deepgram API rate limited the request.
+    ///
+    /// See [`DeepgramError::RateLimited`].
+    #[error("The Deepgram API rate limited the request.")]
+    RateLimited {
+        /// How long the API asked to wait before retrying, from the `Retry-After` header.
+        retry_after: Option<Duration>,
+
+        /// Error message from the Deepgram API.
+        body: String,
+
+        /// Underlying [`reqwest::Error`] from the HTTP request.
+        err: ReqwestError,
+    },
+
+    /// The request did not complete within the configured timeout.
+    #[error("The request timed out")]
+    Timeout,
+
+    /// Something went wrong when generating the http request.
+    #[error("Something went wrong when generating the http request: {0}")]
+    HttpError(HttpError),
+
+    /// Something went wrong when making the HTTP request.
+    #[error("Something went wrong when making the HTTP request: {0}")]
+    ReqwestError(ReqwestError),
+
+    /// Something went wrong during I/O.
+    #[error("Something went wrong during I/O: {0}")]
+    IoError(io::Error),
+
+    /// Something went wrong during serialization/deserialization.
+    #[error("Something went wrong during json serialization/deserialization: {0}")]
+    JsonError(SerdeJsonError),
+
+    /// Something went wrong during serialization/deserialization.
+    #[error("Something went wrong during query serialization: {0}")]
+    UrlencodedError(SerdeUrlencodedError),
+
+    /// The provided audio is in a format this client cannot handle.
+    #[error("The provided audio is not supported: {0}")]
+    UnsupportedAudio(String),
+
+    /// Any other error, such as one returned by a [`TokenProvider`](crate::TokenProvider).
+    #[error(transparent)]
+    Other(DeepgramError),
+}
+
+#[cfg(feature = "listen-rest")]
+impl ListenError {
+    /// The structured error details returned by the Deepgram API, if any.
+    ///
+    /// See [`DeepgramError::api_error`].
+    pub fn api_error(&self) -> Option<ApiErrorBody> {
+        self.view().api_error()
+    }
+
+    /// The HTTP status code of the response that caused this error, if any.
+    ///
+    /// See [`DeepgramError::status_code`].
+    pub fn status_code(&self) -> Option<StatusCode> {
+        self.view().status_code()
+    }
+
+    /// The Deepgram error code, such as `INVALID_AUTH`, if the API returned one.
+    ///
+    /// See [`DeepgramError::error_code`].
+    pub fn error_code(&self) -> Option<String> {
+        self.view().error_code()
+    }
+
+    /// Whether retrying the request that caused this error might succeed.
+    ///
+    /// See [`DeepgramError::is_retryable`].
+    pub fn is_retryable(&self) -> bool {
+        self.view().is_retryable()
+    }
+
+    fn view(&self) -> ErrorView<'_> {
+        match self {
+            ListenError::DeepgramApiError { body, err } => ErrorView::Api { body, err },
+            ListenError::RateLimited { body, err, .. } => ErrorView::RateLimited { body, err },
+            ListenError::Timeout => ErrorView::Timeout,
+            ListenError::ReqwestError(err) => ErrorView::Reqwest(err),
+            ListenError::IoError(err) => ErrorView::Io(err),
+            ListenError::Other(err) => err.view(),
+            ListenError::HttpError(_)
+            | ListenError::JsonError(_)
+            | ListenError::UrlencodedError(_)
+            | ListenError::UnsupportedAudio(_) => ErrorView::Other,
+        }
+    }
+}
+
+#[cfg(feature = "listen-rest")]
+impl From<DeepgramError> for ListenError {
+    fn from(err: DeepgramError) -> Self {
+        match err {
+            DeepgramError::DeepgramApiError { body, err } => Self::DeepgramApiError { body, err },
+            DeepgramError::RateLimited {
+                retry_after,
+                body,
+                err,
+            } => Self::RateLimited {
+                retry_after,
+                body,
+                err,
+            },
+            DeepgramError::Timeout => Self::Timeout,
+            DeepgramError::HttpError(err) => Self::HttpError(err),
+            DeepgramError::ReqwestError(err) => Self::ReqwestError(err),
+            DeepgramError::IoError(err) => Self::IoError(err),
+            DeepgramError::JsonError(err) => Self::JsonError(err),
+            DeepgramError::UrlencodedError(err) => Self::UrlencodedError(err),
+            DeepgramError::UnsupportedAudio(err) => Self::UnsupportedAudio(err),
+            err => Self::Other(err),
+        }
+    }
+}
+
+#[cfg(feature = "listen-rest")]
+impl From<ListenError> for DeepgramError {
+    fn from(err: ListenError) -> Self {
+        match err {
+            ListenError::DeepgramApiError { body, err } => Self::DeepgramApiError { body, err },
+            ListenError::RateLimited {
+                retry_after,
+                body,
+                err,
+            } => Self::RateLimited {
+                retry_after,
+                body,
+                err,
+            },
+            ListenError::Timeout => Self::Timeout,
+            ListenError::HttpError(err) => Self::HttpError(err),
+            ListenError::ReqwestError(err) => Self::ReqwestError(err),
+            ListenError::IoError(err) => Self::IoError(err),
+            ListenError::JsonError(err) => Self::JsonError(err),
+            ListenError::UrlencodedError(err) => Self::UrlencodedError(err),
+            ListenError::UnsupportedAudio(err) => Self::UnsupportedAudio(err),
+            ListenError::Other(err) => err,
+        }
+    }
+}
+
+#[cfg(feature = "listen-rest")]
+impl From<io::Error> for ListenError {
+    fn from(err: io::Error) -> Self {
+        Self::IoError(err)
+    }
+}
+
+#[cfg(feature = "listen-rest")]
+impl From<SerdeJsonError> for ListenError {
+    fn from(err: SerdeJsonError) -> Self {
+        Self::JsonError(err)
+    }
+}
+
+#[cfg(feature = "listen-rest")]
+impl From<ReqwestError> for ListenError {
+    fn from(err: ReqwestError) -> Self {
+        DeepgramError::from(err).into()
+    }
+}
+
+/// Errors that may arise from the live [`websocket`] and [`flux`] APIs.
+///
+/// Converts into [`DeepgramError`] with `?`, keeping the same variant.
+#[cfg(feature = "listen-ws")]
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum LiveError {
+    /// Something went wrong when generating the http request.
+    #[error("Something went wrong when generating the http request: {0}")]
+    HttpError(HttpError),
+
+    /// Something went wrong during I/O.
+    #[error("Something went wrong during I/O: {0}")]
+    IoError(io::Error),
+
+    /// Something went wrong with WS.
+    #[error("Something went wrong with WS: {0}")]
+    WsError(Box<TungsteniteError>),
+
+    /// Something went wrong during serialization/deserialization.
+    #[error("Something went wrong during json serialization/deserialization: {0}")]
+    JsonError(SerdeJsonError),
+
+    /// A message received over a streaming connection could not be deserialized.
+    ///
+    /// See [`DeepgramError::UnparseableResponse`].
+    #[error("Could not deserialize a streaming response: {source}")]
+    UnparseableResponse {
+        /// The raw message as received from the server.
+        payload: String,
+
+        /// The underlying deserialization error.
+        source: SerdeJsonError,
+    },
+
+    /// Something went wrong during serialization/deserialization.
+    #[error("Something went wrong during query serialization: {0}")]
+    UrlencodedError(SerdeUrlencodedError),
+
+    /// The data stream produced an error
+    #[error("The data stream produced an error: {0}")]
+    StreamError(Box<dyn std::error::Error + Send + Sync + 'static>),
+
+    /// A websocket close from was received indicating an error
+    #[error("websocket close frame received with error content: code: {code}, reason: {reason}")]
+    WebsocketClose {
+        /// The numerical code indicating the reason for the error
+        code: u16,
+        /// A textual description of the error reason
+        reason: String,
+    },
+
+    /// Deepgram did not finish sending results within the drain timeout.
+    ///
+    /// See [`DeepgramError::DrainTimeout`].
+    #[error("Results were still pending {timeout:?} after closing the stream")]
+    DrainTimeout {
+        /// How long results were waited for.
+        timeout: Duration,
+    },
+
+    /// Deepgram did not answer a ping within the pong timeout.
+    ///
+    /// See [`DeepgramError::ConnectionLost`].
+    #[error("No pong was received within {timeout:?} of a ping; the connection was lost")]
+    ConnectionLost {
+        /// How long a pong was waited for.
+        timeout: Duration,
+    },
+
+    /// A streaming connection was not established within the connect timeout.
+    ///
+    /// See [`DeepgramError::ConnectTimeout`].
+    #[error("The websocket connection was not established within {timeout:?}")]
+    ConnectTimeout {
+        /// How long the connection was waited for.
+        timeout: Duration,
+    },
+
+    /// Deepgram rejected the websocket upgrade request of a streaming connection.
+    ///
+    /// See [`DeepgramError::WebsocketHandshake`].
+    #[error("Deepgram rejected the websocket connection with HTTP status {status}: {body}")]
+    WebsocketHandshake {
+        /// The HTTP status of the upgrade response.
+        status: StatusCode,
+
+        /// The body of the upgrade response.
+        body: String,
+    },
+
+    /// An unexpected error occurred in the client
+    #[error("an unepected error occurred in the deepgram client: {0}")]
+    InternalClientError(anyhow::Error),
+
+    /// A Deepgram API server response was not in the expected format.
+    #[error("The Deepgram API server response was not in the expected format: {0}")]
+    UnexpectedServerResponse(anyhow::Error),
+
+    /// The provided audio is in a format this client cannot handle.
+    #[error("The provided audio is not supported: {0}")]
+    UnsupportedAudio(String),
+
+    /// A request option has a value Deepgram would reject.
+    #[error("Invalid request option: {0}")]
+    InvalidOption(String),
+
+    #[cfg(feature = "microphone")]
+    /// Something went wrong with an audio input device.
+    #[error("Something went wrong with the audio input device: {0}")]
+    AudioDeviceError(Box<dyn std::error::Error + Send + Sync + 'static>),
+
+    /// Any other error, such as one returned by a [`TokenProvider`](crate::TokenProvider).
+    #[error(transparent)]
+    Other(DeepgramError),
+}
+
+#[cfg(feature = "listen-ws")]
+impl LiveError {
+    /// The structured error details returned by the Deepgram API, if any.
+    ///
+    /// See [`DeepgramError::api_error`].
+    pub fn api_error(&self) -> Option<ApiErrorBody> {
+        self.view().api_error()
+    }
+
+    /// What the websocket close frame that caused this error means, if any.
+    ///
+    /// See [`DeepgramError::close_reason`].
+    pub fn close_reason(&self) -> Option<CloseReason> {
+        self.view().close_reason()
+    }
+
+    /// The HTTP status code of the response that caused this error, if any.
+    ///
+    /// See [`DeepgramError::status_code`].
+    pub fn status_code(&self) -> Option<StatusCode> {
+        self.view().status_code()
+    }
+
+    /// The Deepgram error code, such as `INVALID_AUTH`, if the API returned one.
+    ///
+    /// See [`DeepgramError::error_code`].
+    pub fn error_code(&self) -> Option<String> {
+        self.view().error_code()
+    }
+
+    /// Whether retrying the connection that caused this error might succeed.
+    ///
+    /// See [`DeepgramError::is_retryable`].
+    pub fn is_retryable(&self) -> bool {
+        self.view().is_retryable()
+    }
+
+    fn view(&self) -> ErrorView<'_> {
+        match self {
+            LiveError::IoError(err) => ErrorView::Io(err),
+            LiveError::WsError(err) => ErrorView::Ws(err),
+            LiveError::WebsocketClose { code, reason } => ErrorView::WebsocketClose {
+                code: *code,
+                reason,
+            },
+            LiveError::ConnectionLost { .. } | LiveError::ConnectTimeout { .. } => {
+                ErrorView::Disconnected
+            }
+            LiveError::WebsocketHandshake { status, body } => ErrorView::WebsocketHandshake {
+                status: *status,
+                body,
+            },
+            LiveError::Other(err) => err.view(),
+            _ => ErrorView::Other,
+        }
+    }
+}
+
+#[cfg(feature = "listen-ws")]
+impl From<DeepgramError> for LiveError {
+    fn from(err: DeepgramError) -> Self {
+        match err {
+            DeepgramError::HttpError(err) => Self::HttpError(err),
+            DeepgramError::IoError(err) => Self::IoError(err),
+            DeepgramError::WsError(err) => Self::WsError(err),
+            DeepgramError::JsonError(err) => Self::JsonError(err),
+            DeepgramError::UnparseableResponse { payload, source } => {
+                Self::UnparseableResponse { payload, source }
+            }
+            DeepgramError::UrlencodedError(err) => Self::UrlencodedError(err),
+            DeepgramError::StreamError(err) => Self::StreamError(err),
+            DeepgramError::WebsocketClose { code, reason } => Self::WebsocketClose { code, reason },
+            DeepgramError::DrainTimeout { timeout } => Self::DrainTimeout { timeout },
+            DeepgramError::ConnectionLost { timeout } => Self::ConnectionLost { timeout },
+            DeepgramError::ConnectTimeout { timeout } => Self::ConnectTimeout { timeout },
+            DeepgramError::WebsocketHandshake { status, body } => {
+                Self::WebsocketHandshake { status, body }
+            }
+            DeepgramError::InternalClientError(err) => Self::InternalClientError(err),
+            DeepgramError::UnexpectedServerResponse(err) => Self::UnexpectedServerResponse(err),
+            DeepgramError::UnsupportedAudio(err) => Self::UnsupportedAudio(err),
+            DeepgramError::InvalidOption(err) => Self::InvalidOption(err),
+            #[cfg(feature = "microphone")]
+            DeepgramError::AudioDeviceError(err) => Self::AudioDeviceError(err),
+            err => Self::Other(err),
+        }
+    }
+}
+
+#[cfg(feature = "listen-ws")]
+impl From<LiveError> for DeepgramError {
+    fn from(err: LiveError) -> Self {
+        match err {
+            LiveError::HttpError(err) => Self::HttpError(err),
+            LiveError::IoError(err) => Self::IoError(err),
+            LiveError::WsError(err) => Self::WsError(err),
+            LiveError::JsonError(err) => Self::JsonError(err),
+            LiveError::UnparseableResponse { payload, source } => {
+                Self::UnparseableResponse { payload, source }
+            }
+            LiveError::UrlencodedError(err) => Self::UrlencodedError(err),
+            LiveError::StreamError(err) => Self::StreamError(err),
+            LiveError::WebsocketClose { code, reason } => Self::WebsocketClose { code, reason },
+            LiveError::DrainTimeout { timeout } => Self::DrainTimeout { timeout },
+            LiveError::ConnectionLost { timeout } => Self::ConnectionLost { timeout },
+            LiveError::ConnectTimeout { timeout } => Self::ConnectTimeout { timeout },
+            LiveError::WebsocketHandshake { status, body } => {
+                Self::WebsocketHandshake { status, body }
+            }
+            LiveError::InternalClientError(err) => Self::InternalClientError(err),
+            LiveError::UnexpectedServerResponse(err) => Self::UnexpectedServerResponse(err),
+            LiveError::UnsupportedAudio(err) => Self::UnsupportedAudio(err),
+            LiveError::InvalidOption(err) => Self::InvalidOption(err),
+            #[cfg(feature = "microphone")]
+            LiveError::AudioDeviceError(err) => Self::AudioDeviceError(err),
+            LiveError::Other(err) => err,
+        }
+    }
+}
+
+#[cfg(feature = "listen-ws")]
+impl From<io::Error> for LiveError {
+    fn from(err: io::Error) -> Self {
+        Self::IoError(err)
+    }
+}
+
+#[cfg(feature = "listen-ws")]
+impl From<SerdeJsonError> for LiveError {
+    fn from(err: SerdeJsonError) -> Self {
+        Self::JsonError(err)
+    }
+}
+
+/// Deserialize a message received over a streaming connection.
+///
+/// With `keep_payload`, a message that fails to deserialize is reported as
+/// [`DeepgramError::UnparseableResponse`] carrying the raw message.
+#[cfg(feature = "listen-ws")]
+pub(crate) fn parse_response<T: DeserializeOwned>(payload: &[u8], keep_payload: bool) -> Result<T> {
+    json::from_slice(payload).map_err(|source| {
+        if keep_payload {
+            DeepgramError::UnparseableResponse {
+                payload: String::from_utf8_lossy(payload).into_owned(),
+                source,
+            }
+        } else {
+            source.into()
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::DeepgramError;
+
+    #[cfg(feature = "listen-rest")]
+    #[test]
+    fn converts_to_and_from_deepgram_error() {
+        use super::ListenError;
+
+        let err = ListenError::from(DeepgramError::Timeout);
+        assert!(matches!(err, ListenError::Timeout));
+        assert!(err.is_retryable());
+        assert!(matches!(DeepgramError::from(err), DeepgramError::Timeout));
+
+        let err = ListenError::from(DeepgramError::InvalidUrl);
+        assert!(matches!(err, ListenError::Other(DeepgramError::InvalidUrl)));
+        assert!(matches!(
+            DeepgramError::from(err),
+            DeepgramError::InvalidUrl
+        ));
+    }
+
+    #[cfg(feature = "listen-ws")]
+    #[test]
+    fn converts_live_errors_to_and_from_deepgram_error() {
+        use super::LiveError;
+
+        let err = LiveError::from(DeepgramError::WebsocketClose {
+            code: 1011,
+            reason: "internal error".to_string(),
+        });
+        assert!(matches!(err, LiveError::WebsocketClose { code: 1011, .. }));
+        assert!(matches!(
+            DeepgramError::from(err),
+            DeepgramError::WebsocketClose { code: 1011, .. }
+        ));
+
+        let err = LiveError::from(DeepgramError::Timeout);
+        assert!(matches!(err, LiveError::Other(DeepgramError::Timeout)));
+        assert!(err.is_retryable());
+    }
+
+    #[cfg(feature = "listen-ws")]
+    #[test]
+    fn classifies_close_frames() {
+        use super::LiveError;
+        use crate::CloseReason;
+
+        let err = LiveError::from(DeepgramError::WebsocketClose {
+            code: 1011,
+            reason: String::from("NET-0001"),
+        });
+        assert_eq!(err.close_reason(), Some(CloseReason::ClientTimeout));
+        assert_eq!(LiveError::from(DeepgramError::Timeout).close_reason(), None);
+    }
+
+    #[cfg(feature = "listen-ws")]
+    #[test]
+    fn unparseable_response_keeps_payload() {
+        use super::parse_response;
+        use crate::common::stream_response::StreamResponse;
+
+        let payload = br#"{"type":"Results"}"#;
+
+        let err = parse_response::<StreamResponse>(payload, false).unwrap_err();
+        assert!(matches!(err, DeepgramError::JsonError(_)));
+
+        let err = parse_response::<StreamResponse>(payload, true).unwrap_err();
+        match err {
+            DeepgramError::UnparseableResponse { payload, .. } => {
+                assert_eq!(payload, r#"{"type":"Results"}"#)
+            }
+            err => panic!("unexpected error: {err:?}"),
+        }
+    }
+}
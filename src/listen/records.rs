@@ -0,0 +1,313 @@
+//! Incremental parsing of pre-recorded transcription responses.
+//!
+//! A pre-recorded response for hours of audio can be tens of megabytes of
+//! JSON. [`Transcription::prerecorded_records`] parses the response body as it
+//! arrives and yields the metadata, each channel, and each utterance as soon
+//! as it is complete, so only one record at a time needs to be held in memory.
+//!
+//! [`Transcription::prerecorded_records`]: crate::Transcription::prerecorded_records
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use bytes::Bytes;
+use futures::{stream::BoxStream, Stream, StreamExt};
+
+use crate::{
+    common::batch_response::{ChannelResult, ListenMetadata, Utterance},
+    json,
+    listen::ListenError,
+    Result,
+};
+
+/// One part of a pre-recorded transcription response.
+#[derive(Debug, PartialEq, Clone)]
+#[non_exhaustive]
+pub enum Record {
+    /// The response's `metadata`.
+    Metadata(ListenMetadata),
+
+    /// One entry of `results.channels`.
+    Channel(ChannelResult),
+
+    /// One entry of `results.utterances`, present when the
+    /// [Utterances feature](https://developers.deepgram.com/documentation/features/utterances/)
+    /// is set.
+    Utterance(Utterance),
+}
+
+/// The [`Record`]s of a pre-recorded transcription response, parsed as the body arrives.
+///
+/// Returned by [`Transcription::prerecorded_records`](crate::Transcription::prerecorded_records).
+/// Records are yielded in the order they appear in the response. Other parts
+/// of the results, such as summaries and topics, are skipped.
+pub struct RecordStream {
+    body: BoxStream<'static, reqwest::Result<Bytes>>,
+    scanner: RecordScanner,
+    ready: VecDeque<Result<Record>>,
+}
+
+impl fmt::Debug for RecordStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecordStream").finish_non_exhaustive()
+    }
+}
+
+impl RecordStream {
+    pub(crate) fn new(
+        body: impl Stream<Item = reqwest::Result<Bytes>> + Send + 'static,
+    ) -> RecordStream {
+        RecordStream {
+            body: body.boxed(),
+            scanner: RecordScanner::default(),
+            ready: VecDeque::new(),
+        }
+    }
+}
+
+impl Stream for RecordStream {
+    type Item = Result<Record, ListenError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if let Some(record) = self.ready.pop_front() {
+                return Poll::Ready(Some(record.map_err(Into::into)));
+            }
+
+            match self.body.poll_next_unpin(cx) {
+                Poll::Ready(Some(Ok(chunk))) => {
+                    let this = &mut *self;
+                    this.scanner.feed(&chunk, |target, raw| {
+                        this.ready.push_back(target.parse(raw));
+                    });
+                }
+                Poll::Ready(Some(Err(err))) => return Poll::Ready(Some(Err(err.into()))),
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// The parts of the response that are yielded as records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Target {
+    Metadata,
+    Channel,
+    Utterance,
+}
+
+impl Target {
+    fn parse(self, raw: &[u8]) -> Result<Record> {
+        Ok(match self {
+            Target::Metadata => Record::Metadata(json::from_slice(raw)?),
+            Target::Channel => Record::Channel(json::from_slice(raw)?),
+            Target::Utterance => Record::Utterance(json::from_slice(raw)?),
+        })
+    }
+}
+
+#[derive(Debug)]
+enum Frame {
+    Object {
+        /// The most recently read key.
+        key: Vec<u8>,
+        expecting_key: bool,
+    },
+    Array,
+}
+
+impl Frame {
+    fn key(&self) -> Option<&[u8]> {
+        match self {
+            Frame::Object { key, .. } => Some(key),
+            Frame::Array => None,
+        }
+    }
+}
+
+/// Tracks just enough of the JSON structure to find the records in it.
+///
+/// Bytes are only buffered while a record is being read; everything else is
+/// discarded as soon as it has been scanned.
+#[derive(Debug, Default)]
+struct RecordScanner {
+    stack: Vec<Frame>,
+    in_string: bool,
+    escaped: bool,
+    reading_key: bool,
+    key: Vec<u8>,
+    /// The record being read, its raw JSON so far, and the depth it started at.
+    capture: Option<(Target, Vec<u8>, usize)>,
+}
+
+impl RecordScanner {
+    fn feed(&mut self, chunk: &[u8], mut emit: impl FnMut(Target, &[u8])) {
+        let mut capture_start = 0;
+        for (i, &byte) in chunk.iter().enumerate() {
+            if self.in_string {
+                if self.escaped {
+                    self.escaped = false;
+                } else if byte == b'\\' {
+                    self.escaped = true;
+                } else if byte == b'"' {
+                    self.in_string = false;
+                    if self.reading_key {
+                        self.reading_key = false;
+                        if let Some(Frame::Object { key, .. }) = self.stack.last_mut() {
+                            std::mem::swap(key, &mut self.key);
+                        }
+                    }
+                    continue;
+                }
+                if self.reading_key {
+                    self.key.push(byte);
+                }
+                continue;
+            }
+
+            match byte {
+                b'"' => {
+                    self.in_string = true;
+                    if let Some(Frame::Object {
+                        expecting_key: true,
+                        ..
+                    }) = self.stack.last()
+                    {
+                        self.reading_key = true;
+                        self.key.clear();
+                    }
+                }
+                b'{' | b'[' => {
+                    if self.capture.is_none() && byte == b'{' {
+                        if let Some(target) = self.target() {
+                            self.capture = Some((target, Vec::new(), self.stack.len()));
+                            capture_start = i;
+                        }
+                    }
+                    self.stack.push(if byte == b'{' {
+                        Frame::Object {
+                            key: Vec::new(),
+                            expecting_key: true,
+                        }
+                    } else {
+                        Frame::Array
+                    });
+                }
+                b'}' | b']' => {
+                    self.stack.pop();
+                    if matches!(&self.capture, Some((_, _, depth)) if *depth == self.stack.len()) {
+                        let (target, mut raw, _) = self.capture.take().expect("checked above");
+                        raw.extend_from_slice(&chunk[capture_start..=i]);
+                        emit(target, &raw);
+                    }
+                }
+                b':' => {
+                    if let Some(Frame::Object { expecting_key, .. }) = self.stack.last_mut() {
+                        *expecting_key = false;
+                    }
+                }
+                b',' => {
+                    if let Some(Frame::Object { expecting_key, .. }) = self.stack.last_mut() {
+                        *expecting_key = true;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if let Some((_, raw, _)) = &mut self.capture {
+            raw.extend_from_slice(&chunk[capture_start..]);
+        }
+    }
+
+    /// The record that an object starting at the current position would be.
+    fn target(&self) -> Option<Target> {
+        match self.stack.as_slice() {
+            [root] if root.key() == Some(b"metadata") => Some(Target::Metadata),
+            [root, results, Frame::Array] if root.key() == Some(b"results") => {
+                match results.key() {
+                    Some(b"channels") => Some(Target::Channel),
+                    Some(b"utterances") => Some(Target::Utterance),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{stream, StreamExt};
+
+    use super::*;
+    use crate::common::{
+        batch_response::Response,
+        fixtures::{ResponseBuilder, WordBuilder},
+    };
+
+    fn response() -> Response {
+        ResponseBuilder::new()
+            .channel([
+                WordBuilder::new("hello", 0.0, 0.4).build(),
+                WordBuilder::new("\"world\"", 0.5, 0.9).build(),
+            ])
+            .channel([WordBuilder::new("{again}", 1.0, 1.4).build()])
+            .utterance(0, [WordBuilder::new("hello", 0.0, 0.4).build()])
+            .utterance(1, [WordBuilder::new("[again]", 1.0, 1.4).build()])
+            .build()
+    }
+
+    async fn records(json: &[u8], chunk_size: usize) -> Vec<Record> {
+        let chunks: Vec<reqwest::Result<Bytes>> = json
+            .chunks(chunk_size)
+            .map(|chunk| Ok(Bytes::copy_from_slice(chunk)))
+            .collect();
+        RecordStream::new(stream::iter(chunks))
+            .map(|record| record.unwrap())
+            .collect()
+            .await
+    }
+
+    #[tokio::test]
+    async fn yields_records_in_order() {
+        let response = response();
+        let json = serde_json::to_vec_pretty(&response).unwrap();
+
+        let mut expected = vec![Record::Metadata(response.metadata.clone())];
+        expected.extend(
+            response
+                .results
+                .channels
+                .iter()
+                .cloned()
+                .map(Record::Channel),
+        );
+        expected.extend(
+            response
+                .results
+                .utterances
+                .clone()
+                .unwrap()
+                .into_iter()
+                .map(Record::Utterance),
+        );
+
+        for chunk_size in [1, 7, json.len()] {
+            assert_eq!(records(&json, chunk_size).await, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn reports_malformed_records() {
+        let json = br#"{"metadata": {"request_id": 1}, "results": {"channels": []}}"#;
+        let mut stream = RecordStream::new(stream::iter([Ok(Bytes::from_static(json))]));
+        assert!(stream.next().await.unwrap().is_err());
+        assert!(stream.next().await.is_none());
+    }
+}
@@ -4,17 +4,26 @@
 //!
 //! [api]: https://developers.deepgram.com/api-reference/#transcription-prerecorded
 
+use std::{path::Path, time::Duration};
+
+use futures::{stream, Stream, StreamExt};
 use reqwest::RequestBuilder;
 use url::Url;
 
-use crate::common::audio_source::AudioSource;
-use crate::{send_and_translate_response, Transcription};
+use crate::common::audio_source::{AudioSource, ReplayableAudioSource};
+use crate::listen::{records::RecordStream, ListenError};
+use crate::{
+    check_status, json, send_and_check_status, send_and_translate_response, Transcription,
+};
 
-use crate::common::batch_response::{CallbackResponse, Response};
-use crate::common::options::{Options, SerializableOptions};
+use crate::common::batch_response::{CallbackResponse, RawResponse, Response};
+use crate::common::options::{Callback, Options, SerializableOptions};
 
 static DEEPGRAM_API_URL_LISTEN: &str = "v1/listen";
 
+/// How many times [`Transcription::prerecorded_batch`] tries each source.
+const BATCH_ATTEMPTS: u32 = 3;
+
 impl Transcription<'_> {
     /// Sends a request to Deepgram to transcribe pre-recorded audio.
     /// If you wish to use the Callback feature, you should use [`Transcription::prerecorded_callback`] instead.
@@ -64,10 +73,363 @@ impl Transcription<'_> {
         &self,
         source: AudioSource,
         options: &Options,
-    ) -> crate::Result<Response> {
+    ) -> Result<Response, ListenError> {
+        let request_builder = self.make_prerecorded_request_builder(source, options);
+
+        Ok(send_and_translate_response(self.0, request_builder).await?)
+    }
+
+    /// Transcribes the audio at `url`, returning the transcript along with
+    /// the full response.
+    ///
+    /// The transcript is that of the first alternative of the first channel,
+    /// or empty if the response has none. This is shorthand for calling
+    /// [`Transcription::prerecorded`] with [`AudioSource::from_url`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use deepgram::{common::options::Options, Deepgram, DeepgramError};
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), DeepgramError> {
+    /// let dg_client = Deepgram::from_env()?;
+    ///
+    /// let (transcript, _response) = dg_client
+    ///     .transcription()
+    ///     .transcribe_url(
+    ///         "https://static.deepgram.com/examples/Bueller-Life-moves-pretty-fast.wav",
+    ///         &Options::default(),
+    ///     )
+    ///     .await?;
+    ///
+    /// println!("{transcript}");
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn transcribe_url(
+        &self,
+        url: impl Into<String>,
+        options: &Options,
+    ) -> Result<(String, Response), ListenError> {
+        let response = self
+            .prerecorded(AudioSource::from_url(url), options)
+            .await?;
+
+        Ok((first_transcript(&response), response))
+    }
+
+    /// Transcribes the audio file at `path`, returning the transcript along
+    /// with the full response.
+    ///
+    /// The transcript is that of the first alternative of the first channel,
+    /// or empty if the response has none. This is shorthand for calling
+    /// [`Transcription::prerecorded`] with [`AudioSource::from_path`], so the
+    /// same formats are supported.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use deepgram::{common::options::Options, Deepgram, DeepgramError};
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), DeepgramError> {
+    /// let dg_client = Deepgram::from_env()?;
+    ///
+    /// let (transcript, _response) = dg_client
+    ///     .transcription()
+    ///     .transcribe_file("examples/audio/bueller.wav", &Options::default())
+    ///     .await?;
+    ///
+    /// println!("{transcript}");
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn transcribe_file(
+        &self,
+        path: impl AsRef<Path>,
+        options: &Options,
+    ) -> Result<(String, Response), ListenError> {
+        let source = AudioSource::from_path(path).await?;
+        let response = self.prerecorded(source, options).await?;
+
+        Ok((first_transcript(&response), response))
+    }
+
+    /// Same as [`Transcription::prerecorded`], but fails with [`ListenError::Timeout`]
+    /// if the transcription takes longer than `timeout`.
+    ///
+    /// This overrides the client's [`DeepgramBuilder::timeout`](crate::DeepgramBuilder::timeout)
+    /// for this request only.
+    pub async fn prerecorded_with_timeout(
+        &self,
+        source: AudioSource,
+        options: &Options,
+        timeout: Duration,
+    ) -> Result<Response, ListenError> {
+        let request_builder = self
+            .make_prerecorded_request_builder(source, options)
+            .timeout(timeout);
+
+        Ok(send_and_translate_response(self.0, request_builder).await?)
+    }
+
+    /// Same as [`Transcription::prerecorded`], but returns the response body
+    /// as untyped JSON along with its status and request ID.
+    ///
+    /// This gives access to response fields this crate does not model yet.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use deepgram::{
+    /// #     common::{audio_source::AudioSource, options::Options},
+    /// #     Deepgram, DeepgramError,
+    /// # };
+    /// #
+    /// # static AUDIO_URL: &str = "https://static.deepgram.com/examples/Bueller-Life-moves-pretty-fast.wav";
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), DeepgramError> {
+    /// let dg_client = Deepgram::new(std::env::var("DEEPGRAM_API_KEY").unwrap_or_default())?;
+    ///
+    /// let source = AudioSource::from_url(AUDIO_URL);
+    /// let options = Options::builder().build();
+    ///
+    /// let response = dg_client
+    ///     .transcription()
+    ///     .prerecorded_raw(source, &options)
+    ///     .await?;
+    ///
+    /// println!("{:?}", response.body["metadata"]["models"]);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn prerecorded_raw(
+        &self,
+        source: AudioSource,
+        options: &Options,
+    ) -> Result<RawResponse, ListenError> {
         let request_builder = self.make_prerecorded_request_builder(source, options);
+        let response = send_and_check_status(self.0, request_builder).await?;
+
+        let status = response.status();
+        let header_request_id = response
+            .headers()
+            .get("dg-request-id")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok());
+        let body: serde_json::Value = json::from_slice(&response.bytes().await?)?;
+        let request_id = header_request_id.or_else(|| {
+            body["metadata"]["request_id"]
+                .as_str()
+                .and_then(|value| value.parse().ok())
+        });
 
-        send_and_translate_response(request_builder).await
+        Ok(RawResponse {
+            status,
+            request_id,
+            body,
+        })
+    }
+
+    /// Sends a request to Deepgram to transcribe pre-recorded audio, and
+    /// parses the response incrementally as it arrives.
+    ///
+    /// Instead of buffering the whole response like [`Transcription::prerecorded`],
+    /// this yields the metadata, each channel, and each utterance as a
+    /// [`Record`](crate::listen::records::Record) once it has been received,
+    /// which bounds memory use for very long recordings.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::env;
+    /// #
+    /// # use deepgram::{
+    /// #     common::{
+    /// #         audio_source::AudioSource,
+    /// #         options::{Language, Options},
+    /// #     },
+    /// #     listen::records::Record,
+    /// #     Deepgram, DeepgramError,
+    /// # };
+    /// # use futures::StreamExt;
+    /// #
+    /// # static AUDIO_URL: &str = "https://static.deepgram.com/examples/Bueller-Life-moves-pretty-fast.wav";
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), DeepgramError> {
+    /// # let deepgram_api_key =
+    /// #     env::var("DEEPGRAM_API_KEY").expect("DEEPGRAM_API_KEY environmental variable");
+    /// #
+    /// let dg_client = Deepgram::new(&deepgram_api_key)?;
+    ///
+    /// let source = AudioSource::from_url(AUDIO_URL);
+    ///
+    /// let options = Options::builder().utterances(true).build();
+    ///
+    /// let mut records = dg_client
+    ///     .transcription()
+    ///     .prerecorded_records(source, &options)
+    ///     .await?;
+    ///
+    /// while let Some(record) = records.next().await {
+    ///     if let Record::Utterance(utterance) = record? {
+    ///         println!("{}", utterance.transcript);
+    ///     }
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn prerecorded_records(
+        &self,
+        source: AudioSource,
+        options: &Options,
+    ) -> Result<RecordStream, ListenError> {
+        let request_builder = self.make_prerecorded_request_builder(source, options);
+        let response = send_and_check_status(self.0, request_builder).await?;
+
+        Ok(RecordStream::new(response.bytes_stream()))
+    }
+
+    /// Same as [`Transcription::prerecorded`], but the audio can be uploaded
+    /// again if the request is retried.
+    ///
+    /// With [`DeepgramBuilder::retry_rate_limited`](crate::DeepgramBuilder::retry_rate_limited),
+    /// a rate limited request is retried with a fresh upload of `source`, even
+    /// when it is read from a file.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use deepgram::{
+    /// #     common::{audio_source::ReplayableAudioSource, options::Options},
+    /// #     Deepgram, DeepgramError,
+    /// # };
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), DeepgramError> {
+    /// let dg_client = Deepgram::builder()
+    ///     .api_key(std::env::var("DEEPGRAM_API_KEY").unwrap_or_default())
+    ///     .retry_rate_limited(3)
+    ///     .build()?;
+    ///
+    /// let source = ReplayableAudioSource::from_path("examples/audio/bueller.wav");
+    /// let options = Options::builder().punctuate(true).build();
+    ///
+    /// let response = dg_client
+    ///     .transcription()
+    ///     .prerecorded_replayable(&source, &options)
+    ///     .await?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn prerecorded_replayable(
+        &self,
+        source: &ReplayableAudioSource,
+        options: &Options,
+    ) -> Result<Response, ListenError> {
+        let request_builder =
+            self.make_prerecorded_request_builder(source.audio_source().await?, options);
+        let response = self
+            .0
+            .client
+            .send_replayable(request_builder, || async {
+                let source = source.audio_source().await?;
+                Ok(Some(self.make_prerecorded_request_builder(source, options)))
+            })
+            .await?;
+        let response = check_status(response).await?;
+
+        Ok(json::from_slice(&response.bytes().await?)?)
+    }
+
+    /// Transcribe many pre-recorded sources, at most `concurrency` at a time.
+    ///
+    /// Each result is yielded as soon as it is ready, together with the index
+    /// of its source, so results can arrive out of order. Requests that fail
+    /// with an error that [may succeed on retry](ListenError::is_retryable),
+    /// such as a timeout or server error, are retried up to twice with
+    /// exponential backoff, or after the delay a rate limited response asks
+    /// for. Rate limited requests are left to the client when
+    /// [`DeepgramBuilder::retry_rate_limited`](crate::DeepgramBuilder::retry_rate_limited)
+    /// is set, so they are not retried twice over.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use deepgram::{
+    /// #     common::{audio_source::ReplayableAudioSource, options::Options},
+    /// #     Deepgram, DeepgramError,
+    /// # };
+    /// # use futures::StreamExt;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), DeepgramError> {
+    /// let dg_client = Deepgram::from_env()?;
+    ///
+    /// let sources = ["call-1.wav", "call-2.wav", "call-3.wav"].map(ReplayableAudioSource::from_path);
+    /// let options = Options::builder().punctuate(true).build();
+    ///
+    /// let transcription = dg_client.transcription();
+    /// let mut results = transcription.prerecorded_batch(sources, &options, 2);
+    /// while let Some((index, result)) = results.next().await {
+    ///     match result {
+    ///         Ok(response) => println!("{index}: {:?}", response.results.channels),
+    ///         Err(err) => eprintln!("{index} failed: {err}"),
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn prerecorded_batch<'b, S>(
+        &'b self,
+        sources: S,
+        options: &'b Options,
+        concurrency: usize,
+    ) -> impl Stream<Item = (usize, Result<Response, ListenError>)> + 'b
+    where
+        S: IntoIterator<Item = ReplayableAudioSource>,
+        S::IntoIter: 'b,
+    {
+        stream::iter(sources.into_iter().enumerate())
+            .map(move |(index, source)| async move {
+                (index, self.prerecorded_with_retries(&source, options).await)
+            })
+            .buffer_unordered(concurrency.max(1))
+    }
+
+    /// [`Transcription::prerecorded_replayable`], retrying errors that may be temporary.
+    async fn prerecorded_with_retries(
+        &self,
+        source: &ReplayableAudioSource,
+        options: &Options,
+    ) -> Result<Response, ListenError> {
+        let mut attempt = 0;
+        loop {
+            let err = match self.prerecorded_replayable(source, options).await {
+                Err(err) if err.is_retryable() && attempt + 1 < BATCH_ATTEMPTS => err,
+                result => return result,
+            };
+            let delay = match err {
+                ListenError::RateLimited { .. } if self.0.client.retries_rate_limited() => {
+                    return Err(err)
+                }
+                ListenError::RateLimited {
+                    retry_after: Some(retry_after),
+                    ..
+                } => retry_after,
+                _ => Duration::from_secs(1 << attempt),
+            };
+            self.0.client.sleep(delay).await;
+            attempt += 1;
+        }
     }
 
     /// Sends a request to Deepgram to transcribe pre-recorded audio using the Callback feature.
@@ -85,7 +447,7 @@ impl Transcription<'_> {
     /// # use deepgram::{
     /// #     common::{
     /// #         audio_source::AudioSource,
-    /// #         options::{Language, Options},
+    /// #         options::{Callback, Language, Options},
     /// #     },
     /// #     Deepgram, DeepgramError,
     /// # };
@@ -109,9 +471,11 @@ impl Transcription<'_> {
     /// # let callback_url =
     /// #     env::var("DEEPGRAM_CALLBACK_URL").expect("DEEPGRAM_CALLBACK_URL environmental variable");
     /// #
+    /// let callback = Callback::new(&callback_url)?;
+    ///
     /// let response = dg_client
     ///     .transcription()
-    ///     .prerecorded_callback(source, &options, &callback_url)
+    ///     .prerecorded_callback(source, &options, &callback)
     ///     .await?;
     /// #
     /// # Ok(())
@@ -121,12 +485,12 @@ impl Transcription<'_> {
         &self,
         source: AudioSource,
         options: &Options,
-        callback: &str,
-    ) -> crate::Result<CallbackResponse> {
+        callback: &Callback,
+    ) -> Result<CallbackResponse, ListenError> {
         let request_builder =
             self.make_prerecorded_callback_request_builder(source, options, callback);
 
-        send_and_translate_response(request_builder).await
+        Ok(send_and_translate_response(self.0, request_builder).await?)
     }
 
     /// Makes a [`reqwest::RequestBuilder`] without actually sending the request.
@@ -192,7 +556,7 @@ impl Transcription<'_> {
             .0
             .client
             .post(self.listen_url())
-            .query(&SerializableOptions(options));
+            .query(&SerializableOptions(&self.0.options_with_defaults(options)));
 
         source.fill_body(request_builder)
     }
@@ -212,7 +576,7 @@ impl Transcription<'_> {
     /// # use deepgram::{
     /// #     common::{
     /// #         audio_source::AudioSource,
-    /// #         options::{Language, Options},
+    /// #         options::{Callback, Language, Options},
     /// #         batch_response::{Response, CallbackResponse},
     /// #     },
     /// #     Deepgram, DeepgramError,
@@ -238,9 +602,11 @@ impl Transcription<'_> {
     /// # let callback_url =
     /// #     env::var("DEEPGRAM_CALLBACK_URL").expect("DEEPGRAM_CALLBACK_URL environmental variable");
     /// #
+    /// # let callback = Callback::new(&callback_url)?;
+    /// #
     /// let request_builder = dg_client
     ///     .transcription()
-    ///     .make_prerecorded_callback_request_builder(source, &options, &callback_url);
+    ///     .make_prerecorded_callback_request_builder(source, &options, &callback);
     ///
     /// // Customize the RequestBuilder here
     /// let customized_request_builder = request_builder
@@ -258,20 +624,118 @@ impl Transcription<'_> {
         &self,
         source: AudioSource,
         options: &Options,
-        callback: &str,
+        callback: &Callback,
     ) -> RequestBuilder {
-        self.make_prerecorded_request_builder(source, options)
-            .query(&[("callback", callback)])
+        let request_builder = match callback.callback_method() {
+            Some(method) => {
+                self.make_prerecorded_request_builder(source, &options.with_callback_method(method))
+            }
+            None => self.make_prerecorded_request_builder(source, options),
+        };
+
+        request_builder.query(&[("callback", callback.url().as_str())])
+    }
+
+    /// Open a connection to the Deepgram API ahead of the first request.
+    ///
+    /// The client keeps connections open and reuses them, so calling this
+    /// before [`Transcription::prerecorded`] moves the cost of DNS resolution
+    /// and the TCP and TLS handshakes out of the first request. Idle
+    /// connections are eventually closed, so call it shortly before the
+    /// connection is needed.
+    ///
+    #[cfg_attr(
+        feature = "listen-ws",
+        doc = "For streaming requests, use [`WebsocketBuilder::prewarm`](crate::listen::websocket::WebsocketBuilder::prewarm) instead."
+    )]
+    #[cfg_attr(
+        not(feature = "listen-ws"),
+        doc = "For streaming requests, use `WebsocketBuilder::prewarm` instead."
+    )]
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ListenError::ReqwestError`]
+    /// if the server cannot be reached. The response itself is ignored.
+    pub async fn prewarm(&self) -> Result<(), ListenError> {
+        self.0
+            .client
+            .send(self.0.client.head(self.listen_url()))
+            .await?;
+        Ok(())
     }
 
     fn listen_url(&self) -> Url {
-        self.0.base_url.join(DEEPGRAM_API_URL_LISTEN).unwrap()
+        self.0
+            .listen_base_url
+            .join(DEEPGRAM_API_URL_LISTEN)
+            .unwrap()
     }
 }
 
+/// The transcript of the first alternative of the first channel.
+fn first_transcript(response: &Response) -> String {
+    response
+        .results
+        .channels
+        .first()
+        .and_then(|channel| channel.alternatives.first())
+        .map(|alternative| alternative.transcript.clone())
+        .unwrap_or_default()
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::Deepgram;
+    use crate::{
+        common::{
+            audio_source::AudioSource,
+            options::{Callback, CallbackMethod, Options},
+        },
+        Deepgram,
+    };
+
+    use super::first_transcript;
+
+    #[tokio::test]
+    async fn batch_honors_client_rate_limit_retries() {
+        use futures::StreamExt;
+
+        use crate::{
+            client::tests::{serve, OK, RATE_LIMITED},
+            common::audio_source::ReplayableAudioSource,
+            listen::ListenError,
+        };
+
+        // Without client retries the batch retries, waiting as long as `Retry-After` asks.
+        let (url, server) = serve(&[RATE_LIMITED, RATE_LIMITED, OK]).await;
+        let dg = Deepgram::with_base_url(url.as_str()).unwrap();
+        let transcription = dg.transcription();
+        let source = ReplayableAudioSource::from_url("https://example.com/audio.wav");
+        let results: Vec<_> = transcription
+            .prerecorded_batch([source.clone()], &Options::builder().build(), 1)
+            .collect()
+            .await;
+        assert!(matches!(results[..], [(0, Err(ListenError::JsonError(_)))]));
+        assert_eq!(server.await.unwrap().len(), 3);
+
+        // With client retries, a rate limit that outlasts them is not retried again.
+        let (url, server) = serve(&[RATE_LIMITED, RATE_LIMITED]).await;
+        let dg = Deepgram::builder()
+            .base_url(url.parse().unwrap())
+            .retry_rate_limited(1)
+            .build()
+            .unwrap();
+        let transcription = dg.transcription();
+        let results: Vec<_> = transcription
+            .prerecorded_batch([source], &Options::builder().build(), 1)
+            .collect()
+            .await;
+        assert!(matches!(
+            results[..],
+            [(0, Err(ListenError::RateLimited { .. }))]
+        ));
+        assert_eq!(server.await.unwrap().len(), 2);
+    }
 
     #[test]
     fn listen_url() {
@@ -290,4 +754,45 @@ mod tests {
             "http://localhost:8888/abc/v1/listen"
         );
     }
+
+    #[test]
+    fn callback_method_overrides_options() {
+        let dg = Deepgram::new("token").unwrap();
+        let options = Options::builder()
+            .callback_method(CallbackMethod::POST)
+            .build();
+        let callback = Callback::new("https://example.com/hook")
+            .unwrap()
+            .method(CallbackMethod::PUT);
+
+        let request = dg
+            .transcription()
+            .make_prerecorded_callback_request_builder(
+                AudioSource::from_url("https://example.com/audio.wav"),
+                &options,
+                &callback,
+            )
+            .build()
+            .unwrap();
+        assert_eq!(
+            request.url().query(),
+            Some("callback_method=put&callback=https%3A%2F%2Fexample.com%2Fhook")
+        );
+    }
+
+    #[test]
+    fn first_transcript_of_response() {
+        use crate::common::fixtures::{ResponseBuilder, WordBuilder};
+
+        let response = ResponseBuilder::new()
+            .channel([
+                WordBuilder::new("hello", 0.0, 0.5).build(),
+                WordBuilder::new("world", 0.5, 1.0).build(),
+            ])
+            .build();
+        assert_eq!(first_transcript(&response), "hello world");
+
+        let response = ResponseBuilder::new().build();
+        assert_eq!(first_transcript(&response), "");
+    }
 }
@@ -0,0 +1,358 @@
+//! Many live transcription sessions sharing one configuration.
+//!
+//! Applications such as telephony gateways transcribe hundreds of calls at
+//! once. A [`SessionManager`] starts a live connection for each of them from
+//! the same [`WebsocketBuilder`], keeps the number of open connections under
+//! a limit, and spreads out the reconnects of sessions that drop together.
+//! Each session is identified by a [`SessionId`], which can be used to
+//! control it from elsewhere in the application.
+
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use bytes::Bytes;
+use futures::{channel::mpsc, lock::Mutex as AsyncMutex, Stream, StreamExt};
+use uuid::Uuid;
+
+use crate::{
+    common::stream_response::StreamResponse,
+    listen::{
+        websocket::{ConnectionInfo, StreamControl, TranscriptionStream, WebsocketBuilder},
+        LiveError,
+    },
+};
+
+/// Identifies a session started by a [`SessionManager`].
+///
+/// IDs are assigned in order, starting from zero, and are never reused by the
+/// same manager.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SessionId(u64);
+
+impl fmt::Display for SessionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Starts live transcription sessions from a shared configuration.
+///
+/// ```no_run
+/// # use bytes::Bytes;
+/// # use deepgram::{
+/// #     common::options::Encoding,
+/// #     listen::{sessions::SessionManager, websocket::ReconnectPolicy},
+/// #     Deepgram, DeepgramError,
+/// # };
+/// # use futures::StreamExt;
+/// # use std::time::Duration;
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), DeepgramError> {
+/// let dg = Deepgram::new(std::env::var("DEEPGRAM_API_KEY").unwrap_or_default())?;
+/// let transcription = dg.transcription();
+/// let builder = transcription
+///     .stream_request()
+///     .encoding(Encoding::Mulaw)
+///     .sample_rate(8000)
+///     .reconnect(ReconnectPolicy::new(3));
+/// let manager = SessionManager::new(&builder, 200).reconnect_spread(Duration::from_secs(5));
+///
+/// // For each incoming call:
+/// # let call_audio = futures::stream::empty::<Result<Bytes, std::io::Error>>();
+/// let mut session = manager.start(call_audio).await?;
+/// println!("call is session {}", session.id());
+/// while let Some(result) = session.next().await {
+///     println!("{:?}", result?);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SessionManager {
+    builder: WebsocketBuilder<'static>,
+    max_sessions: usize,
+    reconnect_spread: Duration,
+    permits: Arc<Permits>,
+    next_id: Arc<AtomicU64>,
+    sessions: Arc<Mutex<HashMap<SessionId, StreamControl>>>,
+}
+
+impl SessionManager {
+    /// Start sessions configured by `builder`, at most `max_sessions` at a time.
+    ///
+    /// Sessions are reconnected if `builder` has a
+    /// [reconnect policy](WebsocketBuilder::reconnect).
+    pub fn new(builder: &WebsocketBuilder<'_>, max_sessions: usize) -> Self {
+        let max_sessions = max_sessions.max(1);
+
+        SessionManager {
+            builder: builder.to_owned_builder(),
+            max_sessions,
+            reconnect_spread: Duration::ZERO,
+            permits: Arc::new(Permits::new(max_sessions)),
+            next_id: Arc::new(AtomicU64::new(0)),
+            sessions: Arc::default(),
+        }
+    }
+
+    /// Spread the first reconnect attempts of sessions that drop at the same
+    /// time over `spread`, instead of reconnecting them all at once.
+    ///
+    /// Each session's first attempt is delayed by a different share of
+    /// `spread`, on top of the backoff of the builder's reconnect policy.
+    /// See [`ReconnectPolicy::stagger`](crate::listen::websocket::ReconnectPolicy::stagger).
+    pub fn reconnect_spread(mut self, spread: Duration) -> Self {
+        self.reconnect_spread = spread;
+
+        self
+    }
+
+    /// Start a session that streams audio from `stream`, like
+    /// [`WebsocketBuilder::stream`].
+    ///
+    /// If `max_sessions` sessions are already running, this waits until one
+    /// of them is dropped.
+    pub async fn start<S, E>(&self, stream: S) -> Result<Session, LiveError>
+    where
+        S: Stream<Item = Result<Bytes, E>> + Send + Unpin + 'static,
+        E: Error + Send + Sync + 'static,
+    {
+        let permit = self.permits.acquire().await;
+        let id = SessionId(self.next_id.fetch_add(1, Ordering::Relaxed));
+
+        let mut builder = self.builder.clone();
+        if let Some(policy) = builder.reconnect_policy() {
+            let policy = policy.clone().stagger(self.stagger(id));
+            builder = builder.reconnect(policy);
+        }
+        let stream = builder.stream(stream).await?;
+
+        self.sessions.lock().unwrap().insert(id, stream.control());
+
+        Ok(Session {
+            id,
+            stream,
+            sessions: Arc::clone(&self.sessions),
+            _permit: permit,
+        })
+    }
+
+    /// The number of sessions that are running.
+    pub fn active(&self) -> usize {
+        self.sessions.lock().unwrap().len()
+    }
+
+    /// The IDs of the sessions that are running, in the order they were started.
+    pub fn session_ids(&self) -> Vec<SessionId> {
+        let mut ids: Vec<_> = self.sessions.lock().unwrap().keys().copied().collect();
+        ids.sort();
+        ids
+    }
+
+    /// A handle for sending control messages to the session with ID `id`, if
+    /// it is still running.
+    pub fn control(&self, id: SessionId) -> Option<StreamControl> {
+        self.sessions.lock().unwrap().get(&id).cloned()
+    }
+
+    /// Close the streams of all running sessions.
+    ///
+    /// Responses to the audio already sent are still received by each
+    /// [`Session`].
+    pub fn close_all(&self) {
+        for control in self.sessions.lock().unwrap().values() {
+            // Sessions whose streams have already ended have nothing to close.
+            let _ = control.close_stream();
+        }
+    }
+
+    /// How long the first reconnect of session `id` is delayed.
+    fn stagger(&self, id: SessionId) -> Duration {
+        let slot = id.0 % self.max_sessions as u64;
+        self.reconnect_spread
+            .mul_f64(slot as f64 / self.max_sessions as f64)
+    }
+}
+
+/// The places under a [`SessionManager`]'s limit, handed out in turn to the
+/// sessions waiting to start.
+#[derive(Debug)]
+struct Permits {
+    free: AsyncMutex<mpsc::UnboundedReceiver<()>>,
+    returned: mpsc::UnboundedSender<()>,
+}
+
+impl Permits {
+    fn new(count: usize) -> Self {
+        let (returned, free) = mpsc::unbounded();
+        for _ in 0..count {
+            returned.unbounded_send(()).expect("the receiver is alive");
+        }
+
+        Permits {
+            free: AsyncMutex::new(free),
+            returned,
+        }
+    }
+
+    /// Wait for a free place.
+    async fn acquire(self: &Arc<Self>) -> Permit {
+        self.free
+            .lock()
+            .await
+            .next()
+            .await
+            .expect("the sender is never dropped");
+        Permit(Arc::clone(self))
+    }
+}
+
+/// A place under a [`SessionManager`]'s limit, freed when dropped.
+#[derive(Debug)]
+struct Permit(Arc<Permits>);
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        let _ = self.0.returned.unbounded_send(());
+    }
+}
+
+/// A live transcription session started by a [`SessionManager`].
+///
+/// Yields the session's responses like a [`TranscriptionStream`]. Dropping
+/// the session frees its place under the manager's limit.
+#[derive(Debug)]
+pub struct Session {
+    id: SessionId,
+    stream: TranscriptionStream,
+    sessions: Arc<Mutex<HashMap<SessionId, StreamControl>>>,
+    _permit: Permit,
+}
+
+impl Session {
+    /// The ID the manager assigned to this session.
+    pub fn id(&self) -> SessionId {
+        self.id
+    }
+
+    /// Returns the Deepgram request ID of the session's first connection.
+    ///
+    /// Reconnects are announced with a [`StreamResponse::Reconnected`]
+    /// carrying the new request ID.
+    pub fn request_id(&self) -> Uuid {
+        self.stream.request_id()
+    }
+
+    /// Details of the session's first connection from the websocket upgrade
+    /// response.
+    pub fn connection_info(&self) -> &ConnectionInfo {
+        self.stream.connection_info()
+    }
+
+    /// A handle for sending control messages while the session is being
+    /// polled elsewhere.
+    pub fn control(&self) -> StreamControl {
+        self.stream.control()
+    }
+}
+
+impl Stream for Session {
+    type Item = Result<StreamResponse, LiveError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.stream).poll_next(cx)
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        if let Ok(mut sessions) = self.sessions.lock() {
+            sessions.remove(&self.id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bytes::Bytes;
+    use futures::{FutureExt, StreamExt};
+    use tungstenite::Message;
+
+    use super::{SessionId, SessionManager};
+    use crate::listen::websocket::{
+        tests::{fake_socket, FakeTransport},
+        ReconnectPolicy,
+    };
+
+    #[tokio::test]
+    async fn limits_concurrent_sessions() {
+        let mut sent = Vec::new();
+        let mut incoming = Vec::new();
+        let transport = FakeTransport::new((0..3).map(|_| {
+            let (socket, sent_rx, incoming_tx) = fake_socket();
+            sent.push(sent_rx);
+            incoming.push(incoming_tx);
+            socket
+        }));
+
+        let dg = crate::Deepgram::new("token").unwrap();
+        let transcription = dg.transcription();
+        let builder = transcription.stream_request().transport(transport);
+        let manager = SessionManager::new(&builder, 2);
+
+        let audio = || futures::stream::pending::<Result<Bytes, std::io::Error>>();
+        let first = manager.start(audio()).await.unwrap();
+        let second = manager.start(audio()).await.unwrap();
+        assert_eq!((first.id(), second.id()), (SessionId(0), SessionId(1)));
+        assert_eq!(manager.session_ids(), [SessionId(0), SessionId(1)]);
+
+        // A third session waits for a place.
+        let mut third = Box::pin(manager.start(audio()));
+        assert!((&mut third).now_or_never().is_none());
+
+        manager.control(SessionId(1)).unwrap().finalize().unwrap();
+        assert_eq!(
+            sent[1].next().await.unwrap(),
+            Message::Text(r#"{"type":"Finalize"}"#.into())
+        );
+
+        drop(first);
+        let third = third.await.unwrap();
+        assert_eq!(third.id(), SessionId(2));
+        assert_eq!(manager.active(), 2);
+        assert!(manager.control(SessionId(0)).is_none());
+
+        manager.close_all();
+        for sent in &mut sent[1..] {
+            assert_eq!(
+                sent.next().await.unwrap(),
+                Message::Text(r#"{"type":"CloseStream"}"#.into())
+            );
+        }
+    }
+
+    #[test]
+    fn spreads_reconnects() {
+        let dg = crate::Deepgram::new("token").unwrap();
+        let transcription = dg.transcription();
+        let builder = transcription
+            .stream_request()
+            .reconnect(ReconnectPolicy::new(3));
+        let manager = SessionManager::new(&builder, 4).reconnect_spread(Duration::from_secs(4));
+
+        let staggers: Vec<_> = (0..5).map(|id| manager.stagger(SessionId(id))).collect();
+        assert_eq!(staggers, [0, 1, 2, 3, 0].map(Duration::from_secs),);
+    }
+}
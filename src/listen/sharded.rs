@@ -0,0 +1,456 @@
+//! Sharded transcription of long recordings.
+//!
+//! A single pre-recorded request for a multi-hour recording is processed from
+//! start to finish, so the wall-clock time grows with the length of the audio.
+//! [`Transcription::prerecorded_sharded`] decodes the file locally, splits it
+//! into overlapping segments, transcribes the segments concurrently, and
+//! merges the results back into one [`Response`] with timestamps relative to
+//! the start of the file.
+//!
+//! Only available with the `decode` feature.
+//!
+//! [`Transcription::prerecorded_sharded`]: crate::Transcription::prerecorded_sharded
+
+use std::{path::Path, task::Poll, time::Duration};
+
+use bytes::{BufMut, Bytes, BytesMut};
+use futures::{stream, Stream, StreamExt, TryStreamExt};
+
+use crate::{
+    audio::decode::DecodedFile,
+    common::{
+        audio_source::AudioSource,
+        batch_response::{
+            average_confidence, join_words, ChannelResult, Response, ResultAlternative, Utterance,
+            Word,
+        },
+        options::Options,
+    },
+    listen::{
+        wav_header::{WavFormat, WAVE_FORMAT_PCM},
+        ListenError,
+    },
+    Transcription,
+};
+
+/// How [`Transcription::prerecorded_sharded`] splits a recording.
+///
+/// [`Transcription::prerecorded_sharded`]: crate::Transcription::prerecorded_sharded
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShardOptions {
+    segment: Duration,
+    overlap: Duration,
+    concurrency: usize,
+}
+
+impl ShardOptions {
+    /// Ten minute segments with five seconds of overlap, four requests at a time.
+    pub fn new() -> Self {
+        Self {
+            segment: Duration::from_secs(600),
+            overlap: Duration::from_secs(5),
+            concurrency: 4,
+        }
+    }
+
+    /// Set the length of each segment, not counting the overlap.
+    pub fn segment(mut self, segment: Duration) -> Self {
+        self.segment = segment;
+
+        self
+    }
+
+    /// Set how much audio each segment shares with the next one.
+    ///
+    /// Words near a segment boundary are transcribed by both segments and
+    /// taken from whichever one heard more context around them, so this
+    /// should be longer than the longest word you expect.
+    pub fn overlap(mut self, overlap: Duration) -> Self {
+        self.overlap = overlap;
+
+        self
+    }
+
+    /// Set the maximum number of segments being transcribed at once.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+
+        self
+    }
+}
+
+impl Default for ShardOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Transcription<'_> {
+    /// Transcribe a long recording as concurrent requests for overlapping segments of it.
+    ///
+    /// The file is decoded to linear16 with [`DecodedFile`], and each segment
+    /// is uploaded as a WAV file. The merged response contains the words and
+    /// transcript of the first alternative of each channel, and the
+    /// utterances if they were requested, with timestamps relative to the
+    /// start of the file. Words and utterances that fall in the overlap
+    /// between two segments are taken from one segment only.
+    ///
+    /// Results that describe a segment as a whole, such as paragraphs,
+    /// entities, search hits, summaries, topics, intents, and sentiments,
+    /// cannot be merged and are left out. The metadata is taken from the
+    /// first segment, with the duration of the whole file.
+    ///
+    /// Each segment is diarized on its own, so speaker 0 in one segment need
+    /// not be speaker 0 in the next. When the file is split into more than
+    /// one segment, the `speaker` of every word and utterance is cleared
+    /// rather than left inconsistent.
+    ///
+    /// This must be called from within a Tokio runtime.
+    ///
+    /// ```no_run
+    /// # use deepgram::{common::options::Options, listen::sharded::ShardOptions, Deepgram, DeepgramError};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), DeepgramError> {
+    /// let dg = Deepgram::new(std::env::var("DEEPGRAM_API_KEY").unwrap_or_default())?;
+    ///
+    /// let options = Options::builder().punctuate(true).build();
+    /// let response = dg
+    ///     .transcription()
+    ///     .prerecorded_sharded("meeting.mp3", &options, &ShardOptions::new())
+    ///     .await?;
+    /// println!("{}", response.results.channels[0].alternatives[0].transcript);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be decoded or is empty, or if any
+    /// segment fails to transcribe.
+    pub async fn prerecorded_sharded(
+        &self,
+        path: impl AsRef<Path>,
+        options: &Options,
+        shard_options: &ShardOptions,
+    ) -> Result<Response, ListenError> {
+        let file = DecodedFile::open(path)?;
+        let sample_rate = file.sample_rate();
+        let channels = file.channels();
+
+        let frame_bytes = 2 * usize::from(channels);
+        let frames =
+            |duration: Duration| (f64::from(sample_rate) * duration.as_secs_f64()).round() as usize;
+        let segment_bytes = frames(shard_options.segment).max(1) * frame_bytes;
+        let overlap_bytes = frames(shard_options.overlap) * frame_bytes;
+
+        let pcm = file.into_stream(Duration::from_secs(1));
+        let segments = split(pcm, segment_bytes, overlap_bytes, sample_rate, channels)
+            .map_err(ListenError::from);
+
+        let shards: Vec<Response> = segments
+            .map_ok(|segment| {
+                self.prerecorded(
+                    AudioSource::from_buffer_with_mime_type(segment, "audio/wav"),
+                    options,
+                )
+            })
+            .try_buffered(shard_options.concurrency)
+            .try_collect()
+            .await?;
+
+        merge(
+            shards,
+            shard_options.segment.as_secs_f64(),
+            shard_options.overlap.as_secs_f64(),
+        )
+        .ok_or_else(|| ListenError::UnsupportedAudio("the file contains no audio".into()))
+    }
+}
+
+/// Split interleaved 16-bit samples from `pcm` into WAV files holding
+/// `segment_bytes` of audio each, plus the first `overlap_bytes` of the next.
+///
+/// Audio at the end that was already in the last segment's overlap isn't
+/// sent again.
+fn split<S, E>(
+    mut pcm: S,
+    segment_bytes: usize,
+    overlap_bytes: usize,
+    sample_rate: u32,
+    channels: u16,
+) -> impl Stream<Item = Result<Bytes, E>>
+where
+    S: Stream<Item = Result<Bytes, E>> + Unpin,
+{
+    let mut buffer = BytesMut::new();
+    let mut done = false;
+    let mut split_any = false;
+
+    stream::poll_fn(move |cx| loop {
+        if done && split_any && buffer.len() <= overlap_bytes {
+            // Everything left was already in the last segment's overlap.
+            buffer.clear();
+        }
+        if buffer.len() >= segment_bytes + overlap_bytes || (done && !buffer.is_empty()) {
+            let len = buffer.len().min(segment_bytes + overlap_bytes);
+            let segment = wav(&buffer[..len], sample_rate, channels);
+            let _ = buffer.split_to(len.min(segment_bytes));
+            split_any = true;
+            return Poll::Ready(Some(Ok(segment)));
+        }
+        if done {
+            return Poll::Ready(None);
+        }
+        match futures::ready!(pcm.poll_next_unpin(cx)) {
+            Some(Ok(chunk)) => {
+                buffer.extend_from_slice(&chunk);
+            }
+            Some(Err(err)) => return Poll::Ready(Some(Err(err))),
+            None => done = true,
+        }
+    })
+}
+
+/// Merge the responses for consecutive segments starting `segment` seconds
+/// apart, each overlapping the next by `overlap` seconds.
+///
+/// A word belongs to the segment whose share of the timeline contains its
+/// start time, with each overlap split down the middle.
+fn merge(shards: Vec<Response>, segment: f64, overlap: f64) -> Option<Response> {
+    let count = shards.len();
+    let mut shards = shards.into_iter().enumerate();
+    let (_, mut merged) = shards.next()?;
+
+    let bounds = |index: usize| {
+        let offset = index as f64 * segment;
+        let start = if index == 0 {
+            f64::NEG_INFINITY
+        } else {
+            offset + overlap / 2.0
+        };
+        let end = if index + 1 == count {
+            f64::INFINITY
+        } else {
+            offset + segment + overlap / 2.0
+        };
+        (offset, start, end)
+    };
+
+    let (_, first_start, first_end) = bounds(0);
+    let mut duration = merged.metadata.duration;
+    for channel in &mut merged.results.channels {
+        strip_channel(channel, first_start, first_end);
+    }
+    if let Some(utterances) = &mut merged.results.utterances {
+        utterances.retain(|utterance| (first_start..first_end).contains(&utterance.start));
+    }
+    merged.results.intents = None;
+    merged.results.sentiments = None;
+    merged.results.topics = None;
+    merged.results.summary = None;
+
+    for (index, shard) in shards {
+        let (offset, start, end) = bounds(index);
+        duration = duration.max(offset + shard.metadata.duration);
+
+        for (channel, shard_channel) in shard.results.channels.into_iter().enumerate() {
+            let Some(alternative) = shard_channel.alternatives.into_iter().next() else {
+                continue;
+            };
+            let words = alternative
+                .words
+                .into_iter()
+                .map(|word| rebase_word(word, offset))
+                .filter(|word| (start..end).contains(&word.start));
+
+            match merged.results.channels.get_mut(channel) {
+                Some(ChannelResult { alternatives, .. }) if !alternatives.is_empty() => {
+                    alternatives[0].words.extend(words);
+                }
+                _ => {}
+            }
+        }
+
+        if let (Some(merged), Some(utterances)) =
+            (&mut merged.results.utterances, shard.results.utterances)
+        {
+            merged.extend(
+                utterances
+                    .into_iter()
+                    .map(|utterance| rebase_utterance(utterance, offset))
+                    .filter(|utterance| (start..end).contains(&utterance.start)),
+            );
+        }
+    }
+
+    for channel in &mut merged.results.channels {
+        if let Some(alternative) = channel.alternatives.first_mut() {
+            alternative.transcript = join_words(&alternative.words);
+            alternative.confidence = average_confidence(&alternative.words).unwrap_or(0.0);
+        }
+    }
+    if count > 1 {
+        clear_speakers(&mut merged);
+    }
+    merged.metadata.duration = duration;
+    Some(merged)
+}
+
+/// Clear the speakers of every word and utterance, since speaker numbers
+/// from different segments don't refer to the same people.
+fn clear_speakers(response: &mut Response) {
+    let alternatives = response
+        .results
+        .channels
+        .iter_mut()
+        .flat_map(|channel| &mut channel.alternatives);
+    for alternative in alternatives {
+        for word in &mut alternative.words {
+            word.speaker = None;
+        }
+    }
+    for utterance in response.results.utterances.iter_mut().flatten() {
+        utterance.speaker = None;
+        for word in &mut utterance.words {
+            word.speaker = None;
+        }
+    }
+}
+
+/// Keep only the first alternative of `channel`, and its words between `start` and `end`.
+fn strip_channel(channel: &mut ChannelResult, start: f64, end: f64) {
+    channel.search = None;
+    channel.alternatives.truncate(1);
+    if let Some(ResultAlternative {
+        words,
+        paragraphs,
+        entities,
+        summaries,
+        ..
+    }) = channel.alternatives.first_mut()
+    {
+        words.retain(|word| (start..end).contains(&word.start));
+        *paragraphs = None;
+        *entities = None;
+        *summaries = None;
+    }
+}
+
+fn rebase_word(mut word: Word, offset: f64) -> Word {
+    word.start += offset;
+    word.end += offset;
+    word
+}
+
+fn rebase_utterance(mut utterance: Utterance, offset: f64) -> Utterance {
+    utterance.start += offset;
+    utterance.end += offset;
+    utterance.words = utterance
+        .words
+        .into_iter()
+        .map(|word| rebase_word(word, offset))
+        .collect();
+    utterance
+}
+
+/// Wrap interleaved 16-bit little-endian samples in a WAV header.
+fn wav(pcm: &[u8], sample_rate: u32, channels: u16) -> Bytes {
+    let format = WavFormat {
+        format_tag: WAVE_FORMAT_PCM,
+        bits_per_sample: 16,
+        sample_rate,
+        channels,
+    };
+
+    let mut wav = BytesMut::with_capacity(44 + pcm.len());
+    wav.put_slice(&format.header(pcm.len() as u32));
+    wav.put_slice(pcm);
+    wav.freeze()
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use futures::{stream, TryStreamExt};
+
+    use super::{merge, split, wav};
+    use crate::common::fixtures::{ResponseBuilder, WordBuilder};
+
+    async fn split_lengths(
+        pcm: &[usize],
+        segment_bytes: usize,
+        overlap_bytes: usize,
+    ) -> Vec<usize> {
+        let chunks = pcm
+            .iter()
+            .map(|&len| Ok::<_, std::io::Error>(Bytes::from(vec![0; len])));
+        split(stream::iter(chunks), segment_bytes, overlap_bytes, 16000, 1)
+            .map_ok(|segment| segment.len() - 44)
+            .try_collect()
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn splits_with_overlap() {
+        assert_eq!(split_lengths(&[25], 10, 2).await, [12, 12, 5]);
+        // Shorter than the overlap, but still transcribed.
+        assert_eq!(split_lengths(&[1], 10, 2).await, [1]);
+    }
+
+    #[tokio::test]
+    async fn does_not_resend_trailing_overlap() {
+        // The audio ends exactly at the end of a segment's overlap.
+        assert_eq!(split_lengths(&[12], 10, 2).await, [12]);
+        assert_eq!(split_lengths(&[10, 12], 10, 2).await, [12, 12]);
+    }
+
+    #[test]
+    fn merges_overlapping_shards() {
+        // Segments start every 10 seconds and overlap by 2.
+        let first = ResponseBuilder::new()
+            .channel([
+                WordBuilder::new("one", 1.0, 1.5).speaker(0).build(),
+                WordBuilder::new("two", 9.5, 10.5).build(),
+                WordBuilder::new("three", 11.5, 11.9).build(),
+            ])
+            .utterance(0, [WordBuilder::new("one", 1.0, 1.5).build()])
+            .utterance(0, [WordBuilder::new("three", 11.5, 11.9).build()])
+            .duration(12.0)
+            .build();
+        let second = ResponseBuilder::new()
+            .channel([
+                WordBuilder::new("two", 0.0, 0.5).build(),
+                WordBuilder::new("three", 1.5, 1.9).build(),
+                WordBuilder::new("four", 4.0, 4.5).speaker(0).build(),
+            ])
+            .utterance(0, [WordBuilder::new("three", 1.5, 1.9).build()])
+            .duration(5.0)
+            .build();
+
+        let merged = merge(vec![first, second], 10.0, 2.0).unwrap();
+
+        let alternative = &merged.results.channels[0].alternatives[0];
+        assert_eq!(alternative.transcript, "one two three four");
+        let starts: Vec<f64> = alternative.words.iter().map(|word| word.start).collect();
+        assert_eq!(starts, [1.0, 9.5, 11.5, 14.0]);
+        assert!(alternative.words.iter().all(|word| word.speaker.is_none()));
+
+        let utterances = merged.results.utterances.unwrap();
+        let starts: Vec<f64> = utterances.iter().map(|utterance| utterance.start).collect();
+        assert_eq!(starts, [1.0, 11.5]);
+        assert_eq!(utterances[1].words[0].end, 11.9);
+
+        assert_eq!(merged.metadata.duration, 15.0);
+    }
+
+    #[test]
+    fn wav_header() {
+        let wav = wav(&[1, 0, 2, 0, 3, 0, 4, 0], 16000, 2);
+        assert_eq!(wav.len(), 52);
+        assert_eq!(&wav[..4], b"RIFF");
+        assert_eq!(&wav[22..24], 2u16.to_le_bytes());
+        assert_eq!(&wav[28..32], 64000u32.to_le_bytes());
+        assert_eq!(&wav[40..44], 8u32.to_le_bytes());
+    }
+}
@@ -0,0 +1,355 @@
+//! Live audio sources for streaming transcription.
+//!
+//! [`Microphone`] captures audio from an input device with [`cpal`] and yields
+//! it as linear16 chunks ready for [`WebsocketBuilder::stream`] or
+//! [`FluxBuilder::stream`]. It reports the device's native sample rate and
+//! channel count, and can configure a builder to match, so the encoding
+//! parameters sent to Deepgram always describe the audio actually captured.
+//!
+//! Only available with the `microphone` feature.
+//!
+//! [`WebsocketBuilder::stream`]: crate::listen::websocket::WebsocketBuilder::stream
+//! [`FluxBuilder::stream`]: crate::listen::flux::FluxBuilder::stream
+
+use std::{
+    fmt,
+    pin::Pin,
+    sync::mpsc as std_mpsc,
+    task::{Context, Poll},
+    thread,
+};
+
+use bytes::{BufMut, Bytes, BytesMut};
+use cpal::{
+    traits::{DeviceTrait, HostTrait, StreamTrait},
+    FromSample, Sample, SampleFormat, SizedSample, StreamConfig, SupportedStreamConfig,
+};
+use futures::{
+    channel::mpsc::{self, UnboundedReceiver, UnboundedSender},
+    Stream, StreamExt,
+};
+
+use crate::{
+    common::options::Encoding,
+    listen::{flux::FluxBuilder, websocket::WebsocketBuilder},
+    DeepgramError, Result,
+};
+
+/// An audio input device, opened with its default configuration.
+///
+/// ```no_run
+/// # use deepgram::{listen::sources::Microphone, Deepgram, DeepgramError};
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), DeepgramError> {
+/// let dg = Deepgram::new(std::env::var("DEEPGRAM_API_KEY").unwrap_or_default())?;
+/// let transcription = dg.transcription();
+///
+/// let microphone = Microphone::default_input()?;
+/// println!(
+///     "Capturing {} Hz, {} channel(s)",
+///     microphone.sample_rate(),
+///     microphone.channels()
+/// );
+///
+/// let builder = microphone.configure(transcription.stream_request());
+/// let results = builder.stream(microphone.into_stream()?).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Microphone {
+    device: cpal::Device,
+    name: String,
+    config: SupportedStreamConfig,
+    mono: bool,
+}
+
+impl fmt::Debug for Microphone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Microphone")
+            .field("name", &self.name)
+            .field("sample_rate", &self.sample_rate())
+            .field("channels", &self.channels())
+            .field("sample_format", &self.config.sample_format())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Microphone {
+    /// Open the default input device of the default audio host.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DeepgramError::AudioDeviceError`] if there is no default
+    /// input device or its configuration cannot be read.
+    pub fn default_input() -> Result<Self> {
+        let device = cpal::default_host()
+            .default_input_device()
+            .ok_or_else(|| DeepgramError::AudioDeviceError("no default input device".into()))?;
+        Self::from_device(device)
+    }
+
+    /// Open the input device called `name`, as listed by [`Microphone::input_devices`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DeepgramError::AudioDeviceError`] if no input device has that
+    /// name or its configuration cannot be read.
+    pub fn by_name(name: &str) -> Result<Self> {
+        let device = cpal::default_host()
+            .input_devices()
+            .map_err(from_cpal_error)?
+            .find(|device| device.name().is_ok_and(|device_name| device_name == name))
+            .ok_or_else(|| {
+                DeepgramError::AudioDeviceError(format!("no input device named {name:?}").into())
+            })?;
+        Self::from_device(device)
+    }
+
+    /// The names of the input devices available on the default audio host.
+    ///
+    /// Devices whose names cannot be read are skipped.
+    pub fn input_devices() -> Result<Vec<String>> {
+        Ok(cpal::default_host()
+            .input_devices()
+            .map_err(from_cpal_error)?
+            .filter_map(|device| device.name().ok())
+            .collect())
+    }
+
+    /// Open `device` with its default input configuration.
+    pub fn from_device(device: cpal::Device) -> Result<Self> {
+        let name = device.name().map_err(from_cpal_error)?;
+        let config = device.default_input_config().map_err(from_cpal_error)?;
+        Ok(Self {
+            device,
+            name,
+            config,
+            mono: false,
+        })
+    }
+
+    /// Mix all channels down to one, as required by Flux.
+    pub fn mono(mut self) -> Self {
+        self.mono = true;
+        self
+    }
+
+    /// The device's name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The device's native sample rate, in samples per second per channel.
+    pub fn sample_rate(&self) -> u32 {
+        self.config.sample_rate().0
+    }
+
+    /// Number of interleaved channels in the stream produced by [`Microphone::into_stream`].
+    pub fn channels(&self) -> u16 {
+        if self.mono {
+            1
+        } else {
+            self.config.channels()
+        }
+    }
+
+    /// Set the encoding, sample rate, and channel count of `builder` to match the captured audio.
+    pub fn configure<'a>(&self, builder: WebsocketBuilder<'a>) -> WebsocketBuilder<'a> {
+        builder
+            .encoding(Encoding::Linear16)
+            .sample_rate(self.sample_rate())
+            .channels(self.channels())
+    }
+
+    /// Set the encoding and sample rate of `builder` to match the captured audio.
+    ///
+    /// Flux only accepts a single channel, so this is usually combined with [`Microphone::mono`].
+    pub fn configure_flux<'a>(&self, builder: FluxBuilder<'a>) -> FluxBuilder<'a> {
+        builder
+            .encoding(Encoding::Linear16)
+            .sample_rate(self.sample_rate())
+    }
+
+    /// Start capturing audio.
+    ///
+    /// Samples are converted to 16-bit linear PCM and yielded as they arrive
+    /// from the device. Capture runs on a dedicated thread, so this does not
+    /// need any particular async runtime, and stops when the stream is dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DeepgramError::UnsupportedAudio`] if the device's sample
+    /// format cannot be converted, or [`DeepgramError::AudioDeviceError`] if
+    /// the device cannot be started. Errors reported by the device while
+    /// capturing are yielded by the stream.
+    pub fn into_stream(self) -> Result<MicrophoneStream> {
+        let sample_rate = self.sample_rate();
+        let channels = self.channels();
+        let (audio_tx, audio_rx) = mpsc::unbounded();
+        let (started_tx, started_rx) = std_mpsc::sync_channel(1);
+        let (stop_tx, stop_rx) = std_mpsc::channel::<()>();
+
+        // cpal streams are not `Send` on every platform, so the stream is
+        // created, played, and dropped on the same thread.
+        thread::spawn(move || {
+            let stream = match self.build_stream(audio_tx) {
+                Ok(stream) => stream,
+                Err(err) => {
+                    let _ = started_tx.send(Err(err));
+                    return;
+                }
+            };
+            if let Err(err) = stream.play() {
+                let _ = started_tx.send(Err(from_cpal_error(err)));
+                return;
+            }
+            let _ = started_tx.send(Ok(()));
+
+            // Returns once the MicrophoneStream, and with it `stop_tx`, is dropped.
+            let _ = stop_rx.recv();
+        });
+
+        started_rx.recv().map_err(|_| {
+            DeepgramError::AudioDeviceError("capture thread exited unexpectedly".into())
+        })??;
+
+        Ok(MicrophoneStream {
+            rx: audio_rx,
+            sample_rate,
+            channels,
+            _stop: stop_tx,
+        })
+    }
+
+    fn build_stream(&self, tx: UnboundedSender<Result<Bytes>>) -> Result<cpal::Stream> {
+        let config: StreamConfig = self.config.config();
+        match self.config.sample_format() {
+            SampleFormat::I8 => self.build_typed_stream::<i8>(&config, tx),
+            SampleFormat::I16 => self.build_typed_stream::<i16>(&config, tx),
+            SampleFormat::I32 => self.build_typed_stream::<i32>(&config, tx),
+            SampleFormat::I64 => self.build_typed_stream::<i64>(&config, tx),
+            SampleFormat::U8 => self.build_typed_stream::<u8>(&config, tx),
+            SampleFormat::U16 => self.build_typed_stream::<u16>(&config, tx),
+            SampleFormat::U32 => self.build_typed_stream::<u32>(&config, tx),
+            SampleFormat::U64 => self.build_typed_stream::<u64>(&config, tx),
+            SampleFormat::F32 => self.build_typed_stream::<f32>(&config, tx),
+            SampleFormat::F64 => self.build_typed_stream::<f64>(&config, tx),
+            format => Err(DeepgramError::UnsupportedAudio(format!(
+                "{format} input devices are not supported"
+            ))),
+        }
+    }
+
+    fn build_typed_stream<T>(
+        &self,
+        config: &StreamConfig,
+        tx: UnboundedSender<Result<Bytes>>,
+    ) -> Result<cpal::Stream>
+    where
+        T: SizedSample,
+        i16: FromSample<T>,
+    {
+        let channels = usize::from(config.channels);
+        let mono = self.mono;
+        let error_tx = tx.clone();
+        self.device
+            .build_input_stream(
+                config,
+                move |data: &[T], _: &_| {
+                    let _ = tx.unbounded_send(Ok(to_linear16(data, channels, mono)));
+                },
+                move |err| {
+                    let _ = error_tx.unbounded_send(Err(from_cpal_error(err)));
+                },
+                None,
+            )
+            .map_err(from_cpal_error)
+    }
+}
+
+/// Audio captured by a [`Microphone`], as 16-bit little-endian linear PCM.
+///
+/// Capture stops when this is dropped.
+#[derive(Debug)]
+pub struct MicrophoneStream {
+    rx: UnboundedReceiver<Result<Bytes>>,
+    sample_rate: u32,
+    channels: u16,
+    _stop: std_mpsc::Sender<()>,
+}
+
+impl MicrophoneStream {
+    /// The sample rate of the captured audio, in samples per second per channel.
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Number of interleaved channels in the captured audio.
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    /// Set the encoding, sample rate, and channel count of `builder` to match the captured audio.
+    pub fn configure<'a>(&self, builder: WebsocketBuilder<'a>) -> WebsocketBuilder<'a> {
+        builder
+            .encoding(Encoding::Linear16)
+            .sample_rate(self.sample_rate)
+            .channels(self.channels)
+    }
+}
+
+impl Stream for MicrophoneStream {
+    type Item = Result<Bytes>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_next_unpin(cx)
+    }
+}
+
+fn to_linear16<T>(data: &[T], channels: usize, mono: bool) -> Bytes
+where
+    T: Sample,
+    i16: FromSample<T>,
+{
+    if mono && channels > 1 {
+        let mut bytes = BytesMut::with_capacity(data.len() / channels * 2);
+        for frame in data.chunks_exact(channels) {
+            let sum: i32 = frame
+                .iter()
+                .map(|&sample| i32::from(sample.to_sample::<i16>()))
+                .sum();
+            bytes.put_i16_le((sum / channels as i32) as i16);
+        }
+        bytes.freeze()
+    } else {
+        let mut bytes = BytesMut::with_capacity(data.len() * 2);
+        for &sample in data {
+            bytes.put_i16_le(sample.to_sample());
+        }
+        bytes.freeze()
+    }
+}
+
+fn from_cpal_error(err: impl std::error::Error + Send + Sync + 'static) -> DeepgramError {
+    DeepgramError::AudioDeviceError(Box::new(err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_linear16;
+
+    #[test]
+    fn converts_samples_to_linear16() {
+        let bytes = to_linear16(&[0.0f32, 1.0, -1.0], 1, false);
+        assert_eq!(&bytes[..], [0, 0, 0xff, 0x7f, 0x00, 0x80]);
+
+        let bytes = to_linear16(&[0u16, 32768], 1, false);
+        assert_eq!(&bytes[..], [0x00, 0x80, 0, 0]);
+    }
+
+    #[test]
+    fn downmixes_to_mono() {
+        let bytes = to_linear16(&[100i16, 300, -50, -150], 2, true);
+        assert_eq!(&bytes[..], [200i16, -100].map(i16::to_le_bytes).concat());
+    }
+}
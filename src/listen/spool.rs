@@ -0,0 +1,259 @@
+//! A FIFO queue of audio chunks that spills to disk past a memory limit.
+//!
+//! Used by [`WebsocketBuilder::spill_to_disk`] to keep reading from an audio
+//! source while the connection is not accepting audio, without holding an
+//! unbounded amount of it in memory or dropping any of it. Also used without
+//! a spill file for [`Backpressure::DropOldest`].
+//!
+//! [`WebsocketBuilder::spill_to_disk`]: crate::listen::websocket::WebsocketBuilder::spill_to_disk
+//! [`Backpressure::DropOldest`]: crate::listen::websocket::Backpressure::DropOldest
+
+use std::{
+    collections::VecDeque,
+    fs::{self, File, OpenOptions},
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use bytes::Bytes;
+
+use crate::listen::transport::{unblock, Runtime};
+
+/// Queued audio, oldest first.
+///
+/// Chunks are kept in memory until they add up to `memory_limit` bytes. After
+/// that, new chunks are appended to a temporary file until everything in it
+/// has been read back, so chunks always come out in the order they went in.
+///
+/// A spool made with [`AudioSpool::dropping_oldest`] never spills, and
+/// instead drops its oldest chunk to make room for a new one.
+#[derive(Debug)]
+pub(crate) struct AudioSpool {
+    memory: VecDeque<Bytes>,
+    memory_bytes: usize,
+    memory_limit: usize,
+    max_chunks: Option<usize>,
+    directory: PathBuf,
+    spill: Option<SpillFile>,
+}
+
+impl AudioSpool {
+    pub(crate) fn new(memory_limit: usize, directory: PathBuf) -> Self {
+        AudioSpool {
+            memory: VecDeque::new(),
+            memory_bytes: 0,
+            memory_limit,
+            max_chunks: None,
+            directory,
+            spill: None,
+        }
+    }
+
+    /// A spool that holds at most `max_chunks` chunks, and at least one.
+    pub(crate) fn dropping_oldest(max_chunks: usize) -> Self {
+        AudioSpool {
+            max_chunks: Some(max_chunks.max(1)),
+            ..AudioSpool::new(usize::MAX, PathBuf::new())
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.memory.is_empty() && self.spill.as_ref().is_none_or(SpillFile::is_drained)
+    }
+
+    /// Add `chunk` to the back of the queue.
+    ///
+    /// Once the memory limit has been reached, the chunk is written with
+    /// [`Runtime::spawn_blocking`].
+    ///
+    /// [`Runtime::spawn_blocking`]: crate::listen::transport::Runtime::spawn_blocking
+    pub(crate) async fn push(&mut self, chunk: Bytes, runtime: &dyn Runtime) -> io::Result<()> {
+        if let Some(max_chunks) = self.max_chunks {
+            if self.memory.len() >= max_chunks {
+                if let Some(oldest) = self.memory.pop_front() {
+                    self.memory_bytes -= oldest.len();
+                }
+            }
+        }
+
+        let spilling = self.spill.as_ref().is_some_and(|spill| !spill.is_drained());
+        if !spilling && self.memory_bytes + chunk.len() <= self.memory_limit {
+            self.memory_bytes += chunk.len();
+            self.memory.push_back(chunk);
+            return Ok(());
+        }
+
+        let spill = self.spill.take();
+        let directory = self.directory.clone();
+        let (spill, result) = unblock(runtime, move || {
+            let mut spill = match spill {
+                Some(spill) => spill,
+                None => match SpillFile::create(&directory) {
+                    Ok(spill) => spill,
+                    Err(err) => return (None, Err(err)),
+                },
+            };
+            let result = spill.write(&chunk);
+            (Some(spill), result)
+        })
+        .await?;
+        self.spill = spill;
+        result
+    }
+
+    /// Remove the chunk at the front of the queue.
+    ///
+    /// A chunk that was spilled to disk is read back with
+    /// [`Runtime::spawn_blocking`].
+    ///
+    /// [`Runtime::spawn_blocking`]: crate::listen::transport::Runtime::spawn_blocking
+    pub(crate) async fn pop(&mut self, runtime: &dyn Runtime) -> io::Result<Option<Bytes>> {
+        if let Some(chunk) = self.memory.pop_front() {
+            self.memory_bytes -= chunk.len();
+            return Ok(Some(chunk));
+        }
+
+        match self.spill.take() {
+            Some(mut spill) if !spill.is_drained() => {
+                let (spill, result) = unblock(runtime, move || {
+                    let result = spill.read();
+                    (spill, result)
+                })
+                .await?;
+                self.spill = Some(spill);
+                result.map(Some)
+            }
+            spill => {
+                self.spill = spill;
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// Length-prefixed chunks in a temporary file, deleted on drop.
+#[derive(Debug)]
+struct SpillFile {
+    file: File,
+    path: PathBuf,
+    read_pos: u64,
+    write_pos: u64,
+}
+
+impl SpillFile {
+    fn create(directory: &Path) -> io::Result<Self> {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+        let path = directory.join(format!(
+            "deepgram-audio-{}-{}.spill",
+            std::process::id(),
+            NEXT_ID.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        Ok(SpillFile {
+            file,
+            path,
+            read_pos: 0,
+            write_pos: 0,
+        })
+    }
+
+    fn is_drained(&self) -> bool {
+        self.read_pos == self.write_pos
+    }
+
+    fn write(&mut self, chunk: &[u8]) -> io::Result<()> {
+        let len = u32::try_from(chunk.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "audio chunk too large"))?;
+        self.file.seek(SeekFrom::Start(self.write_pos))?;
+        self.file.write_all(&len.to_le_bytes())?;
+        self.file.write_all(chunk)?;
+        self.write_pos += 4 + u64::from(len);
+        Ok(())
+    }
+
+    fn read(&mut self) -> io::Result<Bytes> {
+        let mut len = [0; 4];
+        self.file.seek(SeekFrom::Start(self.read_pos))?;
+        self.file.read_exact(&mut len)?;
+        let mut chunk = vec![0; u32::from_le_bytes(len) as usize];
+        self.file.read_exact(&mut chunk)?;
+        self.read_pos += 4 + chunk.len() as u64;
+
+        if self.is_drained() {
+            // Start over so the file doesn't grow across separate outages.
+            self.file.set_len(0)?;
+            self.read_pos = 0;
+            self.write_pos = 0;
+        }
+        Ok(Bytes::from(chunk))
+    }
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::AudioSpool;
+    use crate::listen::transport::TokioRuntime;
+
+    fn chunk(n: u8) -> Bytes {
+        Bytes::from(vec![n; 10])
+    }
+
+    #[tokio::test]
+    async fn spills_past_memory_limit_in_order() {
+        let directory = std::env::temp_dir();
+        let mut spool = AudioSpool::new(25, directory);
+        assert!(spool.is_empty());
+
+        for n in 0..5 {
+            spool.push(chunk(n), &TokioRuntime).await.unwrap();
+        }
+        let path = spool.spill.as_ref().unwrap().path.clone();
+        assert!(path.exists());
+
+        // Memory has room again, but chunks keep going to disk until it's drained.
+        assert_eq!(spool.pop(&TokioRuntime).await.unwrap(), Some(chunk(0)));
+        spool.push(chunk(5), &TokioRuntime).await.unwrap();
+
+        for n in 1..6 {
+            assert_eq!(spool.pop(&TokioRuntime).await.unwrap(), Some(chunk(n)));
+        }
+        assert!(spool.is_empty());
+        assert_eq!(spool.pop(&TokioRuntime).await.unwrap(), None);
+
+        // Once drained, chunks go back to memory.
+        spool.push(chunk(6), &TokioRuntime).await.unwrap();
+        assert_eq!(spool.memory.len(), 1);
+        assert_eq!(spool.pop(&TokioRuntime).await.unwrap(), Some(chunk(6)));
+
+        drop(spool);
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn drops_oldest_past_max_chunks() {
+        let mut spool = AudioSpool::dropping_oldest(2);
+
+        for n in 0..4 {
+            spool.push(chunk(n), &TokioRuntime).await.unwrap();
+        }
+        assert!(spool.spill.is_none());
+
+        assert_eq!(spool.pop(&TokioRuntime).await.unwrap(), Some(chunk(2)));
+        assert_eq!(spool.pop(&TokioRuntime).await.unwrap(), Some(chunk(3)));
+        assert!(spool.is_empty());
+    }
+}
@@ -0,0 +1,787 @@
+//! Pluggable async runtime and websocket transport for streaming requests.
+//!
+//! The live and Flux websocket clients need three things from their
+//! environment: a way to open a websocket connection, a way to spawn
+//! background tasks, and a clock. These are described by the [`Transport`]
+//! and [`Runtime`] traits so the clients are not tied to a particular async
+//! runtime.
+//!
+//! By default everything runs on Tokio ([`TokioTransport`] and
+//! [`TokioRuntime`]), or [`TokioProxyTransport`] when the client has a
+//! [proxy](crate::DeepgramBuilder::proxy).
+#![cfg_attr(
+    feature = "smol",
+    doc = "With the `smol` feature enabled, [`SmolTransport`] and [`SmolRuntime`]"
+)]
+#![cfg_attr(
+    not(feature = "smol"),
+    doc = "With the `smol` feature enabled, `SmolTransport` and `SmolRuntime`"
+)]
+//! run streaming requests on [smol](https://docs.rs/smol) instead.
+//!
+//! Other runtimes can be supported by implementing the traits and
+//! passing them to [`WebsocketBuilder::transport`] and
+//! [`WebsocketBuilder::runtime`] (or the equivalent [`FluxBuilder`] methods).
+//!
+//! [`WebsocketBuilder::transport`]: crate::listen::websocket::WebsocketBuilder::transport
+//! [`WebsocketBuilder::runtime`]: crate::listen::websocket::WebsocketBuilder::runtime
+//! [`FluxBuilder`]: crate::listen::flux::FluxBuilder
+
+use std::{
+    fmt, io,
+    path::PathBuf,
+    pin::Pin,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::anyhow;
+use base64::Engine;
+use futures::{
+    channel::oneshot,
+    future::{self, BoxFuture, Either},
+    Sink, Stream,
+};
+use percent_encoding::percent_decode_str;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::TcpStream,
+};
+use tokio_util::compat::TokioAsyncReadCompatExt;
+use tungstenite::{handshake::client::Response, protocol::Message, Error as WsError};
+use url::Url;
+
+use crate::{DeepgramError, RedactedUrl, Result};
+
+/// A connected websocket, as a stream of incoming messages and a sink for outgoing ones.
+pub trait WebSocket:
+    Stream<Item = std::result::Result<Message, WsError>> + Sink<Message, Error = WsError> + Send
+{
+}
+
+impl<T> WebSocket for T where
+    T: Stream<Item = std::result::Result<Message, WsError>> + Sink<Message, Error = WsError> + Send
+{
+}
+
+/// A boxed [`WebSocket`].
+pub type BoxWebSocket = Pin<Box<dyn WebSocket>>;
+
+/// A boxed reader, as returned by [`Runtime::open_file`].
+pub type BoxAsyncRead = Pin<Box<dyn futures::io::AsyncRead + Send>>;
+
+/// The result of a successful websocket handshake.
+#[derive(Debug)]
+pub struct Connection {
+    /// The connected websocket.
+    pub socket: BoxWebSocket,
+
+    /// The server's handshake response. Deepgram reports the request ID in its headers.
+    pub response: Response,
+}
+
+impl fmt::Debug for dyn WebSocket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("WebSocket")
+    }
+}
+
+/// Opens websocket connections.
+pub trait Transport: fmt::Debug + Send + Sync + 'static {
+    /// Perform the websocket handshake for `request`, including TLS for `wss://` URLs.
+    ///
+    /// The request already carries every header Deepgram needs, including the
+    /// websocket upgrade headers and authorization.
+    fn connect(&self, request: http::Request<()>) -> BoxFuture<'static, Result<Connection>>;
+}
+
+/// Spawns tasks and tracks time.
+///
+/// All timing-sensitive logic in the websocket clients, such as keep-alive
+/// intervals and pacing audio read from a file, goes through [`Runtime::now`]
+/// and [`Runtime::sleep`]. A runtime backed by a virtual clock therefore makes
+/// that logic deterministic in tests.
+pub trait Runtime: fmt::Debug + Send + Sync + 'static {
+    /// Run `future` to completion in the background.
+    fn spawn(&self, future: BoxFuture<'static, ()>);
+
+    /// The current time according to this runtime's clock.
+    ///
+    /// Defaults to [`Instant::now`]. Runtimes with their own notion of time
+    /// should override this so it agrees with [`Runtime::sleep`].
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    /// Complete after `duration` has elapsed.
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()>;
+
+    /// Open a file for reading without blocking the executor.
+    fn open_file(&self, path: PathBuf) -> BoxFuture<'static, io::Result<BoxAsyncRead>>;
+
+    /// Run `task`, which may block, without blocking the executor.
+    ///
+    /// Used for audio [spilled to disk](crate::listen::websocket::WebsocketBuilder::spill_to_disk).
+    /// Defaults to running `task` in place. Runtimes with a thread pool for
+    /// blocking work should override this.
+    fn spawn_blocking(&self, task: Box<dyn FnOnce() + Send>) -> BoxFuture<'static, ()> {
+        task();
+        Box::pin(future::ready(()))
+    }
+}
+
+/// Run `f` with [`Runtime::spawn_blocking`], and return what it returns.
+pub(crate) async fn unblock<T>(
+    runtime: &dyn Runtime,
+    f: impl FnOnce() -> T + Send + 'static,
+) -> io::Result<T>
+where
+    T: Send + 'static,
+{
+    let (tx, rx) = oneshot::channel();
+    runtime
+        .spawn_blocking(Box::new(move || {
+            let _ = tx.send(f());
+        }))
+        .await;
+    rx.await
+        .map_err(|_| io::Error::other("blocking task was cancelled"))
+}
+
+/// The default [`Transport`], built on `tokio-tungstenite`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokioTransport;
+
+impl Transport for TokioTransport {
+    fn connect(&self, request: http::Request<()>) -> BoxFuture<'static, Result<Connection>> {
+        Box::pin(async move {
+            let (socket, response) = tokio_tungstenite::connect_async(request).await?;
+            Ok(Connection {
+                socket: Box::pin(socket),
+                response,
+            })
+        })
+    }
+}
+
+/// The default [`Runtime`]. Must be used from within a Tokio runtime.
+///
+/// Time is read from Tokio's clock, so pausing and advancing time with
+/// `tokio::time::pause` and `tokio::time::advance` applies to the websocket
+/// clients as well.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokioRuntime;
+
+impl Runtime for TokioRuntime {
+    fn spawn(&self, future: BoxFuture<'static, ()>) {
+        tokio::spawn(future);
+    }
+
+    fn now(&self) -> Instant {
+        tokio::time::Instant::now().into_std()
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        Box::pin(tokio::time::sleep(duration))
+    }
+
+    fn open_file(&self, path: PathBuf) -> BoxFuture<'static, io::Result<BoxAsyncRead>> {
+        Box::pin(async move {
+            let file = tokio::fs::File::open(path).await?;
+            Ok(Box::pin(file.compat()) as BoxAsyncRead)
+        })
+    }
+
+    fn spawn_blocking(&self, task: Box<dyn FnOnce() + Send>) -> BoxFuture<'static, ()> {
+        let handle = tokio::task::spawn_blocking(task);
+        Box::pin(async move {
+            if let Err(err) = handle.await {
+                if let Ok(panic) = err.try_into_panic() {
+                    std::panic::resume_unwind(panic);
+                }
+            }
+        })
+    }
+}
+
+/// A [`Transport`] that tunnels connections through an HTTP proxy with `CONNECT`.
+///
+/// Used by default when the client has a [proxy](crate::DeepgramBuilder::proxy).
+/// Credentials in the proxy URL are sent with `Basic` proxy authorization.
+/// TLS for `wss://` URLs is negotiated with Deepgram through the tunnel, so
+/// the proxy only ever sees encrypted traffic.
+#[derive(Clone)]
+pub struct TokioProxyTransport {
+    proxy: Url,
+    tls: Option<TlsConnector>,
+}
+
+impl fmt::Debug for TokioProxyTransport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TokioProxyTransport")
+            .field("proxy", &RedactedUrl(&self.proxy))
+            .field("tls", &self.tls)
+            .finish()
+    }
+}
+
+impl TokioProxyTransport {
+    /// Tunnel connections through the `http://` proxy at `proxy`.
+    pub fn new(proxy: Url) -> Self {
+        Self { proxy, tls: None }
+    }
+}
+
+impl Transport for TokioProxyTransport {
+    fn connect(&self, request: http::Request<()>) -> BoxFuture<'static, Result<Connection>> {
+        let proxy = self.proxy.clone();
+        let tls = self.tls.clone();
+        Box::pin(async move {
+            let stream = connect_tunnel(&proxy, request.uri()).await?;
+            handshake(request, stream, tls).await
+        })
+    }
+}
+
+/// A [`Transport`] that uses the client's
+/// [root certificates](crate::DeepgramBuilder::add_root_certificate) and TLS backend.
+#[cfg(any(feature = "rustls-tls", feature = "native-tls"))]
+#[derive(Debug, Clone)]
+struct TokioTlsTransport(TlsConnector);
+
+#[cfg(any(feature = "rustls-tls", feature = "native-tls"))]
+impl Transport for TokioTlsTransport {
+    fn connect(&self, request: http::Request<()>) -> BoxFuture<'static, Result<Connection>> {
+        let connector = self.0 .0.clone();
+        Box::pin(async move {
+            let (socket, response) = tokio_tungstenite::connect_async_tls_with_config(
+                request,
+                None,
+                false,
+                Some(connector),
+            )
+            .await?;
+            Ok(Connection {
+                socket: Box::pin(socket),
+                response,
+            })
+        })
+    }
+}
+
+/// Perform the websocket handshake over `stream`, negotiating TLS first for `wss://` URLs.
+async fn handshake<S>(
+    request: http::Request<()>,
+    stream: S,
+    tls: Option<TlsConnector>,
+) -> Result<Connection>
+where
+    S: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+{
+    #[cfg(any(feature = "rustls-tls", feature = "native-tls"))]
+    let (socket, response) = tokio_tungstenite::client_async_tls_with_config(
+        request,
+        stream,
+        None,
+        tls.map(|tls| tls.0),
+    )
+    .await?;
+
+    // Without a TLS backend only `ws://` URLs can be connected to.
+    #[cfg(not(any(feature = "rustls-tls", feature = "native-tls")))]
+    let (socket, response) = {
+        let _ = tls;
+        tokio_tungstenite::client_async(request, stream).await?
+    };
+
+    Ok(Connection {
+        socket: Box::pin(socket),
+        response,
+    })
+}
+
+/// The TLS configuration for streaming connections.
+#[derive(Clone)]
+#[cfg_attr(
+    not(any(feature = "rustls-tls", feature = "native-tls")),
+    allow(dead_code)
+)]
+pub(crate) struct TlsConnector(tokio_tungstenite::Connector);
+
+impl fmt::Debug for TlsConnector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsConnector").finish_non_exhaustive()
+    }
+}
+
+/// The TLS configuration for streaming connections that trusts the PEM
+/// encoded `root_certificates` in addition to the usual roots.
+///
+/// Returns `None` if the enabled TLS backend's defaults can be used.
+#[cfg(feature = "rustls-tls")]
+pub(crate) fn tls_connector(root_certificates: &[Vec<u8>]) -> Result<Option<TlsConnector>> {
+    use rustls::pki_types::{pem::PemObject, CertificateDer};
+
+    // With both backends enabled tokio-tungstenite defaults to native-tls,
+    // so rustls has to be configured explicitly to match the HTTP client.
+    if root_certificates.is_empty() && !cfg!(feature = "native-tls") {
+        return Ok(None);
+    }
+
+    let mut roots = rustls::RootCertStore::empty();
+    roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    for pem in root_certificates {
+        for certificate in CertificateDer::pem_slice_iter(pem) {
+            roots
+                .add(certificate.map_err(invalid_certificate)?)
+                .map_err(invalid_certificate)?;
+        }
+    }
+
+    let provider = Arc::new(rustls::crypto::aws_lc_rs::default_provider());
+    let config = rustls::ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(invalid_certificate)?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    Ok(Some(TlsConnector(tokio_tungstenite::Connector::Rustls(
+        Arc::new(config),
+    ))))
+}
+
+/// The TLS configuration for streaming connections that trusts the PEM
+/// encoded `root_certificates` in addition to the usual roots.
+///
+/// Returns `None` if the enabled TLS backend's defaults can be used.
+#[cfg(all(feature = "native-tls", not(feature = "rustls-tls")))]
+pub(crate) fn tls_connector(root_certificates: &[Vec<u8>]) -> Result<Option<TlsConnector>> {
+    if root_certificates.is_empty() {
+        return Ok(None);
+    }
+
+    let mut builder = native_tls::TlsConnector::builder();
+    for pem in root_certificates {
+        for certificate in
+            native_tls::Certificate::stack_from_pem(pem).map_err(invalid_certificate)?
+        {
+            builder.add_root_certificate(certificate);
+        }
+    }
+
+    let connector = builder.build().map_err(invalid_certificate)?;
+    Ok(Some(TlsConnector(tokio_tungstenite::Connector::NativeTls(
+        connector,
+    ))))
+}
+
+/// Without a TLS backend there is nothing to configure.
+#[cfg(not(any(feature = "rustls-tls", feature = "native-tls")))]
+pub(crate) fn tls_connector(_root_certificates: &[Vec<u8>]) -> Result<Option<TlsConnector>> {
+    Ok(None)
+}
+
+#[cfg(any(feature = "rustls-tls", feature = "native-tls"))]
+fn invalid_certificate(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+/// Open a TCP connection to `target` through `proxy`.
+async fn connect_tunnel(proxy: &Url, target: &http::Uri) -> Result<TcpStream> {
+    let proxy_host = proxy.host_str().ok_or(DeepgramError::InvalidUrl)?;
+    let proxy_port = proxy
+        .port_or_known_default()
+        .ok_or(DeepgramError::InvalidUrl)?;
+    let host = target.host().ok_or(DeepgramError::InvalidUrl)?;
+    let port = match (target.port_u16(), target.scheme_str()) {
+        (Some(port), _) => port,
+        (None, Some("wss")) => 443,
+        (None, _) => 80,
+    };
+
+    let mut head = format!("CONNECT {host}:{port} HTTP/1.1\r\nHost: {host}:{port}\r\n");
+    if !proxy.username().is_empty() {
+        let credentials = format!(
+            "{}:{}",
+            percent_decode_str(proxy.username()).decode_utf8_lossy(),
+            percent_decode_str(proxy.password().unwrap_or_default()).decode_utf8_lossy()
+        );
+        head.push_str(&format!(
+            "Proxy-Authorization: Basic {}\r\n",
+            base64::engine::general_purpose::STANDARD.encode(credentials)
+        ));
+    }
+    head.push_str("\r\n");
+
+    let mut stream = TcpStream::connect((proxy_host, proxy_port)).await?;
+    stream.write_all(head.as_bytes()).await?;
+
+    // Read the response head one byte at a time so nothing after it is consumed.
+    let mut response = Vec::new();
+    while !response.ends_with(b"\r\n\r\n") {
+        if response.len() >= 8192 {
+            return Err(DeepgramError::UnexpectedServerResponse(anyhow!(
+                "Proxy response headers too long"
+            )));
+        }
+        response.push(stream.read_u8().await?);
+    }
+
+    let status_line = String::from_utf8_lossy(&response);
+    let status_line = status_line.lines().next().unwrap_or_default();
+    match status_line.split_whitespace().nth(1) {
+        Some(status) if status.starts_with('2') => Ok(stream),
+        _ => Err(DeepgramError::UnexpectedServerResponse(anyhow!(
+            "Proxy refused to connect: {status_line}"
+        ))),
+    }
+}
+
+pub(crate) fn default_transport(
+    proxy: Option<&Url>,
+    tls: Option<&TlsConnector>,
+) -> Arc<dyn Transport> {
+    match (proxy, tls) {
+        (Some(proxy), tls) => Arc::new(TokioProxyTransport {
+            proxy: proxy.clone(),
+            tls: tls.cloned(),
+        }),
+        #[cfg(any(feature = "rustls-tls", feature = "native-tls"))]
+        (None, Some(tls)) => Arc::new(TokioTlsTransport(tls.clone())),
+        (None, _) => Arc::new(TokioTransport),
+    }
+}
+
+pub(crate) fn default_runtime() -> Arc<dyn Runtime> {
+    Arc::new(TokioRuntime)
+}
+
+/// Connect with `transport`, giving up once `timeout`, if any, has passed on
+/// `runtime`.
+///
+/// An upgrade request that Deepgram rejects is reported as a
+/// [`DeepgramError::WebsocketHandshake`] carrying the response body.
+pub(crate) async fn connect(
+    transport: &dyn Transport,
+    runtime: &dyn Runtime,
+    request: http::Request<()>,
+    timeout: Option<Duration>,
+) -> Result<Connection> {
+    let Some(timeout) = timeout else {
+        return transport.connect(request).await.map_err(handshake_error);
+    };
+
+    match future::select(transport.connect(request), runtime.sleep(timeout)).await {
+        Either::Left((result, _)) => result.map_err(handshake_error),
+        Either::Right(_) => Err(DeepgramError::ConnectTimeout { timeout }),
+    }
+}
+
+fn handshake_error(err: DeepgramError) -> DeepgramError {
+    let DeepgramError::WsError(err) = err else {
+        return err;
+    };
+
+    match *err {
+        WsError::Http(response) => {
+            let (parts, body) = response.into_parts();
+            DeepgramError::WebsocketHandshake {
+                status: parts.status,
+                body: String::from_utf8_lossy(&body.unwrap_or_default()).into_owned(),
+            }
+        }
+        err => DeepgramError::from(err),
+    }
+}
+
+#[cfg(feature = "smol")]
+pub use self::smol_runtime::{SmolRuntime, SmolTransport};
+
+#[cfg(feature = "smol")]
+mod smol_runtime {
+    use std::{io, path::PathBuf, sync::Arc, time::Duration};
+
+    use futures::future::BoxFuture;
+    use futures_rustls::{
+        rustls::{self, pki_types::ServerName, ClientConfig, RootCertStore},
+        TlsConnector,
+    };
+    use smol::net::TcpStream;
+
+    use super::{BoxAsyncRead, Connection, Runtime, Transport};
+    use crate::{DeepgramError, Result};
+
+    /// A [`Transport`] for the smol runtime, using `async-tungstenite` and rustls.
+    ///
+    /// Only available with the `smol` feature.
+    #[derive(Debug, Clone, Default)]
+    pub struct SmolTransport {
+        tls: Option<Arc<ClientConfig>>,
+    }
+
+    impl SmolTransport {
+        /// Construct a transport that verifies servers against the Mozilla root certificates.
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Construct a transport that uses `config` for `wss://` connections.
+        pub fn with_tls_config(config: Arc<ClientConfig>) -> Self {
+            Self { tls: Some(config) }
+        }
+
+        fn tls_config(&self) -> Result<Arc<ClientConfig>> {
+            if let Some(config) = &self.tls {
+                return Ok(Arc::clone(config));
+            }
+
+            let provider = rustls::crypto::CryptoProvider::get_default()
+                .cloned()
+                .unwrap_or_else(|| Arc::new(rustls::crypto::aws_lc_rs::default_provider()));
+            let roots = RootCertStore {
+                roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+            };
+            let config = ClientConfig::builder_with_provider(provider)
+                .with_safe_default_protocol_versions()
+                .map_err(|err| DeepgramError::InternalClientError(err.into()))?
+                .with_root_certificates(roots)
+                .with_no_client_auth();
+            Ok(Arc::new(config))
+        }
+    }
+
+    impl Transport for SmolTransport {
+        fn connect(&self, request: http::Request<()>) -> BoxFuture<'static, Result<Connection>> {
+            let tls = self.tls_config();
+            Box::pin(async move {
+                let uri = request.uri();
+                let secure = uri.scheme_str() == Some("wss");
+                let host = uri.host().ok_or(DeepgramError::InvalidUrl)?.to_string();
+                let port = uri.port_u16().unwrap_or(if secure { 443 } else { 80 });
+
+                let tcp = TcpStream::connect((host.as_str(), port)).await?;
+                let (socket, response) = if secure {
+                    let domain = ServerName::try_from(host)
+                        .map_err(|_| DeepgramError::InvalidUrl)?
+                        .to_owned();
+                    let tls = TlsConnector::from(tls?).connect(domain, tcp).await?;
+                    let (socket, response) = async_tungstenite::client_async(request, tls).await?;
+                    (Box::pin(socket) as super::BoxWebSocket, response)
+                } else {
+                    let (socket, response) = async_tungstenite::client_async(request, tcp).await?;
+                    (Box::pin(socket) as super::BoxWebSocket, response)
+                };
+
+                Ok(Connection { socket, response })
+            })
+        }
+    }
+
+    /// A [`Runtime`] backed by the smol global executor.
+    ///
+    /// Only available with the `smol` feature.
+    #[derive(Debug, Clone, Copy, Default)]
+    pub struct SmolRuntime;
+
+    impl Runtime for SmolRuntime {
+        fn spawn(&self, future: BoxFuture<'static, ()>) {
+            smol::spawn(future).detach();
+        }
+
+        fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+            Box::pin(async move {
+                smol::Timer::after(duration).await;
+            })
+        }
+
+        fn open_file(&self, path: PathBuf) -> BoxFuture<'static, io::Result<BoxAsyncRead>> {
+            Box::pin(async move {
+                let file = smol::fs::File::open(path).await?;
+                Ok(Box::pin(file) as BoxAsyncRead)
+            })
+        }
+
+        fn spawn_blocking(&self, task: Box<dyn FnOnce() + Send>) -> BoxFuture<'static, ()> {
+            Box::pin(smol::unblock(task))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{SinkExt, StreamExt};
+
+    use super::*;
+    use crate::listen::file_chunker::FileChunker;
+
+    async fn exercise(runtime: &dyn Runtime) {
+        let (tx, rx) = oneshot::channel();
+        let sleep = runtime.sleep(Duration::from_millis(20));
+        let start = runtime.now();
+        runtime.spawn(Box::pin(async move {
+            sleep.await;
+            let _ = tx.send(());
+        }));
+        rx.await.unwrap();
+        assert!(runtime.now() - start >= Duration::from_millis(20));
+
+        let file = runtime
+            .open_file(PathBuf::from("Cargo.toml"))
+            .await
+            .unwrap();
+        let contents: Vec<u8> = FileChunker::new(file, 64)
+            .map(|chunk| chunk.unwrap().to_vec())
+            .concat()
+            .await;
+        assert_eq!(contents, std::fs::read("Cargo.toml").unwrap());
+
+        assert_eq!(unblock(runtime, || 1 + 1).await.unwrap(), 2);
+    }
+
+    #[derive(Debug)]
+    struct Unresponsive;
+
+    impl Transport for Unresponsive {
+        fn connect(&self, _: http::Request<()>) -> BoxFuture<'static, Result<Connection>> {
+            Box::pin(future::pending())
+        }
+    }
+
+    #[derive(Debug)]
+    struct Unauthorized;
+
+    impl Transport for Unauthorized {
+        fn connect(&self, _: http::Request<()>) -> BoxFuture<'static, Result<Connection>> {
+            let body = r#"{"err_code":"INVALID_AUTH","err_msg":"Invalid credentials."}"#;
+            let response = http::Response::builder()
+                .status(401)
+                .body(Some(body.as_bytes().to_vec()))
+                .unwrap();
+            Box::pin(async move { Err(WsError::Http(Box::new(response)).into()) })
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn connect_times_out() {
+        let request = http::Request::new(());
+        let err = connect(
+            &Unresponsive,
+            &TokioRuntime,
+            request,
+            Some(Duration::from_secs(5)),
+        )
+        .await
+        .unwrap_err();
+
+        assert!(matches!(
+            err,
+            DeepgramError::ConnectTimeout { timeout } if timeout == Duration::from_secs(5)
+        ));
+        assert!(err.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn reports_rejected_handshake() {
+        let request = http::Request::new(());
+        let err = connect(
+            &Unauthorized,
+            &TokioRuntime,
+            request,
+            Some(Duration::from_secs(5)),
+        )
+        .await
+        .unwrap_err();
+
+        assert!(matches!(err, DeepgramError::WebsocketHandshake { .. }));
+        assert_eq!(err.status_code(), Some(http::StatusCode::UNAUTHORIZED));
+        assert_eq!(err.error_code().as_deref(), Some("INVALID_AUTH"));
+        assert!(!err.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn tokio_runtime() {
+        exercise(&TokioRuntime).await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn tokio_runtime_follows_paused_clock() {
+        let runtime = TokioRuntime;
+        let start = runtime.now();
+        tokio::time::advance(Duration::from_secs(60)).await;
+        assert_eq!(runtime.now() - start, Duration::from_secs(60));
+    }
+
+    #[tokio::test]
+    #[allow(clippy::result_large_err)] // The handshake callback's error type is tungstenite's.
+    async fn proxy_transport_tunnels_websocket() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let proxy: Url = format!("http://user:p%40ss@{}", listener.local_addr().unwrap())
+            .parse()
+            .unwrap();
+
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut head = Vec::new();
+            while !head.ends_with(b"\r\n\r\n") {
+                head.push(stream.read_u8().await.unwrap());
+            }
+            stream
+                .write_all(b"HTTP/1.1 200 Connection established\r\n\r\n")
+                .await
+                .unwrap();
+
+            let mut socket = tokio_tungstenite::accept_hdr_async(
+                stream,
+                |_: &tungstenite::handshake::server::Request,
+                 mut response: tungstenite::handshake::server::Response| {
+                    response
+                        .headers_mut()
+                        .insert("dg-request-id", "test".parse().unwrap());
+                    Ok(response)
+                },
+            )
+            .await
+            .unwrap();
+            socket.send(Message::text("hello")).await.unwrap();
+            String::from_utf8(head).unwrap()
+        });
+
+        let dg = crate::Deepgram::builder()
+            .base_url("http://deepgram.internal".parse().unwrap())
+            .proxy(proxy)
+            .build()
+            .unwrap();
+        let request = http::Request::builder()
+            .uri("ws://deepgram.internal/v1/listen")
+            .header("host", "deepgram.internal")
+            .header("connection", "upgrade")
+            .header("upgrade", "websocket")
+            .header("sec-websocket-version", "13")
+            .header(
+                "sec-websocket-key",
+                tungstenite::handshake::client::generate_key(),
+            )
+            .body(())
+            .unwrap();
+
+        let mut connection = default_transport(dg.proxy.as_ref(), dg.tls.as_ref())
+            .connect(request)
+            .await
+            .unwrap();
+        assert_eq!(connection.response.headers()["dg-request-id"], "test");
+        assert_eq!(
+            connection.socket.next().await.unwrap().unwrap(),
+            Message::text("hello")
+        );
+
+        let head = server.await.unwrap();
+        assert!(head.starts_with("CONNECT deepgram.internal:80 HTTP/1.1\r\n"));
+        // "user:p@ss"
+        assert!(head.contains("Proxy-Authorization: Basic dXNlcjpwQHNz\r\n"));
+    }
+
+    #[cfg(feature = "smol")]
+    #[test]
+    fn smol_runtime() {
+        smol::block_on(exercise(&SmolRuntime));
+    }
+}
@@ -0,0 +1,300 @@
+//! Reads the RIFF header at the start of streamed WAV audio, and writes
+//! headers for raw audio.
+//!
+//! Used by [`WebsocketBuilder::detect_wav_header`] to configure the encoding
+//! parameters of a connection from the header, and to keep the header from
+//! being sent as audio.
+//!
+//! [`WebsocketBuilder::detect_wav_header`]: crate::listen::websocket::WebsocketBuilder::detect_wav_header
+
+use std::error::Error;
+
+use bytes::{Bytes, BytesMut};
+use futures::{Stream, StreamExt};
+
+use crate::{common::options::Encoding, DeepgramError, Result};
+
+/// Give up on headers that don't reach the sample data within this many bytes.
+const MAX_HEADER_LEN: usize = 1 << 20;
+
+pub(crate) const WAVE_FORMAT_PCM: u16 = 0x0001;
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 0x0003;
+const WAVE_FORMAT_MULAW: u16 = 0x0007;
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// The encoding parameters described by a WAV header.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct WavHeader {
+    pub(crate) encoding: Encoding,
+    pub(crate) sample_rate: u32,
+    pub(crate) channels: u16,
+}
+
+#[derive(Debug, PartialEq)]
+enum Parsed {
+    /// More bytes are needed to tell.
+    Incomplete,
+    /// The bytes don't start with a RIFF/WAVE header.
+    NotWav,
+    /// The sample data starts at `data_offset`.
+    Wav {
+        header: WavHeader,
+        data_offset: usize,
+    },
+}
+
+/// Read from `stream` until the end of a WAV header.
+///
+/// Returns the header, if the audio starts with one, along with the audio
+/// read past it, which must be sent before the rest of `stream`. Audio that
+/// doesn't start with a WAV header is returned in full.
+pub(crate) async fn read_wav_header<S, E>(stream: &mut S) -> Result<(Option<WavHeader>, Bytes)>
+where
+    S: Stream<Item = std::result::Result<Bytes, E>> + Unpin,
+    E: Error + Send + Sync + 'static,
+{
+    let mut buf = BytesMut::new();
+
+    loop {
+        match parse(&buf)? {
+            Parsed::Incomplete => {}
+            Parsed::NotWav => return Ok((None, buf.freeze())),
+            Parsed::Wav {
+                header,
+                data_offset,
+            } => return Ok((Some(header), buf.freeze().split_off(data_offset))),
+        }
+
+        if buf.len() > MAX_HEADER_LEN {
+            return Err(DeepgramError::UnsupportedAudio(String::from(
+                "WAV header is too long",
+            )));
+        }
+
+        match stream.next().await {
+            Some(Ok(chunk)) => buf.extend_from_slice(&chunk),
+            Some(Err(err)) => {
+                return Err(DeepgramError::from(
+                    Box::new(err) as Box<dyn Error + Send + Sync + 'static>
+                ))
+            }
+            None if buf.len() < 12 => return Ok((None, buf.freeze())),
+            None => {
+                return Err(DeepgramError::UnsupportedAudio(String::from(
+                    "WAV header ends before the sample data",
+                )))
+            }
+        }
+    }
+}
+
+fn parse(buf: &[u8]) -> Result<Parsed> {
+    if !b"RIFF".starts_with(&buf[..buf.len().min(4)])
+        || (buf.len() > 8 && !b"WAVE".starts_with(&buf[8..buf.len().min(12)]))
+    {
+        return Ok(Parsed::NotWav);
+    }
+
+    let mut format = None;
+    let mut offset = 12;
+    while let Some(chunk) = buf.get(offset..offset + 8) {
+        let id = &chunk[..4];
+        let len = u32::from_le_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]) as usize;
+        let body = offset + 8;
+
+        match id {
+            b"data" => {
+                let header = format.ok_or_else(|| {
+                    DeepgramError::UnsupportedAudio(String::from(
+                        "WAV header has no format before the sample data",
+                    ))
+                })?;
+                return Ok(Parsed::Wav {
+                    header,
+                    data_offset: body,
+                });
+            }
+            b"fmt " => match buf.get(body..body + len) {
+                Some(fmt) => format = Some(parse_format(fmt)?),
+                None => return Ok(Parsed::Incomplete),
+            },
+            _ => {}
+        }
+
+        // Chunks are padded to an even length.
+        offset = body + len + len % 2;
+    }
+
+    Ok(Parsed::Incomplete)
+}
+
+fn parse_format(fmt: &[u8]) -> Result<WavHeader> {
+    let u16_at = |at: usize| {
+        fmt.get(at..at + 2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]))
+    };
+    let invalid = || DeepgramError::UnsupportedAudio(String::from("WAV format chunk is invalid"));
+
+    let mut format_tag = u16_at(0).ok_or_else(invalid)?;
+    let channels = u16_at(2).ok_or_else(invalid)?;
+    let sample_rate = fmt
+        .get(4..8)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(invalid)?;
+    let bits_per_sample = u16_at(14).ok_or_else(invalid)?;
+
+    if format_tag == WAVE_FORMAT_EXTENSIBLE {
+        // The real format tag starts the subformat GUID.
+        format_tag = u16_at(24).ok_or_else(invalid)?;
+    }
+
+    let encoding = match (format_tag, bits_per_sample) {
+        (WAVE_FORMAT_PCM, 16) => Encoding::Linear16,
+        (WAVE_FORMAT_IEEE_FLOAT, 32) => Encoding::Linear32,
+        (WAVE_FORMAT_MULAW, 8) => Encoding::Mulaw,
+        (format_tag, bits) => {
+            return Err(DeepgramError::UnsupportedAudio(format!(
+                "{bits}-bit WAV samples with format tag {format_tag:#06x} cannot be streamed"
+            )))
+        }
+    };
+
+    Ok(WavHeader {
+        encoding,
+        sample_rate,
+        channels,
+    })
+}
+
+/// How raw audio is described in a WAV header.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct WavFormat {
+    pub(crate) format_tag: u16,
+    pub(crate) bits_per_sample: u16,
+    pub(crate) sample_rate: u32,
+    pub(crate) channels: u16,
+}
+
+impl WavFormat {
+    /// The format of raw `encoding` audio, if it is one a WAV file can hold.
+    pub(crate) fn new(
+        encoding: Option<&Encoding>,
+        sample_rate: Option<u32>,
+        channels: Option<u16>,
+    ) -> Option<Self> {
+        let (format_tag, bits_per_sample) = match encoding? {
+            Encoding::Linear16 => (WAVE_FORMAT_PCM, 16),
+            Encoding::Linear32 => (WAVE_FORMAT_IEEE_FLOAT, 32),
+            Encoding::Mulaw => (WAVE_FORMAT_MULAW, 8),
+            _ => return None,
+        };
+
+        Some(WavFormat {
+            format_tag,
+            bits_per_sample,
+            sample_rate: sample_rate?,
+            channels: channels.unwrap_or(1),
+        })
+    }
+
+    /// A 44 byte header for `data_len` bytes of samples.
+    pub(crate) fn header(&self, data_len: u32) -> [u8; 44] {
+        let block_align = self.channels * self.bits_per_sample / 8;
+
+        let mut header = [0; 44];
+        header[..4].copy_from_slice(b"RIFF");
+        header[4..8].copy_from_slice(&data_len.saturating_add(36).to_le_bytes());
+        header[8..16].copy_from_slice(b"WAVEfmt ");
+        header[16..20].copy_from_slice(&16u32.to_le_bytes());
+        header[20..22].copy_from_slice(&self.format_tag.to_le_bytes());
+        header[22..24].copy_from_slice(&self.channels.to_le_bytes());
+        header[24..28].copy_from_slice(&self.sample_rate.to_le_bytes());
+        header[28..32].copy_from_slice(&(self.sample_rate * u32::from(block_align)).to_le_bytes());
+        header[32..34].copy_from_slice(&block_align.to_le_bytes());
+        header[34..36].copy_from_slice(&self.bits_per_sample.to_le_bytes());
+        header[36..40].copy_from_slice(b"data");
+        header[40..44].copy_from_slice(&data_len.to_le_bytes());
+        header
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::{parse, read_wav_header, Parsed, WavHeader};
+    use crate::{common::options::Encoding, DeepgramError};
+
+    fn wav(format_tag: u16, channels: u16, sample_rate: u32, bits: u16, data: &[u8]) -> Vec<u8> {
+        let mut wav = Vec::new();
+        wav.extend_from_slice(b"RIFF\0\0\0\0WAVE");
+        wav.extend_from_slice(b"LIST\x03\0\0\0abc\0");
+        wav.extend_from_slice(b"fmt \x10\0\0\0");
+        wav.extend_from_slice(&format_tag.to_le_bytes());
+        wav.extend_from_slice(&channels.to_le_bytes());
+        wav.extend_from_slice(&sample_rate.to_le_bytes());
+        wav.extend_from_slice(&[0; 6]);
+        wav.extend_from_slice(&bits.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        wav.extend_from_slice(data);
+        wav
+    }
+
+    #[test]
+    fn parses_header() {
+        let wav = wav(1, 2, 16_000, 16, b"abcd");
+
+        assert_eq!(parse(&wav[..20]).unwrap(), Parsed::Incomplete);
+        assert_eq!(
+            parse(&wav).unwrap(),
+            Parsed::Wav {
+                header: WavHeader {
+                    encoding: Encoding::Linear16,
+                    sample_rate: 16_000,
+                    channels: 2,
+                },
+                data_offset: wav.len() - 4,
+            }
+        );
+        assert_eq!(parse(b"OggS").unwrap(), Parsed::NotWav);
+        assert_eq!(parse(b"RIFF\0\0\0\0AVI ").unwrap(), Parsed::NotWav);
+    }
+
+    #[test]
+    fn rejects_unsupported_samples() {
+        assert!(matches!(
+            parse(&wav(1, 1, 44_100, 24, b"")),
+            Err(DeepgramError::UnsupportedAudio(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn strips_header_across_chunks() {
+        let wav = Bytes::from(wav(7, 1, 8_000, 8, b"abcd"));
+        let mut stream = futures::stream::iter(
+            wav.chunks(5)
+                .map(|chunk| Ok::<_, std::io::Error>(Bytes::copy_from_slice(chunk)))
+                .collect::<Vec<_>>(),
+        );
+
+        let (header, rest) = read_wav_header(&mut stream).await.unwrap();
+        assert_eq!(header.unwrap().encoding, Encoding::Mulaw);
+
+        let mut data = rest.to_vec();
+        while let Some(chunk) = futures::StreamExt::next(&mut stream).await {
+            data.extend_from_slice(&chunk.unwrap());
+        }
+        assert_eq!(data, b"abcd");
+    }
+
+    #[tokio::test]
+    async fn passes_through_other_audio() {
+        let mut stream =
+            futures::stream::iter([Ok::<_, std::io::Error>(Bytes::from_static(b"OggS..."))]);
+
+        let (header, rest) = read_wav_header(&mut stream).await.unwrap();
+        assert_eq!(header, None);
+        assert_eq!(rest, Bytes::from_static(b"OggS..."));
+    }
+}
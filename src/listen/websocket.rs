@@ -9,63 +9,352 @@
 //! [api]: https://developers.deepgram.com/api-reference/#transcription-streaming
 
 use std::{
+    borrow::Cow,
+    collections::{HashMap, VecDeque},
+    convert::Infallible,
     error::Error,
     fmt,
-    ops::Deref,
-    path::Path,
+    future::Future,
+    io::Write,
+    ops::{ControlFlow, Deref},
+    path::{Path, PathBuf},
     pin::Pin,
+    sync::{Arc, Mutex},
     task::{Context, Poll},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use anyhow::anyhow;
 use bytes::Bytes;
 use futures::{
-    channel::mpsc::{self, Receiver, Sender},
-    future::{pending, FutureExt},
+    channel::{
+        mpsc::{self, Receiver, SendError, Sender, UnboundedSender},
+        oneshot,
+    },
+    future::{pending, poll_fn, BoxFuture, FutureExt},
     select_biased,
     stream::StreamExt,
-    SinkExt, Stream,
+    Sink, SinkExt, Stream,
 };
-use http::Request;
+use http::{HeaderMap, Request};
 use pin_project::pin_project;
+use serde::de::DeserializeOwned;
 use serde_urlencoded;
-use tokio::fs::File;
-use tokio_tungstenite::{tungstenite::protocol::Message, MaybeTlsStream, WebSocketStream};
 use tungstenite::{
     handshake::client,
-    protocol::frame::coding::{Data, OpCode},
+    protocol::{
+        frame::coding::{Data, OpCode},
+        Message,
+    },
     Utf8Bytes,
 };
 use url::Url;
 use uuid::Uuid;
 
-use self::file_chunker::FileChunker;
 use crate::{
+    client::add_missing_headers,
     common::{
-        options::{Encoding, Endpointing, Options},
-        stream_response::StreamResponse,
+        options::{Callback, CallbackMethod, Encoding, Endpointing, Options},
+        stream_response::{ModelInfo, StreamResponse, Word},
+    },
+    listen::{
+        audio_tee::AudioTee,
+        file_chunker::FileChunker,
+        parse_response,
+        spool::AudioSpool,
+        transport::{self, BoxWebSocket, Runtime, Transport},
+        wav_header::read_wav_header,
+        LiveError,
     },
-    Deepgram, DeepgramError, Result, Transcription,
+    Deepgram, DeepgramError, Metrics, RedactedUrl, Result, Transcription,
 };
 
 static LIVE_LISTEN_URL_PATH: &str = "v1/listen";
 
-#[derive(Clone, Debug)]
+/// The smallest `utterance_end_ms` Deepgram accepts.
+const MIN_UTTERANCE_END_MS: u32 = 1000;
+
+/// How long to wait for an answer to a ping unless [`WebsocketBuilder::pong_timeout`] is set.
+const DEFAULT_PONG_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long a connection with keep-alive on may go without sending anything
+/// before a KeepAlive message is sent.
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(3);
+
+/// How loud audio can be and still count as silence unless
+/// [`WebsocketBuilder::silence_threshold`] is set.
+const DEFAULT_SILENCE_THRESHOLD: f32 = 0.01;
+
+#[derive(Clone)]
 pub struct WebsocketBuilder<'a> {
-    deepgram: &'a Deepgram,
+    deepgram: Cow<'a, Deepgram>,
     options: Options,
     encoding: Option<Encoding>,
     sample_rate: Option<u32>,
     channels: Option<u16>,
     endpointing: Option<Endpointing>,
-    utterance_end_ms: Option<u16>,
+    utterance_end_ms: Option<u32>,
     interim_results: Option<bool>,
     no_delay: Option<bool>,
     vad_events: Option<bool>,
     stream_url: Url,
     keep_alive: Option<bool>,
-    callback: Option<Url>,
+    debug_logging: Option<bool>,
+    resilient_parsing: Option<bool>,
+    detect_wav_header: Option<bool>,
+    spill_to_disk: Option<usize>,
+    tee: Option<AudioTee>,
+    audio_buffer: Option<usize>,
+    response_buffer: Option<usize>,
+    backpressure: Option<Backpressure>,
+    reconnect: Option<ReconnectPolicy>,
+    connect_timeout: Option<Duration>,
+    drain_timeout: Option<Duration>,
+    ping_interval: Option<Duration>,
+    pong_timeout: Option<Duration>,
+    extra_query_params: Vec<(String, String)>,
+    extra_headers: HeaderMap,
+    track_metrics: Option<bool>,
+    finalize_on_silence: Option<Duration>,
+    silence_threshold: Option<f32>,
+    subprotocol_auth: Option<bool>,
+    handlers: EventHandlers,
+    callback: Option<Callback>,
+    transport: Arc<dyn Transport>,
+    runtime: Arc<dyn Runtime>,
+}
+
+/// How [`WebsocketBuilder::reconnect`] re-dials a dropped connection.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReconnectPolicy {
+    max_attempts: u32,
+    backoff: Duration,
+    max_backoff: Duration,
+    stagger: Duration,
+}
+
+impl ReconnectPolicy {
+    /// Try to reconnect up to `max_attempts` times after each drop, waiting
+    /// half a second before the first attempt and doubling the wait after
+    /// each failure, up to ten seconds.
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts,
+            backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+            stagger: Duration::ZERO,
+        }
+    }
+
+    /// Set how long to wait before the first attempt.
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+
+        self
+    }
+
+    /// Set the longest wait between attempts.
+    pub fn max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+
+        self
+    }
+
+    /// Wait an extra `stagger` before the first attempt after each drop.
+    ///
+    /// Connections that drop at the same time, for example when a server
+    /// restarts, would otherwise all reconnect at the same time. Giving them
+    /// different staggers spreads their reconnects out.
+    /// [`SessionManager`](crate::listen::sessions::SessionManager) does this
+    /// for the sessions it starts.
+    pub fn stagger(mut self, stagger: Duration) -> Self {
+        self.stagger = stagger;
+
+        self
+    }
+
+    /// How long to wait before `attempt`, counting from 1.
+    fn delay(&self, attempt: u32) -> Duration {
+        let delay = self
+            .backoff
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_backoff);
+        if attempt == 1 {
+            delay + self.stagger
+        } else {
+            delay
+        }
+    }
+
+    /// Call `dial` until it succeeds or runs out of attempts, returning what
+    /// it opened along with the number of attempts it took.
+    pub(crate) async fn retry<T, F>(
+        &self,
+        runtime: &dyn Runtime,
+        mut dial: impl FnMut() -> F,
+    ) -> Result<(T, u32)>
+    where
+        F: Future<Output = Result<T>>,
+    {
+        let mut attempt = 1;
+        loop {
+            runtime.sleep(self.delay(attempt)).await;
+
+            match dial().await {
+                Ok(opened) => return Ok((opened, attempt)),
+                Err(err) if attempt >= self.max_attempts => return Err(err),
+                Err(_) => attempt += 1,
+            }
+        }
+    }
+}
+
+/// A builder that a [`Reconnector`] can redial.
+pub(crate) trait Redial: fmt::Debug + Send + Sync {
+    /// The handle to a connection opened by the builder.
+    type Handle: Send;
+
+    /// The runtime to wait on between attempts.
+    fn runtime(&self) -> &dyn Runtime;
+
+    /// Open a new connection.
+    fn dial(&self) -> BoxFuture<'_, Result<Self::Handle>>;
+}
+
+/// Re-dials dropped connections for [`WebsocketBuilder::reconnect`] and
+/// [`FluxBuilder::reconnect`].
+///
+/// [`FluxBuilder::reconnect`]: crate::listen::flux::FluxBuilder::reconnect
+#[derive(Debug)]
+pub(crate) struct Reconnector<B> {
+    builder: B,
+    policy: ReconnectPolicy,
+}
+
+impl<B: Redial> Reconnector<B> {
+    pub(crate) fn new(builder: B, policy: ReconnectPolicy) -> Self {
+        Reconnector { builder, policy }
+    }
+
+    /// Handle the connection closing with `err`, returning whether the
+    /// stream should keep going.
+    ///
+    /// The stream keeps going if the close is retryable, since the
+    /// connection is redialed once its responses end. Otherwise redialing
+    /// won't help, such as after a policy violation, so `err` is passed along
+    /// and the responses end.
+    pub(crate) async fn closed<R>(
+        &self,
+        err: DeepgramError,
+        tx: &mut Sender<Result<R>>,
+    ) -> ControlFlow<()> {
+        if err.is_retryable() {
+            return ControlFlow::Continue(());
+        }
+
+        let _ = tx.send(Err(err)).await;
+        tx.close_channel();
+        ControlFlow::Break(())
+    }
+
+    /// Open a new connection after the old one's responses ended, and
+    /// announce it with the response made by `reconnected`, which is passed
+    /// the new handle and the number of attempts it took.
+    ///
+    /// Returns `None` if every attempt failed, after passing along the last
+    /// error, or if responses are no longer being received.
+    pub(crate) async fn redial<R>(
+        &self,
+        tx: &mut Sender<Result<R>>,
+        reconnected: impl FnOnce(&B::Handle, u32) -> R,
+    ) -> Option<B::Handle> {
+        match self
+            .policy
+            .retry(self.builder.runtime(), || self.builder.dial())
+            .await
+        {
+            Ok((handle, attempts)) => {
+                tx.send(Ok(reconnected(&handle, attempts))).await.ok()?;
+                Some(handle)
+            }
+            Err(err) => {
+                let _ = tx.send(Err(err)).await;
+                None
+            }
+        }
+    }
+}
+
+/// What [`WebsocketBuilder::stream`] does with audio from the source while
+/// the connection isn't accepting any more.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Backpressure {
+    /// Stop polling the source until the connection has room again.
+    ///
+    /// No audio is lost, but a live source has to hold on to its audio, or
+    /// drop it, while it waits.
+    #[default]
+    Block,
+
+    /// Keep polling the source, queueing up to this many frames and dropping
+    /// the oldest queued frame to make room for each new one.
+    ///
+    /// Suits live audio, where keeping up with the speaker matters more than
+    /// transcribing every frame. At least one frame is always queued.
+    DropOldest(usize),
+}
+
+impl fmt::Debug for WebsocketBuilder<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebsocketBuilder")
+            .field("deepgram", &self.deepgram)
+            .field("options", &self.options)
+            .field("encoding", &self.encoding)
+            .field("sample_rate", &self.sample_rate)
+            .field("channels", &self.channels)
+            .field("endpointing", &self.endpointing)
+            .field("utterance_end_ms", &self.utterance_end_ms)
+            .field("interim_results", &self.interim_results)
+            .field("no_delay", &self.no_delay)
+            .field("vad_events", &self.vad_events)
+            .field("stream_url", &RedactedUrl(&self.stream_url))
+            .field("keep_alive", &self.keep_alive)
+            .field("debug_logging", &self.debug_logging)
+            .field("resilient_parsing", &self.resilient_parsing)
+            .field("detect_wav_header", &self.detect_wav_header)
+            .field("spill_to_disk", &self.spill_to_disk)
+            .field("tee", &self.tee)
+            .field("audio_buffer", &self.audio_buffer)
+            .field("response_buffer", &self.response_buffer)
+            .field("backpressure", &self.backpressure)
+            .field("reconnect", &self.reconnect)
+            .field("connect_timeout", &self.connect_timeout)
+            .field("drain_timeout", &self.drain_timeout)
+            .field("ping_interval", &self.ping_interval)
+            .field("pong_timeout", &self.pong_timeout)
+            .field(
+                "extra_query_params",
+                &self
+                    .extra_query_params
+                    .iter()
+                    .map(|(name, _)| name)
+                    .collect::<Vec<_>>(),
+            )
+            .field(
+                "extra_headers",
+                &self.extra_headers.keys().collect::<Vec<_>>(),
+            )
+            .field("track_metrics", &self.track_metrics)
+            .field("finalize_on_silence", &self.finalize_on_silence)
+            .field("silence_threshold", &self.silence_threshold)
+            .field("subprotocol_auth", &self.subprotocol_auth)
+            .field("handlers", &self.handlers)
+            .field("callback", &self.callback)
+            .field("transport", &self.transport)
+            .field("runtime", &self.runtime)
+            .finish()
+    }
 }
 
 impl Transcription<'_> {
@@ -134,8 +423,8 @@ impl Transcription<'_> {
     /// ```
     pub fn stream_request_with_options(&self, options: Options) -> WebsocketBuilder<'_> {
         WebsocketBuilder {
-            deepgram: self.0,
-            options,
+            deepgram: Cow::Borrowed(self.0),
+            options: self.0.options_with_defaults(&options).into_owned(),
             encoding: None,
             sample_rate: None,
             channels: None,
@@ -146,14 +435,36 @@ impl Transcription<'_> {
             vad_events: None,
             stream_url: self.listen_stream_url(),
             keep_alive: None,
+            debug_logging: None,
+            resilient_parsing: None,
+            detect_wav_header: None,
+            spill_to_disk: None,
+            tee: None,
+            audio_buffer: None,
+            response_buffer: None,
+            backpressure: None,
+            reconnect: None,
+            connect_timeout: None,
+            drain_timeout: None,
+            ping_interval: None,
+            pong_timeout: None,
+            extra_query_params: Vec::new(),
+            extra_headers: HeaderMap::new(),
+            track_metrics: None,
+            finalize_on_silence: None,
+            silence_threshold: None,
+            subprotocol_auth: None,
+            handlers: EventHandlers::default(),
             callback: None,
+            transport: transport::default_transport(self.0.proxy.as_ref(), self.0.tls.as_ref()),
+            runtime: self.0.client.runtime(),
         }
     }
 
     fn listen_stream_url(&self) -> Url {
         // base
         let mut url =
-            self.0.base_url.join(LIVE_LISTEN_URL_PATH).expect(
+            self.0.listen_base_url.join(LIVE_LISTEN_URL_PATH).expect(
                 "base_url is checked to be a valid base_url when constructing Deepgram client",
             );
 
@@ -208,6 +519,28 @@ impl WebsocketBuilder<'_> {
         let Self {
             deepgram: _,
             keep_alive: _,
+            debug_logging: _,
+            resilient_parsing: _,
+            detect_wav_header: _,
+            spill_to_disk: _,
+            tee: _,
+            audio_buffer: _,
+            response_buffer: _,
+            backpressure: _,
+            reconnect: _,
+            connect_timeout: _,
+            drain_timeout: _,
+            ping_interval: _,
+            pong_timeout: _,
+            extra_query_params,
+            extra_headers: _,
+            track_metrics: _,
+            finalize_on_silence: _,
+            silence_threshold: _,
+            subprotocol_auth: _,
+            handlers: _,
+            transport: _,
+            runtime: _,
             options,
             encoding,
             sample_rate,
@@ -232,9 +565,13 @@ impl WebsocketBuilder<'_> {
             //
             // TODO: We should be able to lean on the serde more
             // to avoid multiple serialization rounds.
+            // Streaming callbacks are always sent with POST, so no method
+            // is sent.
             pairs.extend_pairs(
                 serde_urlencoded::from_str::<Vec<(String, String)>>(&options.urlencoded()?)
-                    .expect("constructed query string can be deserialized"),
+                    .expect("constructed query string can be deserialized")
+                    .into_iter()
+                    .filter(|(key, _)| key != "callback_method"),
             );
 
             // Add streaming-specific options
@@ -263,8 +600,10 @@ impl WebsocketBuilder<'_> {
                 pairs.append_pair("vad_events", &vad_events.to_string());
             }
             if let Some(callback) = callback {
-                pairs.append_pair("callback", callback.as_ref());
+                pairs.append_pair("callback", callback.url().as_str());
             }
+
+            pairs.extend_pairs(extra_query_params);
         }
 
         Ok(url)
@@ -294,7 +633,12 @@ impl WebsocketBuilder<'_> {
         self
     }
 
-    pub fn utterance_end_ms(mut self, utterance_end_ms: u16) -> Self {
+    /// Send an UtteranceEnd message once `utterance_end_ms` milliseconds
+    /// have passed without a new word after the last finalized one.
+    ///
+    /// Deepgram requires at least 1000 milliseconds. Smaller values are
+    /// rejected with [`DeepgramError::InvalidOption`] when connecting.
+    pub fn utterance_end_ms(mut self, utterance_end_ms: u32) -> Self {
         self.utterance_end_ms = Some(utterance_end_ms);
 
         self
@@ -318,604 +662,3362 @@ impl WebsocketBuilder<'_> {
         self
     }
 
+    /// Add `params` to the query string of the upgrade request, after the
+    /// options set on the builder.
+    ///
+    /// Use this for parameters required by a gateway in front of a
+    /// self-hosted instance, or for API parameters this version of the SDK
+    /// doesn't support yet. Calling this again adds to the parameters set
+    /// before.
+    pub fn extra_query_params(
+        mut self,
+        params: impl IntoIterator<Item = (String, String)>,
+    ) -> Self {
+        self.extra_query_params.extend(params);
+
+        self
+    }
+
+    /// Send `headers` with the upgrade request.
+    ///
+    /// These take precedence over the client's
+    /// [default headers](crate::DeepgramBuilder::default_headers), but not
+    /// over headers set by the SDK itself, such as `Authorization` and
+    /// `User-Agent`. Calling this again adds to the headers set before,
+    /// replacing any with the same name.
+    pub fn extra_headers(mut self, headers: HeaderMap) -> Self {
+        for (name, value) in &headers {
+            self.extra_headers.insert(name, value.clone());
+        }
+
+        self
+    }
+
     pub fn keep_alive(mut self) -> Self {
         self.keep_alive = Some(true);
 
         self
     }
 
-    pub fn callback(mut self, callback: Url) -> Self {
+    /// Log websocket protocol traffic at the `DEBUG` level using [`tracing`].
+    ///
+    /// Every control message sent (`KeepAlive`, `Finalize`, `CloseStream`) and
+    /// the type of every event received is logged along with the request ID and
+    /// the time elapsed since the connection opened. Audio is not logged.
+    ///
+    /// Events are emitted with the `deepgram::listen::websocket` target, so they
+    /// can be enabled with e.g. `RUST_LOG=deepgram::listen::websocket=debug` when
+    /// using `tracing-subscriber`.
+    pub fn debug_logging(mut self) -> Self {
+        self.debug_logging = Some(true);
+
+        self
+    }
+
+    /// Report messages that fail to deserialize as
+    /// [`DeepgramError::UnparseableResponse`], which carries the raw payload.
+    ///
+    /// The stream keeps running after such an error, so a caller can log or
+    /// inspect the payload and continue polling rather than ending the session.
+    /// Without this, the error is reported as [`DeepgramError::JsonError`].
+    pub fn resilient_parsing(mut self) -> Self {
+        self.resilient_parsing = Some(true);
+
+        self
+    }
+
+    /// Read the header of WAV audio passed to [`WebsocketBuilder::stream`] or
+    /// [`WebsocketBuilder::file`] before connecting, and use it to set the
+    /// encoding, sample rate, and channel count.
+    ///
+    /// The header is not sent to Deepgram, only the sample data after it.
+    /// Audio that doesn't start with a WAV header is sent unchanged. WAV files
+    /// must hold 16-bit integer, 32-bit floating point, or 8-bit mu-law
+    /// samples, otherwise connecting fails with
+    /// [`DeepgramError::UnsupportedAudio`].
+    ///
+    /// Has no effect on [`WebsocketBuilder::connect`],
+    /// [`WebsocketBuilder::handle`], or [`WebsocketBuilder::prewarm`], which
+    /// connect before any audio is available.
+    pub fn detect_wav_header(mut self) -> Self {
+        self.detect_wav_header = Some(true);
+
+        self
+    }
+
+    /// Keep reading from the audio source passed to [`WebsocketBuilder::stream`]
+    /// while the connection isn't accepting audio, queueing up to
+    /// `memory_limit` bytes in memory and the rest in a temporary file.
+    ///
+    /// Without this, a stalled connection stops the source from being polled,
+    /// so a live source has to either buffer without bound or drop audio.
+    /// Queued audio is sent in order once the connection has room for it
+    /// again, and the temporary file is deleted when the stream ends.
+    ///
+    /// Takes precedence over [`WebsocketBuilder::backpressure`].
+    pub fn spill_to_disk(mut self, memory_limit: usize) -> Self {
+        self.spill_to_disk = Some(memory_limit);
+
+        self
+    }
+
+    /// Copy every chunk of audio sent to Deepgram into `writer`, for
+    /// debugging.
+    ///
+    /// Replaying the copy shows exactly what Deepgram heard, including any
+    /// WAV header that was sent. Audio sent after a
+    /// [reconnect](WebsocketBuilder::reconnect) is appended. The writer is
+    /// called with [`Runtime::spawn_blocking`], so it may block without
+    /// holding up the connection, and is flushed when each connection
+    /// closes. If writing fails, the error is logged and nothing more is
+    /// copied. Replaces any earlier [`WebsocketBuilder::tee_audio_wav`].
+    pub fn tee_audio(mut self, writer: impl Write + Send + 'static) -> Self {
+        self.tee = Some(AudioTee::writer(writer));
+
+        self
+    }
+
+    /// Like [`WebsocketBuilder::tee_audio`], but copies the audio into a file
+    /// at `path`.
+    ///
+    /// The file is created when connecting, replacing any existing file.
+    /// Raw linear16, linear32, or mulaw audio with a known
+    /// [sample rate](WebsocketBuilder::sample_rate) gets a WAV header, so the
+    /// file can be played directly once the connection closes. Any other
+    /// audio is copied as is.
+    pub fn tee_audio_wav(mut self, path: impl Into<PathBuf>) -> Self {
+        self.tee = Some(AudioTee::wav(path.into()));
+
+        self
+    }
+
+    /// Queue up to `frames` chunks of audio for the connection before sending
+    /// more has to wait. Defaults to 256.
+    ///
+    /// A larger buffer absorbs longer network stalls at the cost of memory
+    /// and latency once the connection catches up.
+    pub fn audio_buffer(mut self, frames: usize) -> Self {
+        self.audio_buffer = Some(frames);
+
+        self
+    }
+
+    /// Hold up to `responses` responses that haven't been read yet before
+    /// the connection stops reading from Deepgram. Defaults to 256.
+    pub fn response_buffer(mut self, responses: usize) -> Self {
+        self.response_buffer = Some(responses);
+
+        self
+    }
+
+    /// Set what [`WebsocketBuilder::stream`] does with audio once the
+    /// [audio buffer](WebsocketBuilder::audio_buffer) is full. Defaults to
+    /// [`Backpressure::Block`].
+    ///
+    /// Has no effect on [`WebsocketHandle::send_data`], which always waits
+    /// for room in the buffer.
+    pub fn backpressure(mut self, backpressure: Backpressure) -> Self {
+        self.backpressure = Some(backpressure);
+
+        self
+    }
+
+    /// Re-dial the connection with the same options if it drops before the
+    /// audio source passed to [`WebsocketBuilder::stream`] is finished.
+    ///
+    /// Each time the connection is re-established, the stream yields a
+    /// [`StreamResponse::Reconnected`] with the new request ID, then continues
+    /// sending audio from the source, including any queued by
+    /// [`WebsocketBuilder::spill_to_disk`]. Audio still queued for the old
+    /// connection is sent on the new one first, but audio that was already
+    /// written to the old connection may never have been transcribed, and
+    /// timestamps on the new connection start from zero again. If every
+    /// attempt fails, the stream yields the last error and ends.
+    ///
+    /// A connection Deepgram closes for a reason that retrying won't fix, such
+    /// as a policy violation, is not re-dialed. The stream yields the
+    /// [`DeepgramError::WebsocketClose`] and ends instead.
+    ///
+    /// Has no effect on [`WebsocketBuilder::handle`].
+    pub fn reconnect(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect = Some(policy);
+
+        self
+    }
+
+    /// Give up on opening the connection if the websocket handshake hasn't
+    /// completed within `timeout`. By default, there is no timeout.
+    ///
+    /// Connecting then fails with [`DeepgramError::ConnectTimeout`]. With
+    /// [`WebsocketBuilder::reconnect`] set, this also applies to each
+    /// reconnect attempt.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+
+        self
+    }
+
+    /// Wait at most `timeout` for the remaining results once the stream has
+    /// been closed, whether because the audio source ended or because
+    /// CloseStream was sent.
+    ///
+    /// If Deepgram hasn't closed the connection by then, the stream yields
+    /// [`DeepgramError::DrainTimeout`] and ends. Without this, results are
+    /// waited for until Deepgram closes the connection.
+    pub fn drain_timeout(mut self, timeout: Duration) -> Self {
+        self.drain_timeout = Some(timeout);
+
+        self
+    }
+
+    /// Send a websocket ping every `interval` to detect connections that have
+    /// silently died.
+    ///
+    /// If Deepgram doesn't answer a ping within the
+    /// [pong timeout](WebsocketBuilder::pong_timeout), the stream yields
+    /// [`DeepgramError::ConnectionLost`] and ends, or is re-dialed if
+    /// [`WebsocketBuilder::reconnect`] is set. Without this, a connection that
+    /// drops without being closed can wait for results forever.
+    pub fn ping_interval(mut self, interval: Duration) -> Self {
+        self.ping_interval = Some(interval);
+
+        self
+    }
+
+    /// How long to wait for Deepgram to answer a ping sent because of
+    /// [`WebsocketBuilder::ping_interval`].
+    ///
+    /// Defaults to 10 seconds. Any message received from Deepgram counts as
+    /// an answer.
+    pub fn pong_timeout(mut self, timeout: Duration) -> Self {
+        self.pong_timeout = Some(timeout);
+
+        self
+    }
+
+    /// Keep track of the audio sent and results received by the stream, for
+    /// [`TranscriptionStream::metrics`].
+    ///
+    /// The duration of audio sent and the latency of results can only be
+    /// measured for raw audio, with the encoding and sample rate set on the
+    /// builder.
+    pub fn track_metrics(mut self) -> Self {
+        self.track_metrics = Some(true);
+
+        self
+    }
+
+    /// Send Finalize once the audio from [`WebsocketBuilder::stream`] has
+    /// been quiet for `silence`, so Deepgram returns the final results for
+    /// what was said without waiting for more audio.
+    ///
+    /// Useful for push-to-talk, where the speaker stops talking but the
+    /// audio keeps flowing. Each chunk of audio whose loudness (root mean
+    /// square) is below the [silence threshold](WebsocketBuilder::silence_threshold)
+    /// adds to the silence, and Finalize is sent once per silence that
+    /// follows louder audio. The silence is measured in audio time, so a
+    /// source that stops sending audio altogether never triggers it.
+    ///
+    /// Only linear16, linear32, and mulaw audio can be measured, so the
+    /// encoding and sample rate must be set on the builder. Otherwise the
+    /// stream fails to start with [`DeepgramError::InvalidOption`].
+    ///
+    /// Has no effect on [`WebsocketBuilder::handle`].
+    pub fn finalize_on_silence(mut self, silence: Duration) -> Self {
+        self.finalize_on_silence = Some(silence);
+
+        self
+    }
+
+    /// The loudness below which audio counts as silence for
+    /// [`WebsocketBuilder::finalize_on_silence`], as a fraction of full scale.
+    ///
+    /// Defaults to 0.01, about -40 dBFS.
+    pub fn silence_threshold(mut self, threshold: f32) -> Self {
+        self.silence_threshold = Some(threshold);
+
+        self
+    }
+
+    /// Call `handler` with each transcript as it is received.
+    ///
+    /// Handlers are called from the connection's worker task, before the
+    /// response is yielded by the [`TranscriptionStream`] or
+    /// [`WebsocketHandle::receive`], so they should return quickly. The
+    /// responses still have to be read for the connection to make progress.
+    pub fn on_transcript(
+        mut self,
+        handler: impl Fn(&StreamResponse) + Send + Sync + 'static,
+    ) -> Self {
+        self.handlers.transcript = Some(Arc::new(handler));
+
+        self
+    }
+
+    /// Call `handler` with each [`StreamResponse::UtteranceEndResponse`].
+    ///
+    /// See [`WebsocketBuilder::on_transcript`].
+    pub fn on_utterance_end(
+        mut self,
+        handler: impl Fn(&StreamResponse) + Send + Sync + 'static,
+    ) -> Self {
+        self.handlers.utterance_end = Some(Arc::new(handler));
+
+        self
+    }
+
+    /// Call `handler` with each error the connection runs into, including
+    /// Deepgram closing it with an error.
+    ///
+    /// See [`WebsocketBuilder::on_transcript`].
+    pub fn on_error(mut self, handler: impl Fn(&DeepgramError) + Send + Sync + 'static) -> Self {
+        self.handlers.error = Some(Arc::new(handler));
+
+        self
+    }
+
+    /// Call `handler` once the connection has closed, for any reason.
+    ///
+    /// When the connection is re-established by
+    /// [`WebsocketBuilder::reconnect`], `handler` is called for each
+    /// connection.
+    pub fn on_close(mut self, handler: impl Fn() + Send + Sync + 'static) -> Self {
+        self.handlers.close = Some(Arc::new(handler));
+
+        self
+    }
+
+    /// Have Deepgram also send results to `callback`.
+    ///
+    /// Deepgram sends streaming callbacks with POST. A `callback` with
+    /// another [method](Callback::method), or options with a
+    /// [`callback_method`](crate::common::options::OptionsBuilder::callback_method)
+    /// other than POST, are rejected with [`DeepgramError::InvalidOption`]
+    /// when connecting.
+    ///
+    /// See the [Deepgram Callback feature docs][docs] for more info.
+    ///
+    /// [docs]: https://developers.deepgram.com/docs/callback#streaming-audio
+    pub fn callback(mut self, callback: Callback) -> Self {
         self.callback = Some(callback);
 
         self
     }
+
+    /// Send the credentials in the `Sec-WebSocket-Protocol` header instead
+    /// of the `Authorization` header.
+    ///
+    /// Deepgram accepts either. Browsers and some proxies and WebAssembly
+    /// runtimes only let websocket clients set the subprotocol, so a custom
+    /// [`Transport`] for such an environment can forward this header.
+    pub fn subprotocol_auth(mut self) -> Self {
+        self.subprotocol_auth = Some(true);
+
+        self
+    }
+
+    /// Use `transport` to open the websocket connection instead of the default,
+    /// [`TokioTransport`](transport::TokioTransport).
+    pub fn transport(mut self, transport: impl Transport) -> Self {
+        self.transport = Arc::new(transport);
+
+        self
+    }
+
+    /// Use `runtime` to spawn background tasks and keep time instead of the
+    /// client's [runtime](crate::DeepgramBuilder::runtime), which defaults to
+    /// [`TokioRuntime`](transport::TokioRuntime).
+    pub fn runtime(mut self, runtime: impl Runtime) -> Self {
+        self.runtime = Arc::new(runtime);
+
+        self
+    }
 }
 
 impl WebsocketBuilder<'_> {
+    /// Stream the audio file at `filename`, sending it `frame_size` bytes at
+    /// a time, one chunk every `frame_delay`.
+    ///
+    /// Ogg and ADTS files are only split between pages or frames, so their
+    /// chunks can be somewhat shorter than `frame_size`, or longer when a
+    /// single page or frame is.
     pub async fn file(
         self,
         filename: impl AsRef<Path>,
         frame_size: usize,
         frame_delay: Duration,
-    ) -> Result<TranscriptionStream, DeepgramError> {
-        let file = File::open(filename).await?;
+    ) -> Result<TranscriptionStream, LiveError> {
+        let runtime = Arc::clone(&self.runtime);
+        let file = runtime.open_file(filename.as_ref().to_owned()).await?;
         let mut chunker = FileChunker::new(file, frame_size);
-        let (tx, rx) = tokio::sync::mpsc::channel(1);
-        let rx_stream = tokio_stream::wrappers::ReceiverStream::new(rx);
+        let (mut tx, rx) = mpsc::channel(1);
         let task = async move {
             while let Some(frame) = chunker.next().await {
-                tokio::time::sleep(frame_delay).await;
-                // This unwrap() is safe because application logic dictates that the Receiver won't
-                // be dropped before the Sender.
+                runtime.sleep(frame_delay).await;
+                // Stop reading the file once the stream is no longer taking audio.
                 if tx.send(frame).await.is_err() {
                     break;
                 }
             }
         };
-        tokio::spawn(task);
-        self.stream(rx_stream).await
+        self.runtime.spawn(Box::pin(task));
+        self.stream(rx).await
+    }
+
+    /// Connect to Deepgram, returning a sink to push audio into and the
+    /// stream of responses.
+    ///
+    /// Useful when audio arrives from callbacks or channels rather than as a
+    /// [`Stream`]. Otherwise behaves like [`WebsocketBuilder::stream`]: once
+    /// every clone of the sink has been closed or dropped, the audio sent so
+    /// far is finalized and the stream is closed.
+    ///
+    /// ```no_run
+    /// # use deepgram::{Deepgram, DeepgramError};
+    /// # use bytes::Bytes;
+    /// # use futures::SinkExt;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), DeepgramError> {
+    /// let dg = Deepgram::new(std::env::var("DEEPGRAM_API_KEY").unwrap_or_default())?;
+    /// let (mut audio, results) = dg.transcription().stream_request().connect().await?;
+    ///
+    /// audio.send(Bytes::from_static(b"...")).await?;
+    /// audio.close().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn connect(mut self) -> Result<(AudioSink, TranscriptionStream), LiveError> {
+        // The audio only arrives once the sink is returned.
+        self.detect_wav_header = None;
+        let (tx, rx) = mpsc::channel(1);
+        let results = self.stream(rx.map(Ok::<_, Infallible>)).await?;
+
+        Ok((AudioSink { tx }, results))
     }
 
-    pub async fn stream<S, E>(self, stream: S) -> Result<TranscriptionStream>
+    pub async fn stream<S, E>(mut self, mut stream: S) -> Result<TranscriptionStream, LiveError>
     where
         S: Stream<Item = Result<Bytes, E>> + Send + Unpin + 'static,
         E: Error + Send + Sync + 'static,
     {
+        let mut header_audio = None;
+        if self.detect_wav_header.unwrap_or(false) {
+            let (header, audio) = read_wav_header(&mut stream).await?;
+            if let Some(header) = header {
+                self = self
+                    .encoding(header.encoding)
+                    .sample_rate(header.sample_rate)
+                    .channels(header.channels);
+            }
+            header_audio = (!audio.is_empty()).then(|| Ok(audio));
+        }
+        let stream = futures::stream::iter(header_audio).chain(stream);
+
+        let runtime = Arc::clone(&self.runtime);
+        let spool = self.spool();
+        let reconnect = self.reconnector();
+        let stats = self.stream_stats();
+        let silence = self.silence_detector()?;
         let handle = self.handle().await?;
 
-        let (tx, rx) = mpsc::channel(1);
-        let mut is_done = false;
-        let request_id = handle.request_id();
-        tokio::task::spawn(async move {
-            let mut handle = handle;
-            let mut tx = tx;
-            let mut stream = stream.fuse();
-
-            loop {
-                select_biased! {
-                    // Receiving messages from WebsocketHandle
-                    response = handle.response_rx.next() => {
-                        // eprintln!("<stream> got response");
-                        match response {
-                            Some(Ok(response)) if matches!(response, StreamResponse::TerminalResponse { .. }) => {
-                               // eprintln!( "<stream> got terminal response");
-                                if tx.send(Ok(response)).await.is_err() {
-                                    // Receiver has been dropped.
-                                    break;
-                                }
-                            }
-                            Some(response) => {
-                                if tx.send(response).await.is_err() {
-                                    // Receiver has been dropped.
-                                    break;
-                                }
-                            }
-                            None => {
-                                // eprintln!("<stream> got none from handle");
-                                tx.close_channel();
-                                // No more responses
-                                break;
-                            }
-                        }
-                    }
-                    // Receiving audio data from stream.
-                    chunk = stream.next() => {
-                        match chunk {
-                            Some(Ok(audio)) => if let Err(err) = handle.send_data(audio.to_vec()).await {
-                                // eprintln!("<stream> got audio");
-                                if tx.send(Err(err)).await.is_err() {
-                                    break;
-                                }
-                            },
-                            Some(Err(err)) => {
-                                // eprintln!("<stream> got error");
-                                if tx.send(Err(DeepgramError::from(Box::new(err) as Box<dyn Error + Send + Sync + 'static>))).await.is_err() {
-                                    break;
-                                }
-                            }
-                            None => {
-                                if is_done {
+        Ok(stream_handle(
+            handle, runtime, spool, reconnect, stats, silence, stream,
+        ))
+    }
 
-                                    continue;
-                                }
-                                if let Err(err) = handle.finalize().await {
-                                    if tx.send(Err(err)).await.is_err() {
-                                        break;
-                                    }
-                                }
+    /// A low level interface to the Deepgram websocket transcription API.
+    pub async fn handle(self) -> Result<WebsocketHandle, LiveError> {
+        Ok(WebsocketHandle::new(self).await?)
+    }
 
-                                if let Err(err) = handle.close_stream().await {
-                                    if tx.send(Err(err)).await.is_err() {
-                                        break;
-                                    }
-                                }
-                                is_done = true;
-                            }
-                        }
-                    }
+    /// Like [`WebsocketBuilder::handle`], but yields each message from
+    /// Deepgram as the raw [`serde_json::Value`] instead of a
+    /// [`StreamResponse`].
+    ///
+    /// Messages are passed through whatever their type, so a stream is never
+    /// ended by a message this version of the SDK can't deserialize.
+    /// [`WebsocketBuilder::on_transcript`] and
+    /// [`WebsocketBuilder::on_utterance_end`] handlers aren't called for raw
+    /// messages.
+    pub async fn handle_raw(self) -> Result<WebsocketHandle<serde_json::Value>, LiveError> {
+        Ok(WebsocketHandle::new(self).await?)
+    }
 
-                }
+    /// Connect to Deepgram now, and start sending audio later.
+    ///
+    /// DNS resolution, the TCP and TLS handshakes, and the websocket upgrade
+    /// all happen here, so latency-sensitive applications can pay for them
+    /// before the user starts speaking rather than on the first utterance.
+    /// Keep-alive is enabled so the connection stays open until audio
+    /// is sent through the returned [`PrewarmedWebsocket`].
+    ///
+    /// ```no_run
+    /// # use deepgram::{Deepgram, DeepgramError};
+    /// # use bytes::Bytes;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), DeepgramError> {
+    /// let dg = Deepgram::new(std::env::var("DEEPGRAM_API_KEY").unwrap_or_default())?;
+    /// let connection = dg.transcription().stream_request().prewarm().await?;
+    ///
+    /// // ...later, once there is audio to send.
+    /// # let audio = futures::stream::empty::<Result<Bytes, std::io::Error>>();
+    /// let results = connection.stream(audio);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn prewarm(mut self) -> Result<PrewarmedWebsocket, LiveError> {
+        self.keep_alive = Some(true);
+        let runtime = Arc::clone(&self.runtime);
+        let spool = self.spool();
+        let reconnect = self.reconnector();
+        let stats = self.stream_stats();
+        let silence = self.silence_detector()?;
+        let handle = self.handle().await?;
+
+        Ok(PrewarmedWebsocket {
+            handle,
+            runtime,
+            spool,
+            reconnect,
+            stats,
+            silence,
+        })
+    }
+
+    /// Check for options Deepgram would reject with a 400 response.
+    ///
+    /// Deepgram needs the sample rate of any audio whose encoding is given,
+    /// and can't use a sample rate or channel count without the encoding.
+    /// Streaming callbacks can only use POST.
+    fn validate(&self) -> Result<()> {
+        if let Some(utterance_end_ms) = self.utterance_end_ms {
+            if utterance_end_ms < MIN_UTTERANCE_END_MS {
+                return Err(DeepgramError::InvalidOption(format!(
+                    "utterance_end_ms must be at least {MIN_UTTERANCE_END_MS}, got {utterance_end_ms}"
+                )));
+            }
+        }
+
+        let callback_method = self
+            .callback
+            .as_ref()
+            .and_then(Callback::callback_method)
+            .or(self.options.callback_method());
+        match (&self.callback, callback_method) {
+            (_, None | Some(CallbackMethod::POST)) => {}
+            (None, Some(_)) => {
+                return Err(DeepgramError::InvalidOption(String::from(
+                    "callback_method requires a callback",
+                )));
+            }
+            (Some(_), Some(method)) => {
+                return Err(DeepgramError::InvalidOption(format!(
+                    "streaming callbacks are always sent with POST, so callback_method {} is not supported",
+                    method.as_str()
+                )));
             }
+        }
+
+        match (&self.encoding, self.sample_rate, self.channels) {
+            (Some(encoding), None, _) => {
+                return Err(DeepgramError::InvalidOption(format!(
+                    "sample_rate must be set along with encoding {}",
+                    encoding.as_str()
+                )));
+            }
+            (None, Some(_), _) | (None, _, Some(_)) => {
+                return Err(DeepgramError::InvalidOption(String::from(
+                    "sample_rate and channels describe raw audio, and must be set along with encoding",
+                )));
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// The detector for [`WebsocketBuilder::finalize_on_silence`], if it is set.
+    fn silence_detector(&self) -> Result<Option<SilenceDetector>> {
+        let Some(silence) = self.finalize_on_silence else {
+            return Ok(None);
+        };
+
+        let format = match self.encoding {
+            Some(Encoding::Linear16) => SampleFormat::Linear16,
+            Some(Encoding::Linear32) => SampleFormat::Linear32,
+            Some(Encoding::Mulaw) => SampleFormat::Mulaw,
+            _ => {
+                return Err(DeepgramError::InvalidOption(String::from(
+                    "finalize_on_silence needs linear16, linear32, or mulaw audio",
+                )))
+            }
+        };
+        let Some(sample_rate) = self.sample_rate else {
+            return Err(DeepgramError::InvalidOption(String::from(
+                "finalize_on_silence needs the sample rate of the audio",
+            )));
+        };
+
+        Ok(Some(SilenceDetector {
+            format,
+            samples_per_second: f64::from(sample_rate) * f64::from(self.channels.unwrap_or(1)),
+            threshold: f64::from(self.silence_threshold.unwrap_or(DEFAULT_SILENCE_THRESHOLD)),
+            silence: silence.as_secs_f64(),
+            quiet: 0.0,
+            partial: Vec::new(),
+            spoken: false,
+        }))
+    }
+
+    /// The tracker for [`TranscriptionStream::metrics`], if metrics are tracked.
+    fn stream_stats(&self) -> Option<StreamStats> {
+        if !self.track_metrics.unwrap_or(false) {
+            return None;
+        }
+
+        let bytes_per_sample = match self.encoding {
+            Some(Encoding::Linear16) => Some(2),
+            Some(Encoding::Linear32) => Some(4),
+            Some(Encoding::Mulaw) => Some(1),
+            _ => None,
+        };
+        let bytes_per_second = bytes_per_sample.zip(self.sample_rate).map(|(bytes, rate)| {
+            f64::from(bytes) * f64::from(rate) * f64::from(self.channels.unwrap_or(1))
         });
-        Ok(TranscriptionStream {
-            rx,
-            done: false,
-            request_id,
-        })
+
+        Some(StreamStats::new(
+            Arc::clone(&self.runtime),
+            bytes_per_second,
+        ))
     }
 
-    /// A low level interface to the Deepgram websocket transcription API.
-    pub async fn handle(self) -> Result<WebsocketHandle> {
-        WebsocketHandle::new(self).await
+    /// Where [`WebsocketBuilder::stream`] queues audio the connection has no room for.
+    fn spool(&self) -> Option<AudioSpool> {
+        match (self.spill_to_disk, self.backpressure.unwrap_or_default()) {
+            (Some(memory_limit), _) => Some(AudioSpool::new(memory_limit, std::env::temp_dir())),
+            (None, Backpressure::DropOldest(frames)) => Some(AudioSpool::dropping_oldest(frames)),
+            (None, Backpressure::Block) => None,
+        }
+    }
+
+    fn reconnector(&self) -> Option<Reconnector<WebsocketBuilder<'static>>> {
+        self.reconnect
+            .clone()
+            .map(|policy| Reconnector::new(self.to_owned_builder(), policy))
+    }
+
+    /// A copy of the builder that owns its own copy of the client.
+    pub(crate) fn to_owned_builder(&self) -> WebsocketBuilder<'static> {
+        WebsocketBuilder {
+            deepgram: Cow::Owned(self.deepgram.clone().into_owned()),
+            ..self.clone()
+        }
+    }
+
+    /// The policy set with [`WebsocketBuilder::reconnect`].
+    pub(crate) fn reconnect_policy(&self) -> Option<&ReconnectPolicy> {
+        self.reconnect.as_ref()
     }
 }
 
-macro_rules! send_message {
-    ($stream:expr, $response_tx:expr, $msg:expr) => {
-        if let Err(err) = $stream.send($msg).await {
-            if $response_tx.send(Err(err.into())).await.is_err() {
-                // Responses are no longer being received; close the stream.
-                break;
+impl Redial for WebsocketBuilder<'_> {
+    type Handle = WebsocketHandle;
+
+    fn runtime(&self) -> &dyn Runtime {
+        self.runtime.as_ref()
+    }
+
+    fn dial(&self) -> BoxFuture<'_, Result<WebsocketHandle>> {
+        Box::pin(WebsocketHandle::new(self.clone()))
+    }
+}
+
+/// An open websocket connection that has not been sent any audio yet.
+///
+/// Returned by [`WebsocketBuilder::prewarm`].
+#[derive(Debug)]
+pub struct PrewarmedWebsocket {
+    handle: WebsocketHandle,
+    runtime: Arc<dyn Runtime>,
+    spool: Option<AudioSpool>,
+    reconnect: Option<Reconnector<WebsocketBuilder<'static>>>,
+    stats: Option<StreamStats>,
+    silence: Option<SilenceDetector>,
+}
+
+impl PrewarmedWebsocket {
+    /// Start streaming audio over the connection, like [`WebsocketBuilder::stream`].
+    pub fn stream<S, E>(self, stream: S) -> TranscriptionStream
+    where
+        S: Stream<Item = Result<Bytes, E>> + Send + Unpin + 'static,
+        E: Error + Send + Sync + 'static,
+    {
+        stream_handle(
+            self.handle,
+            self.runtime,
+            self.spool,
+            self.reconnect,
+            self.stats,
+            self.silence,
+            stream,
+        )
+    }
+
+    /// Use the connection through the low level [`WebsocketHandle`] interface.
+    pub fn into_handle(self) -> WebsocketHandle {
+        self.handle
+    }
+
+    /// Returns the Deepgram request ID of the connection.
+    pub fn request_id(&self) -> Uuid {
+        self.handle.request_id()
+    }
+
+    /// Details of the connection from the websocket upgrade response.
+    pub fn connection_info(&self) -> &ConnectionInfo {
+        self.handle.connection_info()
+    }
+}
+
+/// Forward audio from `stream` to `handle`, and responses from `handle` to the returned stream.
+fn stream_handle<S, E>(
+    handle: WebsocketHandle,
+    runtime: Arc<dyn Runtime>,
+    mut spool: Option<AudioSpool>,
+    reconnect: Option<Reconnector<WebsocketBuilder<'static>>>,
+    stats: Option<StreamStats>,
+    mut silence: Option<SilenceDetector>,
+    stream: S,
+) -> TranscriptionStream
+where
+    S: Stream<Item = Result<Bytes, E>> + Send + Unpin + 'static,
+    E: Error + Send + Sync + 'static,
+{
+    let stats = stats.map(|stats| Arc::new(Mutex::new(stats)));
+    let record = {
+        let stats = stats.clone();
+        move |update: &dyn Fn(&mut StreamStats)| {
+            if let Some(stats) = &stats {
+                update(&mut stats.lock().unwrap());
             }
         }
     };
-}
-async fn run_worker(
-    ws_stream: WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>,
-    mut message_tx: Sender<WsMessage>,
-    mut message_rx: Receiver<WsMessage>,
-    mut response_tx: Sender<Result<StreamResponse>>,
-    keep_alive: bool,
-) -> Result<()> {
-    // We use Vec<u8> for partial frames because we don't know if a fragment of a string is valid utf-8.
-    let mut partial_frame: Vec<u8> = Vec::new();
-    let (mut ws_stream_send, ws_stream_recv) = ws_stream.split();
-    let mut ws_stream_recv = ws_stream_recv.fuse();
-    let mut is_open: bool = true;
-    let mut last_sent_message = tokio::time::Instant::now();
-    loop {
-        // eprintln!("<worker> loop");
-        let sleep = tokio::time::sleep_until(last_sent_message + Duration::from_secs(3));
-        // Primary event loop.
-        select_biased! {
-            _ = sleep.fuse() => {
-                // eprintln!("<worker> sleep");
-                if keep_alive && is_open {
-                    // Ignore send errors: the channel may have been closed by
-                    // close_stream() (via close_channel()) before the worker
-                    // processes the pending CloseStream message. In that case
-                    // the next iteration will handle CloseStream, stop sending new
-                    // messages, and proceed toward shutdown.
-                    let _ = message_tx.send(WsMessage::ControlMessage(ControlMessage::KeepAlive)).await;
-                    last_sent_message = tokio::time::Instant::now();
-                } else {
-                    pending::<()>().await;
+    let (tx, rx) = mpsc::channel(1);
+    let (control_tx, mut control_rx) = mpsc::unbounded();
+    let mut source_done = false;
+    let mut is_done = false;
+    let mut paused = false;
+    // The keep-alive interval set through StreamControl, if any.
+    let mut keep_alive = None;
+    // Audio that didn't reach a connection before it dropped, to send on the next one.
+    let mut unsent = Vec::new();
+    let info = handle.connection_info().clone();
+    Arc::clone(&runtime).spawn(Box::pin(async move {
+        let mut handle = handle;
+        let mut tx = tx;
+        let mut stream = stream.fuse();
+
+        'stream: loop {
+            let spooled = spool.as_ref().is_some_and(|spool| !spool.is_empty());
+            if source_done && !spooled && !is_done {
+                if let Err(err) = handle.finalize().await {
+                    if tx.send(Err(err.into())).await.is_err() {
+                        break;
+                    }
+                }
+
+                if let Err(err) = handle.close_stream().await {
+                    if tx.send(Err(err.into())).await.is_err() {
+                        break;
+                    }
                 }
+                is_done = true;
             }
-            response = ws_stream_recv.next() => {
-                match response {
-                    Some(Ok(Message::Text(response))) => {
-                        // eprintln!("<worker> received dg response");
-                        match serde_json::from_str(&response) {
-                            Ok(response) => {
-                                if (response_tx.send(Ok(response)).await).is_err() {
-                                    // Responses are no longer being received; close the stream.
-                                    break;
-                                }
+
+            select_biased! {
+                // Receiving messages from WebsocketHandle
+                response = handle.response_rx.next() => {
+                    match response {
+                        Some(Ok(response)) if matches!(response, StreamResponse::TerminalResponse { .. }) => {
+                            if tx.send(Ok(response)).await.is_err() {
+                                // Receiver has been dropped.
+                                break;
                             }
-                            Err(err) =>{
-                                if (response_tx.send(Err(err.into())).await).is_err() {
-                                    // Responses are no longer being received; close the stream.
+                        }
+                        Some(Err(err @ DeepgramError::WebsocketClose { .. })) if reconnect.is_some() && !is_done => {
+                            if let Some(reconnect) = &reconnect {
+                                if reconnect.closed(err, &mut tx).await.is_break() {
                                     break;
                                 }
                             }
                         }
-                    }
-                    Some(Ok(Message::Ping(value))) => {
-                        // We don't really care if the server receives the pong.
-                        let _ = ws_stream_send.send(Message::Pong(value)).await;
-                    }
-                    Some(Ok(Message::Close(None))) => {
-                        // eprintln!("<worker> received websocket close");
-                        return Ok(());
-                    }
-                    Some(Ok(Message::Close(Some(closeframe)))) => {
-                        // eprintln!("<worker> received websocket close");
-                        return Err(DeepgramError::WebsocketClose {
-                            code: closeframe.code.into(),
-                            reason: closeframe.reason.to_string(),
-                        });
-                    }
-
-                    Some(Ok(Message::Frame(frame))) => {
-                        match frame.header().opcode
-                        {
-                            OpCode::Data(Data::Text) => {
-                                partial_frame.extend(frame.payload());
-                            }
-                            OpCode::Data(Data::Continue) => {
-                                // We know we're continuing a text frame because otherwise
-                                // partial_frame would be empty.
-                                if !partial_frame.is_empty() {
-                                    partial_frame.extend(frame.payload())
-                                }
+                        Some(response) => {
+                            if let Ok(response) = &response {
+                                record(&|stats| stats.received(response));
                             }
-                            _ => {
-                                // Ignore other partial frames.
+                            if tx.send(response).await.is_err() {
+                                // Receiver has been dropped.
+                                break;
                             }
                         }
-                        if frame.header().is_final {
-                            let response = std::mem::take(&mut partial_frame);
-                            let response = serde_json::from_slice(&response).map_err(|err| err.into());
-                            if (response_tx.send(response).await).is_err() {
-                                // Responses are no longer being received; close the stream.
-                                break
+                        None => {
+                            // The connection dropped before we closed it.
+                            if let (false, Some(reconnect)) = (is_done, &reconnect) {
+                                let mut resend = handle.unsent_audio().await;
+                                resend.append(&mut unsent);
+                                let reconnected = |handle: &WebsocketHandle, attempts| StreamResponse::Reconnected {
+                                    request_id: handle.request_id(),
+                                    attempts,
+                                };
+                                if let Some(new_handle) = reconnect.redial(&mut tx, reconnected).await {
+                                    handle = new_handle;
+                                    record(&StreamStats::reconnected);
+                                    // The new connection starts out with the builder's settings.
+                                    let restored = async {
+                                        if let Some(interval) = keep_alive {
+                                            handle.set_keep_alive_interval(interval).await?;
+                                        }
+                                        if paused {
+                                            handle.set_paused(true).await?;
+                                        }
+                                        Ok::<_, DeepgramError>(())
+                                    };
+                                    if let Err(err) = restored.await {
+                                        if tx.send(Err(err)).await.is_err() {
+                                            break;
+                                        }
+                                    }
+                                    for audio in resend {
+                                        // Already counted and checked for silence when it was first sent.
+                                        if let Err(err) = send_audio(&mut handle, &mut None, Some(&mut unsent), audio).await {
+                                            if tx.send(Err(err)).await.is_err() {
+                                                break 'stream;
+                                            }
+                                        }
+                                    }
+                                    continue;
+                                }
                             }
+
+                            tx.close_channel();
+                            // No more responses
+                            break;
                         }
                     }
-                    Some(Ok(Message::Binary(_) | Message::Pong(_))) => {
-                        // We don't expect binary messages or pongs from the API.
-                        // They can be safely ignored.
-                    }
-
-                    Some(Err(err)) => {
-                        if (response_tx.send(Err(err.into())).await).is_err() {
-                            // Responses are no longer being received; close the stream.
+                }
+                // Sending control messages from StreamControl.
+                message = control_rx.next() => {
+                    let result = match message {
+                        Some(StreamCommand::Send(ControlMessage::Finalize)) => handle.finalize().await,
+                        Some(StreamCommand::Send(ControlMessage::KeepAlive)) => handle.keep_alive().await,
+                        Some(StreamCommand::Send(ControlMessage::CloseStream)) => {
+                            // Stop sending audio, including any that was queued.
+                            source_done = true;
+                            is_done = true;
+                            spool = None;
+                            handle.close_stream().await
+                        }
+                        Some(StreamCommand::KeepAliveInterval(interval)) => {
+                            keep_alive = Some(interval);
+                            handle.set_keep_alive_interval(interval).await
+                        }
+                        Some(StreamCommand::Pause) => {
+                            paused = true;
+                            handle.set_paused(true).await.map_err(Into::into)
+                        }
+                        Some(StreamCommand::Resume) => {
+                            paused = false;
+                            handle.set_paused(false).await.map_err(Into::into)
+                        }
+                        None => Ok(()),
+                    };
+                    if let Err(err) = result {
+                        if tx.send(Err(err.into())).await.is_err() {
                             break;
                         }
-
                     }
-                    None => {
-                        // Upstream is closed
-                        // eprintln!("<worker> received None");
-                        return Ok(())
+                }
+                // Sending spooled audio once the connection has room for it.
+                _ = poll_fn(|cx| if spooled { handle.message_tx.poll_ready(cx) } else { Poll::Pending }).fuse() => {
+                    let popped = match &mut spool {
+                        Some(spool) => Some(spool.pop(&*runtime).await),
+                        None => None,
+                    };
+                    let result = match popped {
+                        Some(Ok(Some(audio))) => {
+                            let len = audio.len();
+                            let result = send_audio(&mut handle, &mut silence, (reconnect.is_some() && !is_done).then_some(&mut unsent), audio).await;
+                            if result.is_ok() {
+                                record(&|stats| stats.sent(len));
+                            }
+                            result
+                        }
+                        Some(Err(err)) => Err(err.into()),
+                        _ => Ok(()),
+                    };
+                    if let Err(err) = result {
+                        if tx.send(Err(err)).await.is_err() {
+                            break;
+                        }
                     }
                 }
-            }
-            message = message_rx.next() => {
-                // eprintln!("<worker> received message: {message:?}, {is_open:?}");
-                if is_open {
-                    match message {
-                        Some(WsMessage::Audio(audio))=> {
-                            send_message!(ws_stream_send, response_tx, Message::Binary(Bytes::from(audio.0)));
-                            last_sent_message = tokio::time::Instant::now();
-
+                // Receiving audio data from stream.
+                chunk = async { if source_done { pending().await } else { stream.next().await } }.fuse() => {
+                    match chunk {
+                        // Audio from while the stream is paused is never sent.
+                        Some(Ok(_)) if paused => {}
+                        Some(Ok(audio)) => {
+                            let result = match &mut spool {
+                                Some(spool) => spool.push(audio, &*runtime).await.map_err(DeepgramError::from),
+                                None => {
+                                    let len = audio.len();
+                                    let result = send_audio(&mut handle, &mut silence, (reconnect.is_some() && !is_done).then_some(&mut unsent), audio).await;
+                                    if result.is_ok() {
+                                        record(&|stats| stats.sent(len));
+                                    }
+                                    result
+                                }
+                            };
+                            if let Err(err) = result {
+                                if tx.send(Err(err)).await.is_err() {
+                                    break;
+                                }
+                            }
                         }
-                        Some(WsMessage::ControlMessage(msg)) => {
-                            send_message!(ws_stream_send, response_tx, Message::Text(
-                                Utf8Bytes::from(serde_json::to_string(&msg).unwrap_or_default())
-                            ));
-                            last_sent_message = tokio::time::Instant::now();
-                            if msg == ControlMessage::CloseStream {
-                                is_open = false;
+                        Some(Err(err)) => {
+                            if tx.send(Err(DeepgramError::from(Box::new(err) as Box<dyn Error + Send + Sync + 'static>))).await.is_err() {
+                                break;
                             }
                         }
                         None => {
-                            // Input stream is shut down.  Keep processing responses.
-                            send_message!(ws_stream_send, response_tx, Message::Text(
-                                Utf8Bytes::from(serde_json::to_string(&ControlMessage::CloseStream).unwrap_or_default())
-                            ));
-                            is_open = false;
+                            source_done = true;
                         }
                     }
                 }
             }
-        };
+        }
+    }));
+    TranscriptionStream {
+        rx,
+        done: false,
+        info,
+        control: StreamControl { tx: control_tx },
+        stats,
     }
-    // eprintln!("<worker> post loop");
-    if let Err(err) = ws_stream_send
-        .send(Message::Text(Utf8Bytes::from(
-            serde_json::to_string(&ControlMessage::CloseStream).unwrap_or_default(),
-        )))
-        .await
-    {
-        // If the response channel is closed, there's nothing to be done about it now.
-        let _ = response_tx.send(Err(err.into())).await;
+}
+
+/// Send `audio`, followed by Finalize if it ends a long enough silence.
+///
+/// If the connection has dropped and `unsent` is given, the audio is kept in
+/// `unsent` to send on the next connection instead of failing.
+async fn send_audio(
+    handle: &mut WebsocketHandle,
+    silence: &mut Option<SilenceDetector>,
+    unsent: Option<&mut Vec<Bytes>>,
+    audio: Bytes,
+) -> Result<()> {
+    let finalize = silence
+        .as_mut()
+        .is_some_and(|silence| silence.ends_silence(&audio));
+    let mut result = handle.send_data(audio.clone()).await;
+    if result.is_ok() && finalize {
+        result = handle.finalize().await;
     }
-    response_tx.close_channel();
-    // Waiting for message_tx to be dropped before exiting
-    while message_rx.next().await.is_some() {
-        // Receiving messages after closing down. Ignore them.
+
+    match (result, unsent) {
+        (Err(_), Some(unsent)) if handle.message_tx.is_closed() => {
+            unsent.push(audio);
+            Ok(())
+        }
+        (result, _) => Ok(result?),
     }
-    // eprintln!("<worker> exit");
-    Ok(())
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-enum WsMessage {
-    Audio(Audio),
-    ControlMessage(ControlMessage),
+/// Activity of a [`TranscriptionStream`], as returned by
+/// [`TranscriptionStream::metrics`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[non_exhaustive]
+pub struct StreamMetrics {
+    /// Bytes of audio sent to Deepgram.
+    pub audio_bytes_sent: u64,
+    /// Duration of the audio sent to Deepgram, if it is raw audio with a
+    /// known encoding and sample rate.
+    pub audio_duration_sent: Option<Duration>,
+    /// Transcript results received, interim or final.
+    pub results_received: u64,
+    /// Words in the first alternative of final results.
+    pub words_emitted: u64,
+    /// How long after the end of the audio it covers was sent the latest
+    /// result arrived.
+    pub last_result_latency: Option<Duration>,
+    /// The average latency of the results received.
+    pub mean_result_latency: Option<Duration>,
+    /// The highest latency of the results received.
+    pub max_result_latency: Option<Duration>,
+    /// How many times the connection was re-established after dropping.
+    pub reconnects: u32,
+    /// How long ago the stream's first connection was opened.
+    pub uptime: Duration,
 }
 
-#[derive(Clone, PartialEq, Eq)]
-struct Audio(Vec<u8>);
+/// The raw encodings whose loudness [`SilenceDetector`] can measure.
+#[derive(Debug, Clone, Copy)]
+enum SampleFormat {
+    Linear16,
+    Linear32,
+    Mulaw,
+}
 
-impl fmt::Debug for Audio {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_tuple("audio")
-            .field(&format!(
-                "<{} bytes (sha256:{})>",
-                self.0.len(),
-                &sha256::digest(&self.0)[..12]
-            ))
-            .finish()
+impl SampleFormat {
+    fn sample_len(self) -> usize {
+        match self {
+            SampleFormat::Linear16 => 2,
+            SampleFormat::Linear32 => 4,
+            SampleFormat::Mulaw => 1,
+        }
+    }
+
+    /// The magnitude of the sample in `bytes`, as a fraction of full scale.
+    fn amplitude(self, bytes: &[u8]) -> f64 {
+        match self {
+            SampleFormat::Linear16 => f64::from(i16::from_le_bytes([bytes[0], bytes[1]])) / 32768.0,
+            SampleFormat::Linear32 => {
+                f64::from(f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            }
+            SampleFormat::Mulaw => f64::from(crate::mulaw::decode_mulaw(bytes[0])) / 32768.0,
+        }
+    }
+}
+
+/// Tells when to send Finalize for [`WebsocketBuilder::finalize_on_silence`].
+#[derive(Debug)]
+struct SilenceDetector {
+    format: SampleFormat,
+    samples_per_second: f64,
+    threshold: f64,
+    /// How many seconds of quiet audio end a silence.
+    silence: f64,
+    /// Seconds of quiet audio sent since the last loud chunk.
+    quiet: f64,
+    /// The start of a sample split between chunks.
+    partial: Vec<u8>,
+    /// Whether loud audio has been sent since the last Finalize.
+    spoken: bool,
+}
+
+impl SilenceDetector {
+    /// Whether sending `audio` ends a silence that Finalize should be sent for.
+    fn ends_silence(&mut self, mut audio: &[u8]) -> bool {
+        let sample_len = self.format.sample_len();
+        let mut sum_of_squares = 0.0;
+        let mut samples = 0;
+
+        if !self.partial.is_empty() {
+            let rest = (sample_len - self.partial.len()).min(audio.len());
+            self.partial.extend_from_slice(&audio[..rest]);
+            audio = &audio[rest..];
+            if self.partial.len() == sample_len {
+                sum_of_squares += self.format.amplitude(&self.partial).powi(2);
+                samples += 1;
+                self.partial.clear();
+            }
+        }
+        let chunks = audio.chunks_exact(sample_len);
+        self.partial.extend_from_slice(chunks.remainder());
+        for sample in chunks {
+            sum_of_squares += self.format.amplitude(sample).powi(2);
+            samples += 1;
+        }
+
+        if samples == 0 {
+            return false;
+        }
+        if (sum_of_squares / f64::from(samples)).sqrt() >= self.threshold {
+            self.quiet = 0.0;
+            self.spoken = true;
+            return false;
+        }
+
+        self.quiet += f64::from(samples) / self.samples_per_second;
+        if self.spoken && self.quiet >= self.silence {
+            self.spoken = false;
+            return true;
+        }
+
+        false
+    }
+}
+
+/// Keeps the [`StreamMetrics`] of a stream up to date.
+#[derive(Debug)]
+struct StreamStats {
+    runtime: Arc<dyn Runtime>,
+    /// For raw audio, how many bytes make up a second of it.
+    bytes_per_second: Option<f64>,
+    opened: Instant,
+    metrics: StreamMetrics,
+    /// Bytes sent on the current connection, whose timestamps start from zero.
+    connection_bytes: u64,
+    /// The position in the audio, in seconds, at the end of each chunk sent on
+    /// the current connection and when it was sent.
+    sent: VecDeque<(f64, Instant)>,
+    total_latency: Duration,
+    latencies: u32,
+}
+
+impl StreamStats {
+    fn new(runtime: Arc<dyn Runtime>, bytes_per_second: Option<f64>) -> Self {
+        let opened = runtime.now();
+        StreamStats {
+            runtime,
+            bytes_per_second,
+            opened,
+            metrics: StreamMetrics::default(),
+            connection_bytes: 0,
+            sent: VecDeque::new(),
+            total_latency: Duration::ZERO,
+            latencies: 0,
+        }
+    }
+
+    fn sent(&mut self, len: usize) {
+        self.metrics.audio_bytes_sent += len as u64;
+        self.connection_bytes += len as u64;
+        if let Some(bytes_per_second) = self.bytes_per_second {
+            self.metrics.audio_duration_sent = Some(Duration::from_secs_f64(
+                self.metrics.audio_bytes_sent as f64 / bytes_per_second,
+            ));
+            self.sent.push_back((
+                self.connection_bytes as f64 / bytes_per_second,
+                self.runtime.now(),
+            ));
+        }
+    }
+
+    fn received(&mut self, response: &StreamResponse) {
+        let StreamResponse::TranscriptResponse {
+            start,
+            duration,
+            is_final,
+            channel,
+            ..
+        } = response
+        else {
+            return;
+        };
+
+        self.metrics.results_received += 1;
+        if *is_final {
+            if let Some(alternative) = channel.alternatives.first() {
+                self.metrics.words_emitted += alternative.words.len() as u64;
+            }
+        }
+
+        // Find when the end of the audio covered by the result was sent,
+        // dropping the chunks before it, which later results won't need.
+        let end = start + duration;
+        while self.sent.len() > 1 && self.sent.front().is_some_and(|(at, _)| *at < end) {
+            self.sent.pop_front();
+        }
+        if let Some((_, sent_at)) = self.sent.front() {
+            let latency = self.runtime.now().saturating_duration_since(*sent_at);
+            self.total_latency += latency;
+            self.latencies += 1;
+            self.metrics.last_result_latency = Some(latency);
+            self.metrics.mean_result_latency = Some(self.total_latency / self.latencies);
+            self.metrics.max_result_latency = self.metrics.max_result_latency.max(Some(latency));
+        }
+    }
+
+    fn reconnected(&mut self) {
+        self.metrics.reconnects += 1;
+        self.connection_bytes = 0;
+        self.sent.clear();
+    }
+
+    fn snapshot(&self) -> StreamMetrics {
+        StreamMetrics {
+            uptime: self.runtime.now().saturating_duration_since(self.opened),
+            ..self.metrics.clone()
+        }
+    }
+}
+
+macro_rules! send_message {
+    ($stream:expr, $response_tx:expr, $msg:expr) => {
+        if let Err(err) = $stream.send($msg).await {
+            if $response_tx.send(Err(err.into())).await.is_err() {
+                // Responses are no longer being received; close the stream.
+                break;
+            }
+        }
+    };
+}
+
+type Handler<T> = Arc<dyn Fn(&T) + Send + Sync>;
+
+/// Closures registered with [`WebsocketBuilder::on_transcript`] and friends.
+#[derive(Clone, Default)]
+struct EventHandlers {
+    transcript: Option<Handler<StreamResponse>>,
+    utterance_end: Option<Handler<StreamResponse>>,
+    error: Option<Handler<DeepgramError>>,
+    close: Option<Arc<dyn Fn() + Send + Sync>>,
+}
+
+impl fmt::Debug for EventHandlers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventHandlers")
+            .field("transcript", &self.transcript.is_some())
+            .field("utterance_end", &self.utterance_end.is_some())
+            .field("error", &self.error.is_some())
+            .field("close", &self.close.is_some())
+            .finish()
+    }
+}
+
+impl EventHandlers {
+    fn received<R: WorkerResponse>(&self, response: &Result<R>) {
+        match response.as_ref().map(WorkerResponse::stream_response) {
+            Ok(Some(response @ StreamResponse::TranscriptResponse { .. })) => {
+                call(&self.transcript, response)
+            }
+            Ok(Some(response @ StreamResponse::UtteranceEndResponse { .. })) => {
+                call(&self.utterance_end, response)
+            }
+            Ok(_) => {}
+            Err(err) => call(&self.error, err),
+        }
+    }
+
+    fn closed(&self, result: &Result<()>) {
+        if let Err(err) = result {
+            call(&self.error, err);
+        }
+        if let Some(handler) = &self.close {
+            handler();
+        }
+    }
+}
+
+fn call<T>(handler: &Option<Handler<T>>, value: &T) {
+    if let Some(handler) = handler {
+        handler(value);
+    }
+}
+
+/// Logs protocol traffic for a single connection when debug logging is enabled.
+#[derive(Debug)]
+struct ProtocolLog {
+    enabled: bool,
+    request_id: Uuid,
+    runtime: Arc<dyn Runtime>,
+    opened: Instant,
+}
+
+impl ProtocolLog {
+    fn new(enabled: bool, request_id: Uuid, runtime: Arc<dyn Runtime>) -> Self {
+        ProtocolLog {
+            enabled,
+            request_id,
+            opened: runtime.now(),
+            runtime,
+        }
+    }
+
+    /// How long the connection has been open, by the runtime's clock.
+    fn elapsed(&self) -> Duration {
+        self.runtime.now().saturating_duration_since(self.opened)
+    }
+
+    fn sent(&self, message: &ControlMessage) {
+        if self.enabled {
+            tracing::debug!(
+                request_id = %self.request_id,
+                elapsed = ?self.elapsed(),
+                message = ?message,
+                "sent control message"
+            );
+        }
+    }
+
+    fn received<R: WorkerResponse>(&self, response: &Result<R>) {
+        if !self.enabled {
+            return;
+        }
+        match response {
+            Ok(response) => tracing::debug!(
+                request_id = %self.request_id,
+                elapsed = ?self.elapsed(),
+                event = response.event_type(),
+                "received event"
+            ),
+            Err(err) => tracing::debug!(
+                request_id = %self.request_id,
+                elapsed = ?self.elapsed(),
+                error = %err,
+                "received unparseable event"
+            ),
+        }
+    }
+
+    fn closed(&self, code: Option<u16>, reason: &str) {
+        if self.enabled {
+            tracing::debug!(
+                request_id = %self.request_id,
+                elapsed = ?self.elapsed(),
+                code,
+                reason,
+                "received websocket close"
+            );
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct WorkerOptions {
+    keep_alive: bool,
+    resilient_parsing: bool,
+    drain_timeout: Option<Duration>,
+    ping_interval: Option<Duration>,
+    pong_timeout: Duration,
+    handlers: EventHandlers,
+    tee: Option<AudioTee>,
+}
+
+/// A message from Deepgram, as parsed by the worker for a [`WebsocketHandle`].
+trait WorkerResponse: DeserializeOwned + Send + 'static {
+    /// The message as a [`StreamResponse`], if it was parsed into one.
+    fn stream_response(&self) -> Option<&StreamResponse>;
+
+    /// The `type` of the message, for logging.
+    fn event_type(&self) -> &str;
+}
+
+impl WorkerResponse for StreamResponse {
+    fn stream_response(&self) -> Option<&StreamResponse> {
+        Some(self)
+    }
+
+    fn event_type(&self) -> &str {
+        StreamResponse::event_type(self)
+    }
+}
+
+impl WorkerResponse for serde_json::Value {
+    fn stream_response(&self) -> Option<&StreamResponse> {
+        None
+    }
+
+    fn event_type(&self) -> &str {
+        self.get("type")
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or_default()
+    }
+}
+
+async fn run_worker<R: WorkerResponse>(
+    ws_stream: BoxWebSocket,
+    runtime: Arc<dyn Runtime>,
+    mut message_tx: Sender<WsMessage>,
+    message_rx: &mut Receiver<WsMessage>,
+    mut response_tx: Sender<Result<R>>,
+    options: WorkerOptions,
+    log: ProtocolLog,
+) -> Result<()> {
+    // We use Vec<u8> for partial frames because we don't know if a fragment of a string is valid utf-8.
+    let mut partial_frame: Vec<u8> = Vec::new();
+    let (mut ws_stream_send, ws_stream_recv) = ws_stream.split();
+    let mut ws_stream_recv = ws_stream_recv.fuse();
+    let mut is_open: bool = true;
+    let mut last_sent_message = runtime.now();
+    // When to give up on pending results after CloseStream, and the timeout that set it.
+    let mut drain: Option<(Instant, Duration)> = None;
+    let mut last_ping = runtime.now();
+    // When the connection is presumed dead if nothing has been received since the last ping.
+    let mut pong_deadline: Option<Instant> = None;
+    let mut keep_alive = options.keep_alive.then_some(KEEP_ALIVE_INTERVAL);
+    let mut paused = false;
+    loop {
+        let keep_alive_at = keep_alive
+            .or(paused.then_some(KEEP_ALIVE_INTERVAL))
+            .filter(|_| is_open)
+            .map(|interval| last_sent_message + interval);
+        let drain_at = drain.filter(|_| !is_open).map(|(deadline, _)| deadline);
+        let ping_at = options
+            .ping_interval
+            .map(|interval| pong_deadline.unwrap_or(last_ping + interval));
+        let sleep = match [keep_alive_at, drain_at, ping_at]
+            .into_iter()
+            .flatten()
+            .min()
+        {
+            Some(wake_at) => runtime.sleep(wake_at.saturating_duration_since(runtime.now())),
+            None => Box::pin(pending()),
+        };
+        // Primary event loop.
+        select_biased! {
+            _ = sleep.fuse() => {
+                let now = runtime.now();
+                if pong_deadline.is_some_and(|deadline| deadline <= now) {
+                    // Nothing has been received since the last ping; the connection is gone.
+                    let response = Err(DeepgramError::ConnectionLost { timeout: options.pong_timeout });
+                    options.handlers.received(&response);
+                    let _ = response_tx.send(response).await;
+                    return Ok(());
+                }
+                if let (Some(at), Some((_, timeout))) = (drain_at, drain) {
+                    if at <= now {
+                        // Results are still pending past the deadline; give up on them.
+                        let response = Err(DeepgramError::DrainTimeout { timeout });
+                        options.handlers.received(&response);
+                        let _ = response_tx.send(response).await;
+                        return Ok(());
+                    }
+                }
+                if ping_at.is_some_and(|at| at <= now) {
+                    send_message!(ws_stream_send, response_tx, Message::Ping(Bytes::new()));
+                    last_ping = now;
+                    pong_deadline = Some(now + options.pong_timeout);
+                }
+                if keep_alive_at.is_some_and(|at| at <= now) {
+                    // Ignore send errors: the channel may have been closed by
+                    // close_stream() (via close_channel()) before the worker
+                    // processes the pending CloseStream message. In that case
+                    // the next iteration will handle CloseStream, stop sending new
+                    // messages, and proceed toward shutdown.
+                    let _ = message_tx.send(WsMessage::ControlMessage(ControlMessage::KeepAlive)).await;
+                    last_sent_message = now;
+                }
+            }
+            response = ws_stream_recv.next() => {
+                if matches!(response, Some(Ok(_))) {
+                    // Anything received shows the connection is still alive.
+                    pong_deadline = None;
+                }
+                match response {
+                    Some(Ok(Message::Text(response))) => {
+                        let response = parse_response(response.as_bytes(), options.resilient_parsing);
+                        log.received(&response);
+                        options.handlers.received(&response);
+                        if (response_tx.send(response).await).is_err() {
+                            // Responses are no longer being received; close the stream.
+                            break;
+                        }
+                    }
+                    Some(Ok(Message::Ping(value))) => {
+                        // We don't really care if the server receives the pong.
+                        let _ = ws_stream_send.send(Message::Pong(value)).await;
+                    }
+                    Some(Ok(Message::Close(None))) => {
+                        log.closed(None, "");
+                        return Ok(());
+                    }
+                    Some(Ok(Message::Close(Some(closeframe)))) => {
+                        let code = closeframe.code.into();
+                        log.closed(Some(code), &closeframe.reason);
+                        let close = || DeepgramError::WebsocketClose {
+                            code,
+                            reason: closeframe.reason.to_string(),
+                        };
+                        // Anything but a normal closure tells the receiver why the responses ended.
+                        if code != 1000 {
+                            let _ = response_tx.send(Err(close())).await;
+                        }
+                        return Err(close());
+                    }
+
+                    Some(Ok(Message::Frame(frame))) => {
+                        #[allow(clippy::collapsible_match)]
+                        match frame.header().opcode
+                        {
+                            OpCode::Data(Data::Text) => {
+                                partial_frame.extend(frame.payload());
+                            }
+                            OpCode::Data(Data::Continue) => {
+                                // We know we're continuing a text frame because otherwise
+                                // partial_frame would be empty.
+                                if !partial_frame.is_empty() {
+                                    partial_frame.extend(frame.payload())
+                                }
+                            }
+                            _ => {
+                                // Ignore other partial frames.
+                            }
+                        }
+                        if frame.header().is_final {
+                            let response = std::mem::take(&mut partial_frame);
+                            let response = parse_response(&response, options.resilient_parsing);
+                            log.received(&response);
+                            options.handlers.received(&response);
+                            if (response_tx.send(response).await).is_err() {
+                                // Responses are no longer being received; close the stream.
+                                break
+                            }
+                        }
+                    }
+                    Some(Ok(Message::Binary(_) | Message::Pong(_))) => {
+                        // We don't expect binary messages or pongs from the API.
+                        // They can be safely ignored.
+                    }
+
+                    Some(Err(err)) => {
+                        let response = Err(err.into());
+                        options.handlers.received(&response);
+                        if (response_tx.send(response).await).is_err() {
+                            // Responses are no longer being received; close the stream.
+                            break;
+                        }
+
+                    }
+                    None => {
+                        // Upstream is closed
+                        return Ok(())
+                    }
+                }
+            }
+            message = message_rx.next() => {
+                if is_open {
+                    match message {
+                        Some(WsMessage::Audio(audio))=> {
+                            if let Some(tee) = &options.tee {
+                                tee.write(&audio.0);
+                            }
+                            send_message!(ws_stream_send, response_tx, Message::Binary(audio.0));
+                            last_sent_message = runtime.now();
+
+                        }
+                        Some(WsMessage::ControlMessage(msg)) => {
+                            send_message!(ws_stream_send, response_tx, Message::Text(
+                                Utf8Bytes::from(serde_json::to_string(&msg).unwrap_or_default())
+                            ));
+                            log.sent(&msg);
+                            last_sent_message = runtime.now();
+                            if msg == ControlMessage::CloseStream {
+                                is_open = false;
+                                drain = options.drain_timeout.map(|timeout| (runtime.now() + timeout, timeout));
+                            }
+                        }
+                        Some(WsMessage::CloseWithTimeout(timeout)) => {
+                            send_message!(ws_stream_send, response_tx, Message::Text(
+                                Utf8Bytes::from(serde_json::to_string(&ControlMessage::CloseStream).unwrap_or_default())
+                            ));
+                            log.sent(&ControlMessage::CloseStream);
+                            is_open = false;
+                            drain = Some((runtime.now() + timeout, timeout));
+                        }
+                        Some(WsMessage::KeepAliveInterval(interval)) => {
+                            keep_alive = interval;
+                        }
+                        Some(WsMessage::Paused(is_paused)) => {
+                            paused = is_paused;
+                        }
+                        None => {
+                            // Input stream is shut down.  Keep processing responses.
+                            send_message!(ws_stream_send, response_tx, Message::Text(
+                                Utf8Bytes::from(serde_json::to_string(&ControlMessage::CloseStream).unwrap_or_default())
+                            ));
+                            log.sent(&ControlMessage::CloseStream);
+                            is_open = false;
+                            drain = options.drain_timeout.map(|timeout| (runtime.now() + timeout, timeout));
+                        }
+                    }
+                }
+            }
+        };
+    }
+    if let Err(err) = ws_stream_send
+        .send(Message::Text(Utf8Bytes::from(
+            serde_json::to_string(&ControlMessage::CloseStream).unwrap_or_default(),
+        )))
+        .await
+    {
+        // If the response channel is closed, there's nothing to be done about it now.
+        let _ = response_tx.send(Err(err.into())).await;
+    } else {
+        log.sent(&ControlMessage::CloseStream);
+    }
+    response_tx.close_channel();
+    // Waiting for message_tx to be dropped before exiting
+    while message_rx.next().await.is_some() {
+        // Receiving messages after closing down. Ignore them.
+    }
+    Ok(())
+}
+
+/// Close `message_rx` and take the audio still queued in it, which a worker
+/// that has stopped never sent.
+fn unsent_audio(mut message_rx: Receiver<WsMessage>) -> Vec<Bytes> {
+    message_rx.close();
+    std::iter::from_fn(|| message_rx.try_recv().ok())
+        .filter_map(|message| match message {
+            WsMessage::Audio(audio) => Some(audio.0),
+            _ => None,
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum WsMessage {
+    Audio(Audio),
+    ControlMessage(ControlMessage),
+    /// Send CloseStream, then wait at most this long for the remaining results.
+    CloseWithTimeout(Duration),
+    /// Send KeepAlive after this long without sending anything, or never.
+    KeepAliveInterval(Option<Duration>),
+    /// Whether audio is paused, so KeepAlive has to be sent even if it is off.
+    Paused(bool),
+}
+
+#[derive(Clone, PartialEq, Eq)]
+struct Audio(Bytes);
+
+impl fmt::Debug for Audio {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("audio")
+            .field(&format!(
+                "<{} bytes (sha256:{})>",
+                self.0.len(),
+                &sha256::digest(&self.0[..])[..12]
+            ))
+            .finish()
+    }
+}
+
+impl Deref for Audio {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// Details of a live connection that Deepgram reported in the websocket
+/// upgrade response.
+///
+/// Returned by [`WebsocketHandle::connection_info`] and
+/// [`TranscriptionStream::connection_info`].
+#[derive(Debug, Clone)]
+pub struct ConnectionInfo {
+    request_id: Uuid,
+    headers: HeaderMap,
+}
+
+impl ConnectionInfo {
+    /// The Deepgram request ID of the connection.
+    pub fn request_id(&self) -> Uuid {
+        self.request_id
+    }
+
+    /// All headers of the upgrade response.
+    ///
+    /// Headers set by Deepgram start with `dg-`.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// The value of the upgrade response header `name`, if it is present and
+    /// valid UTF-8.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name)?.to_str().ok()
+    }
+}
+
+/// A live transcription connection.
+///
+/// Responses are parsed into `R`: a [`StreamResponse`] for handles opened with
+/// [`WebsocketBuilder::handle`], or the raw [`serde_json::Value`] for handles
+/// opened with [`WebsocketBuilder::handle_raw`].
+#[derive(Debug)]
+pub struct WebsocketHandle<R = StreamResponse> {
+    message_tx: Sender<WsMessage>,
+    response_rx: Receiver<Result<R>>,
+    /// The audio left in the message channel once the worker stops.
+    unsent_rx: oneshot::Receiver<Vec<Bytes>>,
+    info: ConnectionInfo,
+    metrics: Option<Arc<dyn Metrics>>,
+}
+
+impl<R> WebsocketHandle<R> {
+    async fn new(builder: WebsocketBuilder<'_>) -> Result<WebsocketHandle<R>>
+    where
+        R: WorkerResponse,
+    {
+        builder.validate()?;
+        let url = builder.as_url()?;
+        let host = url.host_str().ok_or(DeepgramError::InvalidUrl)?;
+
+        let auth = builder.deepgram.current_auth().await?;
+        let mut request = {
+            let http_builder = Request::builder()
+                .method("GET")
+                .uri(url.to_string())
+                .header("sec-websocket-key", client::generate_key())
+                .header("host", host)
+                .header("connection", "upgrade")
+                .header("upgrade", "websocket")
+                .header("sec-websocket-version", "13")
+                .header("user-agent", builder.deepgram.user_agent.clone());
+
+            let builder = match &auth {
+                Some(auth) if builder.subprotocol_auth.unwrap_or(false) => {
+                    http_builder.header("sec-websocket-protocol", auth.websocket_protocol())
+                }
+                Some(auth) => http_builder.header("authorization", auth.header_value()),
+                None => http_builder,
+            };
+            builder.body(())?
+        };
+
+        add_missing_headers(request.headers_mut(), &builder.extra_headers);
+        builder.deepgram.add_default_headers(request.headers_mut());
+
+        let middleware = builder.deepgram.client.middleware();
+        for middleware in middleware {
+            middleware.on_websocket_request(&mut request);
+        }
+
+        let metrics = builder.deepgram.client.metrics().cloned();
+        let start = builder.runtime.now();
+        let connection = transport::connect(
+            builder.transport.as_ref(),
+            builder.runtime.as_ref(),
+            request,
+            builder.connect_timeout,
+        )
+        .await;
+        if let Some(metrics) = &metrics {
+            let duration = builder.runtime.now().saturating_duration_since(start);
+            metrics.record_websocket_connect(&url, connection.is_ok(), duration);
+        }
+        let transport::Connection {
+            socket: ws_stream,
+            response: upgrade_response,
+        } = connection?;
+
+        for middleware in middleware {
+            middleware.on_websocket_response(&upgrade_response);
+        }
+
+        let request_id = upgrade_response
+            .headers()
+            .get("dg-request-id")
+            .ok_or(DeepgramError::UnexpectedServerResponse(anyhow!(
+                "Websocket upgrade headers missing request ID"
+            )))?
+            .to_str()
+            .ok()
+            .and_then(|req_header_str| Uuid::parse_str(req_header_str).ok())
+            .ok_or(DeepgramError::UnexpectedServerResponse(anyhow!(
+                "Received malformed request ID in websocket upgrade headers"
+            )))?;
+
+        if let Some(tee) = &builder.tee {
+            tee.open(
+                &builder.runtime,
+                builder.encoding.as_ref(),
+                builder.sample_rate,
+                builder.channels,
+            )
+            .await?;
+        }
+
+        let (message_tx, mut message_rx) = mpsc::channel(builder.audio_buffer.unwrap_or(256));
+        let (response_tx, response_rx) = mpsc::channel(builder.response_buffer.unwrap_or(256));
+        let (unsent_tx, unsent_rx) = oneshot::channel();
+
+        let runtime = Arc::clone(&builder.runtime);
+        let options = WorkerOptions {
+            keep_alive: builder.keep_alive.unwrap_or(false),
+            resilient_parsing: builder.resilient_parsing.unwrap_or(false),
+            drain_timeout: builder.drain_timeout,
+            ping_interval: builder.ping_interval,
+            pong_timeout: builder.pong_timeout.unwrap_or(DEFAULT_PONG_TIMEOUT),
+            handlers: builder.handlers.clone(),
+            tee: builder.tee.clone(),
+        };
+        let log = ProtocolLog::new(
+            builder.debug_logging.unwrap_or(false),
+            request_id,
+            Arc::clone(&builder.runtime),
+        );
+        let worker_tx = message_tx.clone();
+        builder.runtime.spawn(Box::pin(
+            async move {
+                let result = run_worker(
+                    ws_stream,
+                    runtime,
+                    worker_tx,
+                    &mut message_rx,
+                    response_tx,
+                    options,
+                    log,
+                )
+                .await;
+                // Hand back the audio that never made it out, so a
+                // reconnecting stream can resend it.
+                let _ = unsent_tx.send(unsent_audio(message_rx));
+                result
+            }
+            .then({
+                let handlers = builder.handlers.clone();
+                let tee = builder.tee.clone();
+                move |result| async move {
+                    if let Some(tee) = tee {
+                        tee.flush().await;
+                    }
+                    handlers.closed(&result)
+                }
+            }),
+        ));
+
+        Ok(WebsocketHandle {
+            message_tx,
+            response_rx,
+            unsent_rx,
+            info: ConnectionInfo {
+                request_id,
+                headers: upgrade_response.headers().clone(),
+            },
+            metrics,
+        })
+    }
+
+    /// Send a chunk of audio.
+    ///
+    /// Takes anything convertible into [`Bytes`], such as a [`Vec<u8>`], so
+    /// audio that is already in a [`Bytes`] is sent without being copied.
+    pub async fn send_data(&mut self, data: impl Into<Bytes>) -> Result<(), LiveError> {
+        let audio = Audio(data.into());
+        let len = audio.len();
+
+        self.message_tx
+            .send(WsMessage::Audio(audio))
+            .await
+            .map_err(|err| DeepgramError::InternalClientError(err.into()))?;
+        if let Some(metrics) = &self.metrics {
+            metrics.record_audio_sent(len);
+        }
+        Ok(())
+    }
+
+    /// Send a Finalize message to the Deepgram API to force the server to process
+    /// all the audio it has already received.
+    pub async fn finalize(&mut self) -> Result<(), LiveError> {
+        Ok(self.send_control_message(ControlMessage::Finalize).await?)
+    }
+
+    /// Send a KeepAlive message to the Deepgram API to ensure the connection
+    /// isn't closed due to long idle times.
+    pub async fn keep_alive(&mut self) -> Result<(), LiveError> {
+        Ok(self.send_control_message(ControlMessage::KeepAlive).await?)
+    }
+
+    /// Send a KeepAlive message whenever nothing has been sent for `interval`,
+    /// or stop sending them automatically if `interval` is [`None`].
+    ///
+    /// Overrides [`WebsocketBuilder::keep_alive`], which sends them every
+    /// 3 seconds.
+    pub async fn set_keep_alive_interval(
+        &mut self,
+        interval: Option<Duration>,
+    ) -> Result<(), LiveError> {
+        self.message_tx
+            .send(WsMessage::KeepAliveInterval(interval))
+            .await
+            .map_err(|err| DeepgramError::InternalClientError(err.into()))?;
+        Ok(())
+    }
+
+    async fn set_paused(&mut self, paused: bool) -> Result<()> {
+        self.message_tx
+            .send(WsMessage::Paused(paused))
+            .await
+            .map_err(|err| DeepgramError::InternalClientError(err.into()))
+    }
+
+    /// Close the websocket stream. No more data should be sent after this is called.
+    pub async fn close_stream(&mut self) -> Result<(), LiveError> {
+        if !self.message_tx.is_closed() {
+            self.send_control_message(ControlMessage::CloseStream)
+                .await?;
+            self.message_tx.close_channel();
+        }
+        Ok(())
+    }
+
+    /// Close the websocket stream like [`WebsocketHandle::close_stream`], then
+    /// wait at most `timeout` for the remaining results.
+    ///
+    /// If Deepgram hasn't closed the connection by then,
+    /// [`WebsocketHandle::receive`] returns [`DeepgramError::DrainTimeout`]
+    /// followed by [`None`]. Overrides [`WebsocketBuilder::drain_timeout`].
+    pub async fn close_with_timeout(&mut self, timeout: Duration) -> Result<(), LiveError> {
+        if !self.message_tx.is_closed() {
+            self.message_tx
+                .send(WsMessage::CloseWithTimeout(timeout))
+                .await
+                .map_err(|err| DeepgramError::InternalClientError(err.into()))?;
+            self.message_tx.close_channel();
+        }
+        Ok(())
+    }
+
+    async fn send_control_message(&mut self, message: ControlMessage) -> Result<()> {
+        self.message_tx
+            .send(WsMessage::ControlMessage(message))
+            .await
+            .map_err(|err| DeepgramError::InternalClientError(err.into()))?;
+        Ok(())
+    }
+
+    /// The audio that was queued but never sent, once the connection has
+    /// closed.
+    async fn unsent_audio(&mut self) -> Vec<Bytes> {
+        (&mut self.unsent_rx).await.unwrap_or_default()
+    }
+
+    pub async fn receive(&mut self) -> Option<Result<R, LiveError>> {
+        let response = self.response_rx.next().await?;
+        Some(response.map_err(Into::into))
+    }
+
+    pub fn request_id(&self) -> Uuid {
+        self.info.request_id
+    }
+
+    /// Details of the connection from the websocket upgrade response.
+    pub fn connection_info(&self) -> &ConnectionInfo {
+        &self.info
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(tag = "type")]
+enum ControlMessage {
+    Finalize,
+    KeepAlive,
+    CloseStream,
+}
+
+#[derive(Debug)]
+#[pin_project]
+pub struct TranscriptionStream {
+    #[pin]
+    rx: Receiver<Result<StreamResponse>>,
+    done: bool,
+    info: ConnectionInfo,
+    control: StreamControl,
+    stats: Option<Arc<Mutex<StreamStats>>>,
+}
+
+/// Sends audio over a connection opened with [`WebsocketBuilder::connect`].
+///
+/// Any stream of audio can be sent with [`StreamExt::forward`] or
+/// [`SinkExt::send_all`], without handing it to [`WebsocketBuilder::stream`]
+/// up front.
+///
+/// ```no_run
+/// # use deepgram::{Deepgram, DeepgramError};
+/// # use bytes::Bytes;
+/// # use futures::StreamExt;
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), DeepgramError> {
+/// let dg = Deepgram::new(std::env::var("DEEPGRAM_API_KEY").unwrap_or_default())?;
+/// let (audio, mut results) = dg.transcription().stream_request().connect().await?;
+///
+/// # let source = futures::stream::empty::<Bytes>();
+/// tokio::spawn(source.map(Ok).forward(audio));
+/// while let Some(response) = results.next().await {
+///     println!("{:?}", response?);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct AudioSink {
+    tx: Sender<Bytes>,
+}
+
+impl Sink<Bytes> for AudioSink {
+    type Error = LiveError;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), LiveError>> {
+        self.tx.poll_ready(cx).map_err(sink_error)
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, audio: Bytes) -> Result<(), LiveError> {
+        self.tx.start_send(audio).map_err(sink_error)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), LiveError>> {
+        Pin::new(&mut self.tx).poll_flush(cx).map_err(sink_error)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), LiveError>> {
+        Pin::new(&mut self.tx).poll_close(cx).map_err(sink_error)
+    }
+}
+
+pub(crate) fn sink_error(err: SendError) -> LiveError {
+    DeepgramError::InternalClientError(err.into()).into()
+}
+
+/// Sends control messages over the connection of a [`TranscriptionStream`].
+///
+/// Returned by [`TranscriptionStream::control`]. Messages are sent in order,
+/// between the chunks of audio from the source. Once the stream has ended,
+/// sending fails with [`DeepgramError::InternalClientError`].
+#[derive(Debug, Clone)]
+pub struct StreamControl {
+    tx: UnboundedSender<StreamCommand>,
+}
+
+impl StreamControl {
+    /// Send a Finalize message to the Deepgram API to force the server to process
+    /// all the audio it has already received.
+    pub fn finalize(&self) -> Result<(), LiveError> {
+        Ok(self.send(ControlMessage::Finalize)?)
+    }
+
+    /// Send a KeepAlive message to the Deepgram API to ensure the connection
+    /// isn't closed due to long idle times.
+    pub fn keep_alive(&self) -> Result<(), LiveError> {
+        Ok(self.send(ControlMessage::KeepAlive)?)
+    }
+
+    /// Stop sending audio and close the stream, including any audio that was
+    /// queued. Responses to the audio already sent are still received.
+    pub fn close_stream(&self) -> Result<(), LiveError> {
+        Ok(self.send(ControlMessage::CloseStream)?)
+    }
+
+    /// Stop sending audio, and keep the connection open with KeepAlive
+    /// messages until [`StreamControl::resume`] is called.
+    ///
+    /// Audio read from the source while paused is dropped, so nothing is
+    /// transcribed while, for example, a microphone is muted. KeepAlive
+    /// messages are sent every 3 seconds, or at the interval set with
+    /// [`StreamControl::set_keep_alive_interval`].
+    pub fn pause(&self) -> Result<(), LiveError> {
+        Ok(self.command(StreamCommand::Pause)?)
+    }
+
+    /// Start sending audio from the source again after
+    /// [`StreamControl::pause`].
+    pub fn resume(&self) -> Result<(), LiveError> {
+        Ok(self.command(StreamCommand::Resume)?)
+    }
+
+    /// Send a KeepAlive message whenever nothing has been sent for
+    /// `interval`, or only while paused if `interval` is [`None`].
+    ///
+    /// See [`WebsocketHandle::set_keep_alive_interval`].
+    pub fn set_keep_alive_interval(&self, interval: Option<Duration>) -> Result<(), LiveError> {
+        Ok(self.command(StreamCommand::KeepAliveInterval(interval))?)
+    }
+
+    fn send(&self, message: ControlMessage) -> Result<()> {
+        self.command(StreamCommand::Send(message))
+    }
+
+    fn command(&self, command: StreamCommand) -> Result<()> {
+        self.tx
+            .unbounded_send(command)
+            .map_err(|err| DeepgramError::InternalClientError(err.into()))
+    }
+}
+
+/// A request made through a [`StreamControl`].
+#[derive(Debug)]
+enum StreamCommand {
+    Send(ControlMessage),
+    KeepAliveInterval(Option<Duration>),
+    Pause,
+    Resume,
+}
+
+/// The final transcript of a live stream, as returned by
+/// [`TranscriptionStream::collect_transcript`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct StreamTranscript {
+    /// The final results of the first channel, with a line for each stretch
+    /// of speech that Deepgram ended with a `speech_final` result.
+    pub transcript: String,
+    /// The words of the final results of the first channel, in order.
+    pub words: Vec<Word>,
+    /// The Deepgram request ID of the stream's first connection.
+    pub request_id: Uuid,
+    /// Seconds of audio Deepgram processed, from the Metadata message it sends
+    /// when each connection closes.
+    pub duration: Option<f64>,
+    /// Info about each model used, keyed by UUID.
+    pub model_info: HashMap<String, ModelInfo>,
+}
+
+impl Stream for TranscriptionStream {
+    type Item = Result<StreamResponse, LiveError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        this.rx
+            .poll_next(cx)
+            .map(|response| response.map(|response| response.map_err(Into::into)))
+    }
+}
+
+impl TranscriptionStream {
+    /// Returns the Deepgram request ID for the speech-to-text live request.
+    ///
+    /// A request ID needs to be provided to Deepgram as part of any support
+    /// or troubleshooting assistance related to a specific request.
+    pub fn request_id(&self) -> Uuid {
+        self.info.request_id
+    }
+
+    /// Details of the stream's first connection from the websocket upgrade
+    /// response.
+    ///
+    /// Reconnects are announced with a [`StreamResponse::Reconnected`]
+    /// carrying the new request ID.
+    pub fn connection_info(&self) -> &ConnectionInfo {
+        &self.info
+    }
+
+    /// A handle for sending control messages while the stream is being
+    /// polled elsewhere.
+    pub fn control(&self) -> StreamControl {
+        self.control.clone()
+    }
+
+    /// A snapshot of the audio sent and results received so far, if the
+    /// stream was started with [`WebsocketBuilder::track_metrics`].
+    pub fn metrics(&self) -> Option<StreamMetrics> {
+        self.stats
+            .as_ref()
+            .map(|stats| stats.lock().unwrap().snapshot())
+    }
+
+    /// Force the server to process all the audio it has already received.
+    ///
+    /// See [`StreamControl::finalize`].
+    pub fn finalize(&self) -> Result<(), LiveError> {
+        self.control.finalize()
+    }
+
+    /// Keep the connection open while no audio is being sent.
+    ///
+    /// See [`StreamControl::keep_alive`].
+    pub fn keep_alive(&self) -> Result<(), LiveError> {
+        self.control.keep_alive()
+    }
+
+    /// Stop sending audio and close the stream.
+    ///
+    /// See [`StreamControl::close_stream`].
+    pub fn close_stream(&self) -> Result<(), LiveError> {
+        self.control.close_stream()
+    }
+
+    /// Stop sending audio, keeping the connection open with KeepAlive
+    /// messages.
+    ///
+    /// See [`StreamControl::pause`].
+    pub fn pause(&self) -> Result<(), LiveError> {
+        self.control.pause()
+    }
+
+    /// Start sending audio again after [`TranscriptionStream::pause`].
+    ///
+    /// See [`StreamControl::resume`].
+    pub fn resume(&self) -> Result<(), LiveError> {
+        self.control.resume()
+    }
+
+    /// Change how often KeepAlive messages are sent.
+    ///
+    /// See [`StreamControl::set_keep_alive_interval`].
+    pub fn set_keep_alive_interval(&self, interval: Option<Duration>) -> Result<(), LiveError> {
+        self.control.set_keep_alive_interval(interval)
+    }
+
+    /// Receive the rest of the stream and put its final results together.
+    ///
+    /// Interim results are skipped. Results for channels other than the
+    /// first, and in alternatives other than the first, are ignored.
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use deepgram::{Deepgram, DeepgramError};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), DeepgramError> {
+    /// let dg = Deepgram::new(std::env::var("DEEPGRAM_API_KEY").unwrap_or_default())?;
+    /// let transcript = dg
+    ///     .transcription()
+    ///     .stream_request()
+    ///     .file("audio.ogg", 3174, Duration::from_millis(16))
+    ///     .await?
+    ///     .collect_transcript()
+    ///     .await?;
+    /// println!("{}", transcript.transcript);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error the stream yields.
+    pub async fn collect_transcript(mut self) -> Result<StreamTranscript, LiveError> {
+        let mut collected = StreamTranscript {
+            transcript: String::new(),
+            words: Vec::new(),
+            request_id: self.info.request_id,
+            duration: None,
+            model_info: HashMap::new(),
+        };
+        let mut line = String::new();
+
+        while let Some(response) = self.next().await {
+            match response? {
+                StreamResponse::TranscriptResponse {
+                    is_final: true,
+                    speech_final,
+                    channel,
+                    channel_index,
+                    ..
+                } if channel_index.first().is_none_or(|&index| index == 0) => {
+                    if let Some(alternative) = channel.alternatives.into_iter().next() {
+                        if !alternative.transcript.is_empty() {
+                            if !line.is_empty() {
+                                line.push(' ');
+                            }
+                            line.push_str(&alternative.transcript);
+                        }
+                        collected.words.extend(alternative.words);
+                    }
+                    if speech_final {
+                        push_line(&mut collected.transcript, &mut line);
+                    }
+                }
+                StreamResponse::TerminalResponse {
+                    duration,
+                    model_info,
+                    ..
+                } => {
+                    *collected.duration.get_or_insert(0.0) += duration;
+                    collected.model_info.extend(model_info);
+                }
+                _ => {}
+            }
+        }
+        push_line(&mut collected.transcript, &mut line);
+
+        Ok(collected)
+    }
+}
+
+/// Move `line`, if it has any text, to the end of `transcript`.
+fn push_line(transcript: &mut String, line: &mut String) {
+    if line.is_empty() {
+        return;
+    }
+    if !transcript.is_empty() {
+        transcript.push('\n');
+    }
+    transcript.push_str(line);
+    line.clear();
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use std::{
+        collections::VecDeque,
+        fmt,
+        pin::Pin,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, Mutex,
+        },
+        task::{Context, Poll},
+        time::Duration,
+    };
+
+    use bytes::Bytes;
+    use futures::{
+        channel::{mpsc, oneshot},
+        future::BoxFuture,
+        ready, FutureExt, Sink, SinkExt, Stream, StreamExt,
+    };
+    use tungstenite::{protocol::Message, Error as WsError};
+    use uuid::Uuid;
+
+    use super::{
+        run_worker, ControlMessage, EventHandlers, ProtocolLog, ReconnectPolicy, WorkerOptions,
+        DEFAULT_PONG_TIMEOUT,
+    };
+    use crate::common::{
+        fixtures::{stream_word, TranscriptResponseBuilder},
+        options::{Callback, CallbackMethod, Encoding, Endpointing, Options},
+        stream_response::StreamResponse,
+    };
+    use crate::{
+        listen::transport::{Connection, TokioRuntime, Transport},
+        Result,
+    };
+
+    #[test]
+    fn test_stream_url() {
+        let dg = crate::Deepgram::new("token").unwrap();
+        assert_eq!(
+            dg.transcription().listen_stream_url().to_string(),
+            "wss://api.deepgram.com/v1/listen",
+        );
+    }
+
+    #[test]
+    fn test_stream_url_custom_host() {
+        let dg =
+            crate::Deepgram::with_base_url_and_api_key("http://localhost:8080", "token").unwrap();
+        assert_eq!(
+            dg.transcription().listen_stream_url().to_string(),
+            "ws://localhost:8080/v1/listen",
+        );
+    }
+
+    #[test]
+    fn query_escaping() {
+        let dg = crate::Deepgram::new("token").unwrap();
+        let opts = Options::builder().custom_topics(["A&R"]).build();
+        let transcription = dg.transcription();
+        let builder = transcription.stream_request_with_options(opts.clone());
+        assert_eq!(builder.urlencoded().unwrap(), opts.urlencoded().unwrap())
+    }
+
+    #[test]
+    fn mip_opt_out_is_sent_when_streaming() {
+        let dg = crate::Deepgram::new("token").unwrap();
+        let opts = Options::builder().mip_opt_out(true).build();
+        let transcription = dg.transcription();
+        let builder = transcription.stream_request_with_options(opts);
+        assert!(builder
+            .urlencoded()
+            .unwrap()
+            .split('&')
+            .any(|param| param == "mip_opt_out=true"));
+    }
+
+    #[tokio::test]
+    async fn sends_extra_query_params_and_headers() {
+        #[derive(Debug, Default)]
+        struct RecordHeaders(Arc<Mutex<http::HeaderMap>>);
+
+        impl crate::Middleware for RecordHeaders {
+            fn on_websocket_request(&self, request: &mut http::Request<()>) {
+                *self.0.lock().unwrap() = request.headers().clone();
+            }
+        }
+
+        let recorded = RecordHeaders::default();
+        let headers = Arc::clone(&recorded.0);
+        let mut default_headers = http::HeaderMap::new();
+        default_headers.insert("x-org-id", "default".parse().unwrap());
+        let dg = crate::Deepgram::builder()
+            .api_key("token")
+            .default_headers(default_headers)
+            .middleware(recorded)
+            .build()
+            .unwrap();
+
+        let mut extra_headers = http::HeaderMap::new();
+        extra_headers.insert("x-org-id", "acme".parse().unwrap());
+        extra_headers.insert("authorization", "ignored".parse().unwrap());
+        let transcription = dg.transcription();
+        let builder = transcription
+            .stream_request()
+            .encoding(Encoding::Linear16)
+            .sample_rate(16000)
+            .extra_query_params([("gateway".to_string(), "a&b".to_string())])
+            .extra_headers(extra_headers)
+            .transport(FakeTransport::new([]));
+        assert_eq!(
+            builder.urlencoded().unwrap(),
+            "encoding=linear16&sample_rate=16000&gateway=a%26b"
+        );
+
+        // The fake transport refuses to connect once the headers are recorded.
+        builder.handle().await.unwrap_err();
+        let headers = headers.lock().unwrap();
+        assert_eq!(headers["x-org-id"], "acme");
+        assert_eq!(headers["authorization"], "Token token");
+    }
+
+    #[tokio::test]
+    async fn authenticates_with_configured_method() {
+        #[derive(Debug, Default)]
+        struct RecordHeaders(Arc<Mutex<http::HeaderMap>>);
+
+        impl crate::Middleware for RecordHeaders {
+            fn on_websocket_request(&self, request: &mut http::Request<()>) {
+                *self.0.lock().unwrap() = request.headers().clone();
+            }
+        }
+
+        for subprotocol_auth in [false, true] {
+            let recorded = RecordHeaders::default();
+            let headers = Arc::clone(&recorded.0);
+            let dg = crate::Deepgram::builder()
+                .temp_token("temp")
+                .middleware(recorded)
+                .build()
+                .unwrap();
+            let transcription = dg.transcription();
+            let mut builder = transcription
+                .stream_request()
+                .transport(FakeTransport::new([]));
+            if subprotocol_auth {
+                builder = builder.subprotocol_auth();
+            }
+
+            // The fake transport refuses to connect once the headers are recorded.
+            builder.handle().await.unwrap_err();
+            let headers = headers.lock().unwrap();
+            if subprotocol_auth {
+                assert_eq!(headers["sec-websocket-protocol"], "bearer, temp");
+                assert!(!headers.contains_key("authorization"));
+            } else {
+                assert_eq!(headers["authorization"], "Bearer temp");
+                assert!(!headers.contains_key("sec-websocket-protocol"));
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn validates_utterance_end_ms() {
+        let dg = crate::Deepgram::new("token").unwrap();
+        let transcription = dg.transcription();
+
+        let builder = transcription.stream_request().utterance_end_ms(65_000);
+        assert_eq!(builder.urlencoded().unwrap(), "utterance_end_ms=65000");
+
+        let err = transcription
+            .stream_request()
+            .utterance_end_ms(500)
+            .transport(FakeTransport::new([]))
+            .handle()
+            .await
+            .unwrap_err();
+        assert!(matches!(err, crate::listen::LiveError::InvalidOption(_)));
+    }
+
+    #[tokio::test]
+    async fn validates_raw_audio_options() {
+        let dg = crate::Deepgram::new("token").unwrap();
+        let transcription = dg.transcription();
+
+        for builder in [
+            transcription.stream_request().encoding(Encoding::Linear16),
+            transcription.stream_request().sample_rate(16000),
+            transcription.stream_request().channels(2),
+        ] {
+            let err = builder
+                .transport(FakeTransport::new([]))
+                .handle()
+                .await
+                .unwrap_err();
+            assert!(matches!(err, crate::listen::LiveError::InvalidOption(_)));
+        }
+
+        let err = transcription
+            .stream_request()
+            .encoding(Encoding::Linear16)
+            .sample_rate(16000)
+            .transport(FakeTransport::new([]))
+            .handle()
+            .await
+            .unwrap_err();
+        assert!(!matches!(err, crate::listen::LiveError::InvalidOption(_)));
+    }
+
+    #[tokio::test]
+    async fn validates_streaming_callbacks() {
+        let dg = crate::Deepgram::new("token").unwrap();
+        let transcription = dg.transcription();
+        let callback = Callback::new("https://example.com/hook").unwrap();
+
+        let builder = transcription
+            .stream_request_with_options(
+                Options::builder()
+                    .callback_method(CallbackMethod::POST)
+                    .build(),
+            )
+            .callback(callback.clone().method(CallbackMethod::POST));
+        assert_eq!(
+            builder.urlencoded().unwrap(),
+            "callback=https%3A%2F%2Fexample.com%2Fhook"
+        );
+
+        for builder in [
+            transcription
+                .stream_request()
+                .callback(callback.method(CallbackMethod::PUT)),
+            transcription.stream_request_with_options(
+                Options::builder()
+                    .callback_method(CallbackMethod::PUT)
+                    .build(),
+            ),
+        ] {
+            let err = builder
+                .transport(FakeTransport::new([]))
+                .handle()
+                .await
+                .unwrap_err();
+            assert!(matches!(err, crate::listen::LiveError::InvalidOption(_)));
+        }
+    }
+
+    #[test]
+    fn debug_redacts_callback_credentials() {
+        let dg = crate::Deepgram::new("token").unwrap();
+        let transcription = dg.transcription();
+        let builder = transcription
+            .stream_request()
+            .callback(Callback::new("https://example.com/hook?token=callback_secret").unwrap());
+        assert!(!format!("{builder:?}").contains("callback_secret"));
+    }
+
+    #[test]
+    fn control_message_format() {
+        assert_eq!(
+            &serde_json::to_string(&ControlMessage::CloseStream).unwrap(),
+            r#"{"type":"CloseStream"}"#
+        );
+    }
+
+    #[test]
+    fn logged_event_types() {
+        let speech_started: StreamResponse =
+            serde_json::from_str(r#"{"type":"SpeechStarted","channel":[0,1],"timestamp":1.5}"#)
+                .unwrap();
+        assert_eq!(speech_started.event_type(), "SpeechStarted");
+
+        let terminal: StreamResponse = serde_json::from_str(
+            r#"{"request_id":"abc","created":"2024-01-01T00:00:00Z","duration":1.0,"channels":1}"#,
+        )
+        .unwrap();
+        assert_eq!(terminal.event_type(), "Metadata");
+    }
+
+    /// An in-memory websocket: messages sent by the client arrive on `sent`,
+    /// and messages pushed into `incoming` are received by it.
+    pub(in crate::listen) struct FakeSocket {
+        sent: mpsc::UnboundedSender<Message>,
+        incoming: mpsc::UnboundedReceiver<std::result::Result<Message, WsError>>,
+        /// Holds up sending until the other end is used or dropped.
+        stall: Option<oneshot::Receiver<()>>,
+    }
+
+    impl Stream for FakeSocket {
+        type Item = std::result::Result<Message, WsError>;
+
+        fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            self.incoming.poll_next_unpin(cx)
+        }
+    }
+
+    impl Sink<Message> for FakeSocket {
+        type Error = WsError;
+
+        fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), WsError>> {
+            if let Some(stall) = &mut self.stall {
+                let _ = ready!(stall.poll_unpin(cx));
+                self.stall = None;
+            }
+            Poll::Ready(Ok(()))
+        }
+
+        fn start_send(self: Pin<&mut Self>, item: Message) -> Result<(), WsError> {
+            self.sent
+                .unbounded_send(item)
+                .map_err(|_| WsError::ConnectionClosed)
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), WsError>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), WsError>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn keep_alive_follows_runtime_clock() {
+        let (socket, mut sent_rx, _incoming_tx) = fake_socket();
+        let (message_tx, message_rx) = mpsc::channel(256);
+        let (response_tx, _response_rx) = mpsc::channel(256);
+        let mut message_rx = message_rx;
+        tokio::spawn(async move {
+            run_worker::<StreamResponse>(
+                Box::pin(socket),
+                Arc::new(TokioRuntime),
+                message_tx.clone(),
+                &mut message_rx,
+                response_tx,
+                WorkerOptions {
+                    keep_alive: true,
+                    resilient_parsing: false,
+                    drain_timeout: None,
+                    ping_interval: None,
+                    pong_timeout: DEFAULT_PONG_TIMEOUT,
+                    handlers: EventHandlers::default(),
+                    tee: None,
+                },
+                ProtocolLog::new(false, Uuid::nil(), Arc::new(TokioRuntime)),
+            )
+            .await
+        });
+
+        tokio::time::sleep(Duration::from_millis(2900)).await;
+        assert!(sent_rx.try_recv().is_err(), "nothing sent before 3s");
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(
+            sent_rx.next().await.unwrap(),
+            Message::Text(r#"{"type":"KeepAlive"}"#.into())
+        );
+
+        tokio::time::sleep(Duration::from_millis(2900)).await;
+        assert!(
+            sent_rx.try_recv().is_err(),
+            "interval restarts after a send"
+        );
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(
+            sent_rx.next().await.unwrap(),
+            Message::Text(r#"{"type":"KeepAlive"}"#.into())
+        );
+    }
+
+    /// Hands out each [`FakeSocket`] in turn as if the handshake succeeded,
+    /// with request IDs counting up from zero, then refuses to connect.
+    #[derive(Debug)]
+    pub(in crate::listen) struct FakeTransport {
+        sockets: Mutex<VecDeque<FakeSocket>>,
+        connections: AtomicUsize,
+    }
+
+    impl FakeTransport {
+        pub(in crate::listen) fn new(sockets: impl IntoIterator<Item = FakeSocket>) -> Self {
+            FakeTransport {
+                sockets: Mutex::new(sockets.into_iter().collect()),
+                connections: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    /// A [`FakeSocket`], along with the messages the client sends over it and
+    /// a sender for the messages it receives.
+    pub(in crate::listen) fn fake_socket() -> (
+        FakeSocket,
+        mpsc::UnboundedReceiver<Message>,
+        mpsc::UnboundedSender<std::result::Result<Message, WsError>>,
+    ) {
+        let (sent_tx, sent_rx) = mpsc::unbounded();
+        let (incoming_tx, incoming_rx) = mpsc::unbounded();
+        let socket = FakeSocket {
+            sent: sent_tx,
+            incoming: incoming_rx,
+            stall: None,
+        };
+        (socket, sent_rx, incoming_tx)
+    }
+
+    /// A [`FakeTransport`] with a single [`fake_socket`].
+    pub(in crate::listen) fn fake_connection() -> (
+        FakeTransport,
+        mpsc::UnboundedReceiver<Message>,
+        mpsc::UnboundedSender<std::result::Result<Message, WsError>>,
+    ) {
+        let (socket, sent_rx, incoming_tx) = fake_socket();
+        (FakeTransport::new([socket]), sent_rx, incoming_tx)
+    }
+
+    impl fmt::Debug for FakeSocket {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("FakeSocket")
+        }
+    }
+
+    impl Transport for FakeTransport {
+        fn connect(&self, _: http::Request<()>) -> BoxFuture<'static, Result<Connection>> {
+            let Some(socket) = self.sockets.lock().unwrap().pop_front() else {
+                return Box::pin(async {
+                    Err(std::io::Error::from(std::io::ErrorKind::ConnectionRefused).into())
+                });
+            };
+            let request_id =
+                Uuid::from_u128(self.connections.fetch_add(1, Ordering::SeqCst) as u128);
+            let response = http::Response::builder()
+                .header("dg-request-id", request_id.to_string())
+                .body(None)
+                .unwrap();
+            Box::pin(async move {
+                Ok(Connection {
+                    socket: Box::pin(socket),
+                    response,
+                })
+            })
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn prewarm_keeps_connection_alive() {
+        let (transport, mut sent_rx, _incoming_tx) = fake_connection();
+
+        let dg = crate::Deepgram::new("token").unwrap();
+        let transcription = dg.transcription();
+        let connection = transcription
+            .stream_request()
+            .transport(transport)
+            .prewarm()
+            .await
+            .unwrap();
+        assert_eq!(connection.request_id(), Uuid::nil());
+        assert_eq!(
+            connection.connection_info().header("dg-request-id"),
+            Some("00000000-0000-0000-0000-000000000000")
+        );
+
+        tokio::time::sleep(Duration::from_millis(3100)).await;
+        assert_eq!(
+            sent_rx.next().await.unwrap(),
+            Message::Text(r#"{"type":"KeepAlive"}"#.into())
+        );
+
+        let audio = futures::stream::iter([Ok::<_, std::io::Error>(Bytes::from_static(b"abc"))]);
+        let _results = connection.stream(audio);
+        assert_eq!(
+            sent_rx.next().await.unwrap(),
+            Message::Binary(Bytes::from_static(b"abc"))
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn reconnects_dropped_stream() {
+        let (first, mut first_sent_rx, first_incoming_tx) = fake_socket();
+        let (second, mut second_sent_rx, _second_incoming_tx) = fake_socket();
+        let transport = FakeTransport::new([first, second]);
+
+        let dg = crate::Deepgram::new("token").unwrap();
+        let transcription = dg.transcription();
+        let (mut audio_tx, audio_rx) =
+            mpsc::channel::<std::result::Result<Bytes, std::io::Error>>(1);
+        let mut results = transcription
+            .stream_request()
+            .transport(transport)
+            .reconnect(ReconnectPolicy::new(3).backoff(Duration::from_millis(100)))
+            .stream(audio_rx)
+            .await
+            .unwrap();
+
+        audio_tx.send(Ok(Bytes::from_static(b"abc"))).await.unwrap();
+        assert_eq!(
+            first_sent_rx.next().await.unwrap(),
+            Message::Binary(Bytes::from_static(b"abc"))
+        );
+
+        // The server goes away without closing the stream.
+        drop(first_incoming_tx);
+        match results.next().await.unwrap().unwrap() {
+            StreamResponse::Reconnected {
+                request_id,
+                attempts,
+            } => {
+                assert_eq!(request_id, Uuid::from_u128(1));
+                assert_eq!(attempts, 1);
+            }
+            response => panic!("expected Reconnected, got {response:?}"),
+        }
+
+        audio_tx.send(Ok(Bytes::from_static(b"def"))).await.unwrap();
+        assert_eq!(
+            second_sent_rx.next().await.unwrap(),
+            Message::Binary(Bytes::from_static(b"def"))
+        );
     }
-}
 
-impl Deref for Audio {
-    type Target = [u8];
+    #[tokio::test(start_paused = true)]
+    async fn resends_queued_audio_after_reconnect() {
+        let (mut first, mut first_sent_rx, first_incoming_tx) = fake_socket();
+        let (release, stall) = oneshot::channel();
+        first.stall = Some(stall);
+        let (second, mut second_sent_rx, _second_incoming_tx) = fake_socket();
+        let transport = FakeTransport::new([first, second]);
 
-    fn deref(&self) -> &Self::Target {
-        &self.0
+        let dg = crate::Deepgram::new("token").unwrap();
+        let transcription = dg.transcription();
+        let (mut audio_tx, audio_rx) =
+            mpsc::channel::<std::result::Result<Bytes, std::io::Error>>(1);
+        let mut results = transcription
+            .stream_request()
+            .transport(transport)
+            .reconnect(ReconnectPolicy::new(3).backoff(Duration::from_millis(100)))
+            .stream(audio_rx)
+            .await
+            .unwrap();
+
+        // The first chunk is being sent when the server goes away, and the
+        // second is still queued.
+        audio_tx.send(Ok(Bytes::from_static(b"abc"))).await.unwrap();
+        audio_tx.send(Ok(Bytes::from_static(b"def"))).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        drop(first_incoming_tx);
+        release.send(()).unwrap();
+
+        assert!(matches!(
+            results.next().await.unwrap().unwrap(),
+            StreamResponse::Reconnected { .. }
+        ));
+        assert_eq!(
+            first_sent_rx.next().await.unwrap(),
+            Message::Binary(Bytes::from_static(b"abc"))
+        );
+        let resent = tokio::time::timeout(Duration::from_secs(5), second_sent_rx.next())
+            .await
+            .expect("the queued audio was not resent");
+        assert_eq!(resent.unwrap(), Message::Binary(Bytes::from_static(b"def")));
     }
-}
 
-#[derive(Debug)]
-pub struct WebsocketHandle {
-    message_tx: Sender<WsMessage>,
-    response_rx: Receiver<Result<StreamResponse>>,
-    request_id: Uuid,
-}
+    #[tokio::test(start_paused = true)]
+    async fn does_not_reconnect_after_policy_violation() {
+        use tungstenite::protocol::{frame::coding::CloseCode, CloseFrame};
 
-impl WebsocketHandle {
-    async fn new(builder: WebsocketBuilder<'_>) -> Result<WebsocketHandle> {
-        let url = builder.as_url()?;
-        let host = url.host_str().ok_or(DeepgramError::InvalidUrl)?;
+        let (first, _first_sent_rx, first_incoming_tx) = fake_socket();
+        let (second, _second_sent_rx, _second_incoming_tx) = fake_socket();
+        let transport = FakeTransport::new([first, second]);
 
-        let request = {
-            let http_builder = Request::builder()
-                .method("GET")
-                .uri(url.to_string())
-                .header("sec-websocket-key", client::generate_key())
-                .header("host", host)
-                .header("connection", "upgrade")
-                .header("upgrade", "websocket")
-                .header("sec-websocket-version", "13")
-                .header("user-agent", crate::USER_AGENT);
+        let dg = crate::Deepgram::new("token").unwrap();
+        let transcription = dg.transcription();
+        let (_audio_tx, audio_rx) = mpsc::channel::<std::result::Result<Bytes, std::io::Error>>(1);
+        let mut results = transcription
+            .stream_request()
+            .transport(transport)
+            .reconnect(ReconnectPolicy::new(3).backoff(Duration::from_millis(100)))
+            .stream(audio_rx)
+            .await
+            .unwrap();
 
-            let builder = if let Some(auth) = &builder.deepgram.auth {
-                http_builder.header("authorization", auth.header_value())
-            } else {
-                http_builder
-            };
-            builder.body(())?
-        };
+        first_incoming_tx
+            .unbounded_send(Ok(Message::Close(Some(CloseFrame {
+                code: CloseCode::Policy,
+                reason: "bad request".into(),
+            }))))
+            .unwrap();
+        assert!(matches!(
+            results.next().await,
+            Some(Err(crate::listen::LiveError::WebsocketClose {
+                code: 1008,
+                ..
+            }))
+        ));
+        assert!(results.next().await.is_none());
+    }
 
-        let (ws_stream, upgrade_response) = tokio_tungstenite::connect_async(request).await?;
+    #[tokio::test(start_paused = true)]
+    async fn connect_sends_audio_from_sink() {
+        let (transport, mut sent_rx, _incoming_tx) = fake_connection();
 
-        let request_id = upgrade_response
-            .headers()
-            .get("dg-request-id")
-            .ok_or(DeepgramError::UnexpectedServerResponse(anyhow!(
-                "Websocket upgrade headers missing request ID"
-            )))?
-            .to_str()
-            .ok()
-            .and_then(|req_header_str| Uuid::parse_str(req_header_str).ok())
-            .ok_or(DeepgramError::UnexpectedServerResponse(anyhow!(
-                "Received malformed request ID in websocket upgrade headers"
-            )))?;
+        let dg = crate::Deepgram::new("token").unwrap();
+        let transcription = dg.transcription();
+        let (mut audio, _results) = transcription
+            .stream_request()
+            .transport(transport)
+            .connect()
+            .await
+            .unwrap();
 
-        let (message_tx, message_rx) = mpsc::channel(256);
-        let (response_tx, response_rx) = mpsc::channel(256);
-
-        tokio::task::spawn({
-            let message_tx = message_tx.clone();
-            run_worker(
-                ws_stream,
-                message_tx,
-                message_rx,
-                response_tx,
-                builder.keep_alive.unwrap_or(false),
-            )
-        });
+        audio.send(Bytes::from_static(b"abc")).await.unwrap();
+        assert_eq!(
+            sent_rx.next().await.unwrap(),
+            Message::Binary(Bytes::from_static(b"abc"))
+        );
 
-        Ok(WebsocketHandle {
-            message_tx,
-            response_rx,
-            request_id,
-        })
+        audio.close().await.unwrap();
+        assert_eq!(
+            sent_rx.next().await.unwrap(),
+            Message::Text(r#"{"type":"Finalize"}"#.into())
+        );
+        assert_eq!(
+            sent_rx.next().await.unwrap(),
+            Message::Text(r#"{"type":"CloseStream"}"#.into())
+        );
     }
 
-    pub async fn send_data(&mut self, data: Vec<u8>) -> Result<()> {
-        let audio = Audio(data);
-        // eprintln!("<handle> sending audio: {audio:?}");
+    #[tokio::test(start_paused = true)]
+    async fn forwards_stream_into_sink() {
+        let (transport, mut sent_rx, _incoming_tx) = fake_connection();
 
-        self.message_tx
-            .send(WsMessage::Audio(audio))
+        let dg = crate::Deepgram::new("token").unwrap();
+        let transcription = dg.transcription();
+        let (audio, _results) = transcription
+            .stream_request()
+            .transport(transport)
+            .connect()
             .await
-            .map_err(|err| DeepgramError::InternalClientError(err.into()))?;
-        Ok(())
-    }
+            .unwrap();
 
-    /// Send a Finalize message to the Deepgram API to force the server to process
-    /// all the audio it has already received.
-    pub async fn finalize(&mut self) -> Result<()> {
-        self.send_control_message(ControlMessage::Finalize).await
+        let source =
+            futures::stream::iter([Bytes::from_static(b"abc"), Bytes::from_static(b"def")]);
+        source.map(Ok).forward(audio).await.unwrap();
+        for expected in [b"abc", b"def"] {
+            assert_eq!(
+                sent_rx.next().await.unwrap(),
+                Message::Binary(Bytes::from_static(expected))
+            );
+        }
+        assert_eq!(
+            sent_rx.next().await.unwrap(),
+            Message::Text(r#"{"type":"Finalize"}"#.into())
+        );
     }
 
-    /// Send a KeepAlive message to the Deepgram API to ensure the connection
-    /// isn't closed due to long idle times.
-    pub async fn keep_alive(&mut self) -> Result<()> {
-        self.send_control_message(ControlMessage::KeepAlive).await
-    }
+    #[tokio::test]
+    async fn calls_event_handlers() {
+        let (transport, _sent_rx, incoming_tx) = fake_connection();
 
-    /// Close the websocket stream. No more data should be sent after this is called.
-    pub async fn close_stream(&mut self) -> Result<()> {
-        if !self.message_tx.is_closed() {
-            self.send_control_message(ControlMessage::CloseStream)
-                .await?;
-            self.message_tx.close_channel();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let record = |event: &'static str| {
+            let events = Arc::clone(&events);
+            move || events.lock().unwrap().push(event)
+        };
+        let (transcript, utterance_end, close) = (
+            record("transcript"),
+            record("utterance_end"),
+            record("close"),
+        );
+
+        let dg = crate::Deepgram::new("token").unwrap();
+        let transcription = dg.transcription();
+        let mut handle = transcription
+            .stream_request()
+            .transport(transport)
+            .on_transcript(move |_| transcript())
+            .on_utterance_end(move |_| utterance_end())
+            .on_close(close)
+            .handle()
+            .await
+            .unwrap();
+
+        let results = TranscriptResponseBuilder::new([stream_word("hello", 0.0, 0.5)]).build();
+        for message in [
+            serde_json::to_string(&results).unwrap(),
+            String::from(r#"{"type":"UtteranceEnd","channel":[0,1],"last_word_end":0.5}"#),
+        ] {
+            incoming_tx
+                .unbounded_send(Ok(Message::Text(message.into())))
+                .unwrap();
+            handle.receive().await.unwrap().unwrap();
         }
-        Ok(())
+        drop(incoming_tx);
+        assert!(handle.receive().await.is_none());
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            ["transcript", "utterance_end", "close"]
+        );
     }
 
-    async fn send_control_message(&mut self, message: ControlMessage) -> Result<()> {
-        // eprintln!("<handle> sending control message: {message:?}");
-        self.message_tx
-            .send(WsMessage::ControlMessage(message.clone()))
+    #[tokio::test(start_paused = true)]
+    async fn tracks_stream_metrics() {
+        let (transport, mut sent_rx, incoming_tx) = fake_connection();
+
+        let dg = crate::Deepgram::new("token").unwrap();
+        let transcription = dg.transcription();
+        let (mut audio_tx, audio_rx) =
+            mpsc::channel::<std::result::Result<Bytes, std::io::Error>>(1);
+        let mut results = transcription
+            .stream_request()
+            .transport(transport)
+            .encoding(Encoding::Linear16)
+            .sample_rate(8000)
+            .track_metrics()
+            .stream(audio_rx)
             .await
-            .map_err(|err| {
-                // eprintln!("<handle> error sending control message: {message:?}");
-                DeepgramError::InternalClientError(err.into())
-            })?;
-        // eprintln!("<handle> sent control message");
-        Ok(())
-    }
+            .unwrap();
 
-    #[allow(clippy::let_and_return)]
-    pub async fn receive(&mut self) -> Option<Result<StreamResponse>> {
-        let resp = self.response_rx.next().await;
-        // eprintln!("<handle> receiving response: {resp:?}");
-        resp
-    }
+        // Two half-second chunks, a second apart.
+        for _ in 0..2 {
+            audio_tx.send(Ok(Bytes::from(vec![0; 8000]))).await.unwrap();
+            sent_rx.next().await.unwrap();
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
 
-    pub fn request_id(&self) -> Uuid {
-        self.request_id
+        // A result for the first chunk arrives two seconds after it was sent.
+        let response = TranscriptResponseBuilder::new([stream_word("hello", 0.0, 0.4)])
+            .duration(0.5)
+            .is_final(true)
+            .build();
+        incoming_tx
+            .unbounded_send(Ok(Message::Text(
+                serde_json::to_string(&response).unwrap().into(),
+            )))
+            .unwrap();
+        results.next().await.unwrap().unwrap();
+
+        let metrics = results.metrics().unwrap();
+        assert_eq!(metrics.audio_bytes_sent, 16000);
+        assert_eq!(metrics.audio_duration_sent, Some(Duration::from_secs(1)));
+        assert_eq!(metrics.results_received, 1);
+        assert_eq!(metrics.words_emitted, 1);
+        assert_eq!(metrics.last_result_latency, Some(Duration::from_secs(2)));
+        assert_eq!(metrics.uptime, Duration::from_secs(2));
     }
-}
 
-#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
-#[serde(tag = "type")]
-enum ControlMessage {
-    Finalize,
-    KeepAlive,
-    CloseStream,
-}
+    #[tokio::test(start_paused = true)]
+    async fn pauses_with_keep_alive() {
+        let (transport, mut sent_rx, _incoming_tx) = fake_connection();
 
-#[derive(Debug)]
-#[pin_project]
-pub struct TranscriptionStream {
-    #[pin]
-    rx: Receiver<Result<StreamResponse>>,
-    done: bool,
-    request_id: Uuid,
-}
+        let dg = crate::Deepgram::new("token").unwrap();
+        let transcription = dg.transcription();
+        let (mut audio, results) = transcription
+            .stream_request()
+            .transport(transport)
+            .connect()
+            .await
+            .unwrap();
+        let keep_alive = Message::Text(r#"{"type":"KeepAlive"}"#.into());
 
-impl Stream for TranscriptionStream {
-    type Item = Result<StreamResponse, DeepgramError>;
+        results.pause().unwrap();
+        audio.send(Bytes::from_static(b"muted")).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(3100)).await;
+        assert_eq!(sent_rx.next().await.unwrap(), keep_alive);
 
-    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        let this = self.project();
-        this.rx.poll_next(cx)
-    }
-}
+        results
+            .set_keep_alive_interval(Some(Duration::from_secs(1)))
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        assert_eq!(sent_rx.next().await.unwrap(), keep_alive);
 
-impl TranscriptionStream {
-    /// Returns the Deepgram request ID for the speech-to-text live request.
-    ///
-    /// A request ID needs to be provided to Deepgram as part of any support
-    /// or troubleshooting assistance related to a specific request.
-    pub fn request_id(&self) -> Uuid {
-        self.request_id
+        // Keep-alive is off by default, so only audio is sent after resuming.
+        results.set_keep_alive_interval(None).unwrap();
+        results.resume().unwrap();
+        audio.send(Bytes::from_static(b"audio")).await.unwrap();
+        assert_eq!(
+            sent_rx.next().await.unwrap(),
+            Message::Binary(Bytes::from_static(b"audio"))
+        );
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        assert!(sent_rx.try_recv().is_err());
     }
-}
 
-mod file_chunker {
-    use bytes::{Bytes, BytesMut};
-    use futures::Stream;
-    use pin_project::pin_project;
-    use std::{
-        pin::Pin,
-        task::{Context, Poll},
-    };
-    use tokio::fs::File;
-    use tokio_util::io::ReaderStream;
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
 
-    use crate::{DeepgramError, Result};
+    impl std::io::Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
 
-    #[pin_project]
-    pub(super) struct FileChunker {
-        chunk_size: usize,
-        buf: BytesMut,
-        #[pin]
-        file: ReaderStream<File>,
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
     }
 
-    impl FileChunker {
-        pub(super) fn new(file: File, chunk_size: usize) -> Self {
-            FileChunker {
-                chunk_size,
-                buf: BytesMut::with_capacity(2 * chunk_size),
-                file: ReaderStream::new(file),
-            }
+    #[tokio::test]
+    async fn tees_sent_audio() {
+        let (transport, mut sent_rx, _incoming_tx) = fake_connection();
+
+        let dg = crate::Deepgram::new("token").unwrap();
+        let transcription = dg.transcription();
+        let tee = SharedBuffer::default();
+        let (mut audio, _results) = transcription
+            .stream_request()
+            .transport(transport)
+            .tee_audio(tee.clone())
+            .connect()
+            .await
+            .unwrap();
+
+        for chunk in [&b"abc"[..], b"def"] {
+            audio.send(Bytes::from_static(chunk)).await.unwrap();
+            assert_eq!(
+                sent_rx.next().await.unwrap(),
+                Message::Binary(Bytes::from_static(chunk))
+            );
         }
+        // The copy is written in the background.
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while tee.0.lock().unwrap().len() < 6 {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(*tee.0.lock().unwrap(), b"abcdef");
     }
 
-    impl Stream for FileChunker {
-        type Item = Result<Bytes>;
+    #[tokio::test(start_paused = true)]
+    async fn finalizes_on_silence() {
+        let (transport, mut sent_rx, _incoming_tx) = fake_connection();
 
-        fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
-            let mut this = self.project();
+        let dg = crate::Deepgram::new("token").unwrap();
+        let transcription = dg.transcription();
+        let (mut audio_tx, audio_rx) =
+            mpsc::channel::<std::result::Result<Bytes, std::io::Error>>(1);
+        let _results = transcription
+            .stream_request()
+            .transport(transport)
+            .encoding(Encoding::Linear16)
+            .sample_rate(8000)
+            .finalize_on_silence(Duration::from_millis(500))
+            .stream(audio_rx)
+            .await
+            .unwrap();
 
-            while this.buf.len() < *this.chunk_size {
-                match Pin::new(&mut this.file).poll_next(cx) {
-                    Poll::Pending => return Poll::Pending,
-                    Poll::Ready(next) => match next.transpose() {
-                        Err(e) => return Poll::Ready(Some(Err(DeepgramError::from(e)))),
-                        Ok(None) => {
-                            if this.buf.is_empty() {
-                                return Poll::Ready(None);
-                            } else {
-                                return Poll::Ready(Some(Ok(this
-                                    .buf
-                                    .split_to(this.buf.len())
-                                    .freeze())));
-                            }
-                        }
-                        Ok(Some(next)) => {
-                            this.buf.extend_from_slice(&next);
-                        }
-                    },
-                }
-            }
+        // Quarter seconds of speech and silence, split mid-sample.
+        let speech = Bytes::from([0x00, 0x40].repeat(2000));
+        let silence = Bytes::from(vec![0; 4001]);
+        for audio in [&speech, &silence, &silence] {
+            audio_tx.send(Ok(audio.clone())).await.unwrap();
+            assert_eq!(
+                sent_rx.next().await.unwrap(),
+                Message::Binary(audio.clone())
+            );
+        }
+        assert_eq!(
+            sent_rx.next().await.unwrap(),
+            Message::Text(r#"{"type":"Finalize"}"#.into())
+        );
 
-            Poll::Ready(Some(Ok(this.buf.split_to(*this.chunk_size).freeze())))
+        // Finalize is sent once per silence.
+        for audio in [&silence, &speech] {
+            audio_tx.send(Ok(audio.clone())).await.unwrap();
+            assert_eq!(
+                sent_rx.next().await.unwrap(),
+                Message::Binary(audio.clone())
+            );
         }
+
+        let err = transcription
+            .stream_request()
+            .encoding(Encoding::Opus)
+            .sample_rate(48000)
+            .finalize_on_silence(Duration::from_millis(500))
+            .transport(FakeTransport::new([]))
+            .stream(futures::stream::pending::<
+                std::result::Result<Bytes, std::io::Error>,
+            >())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, crate::listen::LiveError::InvalidOption(_)));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use std::time::Duration;
+    #[tokio::test(start_paused = true)]
+    async fn collects_final_transcript() {
+        let (transport, _sent_rx, incoming_tx) = fake_connection();
+
+        let dg = crate::Deepgram::new("token").unwrap();
+        let transcription = dg.transcription();
+        let results = transcription
+            .stream_request()
+            .transport(transport)
+            .stream(futures::stream::pending::<
+                std::result::Result<Bytes, std::io::Error>,
+            >())
+            .await
+            .unwrap();
 
-    use super::ControlMessage;
-    use crate::common::options::{Encoding, Endpointing, Options};
+        let responses = [
+            TranscriptResponseBuilder::new([stream_word("hello", 0.0, 0.4)]).build(),
+            TranscriptResponseBuilder::new([stream_word("hello", 0.0, 0.4)])
+                .is_final(true)
+                .build(),
+            TranscriptResponseBuilder::new([stream_word("world", 0.5, 0.9)])
+                .start(0.5)
+                .is_final(true)
+                .speech_final(true)
+                .build(),
+            TranscriptResponseBuilder::new([stream_word("again", 2.0, 2.4)])
+                .start(2.0)
+                .is_final(true)
+                .build(),
+        ];
+        for response in responses {
+            incoming_tx
+                .unbounded_send(Ok(Message::Text(
+                    serde_json::to_string(&response).unwrap().into(),
+                )))
+                .unwrap();
+        }
+        incoming_tx
+            .unbounded_send(Ok(Message::Text(
+                r#"{"type":"Metadata","request_id":"00000000-0000-0000-0000-000000000001","created":"","duration":2.5,"channels":1}"#
+                    .into(),
+            )))
+            .unwrap();
+        drop(incoming_tx);
+
+        let transcript = results.collect_transcript().await.unwrap();
+        assert_eq!(transcript.transcript, "hello world\nagain");
+        assert_eq!(transcript.words.len(), 3);
+        assert_eq!(transcript.duration, Some(2.5));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn controls_stream_while_streaming() {
+        let (transport, mut sent_rx, _incoming_tx) = fake_connection();
 
-    #[test]
-    fn test_stream_url() {
         let dg = crate::Deepgram::new("token").unwrap();
+        let transcription = dg.transcription();
+        let (mut audio_tx, audio_rx) =
+            mpsc::channel::<std::result::Result<Bytes, std::io::Error>>(1);
+        let results = transcription
+            .stream_request()
+            .transport(transport)
+            .stream(audio_rx)
+            .await
+            .unwrap();
+
+        audio_tx.send(Ok(Bytes::from_static(b"abc"))).await.unwrap();
         assert_eq!(
-            dg.transcription().listen_stream_url().to_string(),
-            "wss://api.deepgram.com/v1/listen",
+            sent_rx.next().await.unwrap(),
+            Message::Binary(Bytes::from_static(b"abc"))
         );
-    }
 
-    #[test]
-    fn test_stream_url_custom_host() {
-        let dg =
-            crate::Deepgram::with_base_url_and_api_key("http://localhost:8080", "token").unwrap();
+        let control = results.control();
+        control.finalize().unwrap();
         assert_eq!(
-            dg.transcription().listen_stream_url().to_string(),
-            "ws://localhost:8080/v1/listen",
+            sent_rx.next().await.unwrap(),
+            Message::Text(r#"{"type":"Finalize"}"#.into())
+        );
+
+        results.close_stream().unwrap();
+        assert_eq!(
+            sent_rx.next().await.unwrap(),
+            Message::Text(r#"{"type":"CloseStream"}"#.into())
         );
+
+        // Audio from the source is no longer sent.
+        let _ = audio_tx.send(Ok(Bytes::from_static(b"def"))).await;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(sent_rx.try_recv().is_err());
     }
 
-    #[test]
-    fn query_escaping() {
+    #[tokio::test(start_paused = true)]
+    async fn drain_timeout_ends_stream() {
+        let (transport, mut sent_rx, _incoming_tx) = fake_connection();
+
         let dg = crate::Deepgram::new("token").unwrap();
-        let opts = Options::builder().custom_topics(["A&R"]).build();
         let transcription = dg.transcription();
-        let builder = transcription.stream_request_with_options(opts.clone());
-        assert_eq!(builder.urlencoded().unwrap(), opts.urlencoded().unwrap())
-    }
+        let mut handle = transcription
+            .stream_request()
+            .transport(transport)
+            .drain_timeout(Duration::from_secs(5))
+            .handle()
+            .await
+            .unwrap();
 
-    #[test]
-    fn control_message_format() {
+        handle
+            .close_with_timeout(Duration::from_secs(1))
+            .await
+            .unwrap();
         assert_eq!(
-            &serde_json::to_string(&ControlMessage::CloseStream).unwrap(),
-            r#"{"type":"CloseStream"}"#
+            sent_rx.next().await.unwrap(),
+            Message::Text(r#"{"type":"CloseStream"}"#.into())
         );
+
+        let start = tokio::time::Instant::now();
+        assert!(matches!(
+            handle.receive().await,
+            Some(Err(crate::listen::LiveError::DrainTimeout { timeout })) if timeout == Duration::from_secs(1)
+        ));
+        assert_eq!(start.elapsed(), Duration::from_secs(1));
+        assert!(handle.receive().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn raw_handle_passes_messages_through() {
+        let (transport, _sent_rx, incoming_tx) = fake_connection();
+
+        let dg = crate::Deepgram::new("token").unwrap();
+        let transcription = dg.transcription();
+        let mut handle = transcription
+            .stream_request()
+            .transport(transport)
+            .handle_raw()
+            .await
+            .unwrap();
+
+        for payload in [
+            r#"{"type":"Results","not":"a transcript"}"#,
+            r#"{"type":"SomethingNew","value":1}"#,
+        ] {
+            incoming_tx
+                .unbounded_send(Ok(Message::Text(payload.into())))
+                .unwrap();
+            assert_eq!(
+                handle.receive().await.unwrap().unwrap(),
+                serde_json::from_str::<serde_json::Value>(payload).unwrap()
+            );
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn reports_connection_lost_without_pong() {
+        let (transport, mut sent_rx, incoming_tx) = fake_connection();
+
+        let dg = crate::Deepgram::new("token").unwrap();
+        let transcription = dg.transcription();
+        let mut handle = transcription
+            .stream_request()
+            .transport(transport)
+            .ping_interval(Duration::from_secs(5))
+            .pong_timeout(Duration::from_secs(2))
+            .handle()
+            .await
+            .unwrap();
+        let start = tokio::time::Instant::now();
+
+        // The first ping is answered.
+        assert_eq!(sent_rx.next().await.unwrap(), Message::Ping(Bytes::new()));
+        assert_eq!(start.elapsed(), Duration::from_secs(5));
+        incoming_tx
+            .unbounded_send(Ok(Message::Pong(Bytes::new())))
+            .unwrap();
+
+        // The second isn't.
+        assert_eq!(sent_rx.next().await.unwrap(), Message::Ping(Bytes::new()));
+        assert_eq!(start.elapsed(), Duration::from_secs(10));
+        assert!(matches!(
+            handle.receive().await,
+            Some(Err(crate::listen::LiveError::ConnectionLost { timeout }))
+                if timeout == Duration::from_secs(2)
+        ));
+        assert_eq!(start.elapsed(), Duration::from_secs(12));
+        assert!(handle.receive().await.is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn reports_error_when_reconnect_fails() {
+        let (transport, _sent_rx, incoming_tx) = fake_connection();
+
+        let dg = crate::Deepgram::new("token").unwrap();
+        let transcription = dg.transcription();
+        let mut results = transcription
+            .stream_request()
+            .transport(transport)
+            .reconnect(ReconnectPolicy::new(2))
+            .stream(futures::stream::pending::<
+                std::result::Result<Bytes, std::io::Error>,
+            >())
+            .await
+            .unwrap();
+
+        drop(incoming_tx);
+        assert!(results.next().await.unwrap().is_err());
+        assert!(results.next().await.is_none());
     }
 
     /// Reproduces the worker panic from issue #143: close_stream() calls
@@ -6,6 +6,7 @@
 
 use crate::{
     manage::billing::response::{Balance, Balances},
+    manage::ManageError,
     send_and_translate_response, Deepgram,
 };
 
@@ -66,10 +67,12 @@ impl Billing<'_> {
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn list_balance(&self, project_id: &str) -> crate::Result<Balances> {
-        let url = format!("https://api.deepgram.com/v1/projects/{project_id}/balances",);
+    pub async fn list_balance(&self, project_id: &str) -> Result<Balances, ManageError> {
+        let url = self
+            .0
+            .manage_url(&format!("v1/projects/{project_id}/balances"));
 
-        send_and_translate_response(self.0.client.get(url)).await
+        Ok(send_and_translate_response(self.0, self.0.client.get(url)).await?)
     }
 
     /// Get the details of a specific balance.
@@ -107,11 +110,16 @@ impl Billing<'_> {
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn get_balance(&self, project_id: &str, balance_id: &str) -> crate::Result<Balance> {
-        let url =
-            format!("https://api.deepgram.com/v1/projects/{project_id}/balances/{balance_id}",);
+    pub async fn get_balance(
+        &self,
+        project_id: &str,
+        balance_id: &str,
+    ) -> Result<Balance, ManageError> {
+        let url = self
+            .0
+            .manage_url(&format!("v1/projects/{project_id}/balances/{balance_id}"));
 
-        send_and_translate_response(self.0.client.get(url)).await
+        Ok(send_and_translate_response(self.0, self.0.client.get(url)).await?)
     }
 }
 
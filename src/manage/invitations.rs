@@ -4,7 +4,7 @@
 //!
 //! [api]: https://developers.deepgram.com/api-reference/#invitations
 
-use crate::{send_and_translate_response, Deepgram};
+use crate::{manage::ManageError, send_and_translate_response, Deepgram};
 
 use response::Message;
 
@@ -66,9 +66,11 @@ impl Invitations<'_> {
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn leave_project(&self, project_id: &str) -> crate::Result<Message> {
-        let url = format!("https://api.deepgram.com/v1/projects/{project_id}/leave",);
+    pub async fn leave_project(&self, project_id: &str) -> Result<Message, ManageError> {
+        let url = self
+            .0
+            .manage_url(&format!("v1/projects/{project_id}/leave"));
 
-        send_and_translate_response(self.0.client.delete(url)).await
+        Ok(send_and_translate_response(self.0, self.0.client.delete(url)).await?)
     }
 }
@@ -9,6 +9,7 @@ use crate::{
         options::{Options, SerializableOptions},
         response::{MemberAndApiKey, MembersAndApiKeys, NewApiKey},
     },
+    manage::ManageError,
     send_and_translate_response, Deepgram,
 };
 
@@ -73,10 +74,10 @@ impl Keys<'_> {
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn list(&self, project_id: &str) -> crate::Result<MembersAndApiKeys> {
-        let url = format!("https://api.deepgram.com/v1/projects/{project_id}/keys");
+    pub async fn list(&self, project_id: &str) -> Result<MembersAndApiKeys, ManageError> {
+        let url = self.0.manage_url(&format!("v1/projects/{project_id}/keys"));
 
-        send_and_translate_response(self.0.client.get(url)).await
+        Ok(send_and_translate_response(self.0, self.0.client.get(url)).await?)
     }
 
     /// Get details of the specified key.
@@ -112,10 +113,16 @@ impl Keys<'_> {
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn get(&self, project_id: &str, key_id: &str) -> crate::Result<MemberAndApiKey> {
-        let url = format!("https://api.deepgram.com/v1/projects/{project_id}/keys/{key_id}",);
+    pub async fn get(
+        &self,
+        project_id: &str,
+        key_id: &str,
+    ) -> Result<MemberAndApiKey, ManageError> {
+        let url = self
+            .0
+            .manage_url(&format!("v1/projects/{project_id}/keys/{key_id}"));
 
-        send_and_translate_response(self.0.client.get(url)).await
+        Ok(send_and_translate_response(self.0, self.0.client.get(url)).await?)
     }
 
     /// Create a new key in the specified project.
@@ -152,15 +159,19 @@ impl Keys<'_> {
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn create(&self, project_id: &str, options: &Options) -> crate::Result<NewApiKey> {
-        let url = format!("https://api.deepgram.com/v1/projects/{project_id}/keys");
+    pub async fn create(
+        &self,
+        project_id: &str,
+        options: &Options,
+    ) -> Result<NewApiKey, ManageError> {
+        let url = self.0.manage_url(&format!("v1/projects/{project_id}/keys"));
         let request = self
             .0
             .client
             .post(url)
             .json(&SerializableOptions::from(options));
 
-        send_and_translate_response(request).await
+        Ok(send_and_translate_response(self.0, request).await?)
     }
 
     /// Delete the specified key in the specified project.
@@ -196,9 +207,11 @@ impl Keys<'_> {
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn delete(&self, project_id: &str, key_id: &str) -> crate::Result<Message> {
-        let url = format!("https://api.deepgram.com/v1/projects/{project_id}/keys/{key_id}",);
+    pub async fn delete(&self, project_id: &str, key_id: &str) -> Result<Message, ManageError> {
+        let url = self
+            .0
+            .manage_url(&format!("v1/projects/{project_id}/keys/{key_id}"));
 
-        send_and_translate_response(self.0.client.delete(url)).await
+        Ok(send_and_translate_response(self.0, self.0.client.delete(url)).await?)
     }
 }
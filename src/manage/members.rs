@@ -4,7 +4,7 @@
 //!
 //! [api]: https://developers.deepgram.com/api-reference/#members
 
-use crate::{send_and_translate_response, Deepgram};
+use crate::{manage::ManageError, send_and_translate_response, Deepgram};
 
 use response::Message;
 
@@ -66,10 +66,12 @@ impl Members<'_> {
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn list_members(&self, project_id: &str) -> crate::Result<response::Members> {
-        let url = format!("https://api.deepgram.com/v1/projects/{project_id}/members",);
+    pub async fn list_members(&self, project_id: &str) -> Result<response::Members, ManageError> {
+        let url = self
+            .0
+            .manage_url(&format!("v1/projects/{project_id}/members"));
 
-        send_and_translate_response(self.0.client.get(url)).await
+        Ok(send_and_translate_response(self.0, self.0.client.get(url)).await?)
     }
 
     /// Remove the specified member from the specified project.
@@ -106,9 +108,15 @@ impl Members<'_> {
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn remove_member(&self, project_id: &str, member_id: &str) -> crate::Result<Message> {
-        let url = format!("https://api.deepgram.com/v1/projects/{project_id}/members/{member_id}",);
+    pub async fn remove_member(
+        &self,
+        project_id: &str,
+        member_id: &str,
+    ) -> Result<Message, ManageError> {
+        let url = self
+            .0
+            .manage_url(&format!("v1/projects/{project_id}/members/{member_id}"));
 
-        send_and_translate_response(self.0.client.delete(url)).await
+        Ok(send_and_translate_response(self.0, self.0.client.delete(url)).await?)
     }
 }
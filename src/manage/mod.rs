@@ -1,5 +1,12 @@
 //! Manage module
 
+use std::time::Duration;
+
+use reqwest::StatusCode;
+use thiserror::Error;
+
+use crate::{ApiErrorBody, DeepgramError, ErrorView, HttpError, ReqwestError, SerdeJsonError};
+
 pub mod billing;
 pub mod invitations;
 pub mod keys;
@@ -7,3 +14,160 @@ pub mod members;
 pub mod projects;
 pub mod scopes;
 pub mod usage;
+
+/// Errors that may arise from the [`manage`](crate::manage) APIs.
+///
+/// Converts into [`DeepgramError`] with `?`, keeping the same variant.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ManageError {
+    /// The Deepgram API returned an error.
+    #[error("The Deepgram API returned an error.")]
+    DeepgramApiError {
+        /// Error message from the Deepgram API.
+        body: String,
+
+        /// Underlying [`reqwest::Error`] from the HTTP request.
+        err: ReqwestError,
+    },
+
+    /// The Deepgram API rate limited the request.
+    ///
+    /// See [`DeepgramError::RateLimited`].
+    #[error("The Deepgram API rate limited the request.")]
+    RateLimited {
+        /// How long the API asked to wait before retrying, from the `Retry-After` header.
+        retry_after: Option<Duration>,
+
+        /// Error message from the Deepgram API.
+        body: String,
+
+        /// Underlying [`reqwest::Error`] from the HTTP request.
+        err: ReqwestError,
+    },
+
+    /// The request did not complete within the configured timeout.
+    #[error("The request timed out")]
+    Timeout,
+
+    /// Something went wrong when generating the http request.
+    #[error("Something went wrong when generating the http request: {0}")]
+    HttpError(HttpError),
+
+    /// Something went wrong when making the HTTP request.
+    #[error("Something went wrong when making the HTTP request: {0}")]
+    ReqwestError(ReqwestError),
+
+    /// Something went wrong during serialization/deserialization.
+    #[error("Something went wrong during json serialization/deserialization: {0}")]
+    JsonError(SerdeJsonError),
+
+    /// Any other error, such as one returned by a [`TokenProvider`](crate::TokenProvider).
+    #[error(transparent)]
+    Other(DeepgramError),
+}
+
+impl ManageError {
+    /// The structured error details returned by the Deepgram API, if any.
+    ///
+    /// See [`DeepgramError::api_error`].
+    pub fn api_error(&self) -> Option<ApiErrorBody> {
+        self.view().api_error()
+    }
+
+    /// The HTTP status code of the response that caused this error, if any.
+    ///
+    /// See [`DeepgramError::status_code`].
+    pub fn status_code(&self) -> Option<StatusCode> {
+        self.view().status_code()
+    }
+
+    /// The Deepgram error code, such as `INVALID_AUTH`, if the API returned one.
+    ///
+    /// See [`DeepgramError::error_code`].
+    pub fn error_code(&self) -> Option<String> {
+        self.view().error_code()
+    }
+
+    /// Whether retrying the request that caused this error might succeed.
+    ///
+    /// See [`DeepgramError::is_retryable`].
+    pub fn is_retryable(&self) -> bool {
+        self.view().is_retryable()
+    }
+
+    fn view(&self) -> ErrorView<'_> {
+        match self {
+            ManageError::DeepgramApiError { body, err } => ErrorView::Api { body, err },
+            ManageError::RateLimited { body, err, .. } => ErrorView::RateLimited { body, err },
+            ManageError::Timeout => ErrorView::Timeout,
+            ManageError::ReqwestError(err) => ErrorView::Reqwest(err),
+            ManageError::Other(err) => err.view(),
+            ManageError::HttpError(_) | ManageError::JsonError(_) => ErrorView::Other,
+        }
+    }
+}
+
+impl From<DeepgramError> for ManageError {
+    fn from(err: DeepgramError) -> Self {
+        match err {
+            DeepgramError::DeepgramApiError { body, err } => Self::DeepgramApiError { body, err },
+            DeepgramError::RateLimited {
+                retry_after,
+                body,
+                err,
+            } => Self::RateLimited {
+                retry_after,
+                body,
+                err,
+            },
+            DeepgramError::Timeout => Self::Timeout,
+            DeepgramError::HttpError(err) => Self::HttpError(err),
+            DeepgramError::ReqwestError(err) => Self::ReqwestError(err),
+            DeepgramError::JsonError(err) => Self::JsonError(err),
+            err => Self::Other(err),
+        }
+    }
+}
+
+impl From<ManageError> for DeepgramError {
+    fn from(err: ManageError) -> Self {
+        match err {
+            ManageError::DeepgramApiError { body, err } => Self::DeepgramApiError { body, err },
+            ManageError::RateLimited {
+                retry_after,
+                body,
+                err,
+            } => Self::RateLimited {
+                retry_after,
+                body,
+                err,
+            },
+            ManageError::Timeout => Self::Timeout,
+            ManageError::HttpError(err) => Self::HttpError(err),
+            ManageError::ReqwestError(err) => Self::ReqwestError(err),
+            ManageError::JsonError(err) => Self::JsonError(err),
+            ManageError::Other(err) => err,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ManageError;
+    use crate::DeepgramError;
+
+    #[test]
+    fn converts_to_and_from_deepgram_error() {
+        let err = ManageError::from(DeepgramError::Timeout);
+        assert!(matches!(err, ManageError::Timeout));
+        assert!(matches!(DeepgramError::from(err), DeepgramError::Timeout));
+
+        let err = ManageError::from(DeepgramError::InvalidUrl);
+        assert!(matches!(err, ManageError::Other(DeepgramError::InvalidUrl)));
+        assert!(matches!(
+            DeepgramError::from(err),
+            DeepgramError::InvalidUrl
+        ));
+    }
+}
@@ -4,7 +4,7 @@
 //!
 //! [api]: https://developers.deepgram.com/api-reference/#projects
 
-use crate::{send_and_translate_response, Deepgram};
+use crate::{manage::ManageError, send_and_translate_response, Deepgram};
 
 use options::{Options, SerializableOptions};
 
@@ -69,10 +69,10 @@ impl Projects<'_> {
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn list(&self) -> crate::Result<response::Projects> {
-        let request = self.0.client.get("https://api.deepgram.com/v1/projects");
+    pub async fn list(&self) -> Result<response::Projects, ManageError> {
+        let request = self.0.client.get(self.0.manage_url("v1/projects"));
 
-        send_and_translate_response(request).await
+        Ok(send_and_translate_response(self.0, request).await?)
     }
 
     /// Get a specific project.
@@ -106,10 +106,10 @@ impl Projects<'_> {
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn get(&self, project_id: &str) -> crate::Result<Project> {
-        let url = format!("https://api.deepgram.com/v1/projects/{project_id}");
+    pub async fn get(&self, project_id: &str) -> Result<Project, ManageError> {
+        let url = self.0.manage_url(&format!("v1/projects/{project_id}"));
 
-        send_and_translate_response(self.0.client.get(url)).await
+        Ok(send_and_translate_response(self.0, self.0.client.get(url)).await?)
     }
 
     /// Update the specified project.
@@ -148,15 +148,19 @@ impl Projects<'_> {
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn update(&self, project_id: &str, options: &Options) -> crate::Result<Message> {
-        let url = format!("https://api.deepgram.com/v1/projects/{project_id}");
+    pub async fn update(
+        &self,
+        project_id: &str,
+        options: &Options,
+    ) -> Result<Message, ManageError> {
+        let url = self.0.manage_url(&format!("v1/projects/{project_id}"));
         let request = self
             .0
             .client
             .patch(url)
             .json(&SerializableOptions::from(options));
 
-        send_and_translate_response(request).await
+        Ok(send_and_translate_response(self.0, request).await?)
     }
 
     /// Delete the specified project.
@@ -190,10 +194,10 @@ impl Projects<'_> {
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn delete(&self, project_id: &str) -> crate::Result<Message> {
-        let url = format!("https://api.deepgram.com/v1/projects/{project_id}");
+    pub async fn delete(&self, project_id: &str) -> Result<Message, ManageError> {
+        let url = self.0.manage_url(&format!("v1/projects/{project_id}"));
         let request = self.0.client.delete(url);
 
-        send_and_translate_response(request).await
+        Ok(send_and_translate_response(self.0, request).await?)
     }
 }
@@ -6,7 +6,7 @@
 
 use serde::Serialize;
 
-use crate::{send_and_translate_response, Deepgram};
+use crate::{manage::ManageError, send_and_translate_response, Deepgram};
 
 use response::Message;
 
@@ -75,12 +75,12 @@ impl Scopes<'_> {
         &self,
         project_id: &str,
         member_id: &str,
-    ) -> crate::Result<response::Scopes> {
-        let url = format!(
-            "https://api.deepgram.com/v1/projects/{project_id}/members/{member_id}/scopes "
-        );
+    ) -> Result<response::Scopes, ManageError> {
+        let url = self.0.manage_url(&format!(
+            "v1/projects/{project_id}/members/{member_id}/scopes"
+        ));
 
-        send_and_translate_response(self.0.client.get(url)).await
+        Ok(send_and_translate_response(self.0, self.0.client.get(url)).await?)
     }
 
     /// Update the specified project scopes assigned to the specified member.
@@ -122,16 +122,17 @@ impl Scopes<'_> {
         project_id: &str,
         member_id: &str,
         scope: &str,
-    ) -> crate::Result<Message> {
+    ) -> Result<Message, ManageError> {
         #[derive(Serialize)]
         struct Scope<'a> {
             scope: &'a str,
         }
 
-        let url =
-            format!("https://api.deepgram.com/v1/projects/{project_id}/members/{member_id}/scopes");
+        let url = self.0.manage_url(&format!(
+            "v1/projects/{project_id}/members/{member_id}/scopes"
+        ));
         let request = self.0.client.put(url).json(&Scope { scope });
 
-        send_and_translate_response(request).await
+        Ok(send_and_translate_response(self.0, request).await?)
     }
 }
@@ -6,7 +6,7 @@
 
 use response::{Fields, Request, Requests, UsageSummary};
 
-use crate::{send_and_translate_response, Deepgram};
+use crate::{manage::ManageError, send_and_translate_response, Deepgram};
 
 pub mod get_fields_options;
 pub mod get_usage_options;
@@ -77,15 +77,17 @@ impl Usage<'_> {
         &self,
         project_id: &str,
         options: &list_requests_options::Options,
-    ) -> crate::Result<Requests> {
-        let url = format!("https://api.deepgram.com/v1/projects/{project_id}/requests",);
+    ) -> Result<Requests, ManageError> {
+        let url = self
+            .0
+            .manage_url(&format!("v1/projects/{project_id}/requests"));
         let request = self
             .0
             .client
             .get(url)
             .query(&list_requests_options::SerializableOptions::from(options));
 
-        send_and_translate_response(request).await
+        Ok(send_and_translate_response(self.0, request).await?)
     }
 
     /// Get the details of the specified request sent to the Deepgram API for the specified project.
@@ -125,11 +127,16 @@ impl Usage<'_> {
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn get_request(&self, project_id: &str, request_id: &str) -> crate::Result<Request> {
-        let url =
-            format!("https://api.deepgram.com/v1/projects/{project_id}/requests/{request_id}",);
+    pub async fn get_request(
+        &self,
+        project_id: &str,
+        request_id: &str,
+    ) -> Result<Request, ManageError> {
+        let url = self
+            .0
+            .manage_url(&format!("v1/projects/{project_id}/requests/{request_id}"));
 
-        send_and_translate_response(self.0.client.get(url)).await
+        Ok(send_and_translate_response(self.0, self.0.client.get(url)).await?)
     }
 
     /// Get a summary of usage statistics.
@@ -171,15 +178,17 @@ impl Usage<'_> {
         &self,
         project_id: &str,
         options: &get_usage_options::Options,
-    ) -> crate::Result<UsageSummary> {
-        let url = format!("https://api.deepgram.com/v1/projects/{project_id}/usage");
+    ) -> Result<UsageSummary, ManageError> {
+        let url = self
+            .0
+            .manage_url(&format!("v1/projects/{project_id}/usage"));
         let request = self
             .0
             .client
             .get(url)
             .query(&get_usage_options::SerializableOptions::from(options));
 
-        send_and_translate_response(request).await
+        Ok(send_and_translate_response(self.0, request).await?)
     }
 
     /// Get the features, models, tags, languages, and processing method used for requests in the specified project.
@@ -221,14 +230,16 @@ impl Usage<'_> {
         &self,
         project_id: &str,
         options: &get_fields_options::Options,
-    ) -> crate::Result<Fields> {
-        let url = format!("https://api.deepgram.com/v1/projects/{project_id}/usage/fields",);
+    ) -> Result<Fields, ManageError> {
+        let url = self
+            .0
+            .manage_url(&format!("v1/projects/{project_id}/usage/fields"));
         let request = self
             .0
             .client
             .get(url)
             .query(&get_fields_options::SerializableOptions::from(options));
 
-        send_and_translate_response(request).await
+        Ok(send_and_translate_response(self.0, request).await?)
     }
 }
@@ -0,0 +1,18 @@
+//! G.711 mu-law decoding, shared by the audio conversions and silence detection.
+
+pub(crate) const MULAW_BIAS: i32 = 0x84;
+
+/// Decode one G.711 mu-law byte into a linear16 sample.
+pub(crate) fn decode_mulaw(byte: u8) -> i16 {
+    let byte = !byte;
+    let exponent = (byte >> 4) & 0x07;
+    let mantissa = i32::from(byte & 0x0F);
+    let magnitude = (((mantissa << 3) + MULAW_BIAS) << exponent) - MULAW_BIAS;
+
+    // The largest magnitude is 32124, so this never overflows.
+    if byte & 0x80 != 0 {
+        -magnitude as i16
+    } else {
+        magnitude as i16
+    }
+}
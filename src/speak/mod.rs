@@ -1,4 +1,167 @@
 //! Speak module
 
+use std::{io, time::Duration};
+
+use reqwest::StatusCode;
+use thiserror::Error;
+
+use crate::{ApiErrorBody, DeepgramError, ErrorView, HttpError, ReqwestError, SerdeJsonError};
+
 pub mod options;
 pub mod rest;
+
+/// Errors that may arise from the [`speak`](crate::speak) APIs.
+///
+/// Converts into [`DeepgramError`] with `?`, keeping the same variant.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum SpeakError {
+    /// The Deepgram API returned an error.
+    #[error("The Deepgram API returned an error.")]
+    DeepgramApiError {
+        /// Error message from the Deepgram API.
+        body: String,
+
+        /// Underlying [`reqwest::Error`] from the HTTP request.
+        err: ReqwestError,
+    },
+
+    /// The Deepgram API rate limited the request.
+    ///
+    /// See [`DeepgramError::RateLimited`].
+    #[error("The Deepgram API rate limited the request.")]
+    RateLimited {
+        /// How long the API asked to wait before retrying, from the `Retry-After` header.
+        retry_after: Option<Duration>,
+
+        /// Error message from the Deepgram API.
+        body: String,
+
+        /// Underlying [`reqwest::Error`] from the HTTP request.
+        err: ReqwestError,
+    },
+
+    /// The request did not complete within the configured timeout.
+    #[error("The request timed out")]
+    Timeout,
+
+    /// Something went wrong when generating the http request.
+    #[error("Something went wrong when generating the http request: {0}")]
+    HttpError(HttpError),
+
+    /// Something went wrong when making the HTTP request.
+    #[error("Something went wrong when making the HTTP request: {0}")]
+    ReqwestError(ReqwestError),
+
+    /// Something went wrong during serialization/deserialization.
+    #[error("Something went wrong during json serialization/deserialization: {0}")]
+    JsonError(SerdeJsonError),
+
+    /// Something went wrong during I/O.
+    #[error("Something went wrong during I/O: {0}")]
+    IoError(io::Error),
+
+    /// Any other error, such as one returned by a [`TokenProvider`](crate::TokenProvider).
+    #[error(transparent)]
+    Other(DeepgramError),
+}
+
+impl SpeakError {
+    /// The structured error details returned by the Deepgram API, if any.
+    ///
+    /// See [`DeepgramError::api_error`].
+    pub fn api_error(&self) -> Option<ApiErrorBody> {
+        self.view().api_error()
+    }
+
+    /// The HTTP status code of the response that caused this error, if any.
+    ///
+    /// See [`DeepgramError::status_code`].
+    pub fn status_code(&self) -> Option<StatusCode> {
+        self.view().status_code()
+    }
+
+    /// The Deepgram error code, such as `INVALID_AUTH`, if the API returned one.
+    ///
+    /// See [`DeepgramError::error_code`].
+    pub fn error_code(&self) -> Option<String> {
+        self.view().error_code()
+    }
+
+    /// Whether retrying the request that caused this error might succeed.
+    ///
+    /// See [`DeepgramError::is_retryable`].
+    pub fn is_retryable(&self) -> bool {
+        self.view().is_retryable()
+    }
+
+    fn view(&self) -> ErrorView<'_> {
+        match self {
+            SpeakError::DeepgramApiError { body, err } => ErrorView::Api { body, err },
+            SpeakError::RateLimited { body, err, .. } => ErrorView::RateLimited { body, err },
+            SpeakError::Timeout => ErrorView::Timeout,
+            SpeakError::ReqwestError(err) => ErrorView::Reqwest(err),
+            SpeakError::IoError(err) => ErrorView::Io(err),
+            SpeakError::Other(err) => err.view(),
+            SpeakError::HttpError(_) | SpeakError::JsonError(_) => ErrorView::Other,
+        }
+    }
+}
+
+impl From<DeepgramError> for SpeakError {
+    fn from(err: DeepgramError) -> Self {
+        match err {
+            DeepgramError::DeepgramApiError { body, err } => Self::DeepgramApiError { body, err },
+            DeepgramError::RateLimited {
+                retry_after,
+                body,
+                err,
+            } => Self::RateLimited {
+                retry_after,
+                body,
+                err,
+            },
+            DeepgramError::Timeout => Self::Timeout,
+            DeepgramError::HttpError(err) => Self::HttpError(err),
+            DeepgramError::ReqwestError(err) => Self::ReqwestError(err),
+            DeepgramError::JsonError(err) => Self::JsonError(err),
+            DeepgramError::IoError(err) => Self::IoError(err),
+            err => Self::Other(err),
+        }
+    }
+}
+
+impl From<SpeakError> for DeepgramError {
+    fn from(err: SpeakError) -> Self {
+        match err {
+            SpeakError::DeepgramApiError { body, err } => Self::DeepgramApiError { body, err },
+            SpeakError::RateLimited {
+                retry_after,
+                body,
+                err,
+            } => Self::RateLimited {
+                retry_after,
+                body,
+                err,
+            },
+            SpeakError::Timeout => Self::Timeout,
+            SpeakError::HttpError(err) => Self::HttpError(err),
+            SpeakError::ReqwestError(err) => Self::ReqwestError(err),
+            SpeakError::JsonError(err) => Self::JsonError(err),
+            SpeakError::IoError(err) => Self::IoError(err),
+            SpeakError::Other(err) => err,
+        }
+    }
+}
+
+impl From<io::Error> for SpeakError {
+    fn from(err: io::Error) -> Self {
+        Self::IoError(err)
+    }
+}
+
+impl From<ReqwestError> for SpeakError {
+    fn from(err: ReqwestError) -> Self {
+        DeepgramError::from(err).into()
+    }
+}
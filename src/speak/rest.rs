@@ -8,9 +8,12 @@ use tokio::sync::mpsc;
 use tokio_stream::wrappers::ReceiverStream;
 use url::Url;
 
-use crate::{DeepgramError, Speak};
+use crate::{send_and_check_status, Speak};
 
-use super::options::{Options, SerializableOptions};
+use super::{
+    options::{Options, SerializableOptions},
+    SpeakError,
+};
 
 static DEEPGRAM_API_URL_SPEAK: &str = "v1/speak";
 
@@ -21,7 +24,7 @@ impl Speak<'_> {
         text: &str,
         options: &Options,
         output_file: &std::path::Path,
-    ) -> Result<(), DeepgramError> {
+    ) -> Result<(), SpeakError> {
         let payload = Value::Object(
             [("text".to_string(), Value::String(text.to_string()))]
                 .iter()
@@ -44,19 +47,8 @@ impl Speak<'_> {
         &self,
         request_builder: RequestBuilder,
         output_file: &std::path::Path,
-    ) -> Result<(), DeepgramError> {
-        let mut response = request_builder.send().await?;
-
-        if let Err(err) = response.error_for_status_ref() {
-            let status = response.status();
-            let error_text = response.text().await?;
-            eprintln!("Failed to generate speech: {status}");
-            eprintln!("Error details: {error_text}");
-            return Err(DeepgramError::DeepgramApiError {
-                body: error_text,
-                err,
-            });
-        }
+    ) -> Result<(), SpeakError> {
+        let mut response = send_and_check_status(self.0, request_builder).await?;
 
         // Create the output file
         let mut file = std::fs::File::create(output_file)?;
@@ -76,7 +68,7 @@ impl Speak<'_> {
         &self,
         text: &str,
         options: &Options,
-    ) -> Result<impl Stream<Item = Bytes>, DeepgramError> {
+    ) -> Result<impl Stream<Item = Bytes>, SpeakError> {
         let payload = Value::Object(
             [("text".to_string(), Value::String(text.to_string()))]
                 .iter()
@@ -97,19 +89,8 @@ impl Speak<'_> {
     async fn send_and_stream_response(
         &self,
         request_builder: RequestBuilder,
-    ) -> Result<impl Stream<Item = Bytes>, DeepgramError> {
-        let response = request_builder.send().await?;
-
-        if let Err(err) = response.error_for_status_ref() {
-            let status = response.status();
-            let error_text = response.text().await?;
-            eprintln!("Failed to generate speech: {status}");
-            eprintln!("Error details: {error_text}");
-            return Err(DeepgramError::DeepgramApiError {
-                body: error_text,
-                err,
-            });
-        }
+    ) -> Result<impl Stream<Item = Bytes>, SpeakError> {
+        let response = send_and_check_status(self.0, request_builder).await?;
 
         let (tx, rx) = mpsc::channel(1024);
         let rx_stream = ReceiverStream::new(rx);
@@ -136,7 +117,7 @@ impl Speak<'_> {
     }
 
     fn speak_url(&self) -> Url {
-        self.0.base_url.join(DEEPGRAM_API_URL_SPEAK).unwrap()
+        self.0.speak_base_url.join(DEEPGRAM_API_URL_SPEAK).unwrap()
     }
 }
 
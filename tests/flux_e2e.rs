@@ -3,7 +3,7 @@
 //! Requires DEEPGRAM_API_KEY in the environment.
 //! Run with: cargo test --test flux_e2e --features listen -- --ignored
 
-#[cfg(feature = "listen")]
+#[cfg(feature = "listen-ws")]
 mod e2e {
     use std::time::Duration;
 
@@ -3,7 +3,7 @@
 //!
 //! Run with: cargo test --test flux_unknown_messages --features listen
 
-#[cfg(feature = "listen")]
+#[cfg(feature = "listen-ws")]
 mod mock {
     use std::net::SocketAddr;
 